@@ -0,0 +1,184 @@
+//! benchmark_module module for model registry
+//!
+//! This module provides attested benchmark scores for registered models. When
+//! the registry's evaluator allowlist is enabled only trusted evaluators can
+//! post results.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::RegistryError;
+use crate::feature_flags_module::{FeatureFlags, ENABLE_BENCHMARKS};
+use crate::model_module::Model;
+use crate::registry_config_module::RegistryConfig;
+use crate::utils_module::{assert_not_globally_paused, get_bump, BPS_DENOMINATOR};
+
+/// Benchmark state account
+#[account]
+pub struct Benchmark {
+    /// The model that was benchmarked
+    pub model: Pubkey,
+
+    /// Identifier of the benchmark within the model
+    pub benchmark_id: u64,
+
+    /// Score in basis points
+    pub score_bps: u32,
+
+    /// Dataset the benchmark was run against
+    pub dataset_ref: Pubkey,
+
+    /// Evaluator attesting to the score
+    pub attested_by: Pubkey,
+
+    /// Time the result was posted
+    pub posted_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Benchmark {
+    pub const LEN: usize = 32 + 8 + 4 + 32 + 32 + 8 + 1;
+}
+
+/// Emitted when a benchmark result is posted
+#[event]
+pub struct BenchmarkPosted {
+    pub model: Pubkey,
+    pub benchmark_id: u64,
+    pub score_bps: u32,
+    pub dataset_ref: Pubkey,
+    pub attested_by: Pubkey,
+}
+
+/// Check `score_bps` is a valid score and `attester` may post it under `config`
+pub fn validate_benchmark(
+    config: &RegistryConfig,
+    attester: &Pubkey,
+    score_bps: u32,
+) -> Result<()> {
+    require!(
+        score_bps as u64 <= BPS_DENOMINATOR,
+        RegistryError::InvalidScore
+    );
+    require!(
+        config.is_trusted_evaluator(attester),
+        RegistryError::UntrustedEvaluator
+    );
+
+    Ok(())
+}
+
+/// Post a benchmark result for a model, attested by the signer
+pub fn post_benchmark(
+    ctx: Context<PostBenchmark>,
+    benchmark_id: u64,
+    score_bps: u32,
+    dataset_ref: Pubkey,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts
+        .feature_flags
+        .require_enabled(ENABLE_BENCHMARKS)?;
+    let attester = ctx.accounts.attester.key();
+    validate_benchmark(&ctx.accounts.registry_config, &attester, score_bps)?;
+
+    let benchmark = &mut ctx.accounts.benchmark;
+    benchmark.model = ctx.accounts.model.key();
+    benchmark.benchmark_id = benchmark_id;
+    benchmark.score_bps = score_bps;
+    benchmark.dataset_ref = dataset_ref;
+    benchmark.attested_by = attester;
+    benchmark.posted_at = Clock::get()?.unix_timestamp;
+    benchmark.bump = get_bump(&ctx.bumps, "benchmark")?;
+
+    emit!(BenchmarkPosted {
+        model: benchmark.model,
+        benchmark_id,
+        score_bps,
+        dataset_ref,
+        attested_by: attester,
+    });
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+#[instruction(benchmark_id: u64)]
+pub struct PostBenchmark<'info> {
+    /// The evaluator attesting to the result
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model that was benchmarked
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// Registry configuration holding the evaluator allowlist
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// Feature flags
+    #[account(
+        seeds = [b"feature-flags".as_ref()],
+        bump = feature_flags.bump,
+    )]
+    pub feature_flags: Account<'info, FeatureFlags>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = attester,
+        space = 8 + Benchmark::LEN,
+        seeds = [b"bench".as_ref(), model.key().as_ref(), benchmark_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub benchmark: Account<'info, Benchmark>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry_config_module::tests::registry_config;
+
+    #[test]
+    fn untrusted_attester_rejected_while_allowlist_on() {
+        let mut config = registry_config();
+        let trusted = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        config.trusted_evaluators = vec![trusted];
+
+        validate_benchmark(&config, &other, 9_000).unwrap();
+        config.evaluator_allowlist_enabled = true;
+        validate_benchmark(&config, &trusted, 9_000).unwrap();
+        assert_eq!(
+            validate_benchmark(&config, &other, 9_000).unwrap_err(),
+            RegistryError::UntrustedEvaluator.into()
+        );
+        assert_eq!(
+            validate_benchmark(&config, &trusted, 10_001).unwrap_err(),
+            RegistryError::InvalidScore.into()
+        );
+    }
+}
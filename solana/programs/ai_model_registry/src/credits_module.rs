@@ -0,0 +1,319 @@
+//! credits_module module for model registry
+//!
+//! This module provides inference credits. Credits are backed one-to-one by
+//! tokens held in the credit vault of the model's mint and are spent before any
+//! new payment in `pay_for_inference`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::RegistryError;
+use crate::model_module::Model;
+use crate::utils_module::{assert_batch_size, assert_not_globally_paused};
+use crate::vaults_module::CREDIT_VAULT_SEED;
+
+/// ConsumerCredits state account
+#[account]
+pub struct ConsumerCredits {
+    /// The consumer owning the credits
+    pub consumer: Pubkey,
+
+    /// The model the credits can be spent on
+    pub model: Pubkey,
+
+    /// Credit balance in base units of the model mint
+    pub balance: u64,
+
+    /// Inferences the consumer has paid for on the model, for loyalty discounts
+    pub inference_count: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ConsumerCredits {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1;
+
+    /// Add `amount` to the balance
+    pub fn credit(&mut self, amount: u64) -> Result<()> {
+        self.balance = self
+            .balance
+            .checked_add(amount)
+            .ok_or(RegistryError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Split paying `total_cost` with `payment` into the credits spent first
+    /// and the part of `payment` left over to credit back
+    pub fn split_payment(&self, total_cost: u64, payment: u64) -> Result<(u64, u64)> {
+        let credits_used = total_cost.min(self.balance);
+        let due = total_cost - credits_used;
+        require!(payment >= due, RegistryError::InsufficientPayment);
+
+        Ok((credits_used, payment - due))
+    }
+
+    /// Count `num_inferences` more inferences paid for
+    pub fn record_inferences(&mut self, num_inferences: u64) -> Result<()> {
+        self.inference_count = self
+            .inference_count
+            .checked_add(num_inferences)
+            .ok_or(RegistryError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Remove `amount` from the balance
+    pub fn debit(&mut self, amount: u64) -> Result<()> {
+        self.balance = self
+            .balance
+            .checked_sub(amount)
+            .ok_or(RegistryError::InsufficientCredits)?;
+
+        Ok(())
+    }
+}
+
+/// Emitted when an overpayment is credited back to a consumer
+#[event]
+pub struct InferenceCredited {
+    pub consumer: Pubkey,
+    pub model: Pubkey,
+    pub credited: u64,
+    pub balance: u64,
+}
+
+/// Grant `amounts[i]` credits on the model to the consumer credits passed as
+/// remaining account `i`, funded by the model owner.
+///
+/// Entries whose account is not an existing `ConsumerCredits` of this model,
+/// or whose balance would overflow, are skipped rather than aborting the
+/// batch. Bit `i` of the little-endian `u64` return data is set for every
+/// skipped entry, so clients can retry only those. Only the granted total is
+/// moved into the credit vault.
+pub fn grant_credits_batch<'info>(
+    ctx: Context<'_, '_, '_, 'info, GrantCreditsBatch<'info>>,
+    amounts: Vec<u64>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    assert_batch_size(amounts.len())?;
+    require!(
+        ctx.remaining_accounts.len() == amounts.len(),
+        RegistryError::InvalidAccountCount
+    );
+
+    let (granted, failures) = credit_batch(
+        ctx.program_id,
+        &ctx.accounts.model.key(),
+        &amounts,
+        ctx.remaining_accounts,
+    );
+    if granted > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.owner_token_account.to_account_info(),
+                    to: ctx.accounts.credit_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            granted,
+        )?;
+    }
+
+    set_return_data(&failures.to_le_bytes());
+
+    Ok(())
+}
+
+/// Credit `amounts[i]` to the `ConsumerCredits` of `model` in `accounts[i]`,
+/// returning the total credited and the bitmap of skipped entries
+fn credit_batch(
+    program_id: &Pubkey,
+    model: &Pubkey,
+    amounts: &[u64],
+    accounts: &[AccountInfo],
+) -> (u64, u64) {
+    let mut failures: u64 = 0;
+    let mut granted: u64 = 0;
+    for (index, (amount, info)) in amounts.iter().zip(accounts).enumerate() {
+        let credited = granted.checked_add(*amount).and_then(|total| {
+            let mut credits = Account::<ConsumerCredits>::try_from(info).ok()?;
+            if credits.model != *model || !info.is_writable {
+                return None;
+            }
+            credits.credit(*amount).ok()?;
+            credits.exit(program_id).ok()?;
+            Some(total)
+        });
+        match credited {
+            Some(total) => granted = total,
+            None => failures |= 1 << index,
+        }
+    }
+
+    (granted, failures)
+}
+
+/// Create the credit vault for a mint
+pub fn initialize_credit_vault(_ctx: Context<InitializeCreditVault>) -> Result<()> {
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeCreditVault<'info> {
+    /// Pays for the vault
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The vault to initialize; it is its own authority
+    #[account(
+        init,
+        payer = payer,
+        seeds = [CREDIT_VAULT_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = credit_vault,
+    )]
+    pub credit_vault: Account<'info, TokenAccount>,
+
+    /// Mint the credits are denominated in
+    pub mint: Account<'info, Mint>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for batch credit grants
+#[derive(Accounts)]
+pub struct GrantCreditsBatch<'info> {
+    /// The owner of the model, funding the grants
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model the credits can be spent on
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// Owner's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Vault backing inference credits for the model mint
+    #[account(
+        mut,
+        seeds = [CREDIT_VAULT_SEED, model.mint.as_ref()],
+        bump,
+    )]
+    pub credit_vault: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credits(balance: u64) -> ConsumerCredits {
+        ConsumerCredits {
+            consumer: Pubkey::new_unique(),
+            model: Pubkey::new_unique(),
+            balance,
+            inference_count: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn overpayment_credited_back() {
+        let mut credits = credits(0);
+        let (used, credited) = credits.split_payment(100, 150).unwrap();
+        credits.debit(used).unwrap();
+        credits.credit(credited).unwrap();
+        assert_eq!(credits.balance, 50);
+
+        let (used, credited) = credits.split_payment(100, 60).unwrap();
+        assert_eq!((used, credited), (50, 10));
+        assert_eq!(
+            credits.split_payment(100, 49).unwrap_err(),
+            RegistryError::InsufficientPayment.into()
+        );
+    }
+
+    #[test]
+    fn frozen_destination_skipped_and_reported() {
+        let program_id = crate::ID;
+        let model = Pubkey::new_unique();
+        let keys = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let mut lamports = [0; 3];
+        let mut data: Vec<Vec<u8>> = (0..3)
+            .map(|_| {
+                let mut credits = credits(0);
+                credits.model = model;
+                let mut data = Vec::new();
+                credits.try_serialize(&mut data).unwrap();
+                data
+            })
+            .collect();
+        let accounts: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .enumerate()
+            .map(|(index, ((key, lamports), data))| {
+                // The second destination is read-only, standing in for a frozen one
+                AccountInfo::new(
+                    key,
+                    false,
+                    index != 1,
+                    lamports,
+                    data,
+                    &program_id,
+                    false,
+                    0,
+                )
+            })
+            .collect();
+
+        let (granted, failures) = credit_batch(&program_id, &model, &[10, 20, 30], &accounts);
+        assert_eq!((granted, failures), (40, 0b010));
+        let balance = |index: usize| {
+            ConsumerCredits::try_deserialize(&mut &accounts[index].data.borrow()[..])
+                .unwrap()
+                .balance
+        };
+        assert_eq!([balance(0), balance(1), balance(2)], [10, 0, 30]);
+    }
+}
@@ -0,0 +1,153 @@
+//! errors_module module for model registry
+//!
+//! This module provides the error codes returned by model registry instructions.
+
+use anchor_lang::prelude::*;
+
+/// Model registry errors
+#[error_code]
+pub enum RegistryError {
+    #[msg("PDA bump not found")]
+    BumpNotFound,
+
+    #[msg("Model is retired")]
+    ModelRetired,
+
+    #[msg("Model is paused")]
+    ModelPaused,
+
+    #[msg("Inference count must be greater than zero")]
+    InvalidInferenceCount,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+
+    #[msg("Owner stake is below the registry minimum")]
+    InsufficientStake,
+
+    #[msg("Inference cost exceeds the consumer's maximum")]
+    CostExceeded,
+
+    #[msg("Unsupported instruction argument version")]
+    UnsupportedVersion,
+
+    #[msg("Account is not of the expected type")]
+    AccountTypeMismatch,
+
+    #[msg("Insufficient inference credits")]
+    InsufficientCredits,
+
+    #[msg("Payment does not cover the inference cost")]
+    InsufficientPayment,
+
+    #[msg("Configuration is frozen")]
+    ConfigFrozen,
+
+    #[msg("Daily spend limit exceeded")]
+    SpendLimitExceeded,
+
+    #[msg("Benchmark score exceeds 10000 basis points")]
+    InvalidScore,
+
+    #[msg("Attester is not a trusted evaluator")]
+    UntrustedEvaluator,
+
+    #[msg("Too many entries")]
+    TooManyEntries,
+
+    #[msg("Feature is disabled")]
+    FeatureDisabled,
+
+    #[msg("Request time-to-live must be positive")]
+    InvalidTtl,
+
+    #[msg("Inference request is not in the expected state")]
+    InvalidRequestStatus,
+
+    #[msg("Inference request has expired")]
+    RequestExpired,
+
+    #[msg("Inference request has not expired")]
+    RequestNotExpired,
+
+    #[msg("Inference request is under dispute")]
+    RequestDisputed,
+
+    #[msg("Inference request is not resolved and its result can still be disputed")]
+    RequestNotResolved,
+
+    #[msg("Result is still within its dispute window")]
+    FinalizationNotReached,
+
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+
+    #[msg("Number of accounts does not match the number of entries")]
+    InvalidAccountCount,
+
+    #[msg("Fewer inferences than the model's minimum per purchase")]
+    BelowMinimumInferences,
+
+    #[msg("Signer is not the safety reviewer")]
+    NotSafetyReviewer,
+
+    #[msg("Model has not been approved by the safety reviewer")]
+    SafetyReviewRequired,
+
+    #[msg("Model has reached the maximum number of versions")]
+    TooManyVersions,
+
+    #[msg("Discount exceeds the allowed maximum")]
+    InvalidDiscount,
+
+    #[msg("Accounts do not belong to the inference request")]
+    InvalidRequestAccounts,
+
+    #[msg("License identifier is too long")]
+    LicenseTooLong,
+
+    #[msg("Unknown input modality flag")]
+    InvalidModalities,
+
+    #[msg("Unknown model framework")]
+    InvalidFramework,
+
+    #[msg("Payout cannot exceed 10000 basis points")]
+    InvalidPayoutBps,
+
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+
+    #[msg("Successor must be another active model of the same category")]
+    InvalidSuccessor,
+
+    #[msg("Batch exceeds the maximum size")]
+    BatchTooLarge,
+
+    #[msg("Price is below the registry's minimum")]
+    PriceBelowFloor,
+
+    #[msg("Model is temporarily frozen")]
+    ModelFrozen,
+
+    #[msg("Freeze must end in the future")]
+    InvalidFreezeTime,
+
+    #[msg("Signer is neither the model owner nor the registry admin")]
+    NotModelOwnerOrAdmin,
+
+    #[msg("Stake does not belong to the registry's stake pool")]
+    InvalidStakePool,
+
+    #[msg("Model is not retired")]
+    ModelNotRetired,
+
+    #[msg("Model belongs to another registry")]
+    RegistryMismatch,
+
+    #[msg("Dispute window of the result has closed")]
+    DisputeWindowClosed,
+
+    #[msg("Freeze is longer than the registry allows")]
+    FreezeTooLong,
+}
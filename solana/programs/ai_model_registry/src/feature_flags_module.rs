@@ -0,0 +1,133 @@
+//! feature_flags_module module for model registry
+//!
+//! This module provides per-deployment feature flags, so new instructions can
+//! ship disabled and be switched on by the admin when ready.
+
+use anchor_lang::prelude::*;
+
+use crate::errors_module::RegistryError;
+use crate::utils_module::get_bump;
+
+/// Enables `post_benchmark`
+pub const ENABLE_BENCHMARKS: u64 = 1 << 0;
+
+/// FeatureFlags state account
+#[account]
+pub struct FeatureFlags {
+    /// The admin that can update this account
+    pub admin: Pubkey,
+
+    /// Bit set of enabled features
+    pub flags: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl FeatureFlags {
+    pub const LEN: usize = 32 + 8 + 1;
+
+    pub fn is_enabled(&self, feature: u64) -> bool {
+        self.flags & feature == feature
+    }
+
+    /// Fail with `FeatureDisabled` unless `feature` is enabled
+    pub fn require_enabled(&self, feature: u64) -> Result<()> {
+        require!(self.is_enabled(feature), RegistryError::FeatureDisabled);
+
+        Ok(())
+    }
+
+    /// Enable or disable the features in `features`
+    pub fn set(&mut self, features: u64, enabled: bool) {
+        if enabled {
+            self.flags |= features;
+        } else {
+            self.flags &= !features;
+        }
+    }
+}
+
+/// Initialize the FeatureFlags with every feature disabled
+pub fn initialize_feature_flags(ctx: Context<InitializeFeatureFlags>) -> Result<()> {
+    let feature_flags = &mut ctx.accounts.feature_flags;
+    feature_flags.admin = ctx.accounts.admin.key();
+    feature_flags.flags = 0;
+    feature_flags.bump = get_bump(&ctx.bumps, "feature_flags")?;
+
+    Ok(())
+}
+
+/// Enable or disable the features in `features`
+pub fn set_feature_flags(
+    ctx: Context<UpdateFeatureFlags>,
+    features: u64,
+    enabled: bool,
+) -> Result<()> {
+    ctx.accounts.feature_flags.set(features, enabled);
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeFeatureFlags<'info> {
+    /// The admin of the registry
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + FeatureFlags::LEN,
+        seeds = [b"feature-flags".as_ref()],
+        bump,
+    )]
+    pub feature_flags: Account<'info, FeatureFlags>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateFeatureFlags<'info> {
+    /// The admin of the registry
+    pub admin: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"feature-flags".as_ref()],
+        bump = feature_flags.bump,
+        has_one = admin,
+    )]
+    pub feature_flags: Account<'info, FeatureFlags>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_feature_rejected_until_enabled() {
+        let mut feature_flags = FeatureFlags {
+            admin: Pubkey::new_unique(),
+            flags: 0,
+            bump: 255,
+        };
+        assert_eq!(
+            feature_flags
+                .require_enabled(ENABLE_BENCHMARKS)
+                .unwrap_err(),
+            RegistryError::FeatureDisabled.into()
+        );
+
+        feature_flags.set(ENABLE_BENCHMARKS, true);
+        feature_flags.require_enabled(ENABLE_BENCHMARKS).unwrap();
+
+        feature_flags.set(ENABLE_BENCHMARKS, false);
+        assert!(!feature_flags.is_enabled(ENABLE_BENCHMARKS));
+    }
+}
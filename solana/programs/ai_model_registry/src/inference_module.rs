@@ -0,0 +1,213 @@
+//! inference_module module for model registry
+//!
+//! This module provides inference payments to model owners.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::credits_module::{ConsumerCredits, InferenceCredited};
+use crate::errors_module::RegistryError;
+use crate::model_module::Model;
+use crate::registry_config_module::RegistryConfig;
+use crate::safety_module::{assert_safety_reviewed, SafetyAttestation};
+use crate::spend_limit_module::record_spend;
+use crate::utils_module::{assert_not_globally_paused, get_bump};
+use crate::vaults_module::{credit_vault_signer_seeds, CREDIT_VAULT_SEED};
+use crate::versioning_module::Versioned;
+
+/// Arguments of `pay_for_inference`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PayForInferenceParams {
+    /// Number of inferences being paid for
+    pub num_inferences: u64,
+
+    /// Most the consumer is willing to pay in total
+    pub max_total_cost: u64,
+
+    /// Tokens transferred by the consumer; any excess over the cost is credited
+    pub payment: u64,
+}
+
+/// Pay the model owner for `num_inferences` inferences.
+///
+/// `max_total_cost` is the most the consumer is willing to pay, protecting them
+/// from price changes between quoting and submitting the transaction.
+///
+/// Existing credits are spent first and `payment` covers the rest. The payment
+/// is routed through the credit vault and whatever it contributes beyond the
+/// cost is credited back to the consumer instead of being kept.
+///
+/// Consumers past one of the model's loyalty tiers pay the discounted price,
+/// based on the inferences they paid for before this call.
+///
+/// While the registry requires safety review, the model's `SafetyAttestation`
+/// must be passed and approve it.
+pub fn pay_for_inference(
+    ctx: Context<PayForInference>,
+    args: Versioned<PayForInferenceParams>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let PayForInferenceParams {
+        num_inferences,
+        max_total_cost,
+        payment,
+    } = args.into_v1()?;
+    let now = Clock::get()?.unix_timestamp;
+    let model = &ctx.accounts.model;
+    model.assert_accepting_inference(now)?;
+    assert_safety_reviewed(
+        &ctx.accounts.registry_config,
+        ctx.accounts.safety_attestation.as_deref(),
+    )?;
+    let total_cost = model.total_cost(
+        num_inferences,
+        ctx.accounts.consumer_credits.inference_count,
+        max_total_cost,
+    )?;
+
+    let (credits_used, credited) = ctx
+        .accounts
+        .consumer_credits
+        .split_payment(total_cost, payment)?;
+    record_spend(&ctx.accounts.spend_window, payment, now, ctx.program_id)?;
+
+    if payment > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.consumer_token_account.to_account_info(),
+                    to: ctx.accounts.credit_vault.to_account_info(),
+                    authority: ctx.accounts.consumer.to_account_info(),
+                },
+            ),
+            payment,
+        )?;
+    }
+
+    let mint = model.mint;
+    let bump = [get_bump(&ctx.bumps, "credit_vault")?];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.credit_vault.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.credit_vault.to_account_info(),
+            },
+            &[&credit_vault_signer_seeds(&mint, &bump)],
+        ),
+        total_cost,
+    )?;
+
+    let credits = &mut ctx.accounts.consumer_credits;
+    if credits.consumer == Pubkey::default() {
+        credits.consumer = ctx.accounts.consumer.key();
+        credits.model = ctx.accounts.model.key();
+        credits.bump = get_bump(&ctx.bumps, "consumer_credits")?;
+    }
+    credits.debit(credits_used)?;
+    credits.credit(credited)?;
+    credits.record_inferences(num_inferences)?;
+    ctx.accounts.model.last_inference_at = now;
+
+    if credited > 0 {
+        emit!(InferenceCredited {
+            consumer: credits.consumer,
+            model: credits.model,
+            credited,
+            balance: credits.balance,
+        });
+    }
+
+    Ok(())
+}
+
+/// Account validation for inference payment
+#[derive(Accounts)]
+pub struct PayForInference<'info> {
+    /// The consumer paying for inference
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model being used
+    #[account(
+        mut,
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// The model's safety attestation, required while safety review is enforced
+    #[account(
+        seeds = [b"safety".as_ref(), model.key().as_ref()],
+        bump = safety_attestation.bump,
+    )]
+    pub safety_attestation: Option<Account<'info, SafetyAttestation>>,
+
+    /// Consumer's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = consumer,
+    )]
+    pub consumer_token_account: Account<'info, TokenAccount>,
+
+    /// Model owner's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = model.owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Consumer's spend window PDA, enforced only once the consumer has opted in
+    #[account(
+        mut,
+        seeds = [b"spend-window".as_ref(), consumer.key().as_ref()],
+        bump,
+    )]
+    pub spend_window: UncheckedAccount<'info>,
+
+    /// Consumer's inference credits for this model
+    #[account(
+        init_if_needed,
+        payer = consumer,
+        space = 8 + ConsumerCredits::LEN,
+        seeds = [b"credits".as_ref(), model.key().as_ref(), consumer.key().as_ref()],
+        bump,
+    )]
+    pub consumer_credits: Account<'info, ConsumerCredits>,
+
+    /// Vault backing inference credits for the model mint
+    #[account(
+        mut,
+        seeds = [CREDIT_VAULT_SEED, model.mint.as_ref()],
+        bump,
+    )]
+    pub credit_vault: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,358 @@
+use anchor_lang::prelude::*;
+
+pub mod benchmark_module;
+pub mod credits_module;
+pub mod errors_module;
+pub mod feature_flags_module;
+pub mod inference_module;
+pub mod model_card_module;
+pub mod model_module;
+pub mod model_version_module;
+pub mod rating_module;
+pub mod registry_config_module;
+pub mod request_module;
+pub mod safety_module;
+pub mod spend_limit_module;
+pub mod utils_module;
+pub mod vaults_module;
+pub mod versioning_module;
+
+use benchmark_module::*;
+use credits_module::*;
+use feature_flags_module::*;
+use inference_module::*;
+use model_card_module::*;
+use model_module::*;
+use model_version_module::*;
+use rating_module::*;
+use registry_config_module::*;
+use request_module::*;
+use safety_module::*;
+use spend_limit_module::*;
+use versioning_module::*;
+
+declare_id!("5HikjGZ1fJoCDLEf5zunRZHdKgFwe5GShyjgGfe93uf2");
+
+#[program]
+pub mod ai_model_registry {
+    use super::*;
+
+    /// Post a benchmark result for a model, attested by the signer
+    pub fn post_benchmark(
+        ctx: Context<PostBenchmark>,
+        benchmark_id: u64,
+        score_bps: u32,
+        dataset_ref: Pubkey,
+    ) -> Result<()> {
+        benchmark_module::post_benchmark(ctx, benchmark_id, score_bps, dataset_ref)
+    }
+
+    /// Grant `amounts[i]` credits on the model to the consumer credits passed as
+    /// remaining account `i`, funded by the model owner.
+    pub fn grant_credits_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, GrantCreditsBatch<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        credits_module::grant_credits_batch(ctx, amounts)
+    }
+
+    /// Create the credit vault for a mint
+    pub fn initialize_credit_vault(ctx: Context<InitializeCreditVault>) -> Result<()> {
+        credits_module::initialize_credit_vault(ctx)
+    }
+
+    /// Initialize the FeatureFlags with every feature disabled
+    pub fn initialize_feature_flags(ctx: Context<InitializeFeatureFlags>) -> Result<()> {
+        feature_flags_module::initialize_feature_flags(ctx)
+    }
+
+    /// Enable or disable the features in `features`
+    pub fn set_feature_flags(
+        ctx: Context<UpdateFeatureFlags>,
+        features: u64,
+        enabled: bool,
+    ) -> Result<()> {
+        feature_flags_module::set_feature_flags(ctx, features, enabled)
+    }
+
+    /// Pay the model owner for `num_inferences` inferences.
+    pub fn pay_for_inference(
+        ctx: Context<PayForInference>,
+        args: Versioned<PayForInferenceParams>,
+    ) -> Result<()> {
+        inference_module::pay_for_inference(ctx, args)
+    }
+
+    /// Create or replace the card of a Model
+    pub fn set_model_card(
+        ctx: Context<SetModelCard>,
+        description_hash: [u8; 32],
+        license: String,
+        input_modalities: u8,
+        parameter_count: u64,
+        framework: u8,
+    ) -> Result<()> {
+        model_card_module::set_model_card(
+            ctx,
+            description_hash,
+            license,
+            input_modalities,
+            parameter_count,
+            framework,
+        )
+    }
+
+    /// Register a new Model.
+    pub fn register_model(
+        ctx: Context<RegisterModel>,
+        model_id: u64,
+        price_per_inference: u64,
+        category: ModelCategory,
+        input_schema_hash: [u8; 32],
+        output_schema_hash: [u8; 32],
+    ) -> Result<()> {
+        model_module::register_model(
+            ctx,
+            model_id,
+            price_per_inference,
+            category,
+            input_schema_hash,
+            output_schema_hash,
+        )
+    }
+
+    /// Update the input and output schema hashes of a Model
+    pub fn set_model_schemas(
+        ctx: Context<UpdateModel>,
+        input_schema_hash: [u8; 32],
+        output_schema_hash: [u8; 32],
+    ) -> Result<()> {
+        model_module::set_model_schemas(ctx, input_schema_hash, output_schema_hash)
+    }
+
+    /// Set how long committed results stay open to dispute before finalization
+    pub fn set_result_ttl(ctx: Context<UpdateModel>, result_ttl: i64) -> Result<()> {
+        model_module::set_result_ttl(ctx, result_ttl)
+    }
+
+    /// Set the fewest inferences a consumer may pay for at once
+    pub fn set_min_inferences_per_purchase(
+        ctx: Context<UpdateModel>,
+        min_inferences_per_purchase: u64,
+    ) -> Result<()> {
+        model_module::set_min_inferences_per_purchase(ctx, min_inferences_per_purchase)
+    }
+
+    /// Replace the repeat consumer discount tiers of a Model.
+    pub fn set_loyalty_tiers(ctx: Context<UpdateModel>, tiers: Vec<LoyaltyTier>) -> Result<()> {
+        model_module::set_loyalty_tiers(ctx, tiers)
+    }
+
+    /// Permanently retire a Model, optionally pointing consumers to a successor.
+    pub fn retire_model(ctx: Context<RetireModel>) -> Result<()> {
+        model_module::retire_model(ctx)
+    }
+
+    /// Release the stake lock of a retired Model
+    pub fn release_model_stake(ctx: Context<ReleaseModelStake>) -> Result<()> {
+        model_module::release_model_stake(ctx)
+    }
+
+    /// Retire every model passed in remaining accounts that has gone unused for
+    /// longer than the registry's `stale_period`.
+    pub fn bulk_retire<'info>(ctx: Context<'_, '_, '_, 'info, BulkRetire<'info>>) -> Result<()> {
+        model_module::bulk_retire(ctx)
+    }
+
+    /// Move a Model to another registry configuration.
+    pub fn reassign_registry(ctx: Context<ReassignRegistry>) -> Result<()> {
+        model_module::reassign_registry(ctx)
+    }
+
+    /// Temporarily stop a Model from accepting inference payments
+    pub fn pause_model(ctx: Context<UpdateModel>) -> Result<()> {
+        model_module::pause_model(ctx)
+    }
+
+    /// Block inference payments on a Model until `until`, after which they resume
+    /// without another transaction.
+    pub fn freeze_model_until(ctx: Context<FreezeModel>, until: i64) -> Result<()> {
+        model_module::freeze_model_until(ctx, until)
+    }
+
+    /// Resume inference payments for a paused Model
+    pub fn unpause_model(ctx: Context<UpdateModel>) -> Result<()> {
+        model_module::unpause_model(ctx)
+    }
+
+    /// Register the next version of a Model.
+    pub fn register_new_version(
+        ctx: Context<RegisterNewVersion>,
+        artifact_hash: [u8; 32],
+    ) -> Result<()> {
+        model_version_module::register_new_version(ctx, artifact_hash)
+    }
+
+    /// Close a ModelVersion, returning its rent to the owner.
+    pub fn prune_version(ctx: Context<PruneVersion>, version: u32) -> Result<()> {
+        model_version_module::prune_version(ctx, version)
+    }
+
+    /// Rate a model for a settled request and update its average rating
+    pub fn rate_model(ctx: Context<RateModel>, rating: u8) -> Result<()> {
+        rating_module::rate_model(ctx, rating)
+    }
+
+    /// Initialize the RegistryConfig
+    pub fn initialize_registry_config(
+        ctx: Context<InitializeRegistryConfig>,
+        registry_id: u64,
+        min_model_stake: u64,
+        stake_pool: Pubkey,
+    ) -> Result<()> {
+        registry_config_module::initialize_registry_config(
+            ctx,
+            registry_id,
+            min_model_stake,
+            stake_pool,
+        )
+    }
+
+    /// Update the minimum stake required to register a model
+    pub fn set_min_model_stake(
+        ctx: Context<UpdateRegistryConfig>,
+        min_model_stake: u64,
+    ) -> Result<()> {
+        registry_config_module::set_min_model_stake(ctx, min_model_stake)
+    }
+
+    /// Replace the trusted evaluator allowlist and turn enforcement on or off
+    pub fn set_trusted_evaluators(
+        ctx: Context<UpdateRegistryConfig>,
+        evaluators: Vec<Pubkey>,
+        enabled: bool,
+    ) -> Result<()> {
+        registry_config_module::set_trusted_evaluators(ctx, evaluators, enabled)
+    }
+
+    /// Set the safety reviewer and turn the safety review requirement on or off.
+    pub fn set_safety_review(
+        ctx: Context<UpdateRegistryConfig>,
+        safety_reviewer: Pubkey,
+        require_safety_review: bool,
+    ) -> Result<()> {
+        registry_config_module::set_safety_review(ctx, safety_reviewer, require_safety_review)
+    }
+
+    /// Update the lowest price per inference of newly registered or reassigned models
+    pub fn set_min_price_per_inference(
+        ctx: Context<UpdateRegistryConfig>,
+        min_price_per_inference: u64,
+    ) -> Result<()> {
+        registry_config_module::set_min_price_per_inference(ctx, min_price_per_inference)
+    }
+
+    /// Update how many versions a model may keep open at once
+    pub fn set_max_versions(ctx: Context<UpdateRegistryConfig>, max_versions: u32) -> Result<()> {
+        registry_config_module::set_max_versions(ctx, max_versions)
+    }
+
+    /// Update how long a model must go unused before it counts as stale
+    pub fn set_stale_period(ctx: Context<UpdateRegistryConfig>, stale_period: i64) -> Result<()> {
+        registry_config_module::set_stale_period(ctx, stale_period)
+    }
+
+    /// Update the longest a model can be frozen for
+    pub fn set_max_freeze_duration(
+        ctx: Context<UpdateRegistryConfig>,
+        max_freeze_duration: i64,
+    ) -> Result<()> {
+        registry_config_module::set_max_freeze_duration(ctx, max_freeze_duration)
+    }
+
+    /// Update the grace window for late results and the payout they earn
+    pub fn set_late_fulfillment(
+        ctx: Context<UpdateRegistryConfig>,
+        late_grace: i64,
+        late_payout_bps: u16,
+    ) -> Result<()> {
+        registry_config_module::set_late_fulfillment(ctx, late_grace, late_payout_bps)
+    }
+
+    /// Permanently freeze the configuration.
+    pub fn freeze_config(ctx: Context<UpdateRegistryConfig>) -> Result<()> {
+        registry_config_module::freeze_config(ctx)
+    }
+
+    /// Escrow the price of one inference and open a request expiring after `ttl` seconds
+    pub fn request_inference(
+        ctx: Context<RequestInference>,
+        request_id: u64,
+        ttl: i64,
+    ) -> Result<()> {
+        request_module::request_inference(ctx, request_id, ttl)
+    }
+
+    /// Commit the hash of the result of a pending request.
+    pub fn commit_result(ctx: Context<CommitResult>, result_hash: [u8; 32]) -> Result<()> {
+        request_module::commit_result(ctx, result_hash)
+    }
+
+    /// Accept a committed result, releasing the payment to the model owner
+    pub fn accept_result(ctx: Context<SettleInferenceRequest>) -> Result<()> {
+        request_module::accept_result(ctx)
+    }
+
+    /// Release the payment for a result left undisputed past its finalization time.
+    pub fn finalize_inference(ctx: Context<FinalizeInference>, request_id: u64) -> Result<()> {
+        request_module::finalize_inference(ctx, request_id)
+    }
+
+    /// Refund a request that expired without a result, once the late grace
+    /// window has passed
+    pub fn refund_expired_request(ctx: Context<SettleInferenceRequest>) -> Result<()> {
+        request_module::refund_expired_request(ctx)
+    }
+
+    /// Refund every expired, unfulfilled request passed in remaining accounts.
+    pub fn auto_refund_expired<'info>(
+        ctx: Context<'_, '_, '_, 'info, AutoRefundExpired<'info>>,
+    ) -> Result<()> {
+        request_module::auto_refund_expired(ctx)
+    }
+
+    /// Dispute a committed result, leaving the payment escrowed for the admin to resolve
+    pub fn dispute_result(ctx: Context<DisputeResult>) -> Result<()> {
+        request_module::dispute_result(ctx)
+    }
+
+    /// Resolve a disputed request, refunding the consumer or paying the owner
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, refund: bool) -> Result<()> {
+        request_module::resolve_dispute(ctx, refund)
+    }
+
+    /// Close a result commitment and reclaim its rent.
+    pub fn close_result_commitment(
+        ctx: Context<CloseResultCommitment>,
+        request_id: u64,
+    ) -> Result<()> {
+        request_module::close_result_commitment(ctx, request_id)
+    }
+
+    /// Record the outcome of a model's safety review.
+    pub fn attest_safety(
+        ctx: Context<AttestSafety>,
+        approved: bool,
+        review_hash: [u8; 32],
+    ) -> Result<()> {
+        safety_module::attest_safety(ctx, approved, review_hash)
+    }
+
+    /// Opt in to a daily spending limit
+    pub fn set_daily_spend_limit(
+        ctx: Context<SetDailySpendLimit>,
+        daily_spend_limit: u64,
+    ) -> Result<()> {
+        spend_limit_module::set_daily_spend_limit(ctx, daily_spend_limit)
+    }
+}
@@ -0,0 +1,240 @@
+//! model_card_module module for model registry
+//!
+//! This module provides model cards: structured metadata that discovery tools
+//! can filter on without fetching the off-chain description. Each model has at
+//! most one card, created and updated by the model owner.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::RegistryError;
+use crate::model_module::Model;
+use crate::utils_module::{assert_not_globally_paused, get_bump};
+
+/// Maximum length of a model card license identifier, e.g. an SPDX id
+pub const MAX_LICENSE_LEN: usize = 32;
+
+/// `input_modalities` flag for text input
+pub const MODALITY_TEXT: u8 = 1 << 0;
+
+/// `input_modalities` flag for image input
+pub const MODALITY_IMAGE: u8 = 1 << 1;
+
+/// `input_modalities` flag for audio input
+pub const MODALITY_AUDIO: u8 = 1 << 2;
+
+/// `input_modalities` flag for video input
+pub const MODALITY_VIDEO: u8 = 1 << 3;
+
+/// `input_modalities` flag for tabular input
+pub const MODALITY_TABULAR: u8 = 1 << 4;
+
+/// Every defined `input_modalities` flag
+pub const ALL_MODALITIES: u8 =
+    MODALITY_TEXT | MODALITY_IMAGE | MODALITY_AUDIO | MODALITY_VIDEO | MODALITY_TABULAR;
+
+/// `framework` value for PyTorch
+pub const FRAMEWORK_PYTORCH: u8 = 0;
+
+/// `framework` value for TensorFlow
+pub const FRAMEWORK_TENSORFLOW: u8 = 1;
+
+/// `framework` value for JAX
+pub const FRAMEWORK_JAX: u8 = 2;
+
+/// `framework` value for ONNX
+pub const FRAMEWORK_ONNX: u8 = 3;
+
+/// `framework` value for any other framework; the highest valid value
+pub const FRAMEWORK_OTHER: u8 = 4;
+
+/// ModelCard state account
+#[account]
+pub struct ModelCard {
+    /// The model described
+    pub model: Pubkey,
+
+    /// Hash of the off-chain model description
+    pub description_hash: [u8; 32],
+
+    /// License identifier the model is released under
+    pub license: String,
+
+    /// Bitflags of the `MODALITY_*` inputs the model accepts
+    pub input_modalities: u8,
+
+    /// Number of model parameters
+    pub parameter_count: u64,
+
+    /// One of the `FRAMEWORK_*` values
+    pub framework: u8,
+
+    /// Time of the latest update
+    pub updated_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ModelCard {
+    pub const LEN: usize = 32 + 32 + (4 + MAX_LICENSE_LEN) + 1 + 8 + 1 + 8 + 1;
+
+    /// Replace the card's fields at `now`, rejecting a license that is too
+    /// long, undefined modality flags or an unknown framework
+    pub fn update(
+        &mut self,
+        description_hash: [u8; 32],
+        license: String,
+        input_modalities: u8,
+        parameter_count: u64,
+        framework: u8,
+        now: i64,
+    ) -> Result<()> {
+        require!(
+            license.len() <= MAX_LICENSE_LEN,
+            RegistryError::LicenseTooLong
+        );
+        require!(
+            input_modalities & !ALL_MODALITIES == 0,
+            RegistryError::InvalidModalities
+        );
+        require!(
+            framework <= FRAMEWORK_OTHER,
+            RegistryError::InvalidFramework
+        );
+
+        self.description_hash = description_hash;
+        self.license = license;
+        self.input_modalities = input_modalities;
+        self.parameter_count = parameter_count;
+        self.framework = framework;
+        self.updated_at = now;
+
+        Ok(())
+    }
+}
+
+/// Create or replace the card of a Model
+pub fn set_model_card(
+    ctx: Context<SetModelCard>,
+    description_hash: [u8; 32],
+    license: String,
+    input_modalities: u8,
+    parameter_count: u64,
+    framework: u8,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let card = &mut ctx.accounts.model_card;
+    card.update(
+        description_hash,
+        license,
+        input_modalities,
+        parameter_count,
+        framework,
+        Clock::get()?.unix_timestamp,
+    )?;
+    card.model = ctx.accounts.model.key();
+    card.bump = get_bump(&ctx.bumps, "model_card")?;
+
+    Ok(())
+}
+
+/// Account validation for setting a model card
+#[derive(Accounts)]
+pub struct SetModelCard<'info> {
+    /// The owner of the model
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model described
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The model's card, created on first use
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ModelCard::LEN,
+        seeds = [b"model-card".as_ref(), model.key().as_ref()],
+        bump,
+    )]
+    pub model_card: Account<'info, ModelCard>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_card_created_and_updated() {
+        let mut card = ModelCard {
+            model: Pubkey::new_unique(),
+            description_hash: [0; 32],
+            license: String::new(),
+            input_modalities: 0,
+            parameter_count: 0,
+            framework: 0,
+            updated_at: 0,
+            bump: 255,
+        };
+        card.update(
+            [1; 32],
+            "Apache-2.0".to_string(),
+            MODALITY_TEXT,
+            7_000_000_000,
+            FRAMEWORK_PYTORCH,
+            100,
+        )
+        .unwrap();
+        card.update(
+            [2; 32],
+            "MIT".to_string(),
+            MODALITY_TEXT | MODALITY_IMAGE,
+            13_000_000_000,
+            FRAMEWORK_JAX,
+            200,
+        )
+        .unwrap();
+        assert_eq!(card.license, "MIT");
+        assert_eq!(card.input_modalities, MODALITY_TEXT | MODALITY_IMAGE);
+        assert_eq!(
+            (card.parameter_count, card.updated_at),
+            (13_000_000_000, 200)
+        );
+
+        assert_eq!(
+            card.update([3; 32], "MIT".to_string(), 1 << 5, 0, FRAMEWORK_JAX, 300)
+                .unwrap_err(),
+            RegistryError::InvalidModalities.into()
+        );
+        assert_eq!(
+            card.update(
+                [3; 32],
+                "MIT".to_string(),
+                MODALITY_TEXT,
+                0,
+                FRAMEWORK_OTHER + 1,
+                300
+            )
+            .unwrap_err(),
+            RegistryError::InvalidFramework.into()
+        );
+        assert_eq!(card.description_hash, [2; 32]);
+    }
+}
@@ -0,0 +1,1227 @@
+//! model_module module for model registry
+//!
+//! This module provides registered AI models and their lifecycle.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+use mcp_token::program::McpToken;
+use mcp_token::staking_module::StakeAccount;
+
+use crate::errors_module::RegistryError;
+use crate::registry_config_module::RegistryConfig;
+use crate::utils_module::{
+    assert_batch_size, assert_not_globally_paused, get_bump, load_foreign_account, mul_div,
+    validate_bounded_vec, BPS_DENOMINATOR,
+};
+
+/// Dispute window applied to newly registered models, in seconds
+pub const DEFAULT_RESULT_TTL: i64 = 3 * 24 * 60 * 60;
+
+/// Maximum number of loyalty discount tiers per model
+pub const MAX_LOYALTY_TIERS: usize = 4;
+
+/// Largest loyalty discount, keeping the price at or above half of `price_per_inference`
+pub const MAX_LOYALTY_DISCOUNT_BPS: u16 = 5_000;
+
+/// A discount for consumers that have paid for at least `min_inferences` inferences
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct LoyaltyTier {
+    /// Cumulative inferences on the model needed to qualify
+    pub min_inferences: u64,
+
+    /// Reduction of `price_per_inference` in basis points
+    pub discount_bps: u16,
+}
+
+/// Kind of model, used to filter models in discovery.
+///
+/// Stored as a single byte; instruction arguments with an unknown variant fail
+/// to deserialize, so only the categories below can be registered.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ModelCategory {
+    /// Text generation and understanding
+    Language,
+    /// Image and video models
+    Vision,
+    /// Speech and audio models
+    Audio,
+    /// Models combining several modalities
+    Multimodal,
+    /// Embedding and retrieval models
+    Embedding,
+    /// Anything else
+    Other,
+}
+
+/// Lifecycle of a model
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ModelStatus {
+    /// Model accepts inference payments
+    Active,
+    /// Model has been permanently withdrawn
+    Retired,
+}
+
+/// Model state account
+#[account]
+pub struct Model {
+    /// The owner that can update this account
+    pub owner: Pubkey,
+
+    /// Unique model identifier
+    pub model_id: u64,
+
+    /// Mint inference is paid in
+    pub mint: Pubkey,
+
+    /// Price of a single inference in base units of `mint`
+    pub price_per_inference: u64,
+
+    /// Kind of model. Stored at a fixed offset (`CATEGORY_OFFSET`, byte 88) so
+    /// clients can filter with memcmp on the variant index.
+    pub category: ModelCategory,
+
+    /// Status of the model
+    pub status: ModelStatus,
+
+    /// Stake backing the registration, locked while the model is registered
+    pub stake_account: Pubkey,
+
+    /// Temporarily blocks inference payments without retiring the model
+    pub paused: bool,
+
+    /// Creation time
+    pub created_at: i64,
+
+    /// Hash of the input schema, checked by clients against the schema registry
+    pub input_schema_hash: [u8; 32],
+
+    /// Hash of the output schema, checked by clients against the schema registry
+    pub output_schema_hash: [u8; 32],
+
+    /// Seconds a committed result stays open to dispute before it can be finalized
+    pub result_ttl: i64,
+
+    /// Fewest inferences a single `pay_for_inference` may buy; zero disables the minimum
+    pub min_inferences_per_purchase: u64,
+
+    /// Number of the latest registered version; zero before the first
+    pub version: u32,
+
+    /// Number of `ModelVersion` accounts currently open
+    pub version_count: u32,
+
+    /// Repeat consumer discount tiers
+    pub loyalty_tiers: Vec<LoyaltyTier>,
+
+    /// Time of the latest inference payment, or registration before the first
+    pub last_inference_at: i64,
+
+    /// Sum of all consumer ratings
+    pub rating_sum: u64,
+
+    /// Number of consumer ratings
+    pub rating_count: u64,
+
+    /// Average rating scaled by 10000, rounded down; zero before the first rating
+    pub avg_rating_bps: u32,
+
+    /// Replacement consumers are pointed to once the model is retired
+    pub successor: Option<Pubkey>,
+
+    /// Registry configuration whose rules the model was admitted under
+    pub registry: Pubkey,
+
+    /// Inference payments are blocked until this time; unlike `paused` it
+    /// lifts by itself
+    pub frozen_until: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Model {
+    pub const LEN: usize = 32
+        + 8
+        + 32
+        + 8
+        + 1
+        + 1
+        + 32
+        + 1
+        + 8
+        + 32
+        + 32
+        + 8
+        + 8
+        + 4
+        + 4
+        + (4 + MAX_LOYALTY_TIERS * (8 + 2))
+        + 8
+        + 8
+        + 8
+        + 4
+        + (1 + 32)
+        + 32
+        + 8
+        + 1;
+
+    /// Offset of `category` in the account data, discriminator included, for
+    /// memcmp filters
+    pub const CATEGORY_OFFSET: usize = 8 + 32 + 8 + 32 + 8;
+
+    /// Check that the model can accept inference payments at `now`
+    pub fn assert_accepting_inference(&self, now: i64) -> Result<()> {
+        require!(
+            self.status == ModelStatus::Active,
+            RegistryError::ModelRetired
+        );
+        require!(!self.paused, RegistryError::ModelPaused);
+        require!(now >= self.frozen_until, RegistryError::ModelFrozen);
+
+        Ok(())
+    }
+
+    /// Price of one inference for a consumer that has paid for `inference_count` before.
+    ///
+    /// The largest discount among the tiers the consumer qualifies for applies.
+    pub fn price_for_consumer(&self, inference_count: u64) -> u64 {
+        let discount_bps = self
+            .loyalty_tiers
+            .iter()
+            .filter(|tier| inference_count >= tier.min_inferences)
+            .map(|tier| tier.discount_bps)
+            .max()
+            .unwrap_or(0);
+        let discount =
+            self.price_per_inference as u128 * discount_bps as u128 / BPS_DENOMINATOR as u128;
+        self.price_per_inference - discount as u64
+    }
+
+    /// Cost of `num_inferences` for a consumer that has paid for
+    /// `inference_count` before, rejecting purchases below the model's minimum
+    /// or costing more than `max_total_cost`
+    pub fn total_cost(
+        &self,
+        num_inferences: u64,
+        inference_count: u64,
+        max_total_cost: u64,
+    ) -> Result<u64> {
+        require!(num_inferences > 0, RegistryError::InvalidInferenceCount);
+        require!(
+            num_inferences >= self.min_inferences_per_purchase,
+            RegistryError::BelowMinimumInferences
+        );
+        let total_cost = self
+            .price_for_consumer(inference_count)
+            .checked_mul(num_inferences)
+            .ok_or(RegistryError::MathOverflow)?;
+        require!(total_cost <= max_total_cost, RegistryError::CostExceeded);
+
+        Ok(total_cost)
+    }
+
+    /// Open the next version and return its number, unless `max_versions`
+    /// are already open; zero disables the cap
+    pub fn add_version(&mut self, max_versions: u32) -> Result<u32> {
+        require!(
+            max_versions == 0 || self.version_count < max_versions,
+            RegistryError::TooManyVersions
+        );
+        self.version = self
+            .version
+            .checked_add(1)
+            .ok_or(RegistryError::MathOverflow)?;
+        self.version_count = self
+            .version_count
+            .checked_add(1)
+            .ok_or(RegistryError::MathOverflow)?;
+
+        Ok(self.version)
+    }
+
+    /// Whether the model is unretired and has had no inference for more than
+    /// `stale_period` at `now`
+    pub fn is_stale(&self, now: i64, stale_period: i64) -> bool {
+        self.status != ModelStatus::Retired
+            && now.saturating_sub(self.last_inference_at) > stale_period
+    }
+
+    /// Retire the model stored at `key`, pointing consumers to `successor`,
+    /// which must be another active model of the same category
+    pub fn retire(&mut self, key: &Pubkey, successor: Option<(Pubkey, &Model)>) -> Result<()> {
+        if let Some((successor_key, successor)) = successor {
+            require!(
+                successor_key != *key
+                    && successor.status == ModelStatus::Active
+                    && successor.category == self.category,
+                RegistryError::InvalidSuccessor
+            );
+        }
+        self.status = ModelStatus::Retired;
+        self.successor = successor.map(|(successor_key, _)| successor_key);
+
+        Ok(())
+    }
+
+    /// Free the slot of a pruned version
+    pub fn remove_version(&mut self) -> Result<()> {
+        self.version_count = self
+            .version_count
+            .checked_sub(1)
+            .ok_or(RegistryError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Add a rating and recompute `avg_rating_bps`
+    pub fn record_rating(&mut self, rating: u8) -> Result<()> {
+        self.rating_sum = self
+            .rating_sum
+            .checked_add(rating as u64)
+            .ok_or(RegistryError::MathOverflow)?;
+        self.rating_count = self
+            .rating_count
+            .checked_add(1)
+            .ok_or(RegistryError::MathOverflow)?;
+        self.avg_rating_bps = self.average_rating_bps()?;
+
+        Ok(())
+    }
+
+    /// Average rating scaled by `BPS_DENOMINATOR`, rounded down
+    pub fn average_rating_bps(&self) -> Result<u32> {
+        if self.rating_count == 0 {
+            return Ok(0);
+        }
+        let average = mul_div(self.rating_sum, BPS_DENOMINATOR, self.rating_count)?;
+
+        u32::try_from(average).map_err(|_| error!(RegistryError::MathOverflow))
+    }
+}
+
+/// Emitted when a model is retired
+#[event]
+pub struct ModelRetired {
+    pub model: Pubkey,
+    pub last_inference_at: i64,
+    pub successor: Option<Pubkey>,
+}
+
+/// Emitted when a model is frozen or its freeze is extended
+#[event]
+pub struct ModelFrozen {
+    pub model: Pubkey,
+    pub frozen_until: i64,
+    pub authority: Pubkey,
+}
+
+/// Emitted when a model's schema hashes change
+#[event]
+pub struct SchemaUpdated {
+    pub model: Pubkey,
+    pub input_schema_hash: [u8; 32],
+    pub output_schema_hash: [u8; 32],
+}
+
+/// Register a new Model.
+///
+/// The owner must have at least `min_model_stake` MCP tokens staked in the
+/// registry's stake pool. The stake is locked to the model until it is retired
+/// and released with `release_model_stake`, so it cannot be withdrawn while it
+/// backs the registration, and disputes resolved with a refund slash it.
+pub fn register_model(
+    ctx: Context<RegisterModel>,
+    model_id: u64,
+    price_per_inference: u64,
+    category: ModelCategory,
+    input_schema_hash: [u8; 32],
+    output_schema_hash: [u8; 32],
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let stake_account =
+        load_foreign_account::<StakeAccount>(&ctx.accounts.stake_account, &mcp_token::ID)?;
+    require_keys_eq!(
+        stake_account.owner,
+        ctx.accounts.owner.key(),
+        RegistryError::InsufficientStake
+    );
+    ctx.accounts
+        .registry_config
+        .assert_model_allowed(price_per_inference, &stake_account)?;
+
+    let bump = get_bump(&ctx.bumps, "model")?;
+    let model_id_bytes = model_id.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[b"model".as_ref(), model_id_bytes.as_ref(), &[bump]];
+    mcp_token::cpi::lock_stake(CpiContext::new_with_signer(
+        ctx.accounts.mcp_token_program.to_account_info(),
+        mcp_token::cpi::accounts::LockStake {
+            owner: ctx.accounts.owner.to_account_info(),
+            global_config: ctx.accounts.global_config.to_account_info(),
+            locker: ctx.accounts.model.to_account_info(),
+            stake_account: ctx.accounts.stake_account.to_account_info(),
+        },
+        &[signer_seeds],
+    ))?;
+
+    let model = &mut ctx.accounts.model;
+    model.owner = ctx.accounts.owner.key();
+    model.model_id = model_id;
+    model.mint = ctx.accounts.mint.key();
+    model.price_per_inference = price_per_inference;
+    model.category = category;
+    model.status = ModelStatus::Active;
+    model.stake_account = ctx.accounts.stake_account.key();
+    model.paused = false;
+    model.created_at = Clock::get()?.unix_timestamp;
+    model.last_inference_at = model.created_at;
+    model.input_schema_hash = input_schema_hash;
+    model.output_schema_hash = output_schema_hash;
+    model.result_ttl = DEFAULT_RESULT_TTL;
+    model.min_inferences_per_purchase = 0;
+    model.version = 0;
+    model.version_count = 0;
+    model.loyalty_tiers = Vec::new();
+    model.rating_sum = 0;
+    model.rating_count = 0;
+    model.avg_rating_bps = 0;
+    model.successor = None;
+    model.registry = ctx.accounts.registry_config.key();
+    model.frozen_until = 0;
+    model.bump = bump;
+
+    emit!(SchemaUpdated {
+        model: model.key(),
+        input_schema_hash,
+        output_schema_hash,
+    });
+
+    Ok(())
+}
+
+/// Update the input and output schema hashes of a Model
+pub fn set_model_schemas(
+    ctx: Context<UpdateModel>,
+    input_schema_hash: [u8; 32],
+    output_schema_hash: [u8; 32],
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let model = &mut ctx.accounts.model;
+    model.input_schema_hash = input_schema_hash;
+    model.output_schema_hash = output_schema_hash;
+
+    emit!(SchemaUpdated {
+        model: model.key(),
+        input_schema_hash,
+        output_schema_hash,
+    });
+
+    Ok(())
+}
+
+/// Set how long committed results stay open to dispute before finalization
+pub fn set_result_ttl(ctx: Context<UpdateModel>, result_ttl: i64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    require!(result_ttl > 0, RegistryError::InvalidTtl);
+    ctx.accounts.model.result_ttl = result_ttl;
+
+    Ok(())
+}
+
+/// Set the fewest inferences a consumer may pay for at once
+pub fn set_min_inferences_per_purchase(
+    ctx: Context<UpdateModel>,
+    min_inferences_per_purchase: u64,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts.model.min_inferences_per_purchase = min_inferences_per_purchase;
+
+    Ok(())
+}
+
+/// Replace the repeat consumer discount tiers of a Model.
+///
+/// No discount may exceed `MAX_LOYALTY_DISCOUNT_BPS`, so loyal consumers still
+/// pay at least half the list price.
+pub fn set_loyalty_tiers(ctx: Context<UpdateModel>, tiers: Vec<LoyaltyTier>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    validate_bounded_vec(&tiers, MAX_LOYALTY_TIERS)?;
+    require!(
+        tiers
+            .iter()
+            .all(|tier| tier.discount_bps <= MAX_LOYALTY_DISCOUNT_BPS),
+        RegistryError::InvalidDiscount
+    );
+    ctx.accounts.model.loyalty_tiers = tiers;
+
+    Ok(())
+}
+
+/// Permanently retire a Model, optionally pointing consumers to a successor.
+///
+/// The successor must be another active model of the same category.
+pub fn retire_model(ctx: Context<RetireModel>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let successor = ctx
+        .accounts
+        .successor
+        .as_ref()
+        .map(|successor| (successor.key(), &**successor));
+    let model = &mut ctx.accounts.model;
+    let model_key = model.key();
+    model.retire(&model_key, successor)?;
+
+    emit!(ModelRetired {
+        model: model_key,
+        last_inference_at: model.last_inference_at,
+        successor: model.successor,
+    });
+
+    Ok(())
+}
+
+/// Release the stake lock of a retired Model so its owner can unstake
+pub fn release_model_stake(ctx: Context<ReleaseModelStake>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let model = &ctx.accounts.model;
+    let model_id_bytes = model.model_id.to_le_bytes();
+    let bump = [model.bump];
+    let signer_seeds: &[&[u8]] = &[b"model".as_ref(), model_id_bytes.as_ref(), &bump];
+    mcp_token::cpi::unlock_stake(CpiContext::new_with_signer(
+        ctx.accounts.mcp_token_program.to_account_info(),
+        mcp_token::cpi::accounts::UnlockStake {
+            locker: model.to_account_info(),
+            global_config: ctx.accounts.global_config.to_account_info(),
+            stake_account: ctx.accounts.stake_account.to_account_info(),
+        },
+        &[signer_seeds],
+    ))
+}
+
+/// Retire every model passed in remaining accounts that has gone unused for
+/// longer than the registry's `stale_period`.
+///
+/// Recently used and already retired models are skipped, so maintainers can
+/// pass a mixed batch; every model must belong to the admin's registry.
+pub fn bulk_retire<'info>(ctx: Context<'_, '_, '_, 'info, BulkRetire<'info>>) -> Result<()> {
+    let stale_period = ctx.accounts.registry_config.stale_period;
+    require!(stale_period > 0, RegistryError::InvalidTtl);
+    assert_batch_size(ctx.remaining_accounts.len())?;
+    let now = Clock::get()?.unix_timestamp;
+
+    for info in ctx.remaining_accounts {
+        let mut model = Account::<Model>::try_from(info)?;
+        require_keys_eq!(
+            model.registry,
+            ctx.accounts.registry_config.key(),
+            RegistryError::RegistryMismatch
+        );
+        if !model.is_stale(now, stale_period) {
+            continue;
+        }
+        model.status = ModelStatus::Retired;
+        model.exit(ctx.program_id)?;
+
+        emit!(ModelRetired {
+            model: info.key(),
+            last_inference_at: model.last_inference_at,
+            successor: None,
+        });
+    }
+
+    Ok(())
+}
+
+/// Move a Model to another registry configuration.
+///
+/// Both the model owner and the admin of the target registry must sign, and
+/// the model must meet the target's stake and price rules.
+pub fn reassign_registry(ctx: Context<ReassignRegistry>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let stake_account =
+        load_foreign_account::<StakeAccount>(&ctx.accounts.stake_account, &mcp_token::ID)?;
+    require_keys_eq!(
+        stake_account.owner,
+        ctx.accounts.owner.key(),
+        RegistryError::InsufficientStake
+    );
+    let model = &mut ctx.accounts.model;
+    ctx.accounts
+        .new_registry
+        .assert_model_allowed(model.price_per_inference, &stake_account)?;
+    model.registry = ctx.accounts.new_registry.key();
+
+    Ok(())
+}
+
+/// Temporarily stop a Model from accepting inference payments
+pub fn pause_model(ctx: Context<UpdateModel>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let model = &mut ctx.accounts.model;
+    require!(
+        model.status == ModelStatus::Active,
+        RegistryError::ModelRetired
+    );
+    model.paused = true;
+
+    Ok(())
+}
+
+/// Block inference payments on a Model until `until`, after which they resume
+/// without another transaction.
+///
+/// Callable by the model owner or the registry admin. A freeze can only be
+/// extended, so neither can cut short a freeze set by the other; `pause_model`
+/// remains the way to stop a model indefinitely. `until` can be at most the
+/// registry's `max_freeze_duration` away, so an admin cannot use a freeze to
+/// lock a model out for good.
+pub fn freeze_model_until(ctx: Context<FreezeModel>, until: i64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts
+        .registry_config
+        .assert_freeze_allowed(until, Clock::get()?.unix_timestamp)?;
+    let model = &mut ctx.accounts.model;
+    model.frozen_until = model.frozen_until.max(until);
+
+    emit!(ModelFrozen {
+        model: model.key(),
+        frozen_until: model.frozen_until,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
+
+/// Resume inference payments for a paused Model
+pub fn unpause_model(ctx: Context<UpdateModel>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let model = &mut ctx.accounts.model;
+    require!(
+        model.status == ModelStatus::Active,
+        RegistryError::ModelRetired
+    );
+    model.paused = false;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+#[instruction(model_id: u64)]
+pub struct RegisterModel<'info> {
+    /// The owner registering the model
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Model::LEN,
+        seeds = [b"model".as_ref(), model_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// CHECK: The owner's MCP token stake, verified in the handler and locked
+    /// through the MCP token program
+    #[account(mut)]
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// Mint inference is paid in
+    pub mint: Account<'info, Mint>,
+
+    /// MCP token program
+    pub mcp_token_program: Program<'info, McpToken>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateModel<'info> {
+    /// The owner of the model
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+    )]
+    pub model: Account<'info, Model>,
+}
+
+/// Account validation for retirement
+#[derive(Accounts)]
+pub struct RetireModel<'info> {
+    /// The owner of the model
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model to retire
+    #[account(
+        mut,
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The replacement model, if any
+    #[account(
+        seeds = [b"model".as_ref(), successor.model_id.to_le_bytes().as_ref()],
+        bump = successor.bump,
+    )]
+    pub successor: Option<Account<'info, Model>>,
+}
+
+/// Account validation for releasing a retired model's stake
+#[derive(Accounts)]
+pub struct ReleaseModelStake<'info> {
+    /// The owner of the model
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The retired model
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+        has_one = stake_account,
+        constraint = model.status == ModelStatus::Retired @ RegistryError::ModelNotRetired,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// CHECK: The stake backing the model, validated by the MCP token program
+    #[account(mut)]
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// MCP token program
+    pub mcp_token_program: Program<'info, McpToken>,
+}
+
+/// Account validation for a temporary freeze
+#[derive(Accounts)]
+pub struct FreezeModel<'info> {
+    /// The model owner or the registry admin
+    #[account(
+        constraint = authority.key() == model.owner
+            || authority.key() == registry_config.admin @ RegistryError::NotModelOwnerOrAdmin,
+    )]
+    pub authority: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model to freeze
+    #[account(
+        mut,
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+}
+
+/// Account validation for moving a model between registries
+#[derive(Accounts)]
+pub struct ReassignRegistry<'info> {
+    /// The owner of the model
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The admin of the target registry
+    pub admin: Signer<'info>,
+
+    /// The model to move
+    #[account(
+        mut,
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+        has_one = stake_account,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The target registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), new_registry.registry_id.to_le_bytes().as_ref()],
+        bump = new_registry.bump,
+        has_one = admin,
+    )]
+    pub new_registry: Account<'info, RegistryConfig>,
+
+    /// CHECK: The stake backing the model, verified in the handler
+    pub stake_account: UncheckedAccount<'info>,
+}
+
+/// Account validation for bulk retirement
+#[derive(Accounts)]
+pub struct BulkRetire<'info> {
+    /// The registry admin
+    pub admin: Signer<'info>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        has_one = admin,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::registry_config_module::tests::registry_config;
+    use crate::utils_module::tests::global_config_data;
+    use anchor_lang::error::ErrorCode;
+    use mcp_token::utils_module::MAX_BATCH;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// An active model priced at 100 per inference
+    pub(crate) fn model() -> Model {
+        Model {
+            owner: Pubkey::new_unique(),
+            model_id: 1,
+            mint: Pubkey::new_unique(),
+            price_per_inference: 100,
+            category: ModelCategory::Language,
+            status: ModelStatus::Active,
+            stake_account: Pubkey::new_unique(),
+            paused: false,
+            created_at: 0,
+            input_schema_hash: [0; 32],
+            output_schema_hash: [0; 32],
+            result_ttl: DEFAULT_RESULT_TTL,
+            min_inferences_per_purchase: 0,
+            version: 0,
+            version_count: 0,
+            loyalty_tiers: Vec::new(),
+            last_inference_at: 0,
+            rating_sum: 0,
+            rating_count: 0,
+            avg_rating_bps: 0,
+            successor: None,
+            registry: Pubkey::new_unique(),
+            frozen_until: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn paused_model_accepts_payment_once_unpaused() {
+        let mut model = model();
+        model.paused = true;
+        assert_eq!(
+            model.assert_accepting_inference(0).unwrap_err(),
+            RegistryError::ModelPaused.into()
+        );
+
+        model.paused = false;
+        model.assert_accepting_inference(0).unwrap();
+
+        model.status = ModelStatus::Retired;
+        assert_eq!(
+            model.assert_accepting_inference(0).unwrap_err(),
+            RegistryError::ModelRetired.into()
+        );
+    }
+
+    #[test]
+    fn price_bump_after_quote_exceeds_cost_cap() {
+        let mut model = model();
+        let quote = model.total_cost(3, 0, u64::MAX).unwrap();
+        assert_eq!(quote, 300);
+
+        model.price_per_inference = 101;
+        assert_eq!(
+            model.total_cost(3, 0, quote).unwrap_err(),
+            RegistryError::CostExceeded.into()
+        );
+        assert_eq!(model.total_cost(3, 0, 303).unwrap(), 303);
+    }
+
+    /// Run `handler` on `model` updated by `signer`, with the protocol-wide
+    /// pause set or not, after validating the `UpdateModel` accounts
+    fn update_model(
+        model: &Model,
+        signer: &Pubkey,
+        global_paused: bool,
+        handler: impl FnOnce(Context<UpdateModel>) -> Result<()>,
+    ) -> Result<()> {
+        let program_id = crate::ID;
+        let (model_key, bump) = Pubkey::find_program_address(
+            &[b"model", model.model_id.to_le_bytes().as_ref()],
+            &program_id,
+        );
+        let (global_config_key, _) =
+            Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &mcp_token::ID);
+        let mut model = model.clone();
+        model.bump = bump;
+        let mut data = Vec::new();
+        model.try_serialize(&mut data)?;
+        let mut global_config_data = global_config_data(global_paused);
+        let (mut signer_lamports, mut config_lamports, mut model_lamports) = (0, 0, 0);
+        let mut signer_data = [];
+        let system_program = System::id();
+        let accounts = [
+            AccountInfo::new(
+                signer,
+                true,
+                false,
+                &mut signer_lamports,
+                &mut signer_data,
+                &system_program,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &global_config_key,
+                false,
+                false,
+                &mut config_lamports,
+                &mut global_config_data,
+                &mcp_token::ID,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &model_key,
+                false,
+                true,
+                &mut model_lamports,
+                &mut data,
+                &program_id,
+                false,
+                0,
+            ),
+        ];
+
+        let mut bumps = BTreeMap::new();
+        let mut update_accounts = UpdateModel::try_accounts(
+            &program_id,
+            &mut &accounts[..],
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        handler(Context::new(&program_id, &mut update_accounts, &[], bumps))
+    }
+
+    #[test]
+    fn only_owner_updates_schemas() {
+        let model = model();
+
+        update_model(&model, &model.owner, false, |_| Ok(())).unwrap();
+        assert_eq!(
+            update_model(&model, &Pubkey::new_unique(), false, |_| Ok(())).unwrap_err(),
+            ErrorCode::ConstraintHasOne.into()
+        );
+    }
+
+    #[test]
+    fn loyalty_tiers_over_bound_rejected() {
+        let model = model();
+        let tiers = |count: usize| {
+            (0..count)
+                .map(|i| LoyaltyTier {
+                    min_inferences: i as u64 + 1,
+                    discount_bps: 100,
+                })
+                .collect()
+        };
+
+        update_model(&model, &model.owner, false, |ctx| {
+            set_loyalty_tiers(ctx, tiers(MAX_LOYALTY_TIERS))
+        })
+        .unwrap();
+        assert_eq!(
+            update_model(&model, &model.owner, false, |ctx| {
+                set_loyalty_tiers(ctx, tiers(MAX_LOYALTY_TIERS + 1))
+            })
+            .unwrap_err(),
+            RegistryError::TooManyEntries.into()
+        );
+    }
+
+    #[test]
+    fn global_pause_blocks_model_updates() {
+        let model = model();
+
+        update_model(&model, &model.owner, false, |ctx| set_result_ttl(ctx, 60)).unwrap();
+        assert_eq!(
+            update_model(&model, &model.owner, true, |ctx| set_result_ttl(ctx, 60)).unwrap_err(),
+            RegistryError::ProtocolPaused.into()
+        );
+        assert_eq!(
+            update_model(&model, &model.owner, true, pause_model).unwrap_err(),
+            RegistryError::ProtocolPaused.into()
+        );
+    }
+
+    #[test]
+    fn models_filtered_by_category_byte() {
+        let mut vision = model();
+        vision.category = ModelCategory::Vision;
+        let accounts: Vec<Vec<u8>> = [model(), vision]
+            .iter()
+            .map(|model| {
+                let mut data = Vec::new();
+                model.try_serialize(&mut data).unwrap();
+                data
+            })
+            .collect();
+
+        let filter = |category: ModelCategory| {
+            accounts
+                .iter()
+                .filter(|data| data[Model::CATEGORY_OFFSET] == category as u8)
+                .count()
+        };
+        assert_eq!(filter(ModelCategory::Language), 1);
+        assert_eq!(filter(ModelCategory::Vision), 1);
+        assert_eq!(filter(ModelCategory::Audio), 0);
+    }
+
+    #[test]
+    fn purchase_below_minimum_inferences_rejected() {
+        let mut model = model();
+        model.min_inferences_per_purchase = 5;
+        assert_eq!(
+            model.total_cost(4, 0, u64::MAX).unwrap_err(),
+            RegistryError::BelowMinimumInferences.into()
+        );
+        assert_eq!(model.total_cost(5, 0, u64::MAX).unwrap(), 500);
+
+        model.min_inferences_per_purchase = 0;
+        assert_eq!(model.total_cost(1, 0, u64::MAX).unwrap(), 100);
+    }
+
+    #[test]
+    fn version_cap_reached_until_old_version_pruned() {
+        let mut model = model();
+        assert_eq!(model.add_version(2).unwrap(), 1);
+        assert_eq!(model.add_version(2).unwrap(), 2);
+        assert_eq!(
+            model.add_version(2).unwrap_err(),
+            RegistryError::TooManyVersions.into()
+        );
+
+        model.remove_version().unwrap();
+        assert_eq!(model.add_version(2).unwrap(), 3);
+        assert_eq!(model.version_count, 2);
+    }
+
+    #[test]
+    fn repeat_consumer_past_threshold_pays_discounted_rate() {
+        let mut model = model();
+        model.loyalty_tiers = vec![
+            LoyaltyTier {
+                min_inferences: 10,
+                discount_bps: 1_000,
+            },
+            LoyaltyTier {
+                min_inferences: 50,
+                discount_bps: 2_500,
+            },
+        ];
+
+        assert_eq!(model.total_cost(2, 9, u64::MAX).unwrap(), 200);
+        assert_eq!(model.total_cost(2, 10, u64::MAX).unwrap(), 180);
+        assert_eq!(model.total_cost(2, 50, u64::MAX).unwrap(), 150);
+    }
+
+    #[test]
+    fn mixed_batch_retires_only_stale_models() {
+        let mut recent = model();
+        recent.last_inference_at = 900;
+        let mut retired = model();
+        retired.status = ModelStatus::Retired;
+        let batch = [model(), recent, retired];
+
+        let stale: Vec<bool> = batch
+            .iter()
+            .map(|model| model.is_stale(1_000, 500))
+            .collect();
+        assert_eq!(stale, [true, false, false]);
+    }
+
+    #[test]
+    fn average_rating_updated_after_each_rating() {
+        let mut model = model();
+        assert_eq!(model.average_rating_bps().unwrap(), 0);
+
+        for (rating, expected) in [(5, 50_000), (4, 45_000), (4, 43_333)] {
+            model.record_rating(rating).unwrap();
+            assert_eq!(model.avg_rating_bps, expected);
+        }
+        assert_eq!((model.rating_sum, model.rating_count), (13, 3));
+    }
+
+    #[test]
+    fn retire_stores_valid_successor_and_rejects_invalid() {
+        let key = Pubkey::new_unique();
+        let successor_key = Pubkey::new_unique();
+        let mut vision = model();
+        vision.category = ModelCategory::Vision;
+
+        for successor in [(key, model()), (successor_key, vision)] {
+            let mut target = model();
+            assert_eq!(
+                target
+                    .retire(&key, Some((successor.0, &successor.1)))
+                    .unwrap_err(),
+                RegistryError::InvalidSuccessor.into()
+            );
+            assert!(target.status == ModelStatus::Active);
+        }
+
+        let mut target = model();
+        target
+            .retire(&key, Some((successor_key, &model())))
+            .unwrap();
+        assert!(target.status == ModelStatus::Retired);
+        assert_eq!(target.successor, Some(successor_key));
+    }
+
+    #[test]
+    fn oversized_batch_rejected_before_any_retirement() {
+        let program_id = crate::ID;
+        let (config_key, bump) = Pubkey::find_program_address(
+            &[b"registry-config", 0u64.to_le_bytes().as_ref()],
+            &program_id,
+        );
+        let mut config = registry_config();
+        config.stale_period = 1;
+        config.bump = bump;
+        let admin = config.admin;
+        let mut config_data = Vec::new();
+        config.try_serialize(&mut config_data).unwrap();
+        let (mut admin_lamports, mut config_lamports) = (0, 0);
+        let mut admin_data = [];
+        let system_program = System::id();
+        let accounts = [
+            AccountInfo::new(
+                &admin,
+                true,
+                false,
+                &mut admin_lamports,
+                &mut admin_data,
+                &system_program,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &config_key,
+                false,
+                false,
+                &mut config_lamports,
+                &mut config_data,
+                &program_id,
+                false,
+                0,
+            ),
+        ];
+        let mut bumps = BTreeMap::new();
+        let mut bulk_retire_accounts = BulkRetire::try_accounts(
+            &program_id,
+            &mut &accounts[..],
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .unwrap();
+
+        let mut stale = model();
+        stale.registry = config_key;
+        let mut model_data = Vec::new();
+        stale.try_serialize(&mut model_data).unwrap();
+        let keys: Vec<Pubkey> = (0..=MAX_BATCH).map(|_| Pubkey::new_unique()).collect();
+        let mut lamports = [0; MAX_BATCH + 1];
+        let mut data = vec![model_data.clone(); MAX_BATCH + 1];
+        let models: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, true, lamports, data, &program_id, false, 0)
+            })
+            .collect();
+
+        assert_eq!(
+            bulk_retire(Context::new(
+                &program_id,
+                &mut bulk_retire_accounts,
+                &models,
+                bumps
+            ))
+            .unwrap_err(),
+            RegistryError::BatchTooLarge.into()
+        );
+        assert!(models
+            .iter()
+            .all(|info| info.data.borrow()[..] == model_data[..]));
+    }
+}
@@ -0,0 +1,165 @@
+//! model_version_module module for model registry
+//!
+//! This module provides versions of a registered model. Each version is its
+//! own PDA numbered from one; the registry caps how many a model keeps open at
+//! once, and owners prune old versions to make room for new ones.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+use mcp_token::staking_module::StakeAccount;
+
+use crate::errors_module::RegistryError;
+use crate::model_module::Model;
+use crate::registry_config_module::RegistryConfig;
+use crate::utils_module::{assert_not_globally_paused, get_bump, load_foreign_account};
+
+/// ModelVersion state account
+#[account]
+pub struct ModelVersion {
+    /// The model this is a version of
+    pub model: Pubkey,
+
+    /// Version number, starting at one
+    pub version: u32,
+
+    /// Hash of the model artifact
+    pub artifact_hash: [u8; 32],
+
+    /// Registration time
+    pub created_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ModelVersion {
+    pub const LEN: usize = 32 + 4 + 32 + 8 + 1;
+}
+
+/// Register the next version of a Model.
+///
+/// The stake backing the model must still meet the registry's
+/// `min_model_stake`. Fails with `TooManyVersions` once the model has
+/// `max_versions` open; prune an old version first.
+pub fn register_new_version(
+    ctx: Context<RegisterNewVersion>,
+    artifact_hash: [u8; 32],
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let stake_account =
+        load_foreign_account::<StakeAccount>(&ctx.accounts.stake_account, &mcp_token::ID)?;
+    ctx.accounts
+        .registry_config
+        .assert_model_allowed(ctx.accounts.model.price_per_inference, &stake_account)?;
+    let max_versions = ctx.accounts.registry_config.max_versions;
+    let model = &mut ctx.accounts.model;
+    let version = model.add_version(max_versions)?;
+
+    let model_version = &mut ctx.accounts.model_version;
+    model_version.model = model.key();
+    model_version.version = version;
+    model_version.artifact_hash = artifact_hash;
+    model_version.created_at = Clock::get()?.unix_timestamp;
+    model_version.bump = get_bump(&ctx.bumps, "model_version")?;
+
+    Ok(())
+}
+
+/// Close a ModelVersion, returning its rent to the owner.
+///
+/// Version numbers are never reused, so pruning only frees a slot under the cap.
+pub fn prune_version(ctx: Context<PruneVersion>, _version: u32) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts.model.remove_version()
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct RegisterNewVersion<'info> {
+    /// The owner of the model
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model being versioned
+    #[account(
+        mut,
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+        has_one = stake_account,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// CHECK: The stake backing the model, verified in the handler
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ModelVersion::LEN,
+        seeds = [
+            b"model-version".as_ref(),
+            model.key().as_ref(),
+            (model.version + 1).to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub model_version: Account<'info, ModelVersion>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for pruning
+#[derive(Accounts)]
+#[instruction(version: u32)]
+pub struct PruneVersion<'info> {
+    /// The owner of the model
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model the version belongs to
+    #[account(
+        mut,
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The version to close
+    #[account(
+        mut,
+        seeds = [b"model-version".as_ref(), model.key().as_ref(), version.to_le_bytes().as_ref()],
+        bump = model_version.bump,
+        has_one = model,
+        close = owner,
+    )]
+    pub model_version: Account<'info, ModelVersion>,
+}
@@ -0,0 +1,135 @@
+//! rating_module module for model registry
+//!
+//! This module provides consumer ratings of models. A consumer can rate a
+//! model once per settled inference request; the model keeps the running sum
+//! and count along with the average, so clients do not have to divide.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::RegistryError;
+use crate::model_module::Model;
+use crate::request_module::{InferenceRequest, RequestStatus};
+use crate::utils_module::{assert_not_globally_paused, get_bump};
+use crate::vaults_module::INFERENCE_REQUEST_SEED;
+
+/// Lowest rating a consumer can give
+pub const MIN_RATING: u8 = 1;
+
+/// Highest rating a consumer can give
+pub const MAX_RATING: u8 = 5;
+
+/// ModelRating state account, one per rated request
+#[account]
+pub struct ModelRating {
+    /// The model rated
+    pub model: Pubkey,
+
+    /// The consumer that gave the rating
+    pub consumer: Pubkey,
+
+    /// The settled request the rating is for
+    pub inference_request: Pubkey,
+
+    /// Rating from `MIN_RATING` to `MAX_RATING`
+    pub rating: u8,
+
+    /// Time of the rating
+    pub rated_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ModelRating {
+    pub const LEN: usize = 32 + 32 + 32 + 1 + 8 + 1;
+}
+
+/// Emitted when a model is rated
+#[event]
+pub struct ModelRated {
+    pub model: Pubkey,
+    pub rating: u8,
+    pub avg_rating_bps: u32,
+}
+
+/// Rate a model for a settled request and update its average rating
+pub fn rate_model(ctx: Context<RateModel>, rating: u8) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    require!(
+        (MIN_RATING..=MAX_RATING).contains(&rating),
+        RegistryError::InvalidRating
+    );
+
+    let model = &mut ctx.accounts.model;
+    model.record_rating(rating)?;
+
+    let model_rating = &mut ctx.accounts.model_rating;
+    model_rating.model = model.key();
+    model_rating.consumer = ctx.accounts.consumer.key();
+    model_rating.inference_request = ctx.accounts.inference_request.key();
+    model_rating.rating = rating;
+    model_rating.rated_at = Clock::get()?.unix_timestamp;
+    model_rating.bump = get_bump(&ctx.bumps, "model_rating")?;
+
+    emit!(ModelRated {
+        model: model.key(),
+        rating,
+        avg_rating_bps: model.avg_rating_bps,
+    });
+
+    Ok(())
+}
+
+/// Account validation for rating
+#[derive(Accounts)]
+pub struct RateModel<'info> {
+    /// The consumer that paid for the request
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model rated
+    #[account(
+        mut,
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The settled request being rated
+    #[account(
+        seeds = [
+            INFERENCE_REQUEST_SEED,
+            model.key().as_ref(),
+            inference_request.request_id.to_le_bytes().as_ref(),
+        ],
+        bump = inference_request.bump,
+        has_one = model,
+        has_one = consumer,
+        constraint = inference_request.status == RequestStatus::Settled
+            @ RegistryError::InvalidRequestStatus,
+    )]
+    pub inference_request: Account<'info, InferenceRequest>,
+
+    /// The account to initialize, preventing a second rating of the request
+    #[account(
+        init,
+        payer = consumer,
+        space = 8 + ModelRating::LEN,
+        seeds = [b"rating".as_ref(), inference_request.key().as_ref()],
+        bump,
+    )]
+    pub model_rating: Account<'info, ModelRating>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
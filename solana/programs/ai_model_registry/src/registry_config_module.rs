@@ -0,0 +1,516 @@
+//! registry_config_module module for model registry
+//!
+//! This module provides the configuration accounts of model registries. Each
+//! registry is a RegistryConfig PDA derived from its `registry_id`, and every
+//! model records the registry it was registered in.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::staking_module::StakeAccount;
+
+use crate::errors_module::RegistryError;
+use crate::utils_module::{
+    get_bump, validate_bounded_vec, BPS_DENOMINATOR, MAX_TRUSTED_EVALUATORS,
+};
+
+/// Longest a model can be frozen for, in seconds, until the admin changes it
+pub const DEFAULT_MAX_FREEZE_DURATION: i64 = 30 * 24 * 60 * 60;
+
+/// RegistryConfig state account
+#[account]
+pub struct RegistryConfig {
+    /// The admin that can update this account
+    pub admin: Pubkey,
+
+    /// Identifier the configuration's address is derived from
+    pub registry_id: u64,
+
+    /// Minimum MCP tokens an owner must have staked to register a model
+    pub min_model_stake: u64,
+
+    /// MCP token stake pool model stakes must belong to
+    pub stake_pool: Pubkey,
+
+    /// Once set, no configuration can be changed again
+    pub config_frozen: bool,
+
+    /// When set, only `trusted_evaluators` can post benchmark results
+    pub evaluator_allowlist_enabled: bool,
+
+    /// Evaluators allowed to attest benchmark results
+    pub trusted_evaluators: Vec<Pubkey>,
+
+    /// Authority whose `SafetyAttestation`s are accepted
+    pub safety_reviewer: Pubkey,
+
+    /// When set, inference can only be paid for on models the reviewer approved
+    pub require_safety_review: bool,
+
+    /// Most versions a model may keep open at once; zero is unlimited
+    pub max_versions: u32,
+
+    /// Seconds without inference after which `bulk_retire` may retire a model;
+    /// zero disables bulk retirement
+    pub stale_period: i64,
+
+    /// Seconds past a request's expiry during which a result can still be
+    /// committed, at a reduced payout
+    pub late_grace: i64,
+
+    /// Share of a late request's payment the model owner keeps, in basis points
+    pub late_payout_bps: u16,
+
+    /// Lowest `price_per_inference` a model in this registry may charge
+    pub min_price_per_inference: u64,
+
+    /// Longest a `freeze_model_until` may reach past the current time, in seconds
+    pub max_freeze_duration: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl RegistryConfig {
+    pub const LEN: usize = 32
+        + 8
+        + 8
+        + 32
+        + 1
+        + 1
+        + (4 + MAX_TRUSTED_EVALUATORS * 32)
+        + 32
+        + 1
+        + 4
+        + 8
+        + 8
+        + 2
+        + 8
+        + 8
+        + 1;
+
+    /// Check whether `evaluator` may post benchmark results
+    pub fn is_trusted_evaluator(&self, evaluator: &Pubkey) -> bool {
+        !self.evaluator_allowlist_enabled || self.trusted_evaluators.contains(evaluator)
+    }
+
+    /// Check a model's price and its owner's stake against this registry's rules
+    pub fn assert_model_allowed(
+        &self,
+        price_per_inference: u64,
+        stake: &StakeAccount,
+    ) -> Result<()> {
+        require_keys_eq!(stake.pool, self.stake_pool, RegistryError::InvalidStakePool);
+        require!(
+            stake.amount >= self.min_model_stake,
+            RegistryError::InsufficientStake
+        );
+        require!(
+            price_per_inference >= self.min_price_per_inference,
+            RegistryError::PriceBelowFloor
+        );
+
+        Ok(())
+    }
+
+    /// Check that a freeze ending at `until` is in the future and no longer
+    /// than `max_freeze_duration`
+    pub fn assert_freeze_allowed(&self, until: i64, now: i64) -> Result<()> {
+        require!(until > now, RegistryError::InvalidFreezeTime);
+        require!(
+            until.saturating_sub(now) <= self.max_freeze_duration,
+            RegistryError::FreezeTooLong
+        );
+
+        Ok(())
+    }
+}
+
+/// Initialize the RegistryConfig
+pub fn initialize_registry_config(
+    ctx: Context<InitializeRegistryConfig>,
+    registry_id: u64,
+    min_model_stake: u64,
+    stake_pool: Pubkey,
+) -> Result<()> {
+    let config = &mut ctx.accounts.registry_config;
+    config.admin = ctx.accounts.admin.key();
+    config.registry_id = registry_id;
+    config.min_model_stake = min_model_stake;
+    config.stake_pool = stake_pool;
+    config.config_frozen = false;
+    config.evaluator_allowlist_enabled = false;
+    config.trusted_evaluators = Vec::new();
+    config.safety_reviewer = Pubkey::default();
+    config.require_safety_review = false;
+    config.max_versions = 0;
+    config.stale_period = 0;
+    config.late_grace = 0;
+    config.late_payout_bps = 0;
+    config.min_price_per_inference = 0;
+    config.max_freeze_duration = DEFAULT_MAX_FREEZE_DURATION;
+    config.bump = get_bump(&ctx.bumps, "registry_config")?;
+
+    Ok(())
+}
+
+/// Update the minimum stake required to register a model
+pub fn set_min_model_stake(ctx: Context<UpdateRegistryConfig>, min_model_stake: u64) -> Result<()> {
+    let config = &mut ctx.accounts.registry_config;
+    config.min_model_stake = min_model_stake;
+
+    Ok(())
+}
+
+/// Replace the trusted evaluator allowlist and turn enforcement on or off
+pub fn set_trusted_evaluators(
+    ctx: Context<UpdateRegistryConfig>,
+    evaluators: Vec<Pubkey>,
+    enabled: bool,
+) -> Result<()> {
+    validate_bounded_vec(&evaluators, MAX_TRUSTED_EVALUATORS)?;
+
+    let config = &mut ctx.accounts.registry_config;
+    config.trusted_evaluators = evaluators;
+    config.evaluator_allowlist_enabled = enabled;
+
+    Ok(())
+}
+
+/// Set the safety reviewer and turn the safety review requirement on or off.
+///
+/// Attestations by a previous reviewer stop counting once it is replaced.
+pub fn set_safety_review(
+    ctx: Context<UpdateRegistryConfig>,
+    safety_reviewer: Pubkey,
+    require_safety_review: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.registry_config;
+    config.safety_reviewer = safety_reviewer;
+    config.require_safety_review = require_safety_review;
+
+    Ok(())
+}
+
+/// Update the lowest price per inference of newly registered or reassigned models
+pub fn set_min_price_per_inference(
+    ctx: Context<UpdateRegistryConfig>,
+    min_price_per_inference: u64,
+) -> Result<()> {
+    ctx.accounts.registry_config.min_price_per_inference = min_price_per_inference;
+
+    Ok(())
+}
+
+/// Update how many versions a model may keep open at once
+pub fn set_max_versions(ctx: Context<UpdateRegistryConfig>, max_versions: u32) -> Result<()> {
+    ctx.accounts.registry_config.max_versions = max_versions;
+
+    Ok(())
+}
+
+/// Update how long a model must go unused before it counts as stale
+pub fn set_stale_period(ctx: Context<UpdateRegistryConfig>, stale_period: i64) -> Result<()> {
+    require!(stale_period >= 0, RegistryError::InvalidTtl);
+    ctx.accounts.registry_config.stale_period = stale_period;
+
+    Ok(())
+}
+
+/// Update the longest a model can be frozen for
+pub fn set_max_freeze_duration(
+    ctx: Context<UpdateRegistryConfig>,
+    max_freeze_duration: i64,
+) -> Result<()> {
+    require!(max_freeze_duration > 0, RegistryError::InvalidFreezeTime);
+    ctx.accounts.registry_config.max_freeze_duration = max_freeze_duration;
+
+    Ok(())
+}
+
+/// Update the grace window for late results and the payout they earn
+pub fn set_late_fulfillment(
+    ctx: Context<UpdateRegistryConfig>,
+    late_grace: i64,
+    late_payout_bps: u16,
+) -> Result<()> {
+    require!(late_grace >= 0, RegistryError::InvalidTtl);
+    require!(
+        late_payout_bps as u64 <= BPS_DENOMINATOR,
+        RegistryError::InvalidPayoutBps
+    );
+    let config = &mut ctx.accounts.registry_config;
+    config.late_grace = late_grace;
+    config.late_payout_bps = late_payout_bps;
+
+    Ok(())
+}
+
+/// Permanently freeze the configuration.
+///
+/// The admin is expected to be the governance authority; after this call every
+/// setter fails with `ConfigFrozen`, letting deployments commit to fixed
+/// parameters.
+pub fn freeze_config(ctx: Context<UpdateRegistryConfig>) -> Result<()> {
+    ctx.accounts.registry_config.config_frozen = true;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+#[instruction(registry_id: u64)]
+pub struct InitializeRegistryConfig<'info> {
+    /// The admin of the registry
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RegistryConfig::LEN,
+        seeds = [b"registry-config".as_ref(), registry_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateRegistryConfig<'info> {
+    /// The admin of the registry
+    pub admin: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        has_one = admin,
+        constraint = !registry_config.config_frozen @ RegistryError::ConfigFrozen,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    pub(crate) fn registry_config() -> RegistryConfig {
+        RegistryConfig {
+            admin: Pubkey::new_unique(),
+            registry_id: 0,
+            min_model_stake: 500,
+            stake_pool: Pubkey::new_unique(),
+            config_frozen: false,
+            evaluator_allowlist_enabled: false,
+            trusted_evaluators: Vec::new(),
+            safety_reviewer: Pubkey::default(),
+            require_safety_review: false,
+            max_versions: 0,
+            stale_period: 0,
+            late_grace: 0,
+            late_payout_bps: 0,
+            min_price_per_inference: 0,
+            max_freeze_duration: DEFAULT_MAX_FREEZE_DURATION,
+            bump: 255,
+        }
+    }
+
+    fn stake(pool: Pubkey, amount: u64) -> StakeAccount {
+        StakeAccount {
+            owner: Pubkey::new_unique(),
+            pool,
+            amount,
+            staked_at: 0,
+            reward_debt: 0,
+            pending_rewards: 0,
+            auto_compound: false,
+            locked_by: None,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn stake_in_another_pool_rejected() {
+        let config = registry_config();
+
+        config
+            .assert_model_allowed(10, &stake(config.stake_pool, 500))
+            .unwrap();
+        assert_eq!(
+            config
+                .assert_model_allowed(10, &stake(Pubkey::new_unique(), 500))
+                .unwrap_err(),
+            RegistryError::InvalidStakePool.into()
+        );
+        assert_eq!(
+            config
+                .assert_model_allowed(10, &stake(config.stake_pool, 499))
+                .unwrap_err(),
+            RegistryError::InsufficientStake.into()
+        );
+    }
+
+    #[test]
+    fn reassignment_rejected_by_new_price_floor() {
+        let mut new_registry = registry_config();
+        new_registry.min_price_per_inference = 100;
+        let stake = stake(new_registry.stake_pool, 500);
+
+        assert!(new_registry.assert_model_allowed(100, &stake).is_ok());
+        assert_eq!(
+            new_registry.assert_model_allowed(99, &stake).unwrap_err(),
+            RegistryError::PriceBelowFloor.into()
+        );
+    }
+
+    #[test]
+    fn freeze_past_max_duration_rejected() {
+        let mut config = registry_config();
+        config.max_freeze_duration = 1_000;
+
+        config.assert_freeze_allowed(1_100, 100).unwrap();
+        assert_eq!(
+            config.assert_freeze_allowed(1_101, 100).unwrap_err(),
+            RegistryError::FreezeTooLong.into()
+        );
+        assert_eq!(
+            config.assert_freeze_allowed(100, 100).unwrap_err(),
+            RegistryError::InvalidFreezeTime.into()
+        );
+        assert_eq!(
+            config.assert_freeze_allowed(i64::MAX, 100).unwrap_err(),
+            RegistryError::FreezeTooLong.into()
+        );
+    }
+
+    #[test]
+    fn trusted_evaluators_over_bound_rejected() {
+        let program_id = crate::ID;
+        let (config_key, bump) = Pubkey::find_program_address(
+            &[b"registry-config", 0u64.to_le_bytes().as_ref()],
+            &program_id,
+        );
+        let evaluators = |count: usize| (0..count).map(|_| Pubkey::new_unique()).collect();
+
+        for (count, result) in [
+            (MAX_TRUSTED_EVALUATORS, Ok(())),
+            (
+                MAX_TRUSTED_EVALUATORS + 1,
+                Err(RegistryError::TooManyEntries.into()),
+            ),
+        ] {
+            let config = RegistryConfig {
+                bump,
+                ..registry_config()
+            };
+            let mut data = Vec::new();
+            config.try_serialize(&mut data).unwrap();
+            let (mut admin_lamports, mut config_lamports) = (0, 0);
+            let mut admin_data = [];
+            let system_program = System::id();
+            let accounts = [
+                AccountInfo::new(
+                    &config.admin,
+                    true,
+                    false,
+                    &mut admin_lamports,
+                    &mut admin_data,
+                    &system_program,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &config_key,
+                    false,
+                    true,
+                    &mut config_lamports,
+                    &mut data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+            ];
+
+            let mut bumps = BTreeMap::new();
+            let mut update = UpdateRegistryConfig::try_accounts(
+                &program_id,
+                &mut &accounts[..],
+                &[],
+                &mut bumps,
+                &mut BTreeSet::new(),
+            )
+            .unwrap();
+            assert_eq!(
+                set_trusted_evaluators(
+                    Context::new(&program_id, &mut update, &[], bumps),
+                    evaluators(count),
+                    true,
+                ),
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn setters_rejected_once_config_frozen() {
+        let program_id = crate::ID;
+        let admin = Pubkey::new_unique();
+        let (config_key, bump) = Pubkey::find_program_address(
+            &[b"registry-config", 0u64.to_le_bytes().as_ref()],
+            &program_id,
+        );
+
+        for frozen in [false, true] {
+            let mut config = registry_config();
+            config.admin = admin;
+            config.bump = bump;
+            config.config_frozen = frozen;
+            let mut data = Vec::new();
+            config.try_serialize(&mut data).unwrap();
+            let (mut admin_lamports, mut config_lamports) = (0, 0);
+            let mut admin_data = [];
+            let system_program = System::id();
+            let accounts = [
+                AccountInfo::new(
+                    &admin,
+                    true,
+                    false,
+                    &mut admin_lamports,
+                    &mut admin_data,
+                    &system_program,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &config_key,
+                    false,
+                    true,
+                    &mut config_lamports,
+                    &mut data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+            ];
+
+            let result = UpdateRegistryConfig::try_accounts(
+                &program_id,
+                &mut &accounts[..],
+                &[],
+                &mut BTreeMap::new(),
+                &mut BTreeSet::new(),
+            );
+            if frozen {
+                assert_eq!(result.err(), Some(RegistryError::ConfigFrozen.into()));
+            } else {
+                assert!(result.is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,1152 @@
+//! request_module module for model registry
+//!
+//! This module provides escrowed inference requests. A consumer escrows the
+//! price of an inference, the model owner commits to a result hash, and the
+//! consumer either accepts the result, paying the owner, or disputes it for the
+//! registry admin to resolve. A dispute resolved with a refund slashes the
+//! owner's model stake by the refunded amount. Results left undisputed for the model's result
+//! TTL can be finalized by anyone. Results committed within the registry's
+//! late grace window after expiry earn a reduced payout, the rest going back to
+//! the consumer. Unfulfilled requests can be refunded once the grace window
+//! has passed, by the consumer or in batches by a keeper.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+use mcp_token::program::McpToken;
+use mcp_token::staking_module::StakeAccount;
+
+use crate::errors_module::RegistryError;
+use crate::model_module::Model;
+use crate::registry_config_module::RegistryConfig;
+use crate::safety_module::{assert_safety_reviewed, SafetyAttestation};
+use crate::utils_module::{
+    assert_batch_size, assert_not_globally_paused, get_bump, load_foreign_account, BPS_DENOMINATOR,
+};
+use crate::vaults_module::{
+    inference_request_signer_seeds, INFERENCE_REQUEST_SEED, REQUEST_VAULT_SEED,
+};
+
+/// Lifecycle of an inference request
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RequestStatus {
+    /// Payment is escrowed and awaits a result
+    Pending,
+    /// The owner committed a result
+    Fulfilled,
+    /// The consumer disputed the committed result
+    Disputed,
+    /// The payment went to the model owner
+    Settled,
+    /// The payment went back to the consumer
+    Refunded,
+    /// The request expired unfulfilled and a keeper refunded the consumer
+    Expired,
+}
+
+/// InferenceRequest state account
+#[account]
+pub struct InferenceRequest {
+    /// The consumer that paid for the request
+    pub consumer: Pubkey,
+
+    /// The model asked to run the inference
+    pub model: Pubkey,
+
+    /// Unique request identifier within the model
+    pub request_id: u64,
+
+    /// Amount escrowed in the request vault
+    pub amount: u64,
+
+    /// Status of the request
+    pub status: RequestStatus,
+
+    /// Creation time
+    pub created_at: i64,
+
+    /// Time after which an unfulfilled request can be refunded
+    pub expires_at: i64,
+
+    /// Time after which an undisputed result can be finalized; set on commit
+    pub finalize_after: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl InferenceRequest {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 8 + 8 + 8 + 1;
+
+    pub fn is_resolved(&self) -> bool {
+        matches!(
+            self.status,
+            RequestStatus::Settled | RequestStatus::Refunded | RequestStatus::Expired
+        )
+    }
+
+    /// Stake of `model` to slash for refunding this request: the refunded
+    /// amount, capped at the stake, while the stake is still locked to the model
+    pub fn refund_slash(&self, model: &Pubkey, stake: &StakeAccount) -> u64 {
+        if stake.locked_by != Some(*model) {
+            return 0;
+        }
+        self.amount.min(stake.amount)
+    }
+
+    /// Reject closing the request's result commitment at `now` while it may
+    /// still be needed as evidence: until the request is resolved or, for a
+    /// fulfilled request, its dispute window has passed
+    pub fn assert_commitment_closable(&self, now: i64) -> Result<()> {
+        require!(
+            self.status != RequestStatus::Disputed,
+            RegistryError::RequestDisputed
+        );
+        require!(
+            self.is_resolved()
+                || (self.status == RequestStatus::Fulfilled && now > self.finalize_after),
+            RegistryError::RequestNotResolved
+        );
+
+        Ok(())
+    }
+
+    /// Check that the committed result can be finalized at `now`, past its
+    /// dispute window without a dispute
+    pub fn assert_finalizable(&self, now: i64) -> Result<()> {
+        require!(
+            self.status == RequestStatus::Fulfilled,
+            RegistryError::InvalidRequestStatus
+        );
+        require!(
+            now > self.finalize_after,
+            RegistryError::FinalizationNotReached
+        );
+
+        Ok(())
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+
+    /// Whether an unfulfilled request can be refunded at `now`, once the late
+    /// grace window after expiry has passed
+    pub fn is_refundable(&self, now: i64, late_grace: i64) -> bool {
+        now > self.expires_at.saturating_add(late_grace)
+    }
+
+    /// Whether the request is still pending without a result and refundable
+    /// at `now`
+    pub fn is_expired_unfulfilled(&self, now: i64, late_grace: i64) -> bool {
+        self.status == RequestStatus::Pending && self.is_refundable(now, late_grace)
+    }
+
+    /// Part of the amount the owner earns by committing a result at `now`:
+    /// all of it on time, `late_payout_bps` of it within the late grace window,
+    /// rejecting commitments after that
+    pub fn fulfillment_payout(&self, now: i64, config: &RegistryConfig) -> Result<u64> {
+        require!(
+            self.status == RequestStatus::Pending,
+            RegistryError::InvalidRequestStatus
+        );
+        require!(
+            !self.is_refundable(now, config.late_grace),
+            RegistryError::RequestExpired
+        );
+        if !self.is_expired(now) {
+            return Ok(self.amount);
+        }
+
+        Ok((self.amount as u128 * config.late_payout_bps as u128 / BPS_DENOMINATOR as u128) as u64)
+    }
+}
+
+/// ResultCommitment state account
+#[account]
+pub struct ResultCommitment {
+    /// The request the result answers
+    pub request: Pubkey,
+
+    /// Hash of the off-chain inference result
+    pub result_hash: [u8; 32],
+
+    /// Time the result was committed
+    pub committed_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ResultCommitment {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+/// Check that `model` can take an escrowed request at `now`, including its
+/// safety review while the registry requires one
+fn assert_requestable(
+    model: &Model,
+    config: &RegistryConfig,
+    attestation: Option<&SafetyAttestation>,
+    now: i64,
+) -> Result<()> {
+    model.assert_accepting_inference(now)?;
+    assert_safety_reviewed(config, attestation)
+}
+
+/// Escrow the price of one inference and open a request expiring after `ttl` seconds.
+///
+/// While the registry requires safety review, the model's `SafetyAttestation`
+/// must be passed and approve it.
+pub fn request_inference(ctx: Context<RequestInference>, request_id: u64, ttl: i64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let now = Clock::get()?.unix_timestamp;
+    let model = &ctx.accounts.model;
+    assert_requestable(
+        model,
+        &ctx.accounts.registry_config,
+        ctx.accounts.safety_attestation.as_deref(),
+        now,
+    )?;
+    require!(ttl > 0, RegistryError::InvalidTtl);
+    let amount = model.price_per_inference;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.consumer_token_account.to_account_info(),
+                to: ctx.accounts.request_vault.to_account_info(),
+                authority: ctx.accounts.consumer.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let request = &mut ctx.accounts.inference_request;
+    request.consumer = ctx.accounts.consumer.key();
+    request.model = model.key();
+    request.request_id = request_id;
+    request.amount = amount;
+    request.status = RequestStatus::Pending;
+    request.created_at = now;
+    request.expires_at = now.checked_add(ttl).ok_or(RegistryError::InvalidTtl)?;
+    request.finalize_after = 0;
+    request.bump = get_bump(&ctx.bumps, "inference_request")?;
+
+    Ok(())
+}
+
+/// Commit the hash of the result of a pending request.
+///
+/// Results committed after expiry but within the registry's `late_grace` keep
+/// only `amount * late_payout_bps / 10000` escrowed for the owner; the rest is
+/// refunded to the consumer straight away.
+pub fn commit_result(ctx: Context<CommitResult>, result_hash: [u8; 32]) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let now = Clock::get()?.unix_timestamp;
+    let config = &ctx.accounts.registry_config;
+    let request = &ctx.accounts.inference_request;
+    let payout = request.fulfillment_payout(now, config)?;
+    let refund = request.amount - payout;
+    if refund > 0 {
+        let request_id = request.request_id.to_le_bytes();
+        let bump = [request.bump];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.request_vault.to_account_info(),
+                    to: ctx.accounts.consumer_token_account.to_account_info(),
+                    authority: request.to_account_info(),
+                },
+                &[&inference_request_signer_seeds(
+                    &request.model,
+                    &request_id,
+                    &bump,
+                )],
+            ),
+            refund,
+        )?;
+    }
+
+    let request = &mut ctx.accounts.inference_request;
+    request.amount = payout;
+    request.status = RequestStatus::Fulfilled;
+    request.finalize_after = now
+        .checked_add(ctx.accounts.model.result_ttl)
+        .ok_or(RegistryError::InvalidTtl)?;
+
+    let commitment = &mut ctx.accounts.result_commitment;
+    commitment.request = request.key();
+    commitment.result_hash = result_hash;
+    commitment.committed_at = now;
+    commitment.bump = get_bump(&ctx.bumps, "result_commitment")?;
+
+    Ok(())
+}
+
+/// Accept a committed result, releasing the payment to the model owner
+pub fn accept_result(ctx: Context<SettleInferenceRequest>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    require!(
+        ctx.accounts.inference_request.status == RequestStatus::Fulfilled,
+        RegistryError::InvalidRequestStatus
+    );
+    let accounts = ctx.accounts;
+    pay_out_request(
+        &mut accounts.inference_request,
+        &accounts.request_vault,
+        accounts.owner_token_account.to_account_info(),
+        &accounts.token_program,
+        RequestStatus::Settled,
+    )
+}
+
+/// Release the payment for a result left undisputed past its finalization time.
+///
+/// Callable by anyone, so the owner is paid without the consumer confirming.
+pub fn finalize_inference(ctx: Context<FinalizeInference>, _request_id: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts
+        .inference_request
+        .assert_finalizable(Clock::get()?.unix_timestamp)?;
+    let accounts = ctx.accounts;
+    pay_out_request(
+        &mut accounts.inference_request,
+        &accounts.request_vault,
+        accounts.owner_token_account.to_account_info(),
+        &accounts.token_program,
+        RequestStatus::Settled,
+    )
+}
+
+/// Refund a request that expired without a result, once the late grace
+/// window has passed
+pub fn refund_expired_request(ctx: Context<SettleInferenceRequest>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let request = &ctx.accounts.inference_request;
+    require!(
+        request.status == RequestStatus::Pending,
+        RegistryError::InvalidRequestStatus
+    );
+    require!(
+        request.is_refundable(
+            Clock::get()?.unix_timestamp,
+            ctx.accounts.registry_config.late_grace
+        ),
+        RegistryError::RequestNotExpired
+    );
+    let accounts = ctx.accounts;
+    pay_out_request(
+        &mut accounts.inference_request,
+        &accounts.request_vault,
+        accounts.consumer_token_account.to_account_info(),
+        &accounts.token_program,
+        RequestStatus::Refunded,
+    )
+}
+
+/// Refund every expired, unfulfilled request passed in remaining accounts.
+///
+/// Remaining accounts come in groups of four: request, the request's model,
+/// request vault and the consumer's token account. The model must belong to
+/// the given registry, whose late grace window applies. Requests are refunded
+/// once past `expires_at` and the late grace window with no committed result;
+/// pending requests have no `finalize_after` yet, so the expiry is their
+/// deadline. Fulfilled, disputed and already settled requests are skipped, so
+/// keepers can pass a mixed batch. Refunded requests are marked `Expired`.
+pub fn auto_refund_expired<'info>(
+    ctx: Context<'_, '_, '_, 'info, AutoRefundExpired<'info>>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    require!(
+        ctx.remaining_accounts.len().is_multiple_of(4),
+        RegistryError::InvalidAccountCount
+    );
+    assert_batch_size(ctx.remaining_accounts.len() / 4)?;
+    let now = Clock::get()?.unix_timestamp;
+    let late_grace = ctx.accounts.registry_config.late_grace;
+    for accounts in ctx.remaining_accounts.chunks(4) {
+        let mut request = Account::<InferenceRequest>::try_from(&accounts[0])?;
+        let model = Account::<Model>::try_from(&accounts[1])?;
+        require_keys_eq!(
+            model.key(),
+            request.model,
+            RegistryError::InvalidRequestAccounts
+        );
+        require_keys_eq!(
+            model.registry,
+            ctx.accounts.registry_config.key(),
+            RegistryError::RegistryMismatch
+        );
+        if !request.is_expired_unfulfilled(now, late_grace) {
+            continue;
+        }
+        let request_vault = Account::<TokenAccount>::try_from(&accounts[2])?;
+        require_keys_eq!(
+            request_vault.owner,
+            request.key(),
+            RegistryError::InvalidRequestAccounts
+        );
+        let consumer_token_account = Account::<TokenAccount>::try_from(&accounts[3])?;
+        require!(
+            consumer_token_account.owner == request.consumer
+                && consumer_token_account.mint == request_vault.mint,
+            RegistryError::InvalidRequestAccounts
+        );
+
+        pay_out_request(
+            &mut request,
+            &request_vault,
+            accounts[3].clone(),
+            &ctx.accounts.token_program,
+            RequestStatus::Expired,
+        )?;
+        request.exit(ctx.program_id)?;
+    }
+
+    Ok(())
+}
+
+/// Dispute a committed result, leaving the payment escrowed for the admin to resolve
+pub fn dispute_result(ctx: Context<DisputeResult>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let request = &mut ctx.accounts.inference_request;
+    require!(
+        request.status == RequestStatus::Fulfilled,
+        RegistryError::InvalidRequestStatus
+    );
+    require!(
+        Clock::get()?.unix_timestamp <= request.finalize_after,
+        RegistryError::DisputeWindowClosed
+    );
+    request.status = RequestStatus::Disputed;
+
+    Ok(())
+}
+
+/// Resolve a disputed request, refunding the consumer or paying the owner.
+///
+/// A refund also slashes the model stake by the refunded amount, forfeiting it
+/// to the stake pool treasury.
+pub fn resolve_dispute(ctx: Context<ResolveDispute>, refund: bool) -> Result<()> {
+    require!(
+        ctx.accounts.inference_request.status == RequestStatus::Disputed,
+        RegistryError::InvalidRequestStatus
+    );
+    let accounts = ctx.accounts;
+    if refund {
+        let stake_account =
+            load_foreign_account::<StakeAccount>(&accounts.stake_account, &mcp_token::ID)?;
+        let model = &accounts.model;
+        let slash = accounts
+            .inference_request
+            .refund_slash(&model.key(), &stake_account);
+        if slash > 0 {
+            let model_id_bytes = model.model_id.to_le_bytes();
+            let bump = [model.bump];
+            let signer_seeds: &[&[u8]] = &[b"model".as_ref(), model_id_bytes.as_ref(), &bump];
+            mcp_token::cpi::slash_stake(
+                CpiContext::new_with_signer(
+                    accounts.mcp_token_program.to_account_info(),
+                    mcp_token::cpi::accounts::SlashStake {
+                        locker: model.to_account_info(),
+                        global_config: accounts.global_config.to_account_info(),
+                        stake_pool: accounts.stake_pool.to_account_info(),
+                        stake_account: accounts.stake_account.to_account_info(),
+                        stake_vault: accounts.stake_vault.to_account_info(),
+                        treasury: accounts.treasury.to_account_info(),
+                        token_program: accounts.token_program.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                slash,
+            )?;
+        }
+    }
+    let (to, status) = if refund {
+        (
+            accounts.consumer_token_account.to_account_info(),
+            RequestStatus::Refunded,
+        )
+    } else {
+        (
+            accounts.owner_token_account.to_account_info(),
+            RequestStatus::Settled,
+        )
+    };
+    pay_out_request(
+        &mut accounts.inference_request,
+        &accounts.request_vault,
+        to,
+        &accounts.token_program,
+        status,
+    )
+}
+
+/// Close a result commitment and reclaim its rent.
+///
+/// Allowed once the request has been settled or refunded, or once a fulfilled
+/// request is past `finalize_after`, when it can no longer be disputed. A
+/// commitment under an open dispute is the evidence the admin resolves it by,
+/// so it can never be closed.
+pub fn close_result_commitment(
+    ctx: Context<CloseResultCommitment>,
+    _request_id: u64,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts
+        .inference_request
+        .assert_commitment_closable(Clock::get()?.unix_timestamp)
+}
+
+fn pay_out_request<'info>(
+    request: &mut Account<'info, InferenceRequest>,
+    request_vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    status: RequestStatus,
+) -> Result<()> {
+    let request_id = request.request_id.to_le_bytes();
+    let bump = [request.bump];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: request_vault.to_account_info(),
+                to,
+                authority: request.to_account_info(),
+            },
+            &[&inference_request_signer_seeds(
+                &request.model,
+                &request_id,
+                &bump,
+            )],
+        ),
+        request.amount,
+    )?;
+
+    request.status = status;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+#[instruction(request_id: u64)]
+pub struct RequestInference<'info> {
+    /// The consumer paying for the inference
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model asked to run the inference
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// The model's safety attestation, required while safety review is enforced
+    #[account(
+        seeds = [b"safety".as_ref(), model.key().as_ref()],
+        bump = safety_attestation.bump,
+    )]
+    pub safety_attestation: Option<Account<'info, SafetyAttestation>>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = consumer,
+        space = 8 + InferenceRequest::LEN,
+        seeds = [INFERENCE_REQUEST_SEED, model.key().as_ref(), request_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub inference_request: Account<'info, InferenceRequest>,
+
+    /// Vault holding the escrowed payment
+    #[account(
+        init,
+        payer = consumer,
+        seeds = [REQUEST_VAULT_SEED, inference_request.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = inference_request,
+    )]
+    pub request_vault: Account<'info, TokenAccount>,
+
+    /// Mint inference is paid in
+    #[account(address = model.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// Consumer's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = consumer,
+    )]
+    pub consumer_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for result commitment
+#[derive(Accounts)]
+pub struct CommitResult<'info> {
+    /// The owner of the model
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model that ran the inference
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The request being answered
+    #[account(
+        mut,
+        seeds = [
+            INFERENCE_REQUEST_SEED,
+            model.key().as_ref(),
+            inference_request.request_id.to_le_bytes().as_ref(),
+        ],
+        bump = inference_request.bump,
+        has_one = model,
+    )]
+    pub inference_request: Account<'info, InferenceRequest>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ResultCommitment::LEN,
+        seeds = [b"result".as_ref(), inference_request.key().as_ref()],
+        bump,
+    )]
+    pub result_commitment: Account<'info, ResultCommitment>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// Vault holding the escrowed payment
+    #[account(
+        mut,
+        seeds = [REQUEST_VAULT_SEED, inference_request.key().as_ref()],
+        bump,
+    )]
+    pub request_vault: Account<'info, TokenAccount>,
+
+    /// Consumer's token account, refunded the late deduction
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = inference_request.consumer,
+    )]
+    pub consumer_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for moving a request's escrowed payment
+#[derive(Accounts)]
+pub struct SettleInferenceRequest<'info> {
+    /// The consumer that paid for the request
+    pub consumer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model asked to run the inference
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The request to settle
+    #[account(
+        mut,
+        seeds = [
+            INFERENCE_REQUEST_SEED,
+            model.key().as_ref(),
+            inference_request.request_id.to_le_bytes().as_ref(),
+        ],
+        bump = inference_request.bump,
+        has_one = model,
+        has_one = consumer,
+    )]
+    pub inference_request: Account<'info, InferenceRequest>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// Vault holding the escrowed payment
+    #[account(
+        mut,
+        seeds = [REQUEST_VAULT_SEED, inference_request.key().as_ref()],
+        bump,
+    )]
+    pub request_vault: Account<'info, TokenAccount>,
+
+    /// Model owner's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = model.owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Consumer's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = consumer,
+    )]
+    pub consumer_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for finalizing an undisputed result
+#[derive(Accounts)]
+#[instruction(request_id: u64)]
+pub struct FinalizeInference<'info> {
+    /// Anyone finalizing the request
+    pub caller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model that ran the inference
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The request to finalize
+    #[account(
+        mut,
+        seeds = [INFERENCE_REQUEST_SEED, model.key().as_ref(), request_id.to_le_bytes().as_ref()],
+        bump = inference_request.bump,
+        has_one = model,
+    )]
+    pub inference_request: Account<'info, InferenceRequest>,
+
+    /// Vault holding the escrowed payment
+    #[account(
+        mut,
+        seeds = [REQUEST_VAULT_SEED, inference_request.key().as_ref()],
+        bump,
+    )]
+    pub request_vault: Account<'info, TokenAccount>,
+
+    /// Model owner's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = model.owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for batch refunds of expired requests
+#[derive(Accounts)]
+pub struct AutoRefundExpired<'info> {
+    /// Anyone running the refunds
+    pub keeper: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for dispute
+#[derive(Accounts)]
+pub struct DisputeResult<'info> {
+    /// The consumer that paid for the request
+    pub consumer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The request to dispute
+    #[account(
+        mut,
+        seeds = [
+            INFERENCE_REQUEST_SEED,
+            inference_request.model.as_ref(),
+            inference_request.request_id.to_le_bytes().as_ref(),
+        ],
+        bump = inference_request.bump,
+        has_one = consumer,
+    )]
+    pub inference_request: Account<'info, InferenceRequest>,
+}
+
+/// Account validation for dispute resolution
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    /// The registry admin
+    pub admin: Signer<'info>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+        has_one = admin,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// The model asked to run the inference
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = stake_account,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The disputed request
+    #[account(
+        mut,
+        seeds = [
+            INFERENCE_REQUEST_SEED,
+            model.key().as_ref(),
+            inference_request.request_id.to_le_bytes().as_ref(),
+        ],
+        bump = inference_request.bump,
+        has_one = model,
+    )]
+    pub inference_request: Account<'info, InferenceRequest>,
+
+    /// Vault holding the escrowed payment
+    #[account(
+        mut,
+        seeds = [REQUEST_VAULT_SEED, inference_request.key().as_ref()],
+        bump,
+    )]
+    pub request_vault: Account<'info, TokenAccount>,
+
+    /// Model owner's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = model.owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Consumer's token account
+    #[account(
+        mut,
+        token::mint = model.mint,
+        token::authority = inference_request.consumer,
+    )]
+    pub consumer_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: MCP token global config, checked for the pause by the MCP token
+    /// program when slashing
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// CHECK: The registry's stake pool, validated by the MCP token program
+    #[account(mut, address = registry_config.stake_pool @ RegistryError::InvalidStakePool)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    /// CHECK: The stake backing the model, verified in the handler and slashed
+    /// by the MCP token program
+    #[account(mut)]
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// CHECK: The stake pool vault, validated by the MCP token program
+    #[account(mut)]
+    pub stake_vault: UncheckedAccount<'info>,
+
+    /// CHECK: The stake pool treasury receiving slashed stake, validated by the
+    /// MCP token program
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// MCP token program
+    pub mcp_token_program: Program<'info, McpToken>,
+}
+
+/// Account validation for closing a result commitment
+#[derive(Accounts)]
+#[instruction(request_id: u64)]
+pub struct CloseResultCommitment<'info> {
+    /// The owner of the model, receiving the rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The model that ran the inference
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+        has_one = owner,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The request the commitment answers
+    #[account(
+        seeds = [INFERENCE_REQUEST_SEED, model.key().as_ref(), request_id.to_le_bytes().as_ref()],
+        bump = inference_request.bump,
+        has_one = model,
+    )]
+    pub inference_request: Account<'info, InferenceRequest>,
+
+    /// The account to close
+    #[account(
+        mut,
+        seeds = [b"result".as_ref(), inference_request.key().as_ref()],
+        bump = result_commitment.bump,
+        close = owner,
+    )]
+    pub result_commitment: Account<'info, ResultCommitment>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_module::tests::model;
+    use crate::registry_config_module::tests::registry_config;
+
+    fn inference_request(status: RequestStatus) -> InferenceRequest {
+        InferenceRequest {
+            consumer: Pubkey::new_unique(),
+            model: Pubkey::new_unique(),
+            request_id: 1,
+            amount: 100,
+            status,
+            created_at: 0,
+            expires_at: 1_000,
+            finalize_after: 2_000,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn refund_slashes_only_stake_still_locked_to_model() {
+        let request = inference_request(RequestStatus::Disputed);
+        let model = Pubkey::new_unique();
+        let mut stake = StakeAccount {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            amount: 1_000,
+            staked_at: 0,
+            reward_debt: 0,
+            pending_rewards: 0,
+            auto_compound: false,
+            locked_by: Some(model),
+            bump: 255,
+        };
+        assert_eq!(request.refund_slash(&model, &stake), 100);
+
+        stake.amount = 40;
+        assert_eq!(request.refund_slash(&model, &stake), 40);
+
+        stake.locked_by = None;
+        assert_eq!(request.refund_slash(&model, &stake), 0);
+    }
+
+    #[test]
+    fn resolved_commitment_closes_and_disputed_does_not() {
+        for status in [RequestStatus::Settled, RequestStatus::Refunded] {
+            assert!(inference_request(status)
+                .assert_commitment_closable(1_500)
+                .is_ok());
+        }
+        assert_eq!(
+            inference_request(RequestStatus::Disputed)
+                .assert_commitment_closable(10_000)
+                .unwrap_err(),
+            RegistryError::RequestDisputed.into()
+        );
+    }
+
+    #[test]
+    fn fulfilled_commitment_kept_until_finalize_after() {
+        let request = inference_request(RequestStatus::Fulfilled);
+        assert_eq!(
+            request.assert_commitment_closable(1_500).unwrap_err(),
+            RegistryError::RequestNotResolved.into()
+        );
+        assert!(request.assert_commitment_closable(2_001).is_ok());
+    }
+
+    #[test]
+    fn finalization_rejected_before_ttl() {
+        let request = inference_request(RequestStatus::Fulfilled);
+        assert_eq!(
+            request.assert_finalizable(2_000).unwrap_err(),
+            RegistryError::FinalizationNotReached.into()
+        );
+        assert!(request.assert_finalizable(2_001).is_ok());
+
+        assert_eq!(
+            inference_request(RequestStatus::Disputed)
+                .assert_finalizable(2_001)
+                .unwrap_err(),
+            RegistryError::InvalidRequestStatus.into()
+        );
+    }
+
+    #[test]
+    fn mixed_batch_refunds_only_expired_unfulfilled() {
+        let batch = [
+            inference_request(RequestStatus::Pending),
+            inference_request(RequestStatus::Fulfilled),
+            inference_request(RequestStatus::Disputed),
+            inference_request(RequestStatus::Settled),
+        ];
+        let refunded = |now| {
+            batch
+                .iter()
+                .map(|request| request.is_expired_unfulfilled(now, 100))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(refunded(1_100), [false; 4]);
+        assert_eq!(refunded(1_101), [true, false, false, false]);
+    }
+
+    #[test]
+    fn on_time_late_and_past_grace_payouts() {
+        let mut config = registry_config();
+        config.late_grace = 100;
+        config.late_payout_bps = 6_000;
+        let request = inference_request(RequestStatus::Pending);
+
+        assert_eq!(request.fulfillment_payout(1_000, &config).unwrap(), 100);
+        assert_eq!(request.fulfillment_payout(1_100, &config).unwrap(), 60);
+        assert_eq!(
+            request.fulfillment_payout(1_101, &config).unwrap_err(),
+            RegistryError::RequestExpired.into()
+        );
+    }
+
+    #[test]
+    fn request_needs_approving_attestation_while_review_required() {
+        let model = model();
+        let mut config = registry_config();
+        assert_requestable(&model, &config, None, 0).unwrap();
+
+        config.require_safety_review = true;
+        config.safety_reviewer = Pubkey::new_unique();
+        assert_eq!(
+            assert_requestable(&model, &config, None, 0).unwrap_err(),
+            RegistryError::SafetyReviewRequired.into()
+        );
+        let mut attestation = SafetyAttestation {
+            model: Pubkey::new_unique(),
+            reviewer: config.safety_reviewer,
+            approved: false,
+            review_hash: [0; 32],
+            reviewed_at: 0,
+            bump: 255,
+        };
+        assert_eq!(
+            assert_requestable(&model, &config, Some(&attestation), 0).unwrap_err(),
+            RegistryError::SafetyReviewRequired.into()
+        );
+        attestation.approved = true;
+        assert_requestable(&model, &config, Some(&attestation), 0).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+//! safety_module module for model registry
+//!
+//! This module provides on-chain attestations that a model passed a safety
+//! review. Attestations are issued by the registry's `safety_reviewer`; when
+//! the registry requires safety review, only approved models accept inference
+//! payments.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::RegistryError;
+use crate::model_module::Model;
+use crate::registry_config_module::RegistryConfig;
+use crate::utils_module::{assert_not_globally_paused, get_bump};
+
+/// SafetyAttestation state account
+#[account]
+pub struct SafetyAttestation {
+    /// The model reviewed
+    pub model: Pubkey,
+
+    /// The reviewer that issued the attestation
+    pub reviewer: Pubkey,
+
+    /// Whether the model passed review
+    pub approved: bool,
+
+    /// Hash of the off-chain review report
+    pub review_hash: [u8; 32],
+
+    /// Time of the latest review
+    pub reviewed_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl SafetyAttestation {
+    pub const LEN: usize = 32 + 32 + 1 + 32 + 8 + 1;
+
+    /// Check that the attestation approves its model under `config`
+    pub fn assert_approved(&self, config: &RegistryConfig) -> Result<()> {
+        require!(
+            self.approved && self.reviewer == config.safety_reviewer,
+            RegistryError::SafetyReviewRequired
+        );
+
+        Ok(())
+    }
+}
+
+/// Reject inference on a model without an approving attestation while the
+/// registry requires safety review
+pub fn assert_safety_reviewed(
+    config: &RegistryConfig,
+    attestation: Option<&SafetyAttestation>,
+) -> Result<()> {
+    if config.require_safety_review {
+        attestation
+            .ok_or(RegistryError::SafetyReviewRequired)?
+            .assert_approved(config)?;
+    }
+
+    Ok(())
+}
+
+/// Record the outcome of a model's safety review.
+///
+/// Later reviews overwrite earlier ones, so approval can also be withdrawn.
+pub fn attest_safety(
+    ctx: Context<AttestSafety>,
+    approved: bool,
+    review_hash: [u8; 32],
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let attestation = &mut ctx.accounts.safety_attestation;
+    attestation.model = ctx.accounts.model.key();
+    attestation.reviewer = ctx.accounts.reviewer.key();
+    attestation.approved = approved;
+    attestation.review_hash = review_hash;
+    attestation.reviewed_at = Clock::get()?.unix_timestamp;
+    attestation.bump = get_bump(&ctx.bumps, "safety_attestation")?;
+
+    Ok(())
+}
+
+/// Account validation for safety attestation
+#[derive(Accounts)]
+pub struct AttestSafety<'info> {
+    /// The configured safety reviewer
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Registry configuration
+    #[account(
+        seeds = [b"registry-config".as_ref(), registry_config.registry_id.to_le_bytes().as_ref()],
+        bump = registry_config.bump,
+        address = model.registry @ RegistryError::RegistryMismatch,
+        constraint = registry_config.safety_reviewer == reviewer.key()
+            @ RegistryError::NotSafetyReviewer,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    /// The model reviewed
+    #[account(
+        seeds = [b"model".as_ref(), model.model_id.to_le_bytes().as_ref()],
+        bump = model.bump,
+    )]
+    pub model: Account<'info, Model>,
+
+    /// The model's attestation, created on first review
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + SafetyAttestation::LEN,
+        seeds = [b"safety".as_ref(), model.key().as_ref()],
+        bump,
+    )]
+    pub safety_attestation: Account<'info, SafetyAttestation>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry_config_module::tests::registry_config;
+
+    #[test]
+    fn unreviewed_model_rejected_while_review_required() {
+        let mut config = registry_config();
+        config.safety_reviewer = Pubkey::new_unique();
+        let attestation = SafetyAttestation {
+            model: Pubkey::new_unique(),
+            reviewer: config.safety_reviewer,
+            approved: true,
+            review_hash: [0; 32],
+            reviewed_at: 0,
+            bump: 255,
+        };
+        assert_safety_reviewed(&config, None).unwrap();
+
+        config.require_safety_review = true;
+        assert_eq!(
+            assert_safety_reviewed(&config, None).unwrap_err(),
+            RegistryError::SafetyReviewRequired.into()
+        );
+        assert_safety_reviewed(&config, Some(&attestation)).unwrap();
+    }
+}
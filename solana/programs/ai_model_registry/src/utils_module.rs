@@ -0,0 +1,199 @@
+//! utils_module module for model registry
+//!
+//! This module provides helpers shared by model registry instructions.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use std::collections::BTreeMap;
+
+use mcp_token::global_config_module::GlobalConfig;
+use mcp_token::utils_module::MAX_BATCH;
+
+use crate::errors_module::RegistryError;
+
+/// Denominator for basis-point values
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Maximum number of trusted evaluators in the registry allowlist
+pub const MAX_TRUSTED_EVALUATORS: usize = 10;
+
+/// Fail with `BatchTooLarge` when a batch has more than the MCP-wide
+/// `MAX_BATCH` entries.
+///
+/// Called before a batch instruction touches any state.
+pub fn assert_batch_size(entries: usize) -> Result<()> {
+    require!(entries <= MAX_BATCH, RegistryError::BatchTooLarge);
+
+    Ok(())
+}
+
+/// Look up a PDA bump recorded by Anchor during account validation.
+///
+/// Returns `BumpNotFound` instead of panicking when `name` is missing.
+pub fn get_bump(bumps: &BTreeMap<String, u8>, name: &str) -> Result<u8> {
+    bumps
+        .get(name)
+        .copied()
+        .ok_or_else(|| error!(RegistryError::BumpNotFound))
+}
+
+/// Check that a vector stored on an account stays within its fixed bound.
+///
+/// Account sizes are computed from these bounds, so every vector field must be
+/// validated here before it is written.
+pub fn validate_bounded_vec<T>(entries: &[T], max: usize) -> Result<()> {
+    require!(entries.len() <= max, RegistryError::TooManyEntries);
+
+    Ok(())
+}
+
+/// Compute `amount * numerator / denominator` without intermediate overflow
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    require!(denominator > 0, RegistryError::MathOverflow);
+    let result = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(RegistryError::MathOverflow)?
+        / denominator as u128;
+
+    u64::try_from(result).map_err(|_| error!(RegistryError::MathOverflow))
+}
+
+/// Check that `info` holds an account of type `T` by its 8-byte discriminator.
+///
+/// Used before reading accounts owned by other programs, where a matching
+/// owner alone does not rule out being handed a different account type.
+pub fn verify_discriminator<T: Discriminator>(info: &AccountInfo) -> Result<()> {
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == T::discriminator(),
+        RegistryError::AccountTypeMismatch
+    );
+
+    Ok(())
+}
+
+/// Deserialize an account of type `T` owned by `program_id`, verifying its
+/// owner and discriminator first
+pub fn load_foreign_account<T: AccountDeserialize + Discriminator>(
+    info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<T> {
+    require_keys_eq!(*info.owner, *program_id, RegistryError::AccountTypeMismatch);
+    verify_discriminator::<T>(info)?;
+
+    let data = info.try_borrow_data()?;
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Fail with `ProtocolPaused` while the MCP token global config has the
+/// protocol-wide pause set. `info` must already be validated as that PDA.
+pub fn assert_not_globally_paused(info: &AccountInfo) -> Result<()> {
+    let config = load_foreign_account::<GlobalConfig>(info, &mcp_token::ID)?;
+    require!(!config.global_paused, RegistryError::ProtocolPaused);
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::model_module::MAX_LOYALTY_TIERS;
+    use mcp_token::staking_module::StakeAccount;
+
+    #[test]
+    fn missing_bump_returns_bump_not_found() {
+        let mut bumps = BTreeMap::new();
+        bumps.insert("mint_authority".to_string(), 254);
+
+        assert_eq!(get_bump(&bumps, "mint_authority").unwrap(), 254);
+        assert_eq!(
+            get_bump(&bumps, "missing").unwrap_err(),
+            RegistryError::BumpNotFound.into()
+        );
+    }
+
+    #[test]
+    fn vec_over_bound_rejected() {
+        for max in [MAX_TRUSTED_EVALUATORS, MAX_LOYALTY_TIERS] {
+            validate_bounded_vec(&vec![Pubkey::default(); max], max).unwrap();
+            assert_eq!(
+                validate_bounded_vec(&vec![Pubkey::default(); max + 1], max).unwrap_err(),
+                RegistryError::TooManyEntries.into()
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_account_type_rejected() {
+        let key = Pubkey::new_unique();
+        let owner = mcp_token::ID;
+        let mut lamports = 0;
+        let mut data = GlobalConfig::discriminator().to_vec();
+        data.resize(64, 0);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        verify_discriminator::<GlobalConfig>(&info).unwrap();
+        assert_eq!(
+            verify_discriminator::<StakeAccount>(&info).unwrap_err(),
+            RegistryError::AccountTypeMismatch.into()
+        );
+        assert_eq!(
+            load_foreign_account::<StakeAccount>(&info, &mcp_token::ID).err(),
+            Some(RegistryError::AccountTypeMismatch.into())
+        );
+    }
+
+    /// Serialized MCP token global config with the protocol-wide pause set or not
+    pub(crate) fn global_config_data(global_paused: bool) -> Vec<u8> {
+        let config = GlobalConfig {
+            admin: Pubkey::new_unique(),
+            mint_limit_per_window: 0,
+            window_seconds: 0,
+            global_paused,
+            clock_skew_tolerance: 0,
+            global_freeze: false,
+            freeze_admins: Vec::new(),
+            config_frozen: false,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn global_pause_blocks_inference_payments() {
+        for global_paused in [false, true] {
+            let mut data = global_config_data(global_paused);
+            let key = Pubkey::new_unique();
+            let owner = mcp_token::ID;
+            let mut lamports = 0;
+            let info = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+
+            let result = assert_not_globally_paused(&info);
+            if global_paused {
+                assert_eq!(result.unwrap_err(), RegistryError::ProtocolPaused.into());
+            } else {
+                assert!(result.is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! vaults_module module for model registry
+//!
+//! This module provides the canonical PDA seeds for model registry token vaults.
+//! Instructions and off-chain clients must derive vault addresses through these
+//! helpers so the seeds cannot drift between call sites.
+
+use anchor_lang::prelude::*;
+
+/// Seed prefix of the token vault backing inference credits for a mint
+pub const CREDIT_VAULT_SEED: &[u8] = b"credit-vault";
+
+/// Seed prefix of an inference request, which is also its vault authority
+pub const INFERENCE_REQUEST_SEED: &[u8] = b"inference-request";
+
+/// Seed prefix of the token vault holding an inference request's payment
+pub const REQUEST_VAULT_SEED: &[u8] = b"request-vault";
+
+/// Seeds of the inference credit vault for `mint`
+pub fn credit_vault_seeds(mint: &Pubkey) -> [&[u8]; 2] {
+    [CREDIT_VAULT_SEED, mint.as_ref()]
+}
+
+/// Seeds used when the credit vault signs for itself
+pub fn credit_vault_signer_seeds<'a>(mint: &'a Pubkey, bump: &'a [u8; 1]) -> [&'a [u8]; 3] {
+    [CREDIT_VAULT_SEED, mint.as_ref(), bump.as_ref()]
+}
+
+/// Derive the inference credit vault address for `mint`
+pub fn find_credit_vault_address(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&credit_vault_seeds(mint), program_id)
+}
+
+/// Seeds used when an inference request signs for its vault
+pub fn inference_request_signer_seeds<'a>(
+    model: &'a Pubkey,
+    request_id: &'a [u8; 8],
+    bump: &'a [u8; 1],
+) -> [&'a [u8]; 4] {
+    [
+        INFERENCE_REQUEST_SEED,
+        model.as_ref(),
+        request_id.as_ref(),
+        bump.as_ref(),
+    ]
+}
+
+/// Seeds of the token vault owned by `request`
+pub fn request_vault_seeds(request: &Pubkey) -> [&[u8]; 2] {
+    [REQUEST_VAULT_SEED, request.as_ref()]
+}
+
+/// Derive the token vault address owned by `request`
+pub fn find_request_vault_address(request: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&request_vault_seeds(request), program_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_addresses_match_off_chain_derivation() {
+        let program_id = crate::ID;
+        let key = Pubkey::new_unique();
+
+        assert_eq!(
+            find_credit_vault_address(&key, &program_id),
+            Pubkey::find_program_address(&[b"credit-vault", key.as_ref()], &program_id)
+        );
+        assert_eq!(
+            find_request_vault_address(&key, &program_id),
+            Pubkey::find_program_address(&[b"request-vault", key.as_ref()], &program_id)
+        );
+
+        let request_id = 7u64.to_le_bytes();
+        let (request, bump) = Pubkey::find_program_address(
+            &[b"inference-request", key.as_ref(), request_id.as_ref()],
+            &program_id,
+        );
+        assert_eq!(
+            Pubkey::create_program_address(
+                &inference_request_signer_seeds(&key, &request_id, &[bump]),
+                &program_id
+            )
+            .unwrap(),
+            request
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! versioning_module module for model registry
+//!
+//! This module provides versioned instruction arguments. A versioned argument is
+//! encoded as a leading version byte followed by the Borsh encoding of that
+//! version's payload, so argument layouts can evolve without breaking clients
+//! that still send older versions.
+
+use anchor_lang::prelude::*;
+use std::io::{Read, Write};
+
+use crate::errors_module::RegistryError;
+
+/// Leading byte of version 1 arguments
+pub const ARGS_V1: u8 = 1;
+
+/// Instruction arguments tagged with a leading version byte.
+///
+/// Unknown versions decode to `Unsupported` rather than failing deserialization,
+/// so handlers can reject them with `UnsupportedVersion`. Only the version byte
+/// of an unsupported payload is consumed, so a versioned argument must be the
+/// last argument of its instruction.
+#[derive(Clone)]
+pub enum Versioned<V1> {
+    V1(V1),
+    Unsupported(u8),
+}
+
+impl<V1> Versioned<V1> {
+    /// Unwrap version 1 arguments
+    pub fn into_v1(self) -> Result<V1> {
+        match self {
+            Versioned::V1(args) => Ok(args),
+            Versioned::Unsupported(_) => err!(RegistryError::UnsupportedVersion),
+        }
+    }
+}
+
+impl<V1: AnchorSerialize> AnchorSerialize for Versioned<V1> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            Versioned::V1(args) => {
+                ARGS_V1.serialize(writer)?;
+                args.serialize(writer)
+            }
+            Versioned::Unsupported(version) => version.serialize(writer),
+        }
+    }
+}
+
+impl<V1: AnchorDeserialize> AnchorDeserialize for Versioned<V1> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        match u8::deserialize_reader(reader)? {
+            ARGS_V1 => Ok(Versioned::V1(V1::deserialize_reader(reader)?)),
+            version => Ok(Versioned::Unsupported(version)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_version_byte_rejected() {
+        let v1 = Versioned::V1(42u64).try_to_vec().unwrap();
+        assert_eq!(v1[0], ARGS_V1);
+        assert_eq!(
+            Versioned::<u64>::try_from_slice(&v1)
+                .unwrap()
+                .into_v1()
+                .unwrap(),
+            42
+        );
+
+        let mut v2 = v1.clone();
+        v2[0] = 2;
+        let decoded = Versioned::<u64>::deserialize(&mut &v2[..]).unwrap();
+        assert_eq!(
+            decoded.into_v1().unwrap_err(),
+            RegistryError::UnsupportedVersion.into()
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! access_module module for data marketplace
+//!
+//! This module provides access grants to listings. Grants are issued to the
+//! buyer of an escrowed purchase or the winner of an auction, or bought by
+//! burning tokens: listings flagged `burn_to_access` can be unlocked by burning
+//! their price from the buyer, which reduces supply instead of paying the seller.
+//!
+//! Grants are non-transferable unless the seller enables transfers on the
+//! listing, in which case each transfer pays the creator a royalty.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::listing_module::Listing;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::stats_module::MarketplaceStats;
+use crate::utils_module::{assert_not_globally_paused, get_bump};
+
+/// AccessGrant state account
+#[account]
+pub struct AccessGrant {
+    /// The listing access was granted to
+    pub listing: Pubkey,
+
+    /// The user holding access
+    pub buyer: Pubkey,
+
+    /// Time access was granted
+    pub granted_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl AccessGrant {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+
+    pub fn new(listing: Pubkey, buyer: Pubkey, granted_at: i64, bump: u8) -> Self {
+        Self {
+            listing,
+            buyer,
+            granted_at,
+            bump,
+        }
+    }
+}
+
+/// Emitted when tokens are burned for access to a listing
+#[event]
+pub struct AccessBurned {
+    pub listing: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted when an access grant changes hands
+#[event]
+pub struct AccessGrantTransferred {
+    pub listing: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub royalty: u64,
+}
+
+/// Burn the listing price from the buyer and grant them access to the dataset.
+///
+/// Listings flagged `contains_pii` require the buyer to set `pii_acknowledged`.
+pub fn burn_for_access(
+    ctx: Context<BurnForAccess>,
+    _listing_id: u64,
+    pii_acknowledged: bool,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts.listing.record_buyer()?;
+    let listing = &ctx.accounts.listing;
+    let now = Clock::get()?.unix_timestamp;
+    let amount = listing.burn_price(now, pii_acknowledged)?;
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let grant = &mut ctx.accounts.access_grant;
+    grant.listing = listing.key();
+    grant.buyer = ctx.accounts.buyer.key();
+    grant.granted_at = now;
+    grant.bump = get_bump(&ctx.bumps, "access_grant")?;
+
+    emit!(AccessBurned {
+        listing: grant.listing,
+        buyer: grant.buyer,
+        amount,
+    });
+
+    ctx.accounts.marketplace_stats.record_sale(amount)
+}
+
+/// Hand an access grant to `to`, paying the creator's royalty on the listing's
+/// secondary price, floored at the listing price.
+///
+/// Only listings with `grants_transferable` set allow this. The royalty is paid
+/// in the listing mint by the current holder. The holder's grant is closed and
+/// re-created at the address derived from `to`.
+pub fn transfer_access_grant(ctx: Context<TransferAccessGrant>, to: Pubkey) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let listing = &ctx.accounts.listing;
+    let holder = ctx.accounts.holder.key();
+    let royalty = listing.grant_transfer_royalty(
+        holder,
+        to,
+        ctx.accounts.marketplace_config.rounding_mode,
+    )?;
+    if royalty > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.holder_token_account.to_account_info(),
+                    to: ctx.accounts.creator_token_account.to_account_info(),
+                    authority: ctx.accounts.holder.to_account_info(),
+                },
+            ),
+            royalty,
+        )?;
+    }
+
+    *ctx.accounts.new_access_grant = AccessGrant::new(
+        listing.key(),
+        to,
+        Clock::get()?.unix_timestamp,
+        get_bump(&ctx.bumps, "new_access_grant")?,
+    );
+
+    emit!(AccessGrantTransferred {
+        listing: listing.key(),
+        from: holder,
+        to,
+        royalty,
+    });
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct BurnForAccess<'info> {
+    /// The buyer burning tokens for access
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing to unlock
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + AccessGrant::LEN,
+        seeds = [b"access".as_ref(), listing.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub access_grant: Account<'info, AccessGrant>,
+
+    /// Mint the listing is priced in, whose supply is reduced
+    #[account(
+        mut,
+        address = listing.mint,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// Buyer's token account
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for access grant transfer
+#[derive(Accounts)]
+#[instruction(to: Pubkey)]
+pub struct TransferAccessGrant<'info> {
+    /// The current holder of the grant, paying for the new grant
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The listing the grant unlocks
+    #[account(address = access_grant.listing)]
+    pub listing: Account<'info, Listing>,
+
+    /// The grant to transfer, closed to the holder
+    #[account(
+        mut,
+        constraint = access_grant.buyer == holder.key() @ MarketplaceError::NotGrantHolder,
+        seeds = [b"access".as_ref(), listing.key().as_ref(), holder.key().as_ref()],
+        bump = access_grant.bump,
+        close = holder,
+    )]
+    pub access_grant: Account<'info, AccessGrant>,
+
+    /// The grant re-created for `to`
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + AccessGrant::LEN,
+        seeds = [b"access".as_ref(), listing.key().as_ref(), to.as_ref()],
+        bump,
+    )]
+    pub new_access_grant: Account<'info, AccessGrant>,
+
+    /// Holder's token account paying the royalty
+    #[account(
+        mut,
+        token::mint = listing.mint,
+        token::authority = holder,
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    /// Creator's token account receiving the royalty
+    #[account(
+        mut,
+        token::mint = listing.mint,
+        token::authority = listing.creator,
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing_module::tests::listing;
+    use crate::marketplace_config_module::tests::marketplace_config;
+    use crate::utils_module::tests::{
+        global_config_data, install_test_syscalls, take_cpis, TestAccount, TEST_NOW,
+    };
+    use anchor_spl::token::spl_token::instruction::TokenInstruction;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// `TransferAccessGrant` accounts moving a grant of `grant_buyer` on a
+    /// transferable listing with a zero secondary price from `holder` to `to`
+    fn transfer_accounts(holder: Pubkey, grant_buyer: Pubkey, to: Pubkey) -> Vec<TestAccount> {
+        let program_id = crate::ID;
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+        let mut listing = listing();
+        listing.creator = Pubkey::new_unique();
+        listing.royalty_bps = 500;
+        listing.grants_transferable = true;
+        let listing_key = Pubkey::new_unique();
+        let (grant_key, bump) = pda(&[b"access", listing_key.as_ref(), holder.as_ref()]);
+        let grant = AccessGrant::new(listing_key, grant_buyer, 0, bump);
+        let (config_key, config_bump) = pda(&[b"marketplace-config"]);
+        let config = MarketplaceConfig {
+            bump: config_bump,
+            ..marketplace_config()
+        };
+
+        vec![
+            TestAccount::wallet(holder).signer().writable(),
+            TestAccount::new(
+                Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &mcp_token::ID).0,
+                mcp_token::ID,
+                global_config_data(false),
+            ),
+            TestAccount::state(config_key, &config, 8 + MarketplaceConfig::LEN),
+            TestAccount::state(listing_key, &listing, 8 + Listing::LEN),
+            TestAccount::state(grant_key, &grant, 8 + AccessGrant::LEN).writable(),
+            // Laid out as the system program would leave it after creation
+            TestAccount::new(
+                pda(&[b"access", listing_key.as_ref(), to.as_ref()]).0,
+                program_id,
+                vec![0; 8 + AccessGrant::LEN],
+            )
+            .writable(),
+            TestAccount::token_account(Pubkey::new_unique(), listing.mint, holder, 1_000)
+                .writable(),
+            TestAccount::token_account(Pubkey::new_unique(), listing.mint, listing.creator, 0)
+                .writable(),
+            TestAccount::program(token::ID),
+            TestAccount::program(System::id()),
+        ]
+    }
+
+    #[test]
+    fn transferred_grant_recreated_for_new_holder_with_royalty() {
+        install_test_syscalls();
+        let program_id = crate::ID;
+        let (holder, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix_data = to.try_to_vec().unwrap();
+
+        let mut accounts = transfer_accounts(holder, Pubkey::new_unique(), to);
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let result = TransferAccessGrant::try_accounts(
+            &program_id,
+            &mut &infos[..],
+            &ix_data,
+            &mut BTreeMap::new(),
+            &mut BTreeSet::new(),
+        );
+        assert_eq!(
+            result
+                .err()
+                .map(|err| err == MarketplaceError::NotGrantHolder.into()),
+            Some(true)
+        );
+
+        let mut accounts = transfer_accounts(holder, holder, to);
+        let new_grant_key = accounts[5].key;
+        let creator_token_account = accounts[7].key;
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let mut bumps = BTreeMap::new();
+        let mut transfer_accounts = TransferAccessGrant::try_accounts(
+            &program_id,
+            &mut &infos[..],
+            &ix_data,
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .unwrap();
+        transfer_access_grant(
+            Context::new(&program_id, &mut transfer_accounts, &[], bumps),
+            to,
+        )
+        .unwrap();
+
+        // 5% of the listing price, as the secondary price is below it
+        let royalties: Vec<_> = take_cpis()
+            .into_iter()
+            .filter_map(|cpi| match TokenInstruction::unpack(&cpi.data) {
+                Ok(TokenInstruction::Transfer { amount }) if cpi.program_id == token::ID => {
+                    Some((cpi.accounts[1].pubkey, amount))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(royalties, vec![(creator_token_account, 50)]);
+        let new_grant = &transfer_accounts.new_access_grant;
+        assert_eq!(new_grant.key(), new_grant_key);
+        assert_eq!(new_grant.buyer, to);
+        assert_eq!(new_grant.granted_at, TEST_NOW);
+    }
+}
@@ -0,0 +1,662 @@
+//! auction_module module for data marketplace
+//!
+//! This module provides reserve-price auctions of listings. Converting a
+//! listing to an auction takes it off fixed-price sale while keeping its
+//! metadata and creator royalty; the highest bid is held in the bid
+//! vault and the previous high bidder is refunded when outbid. Once the auction
+//! ends, a winning bid at or above the reserve is paid to the seller and the
+//! winner receives an `AccessGrant`; otherwise the bid is refunded.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::access_module::AccessGrant;
+use crate::errors_module::MarketplaceError;
+use crate::listing_module::{DeactivationReason, Listing};
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::payout_ledger_module::PayoutLedger;
+use crate::shares_module::{pay_seller_proceeds, DatasetShares};
+use crate::stats_module::MarketplaceStats;
+use crate::utils_module::{
+    assert_not_globally_paused, assert_rent_exempt, create_pda_account, get_bump,
+};
+use crate::vaults_module::{AUCTION_SEED, AUCTION_VAULT_SEED};
+
+/// AuctionListing state account, also the authority of the bid vault
+#[account]
+pub struct AuctionListing {
+    /// The listing being auctioned
+    pub listing: Pubkey,
+
+    /// The seller receiving the winning bid
+    pub seller: Pubkey,
+
+    /// Mint bids are made in
+    pub mint: Pubkey,
+
+    /// Original creator of the dataset, paid the royalty
+    pub creator: Pubkey,
+
+    /// Royalty owed to `creator` out of the winning bid, in basis points
+    pub royalty_bps: u16,
+
+    /// Lowest winning bid
+    pub reserve_price: u64,
+
+    /// Time after which no bids are accepted
+    pub end_time: i64,
+
+    /// Highest bid so far, held in the bid vault
+    pub highest_bid: u64,
+
+    /// Bidder of `highest_bid`; the default key when there are no bids
+    pub highest_bidder: Pubkey,
+
+    /// Whether the auction has been settled
+    pub settled: bool,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl AuctionListing {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 2 + 8 + 8 + 8 + 32 + 1 + 1;
+
+    /// Whether any bid has been placed
+    pub fn has_bid(&self) -> bool {
+        self.highest_bidder != Pubkey::default()
+    }
+
+    /// Make `bidder` the high bidder at `amount`, extending the auction by
+    /// `anti_snipe_window` when the bid lands within it of the end.
+    ///
+    /// Returns the previous high bidder and the bid owed back to them, if any.
+    pub fn record_bid(
+        &mut self,
+        bidder: Pubkey,
+        amount: u64,
+        now: i64,
+        anti_snipe_window: i64,
+    ) -> Result<Option<(Pubkey, u64)>> {
+        require!(now < self.end_time, MarketplaceError::AuctionEnded);
+        require!(amount > self.highest_bid, MarketplaceError::BidTooLow);
+
+        let refund = self
+            .has_bid()
+            .then_some((self.highest_bidder, self.highest_bid));
+        self.highest_bid = amount;
+        self.highest_bidder = bidder;
+        if self.end_time - now <= anti_snipe_window {
+            self.end_time = self
+                .end_time
+                .checked_add(anti_snipe_window)
+                .ok_or(MarketplaceError::MathOverflow)?;
+        }
+
+        Ok(refund)
+    }
+}
+
+/// Emitted when an auction is settled
+#[event]
+pub struct AuctionSettled {
+    pub listing: Pubkey,
+    pub winner: Option<Pubkey>,
+    pub amount: u64,
+}
+
+/// Move `amount` out of the bid vault, signed by the auction
+fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    auction: &Account<'info, AuctionListing>,
+    vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: vault.to_account_info(),
+                to,
+                authority: auction.to_account_info(),
+            },
+            &[&[AUCTION_SEED, auction.listing.as_ref(), &[auction.bump]]],
+        ),
+        amount,
+    )
+}
+
+/// Convert an active fixed-price Listing into an auction ending `duration`
+/// seconds from now.
+///
+/// The listing is deactivated so it can no longer be bought at its fixed
+/// price, and keeps its metadata for the winner. Listings with a purchase
+/// still in escrow cannot convert, nor can listings whose royalty is paid in
+/// another mint.
+pub fn convert_to_auction(
+    ctx: Context<ConvertToAuction>,
+    _listing_id: u64,
+    reserve_price: u64,
+    duration: i64,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    require!(duration > 0, MarketplaceError::InvalidExpiry);
+    let now = Clock::get()?.unix_timestamp;
+
+    let listing = &mut ctx.accounts.listing;
+    listing.assert_auctionable(now)?;
+    listing.deactivate(DeactivationReason::ConvertedToAuction);
+    ctx.accounts.marketplace_stats.record_listing_closed()?;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.listing = listing.key();
+    auction.seller = listing.seller;
+    auction.mint = listing.mint;
+    auction.creator = listing.creator;
+    auction.royalty_bps = if listing.creator == listing.seller {
+        0
+    } else {
+        listing.royalty_bps
+    };
+    auction.reserve_price = reserve_price;
+    auction.end_time = now
+        .checked_add(duration)
+        .ok_or(MarketplaceError::InvalidExpiry)?;
+    auction.highest_bid = 0;
+    auction.highest_bidder = Pubkey::default();
+    auction.settled = false;
+    auction.bump = get_bump(&ctx.bumps, "auction")?;
+
+    Ok(())
+}
+
+/// Bid `amount` on an auction, refunding the previous high bidder.
+///
+/// A bid within the configured `anti_snipe_window` of the end pushes the end
+/// out by that window, so every bidder gets a chance to respond.
+pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let refund = ctx.accounts.auction.record_bid(
+        ctx.accounts.bidder.key(),
+        amount,
+        Clock::get()?.unix_timestamp,
+        ctx.accounts.marketplace_config.anti_snipe_window,
+    )?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.bidder_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.bidder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    if let Some((previous_bidder, previous_bid)) = refund {
+        let previous_bidder_token_account = ctx
+            .accounts
+            .previous_bidder_token_account
+            .as_ref()
+            .ok_or(MarketplaceError::InvalidBidderAccount)?;
+        require_keys_eq!(
+            previous_bidder_token_account.owner,
+            previous_bidder,
+            MarketplaceError::InvalidBidderAccount
+        );
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.auction,
+            &ctx.accounts.vault,
+            previous_bidder_token_account.to_account_info(),
+            previous_bid,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Settle an ended auction; callable by anyone.
+///
+/// A highest bid at or above the reserve is paid to the seller, less the
+/// protocol fee and the creator's royalty, and the winner is granted access
+/// to the listing. A bid below the reserve is refunded to its bidder. An
+/// auction without bids simply closes.
+/// A winner that already holds an access grant to the listing keeps it.
+///
+/// When the listing has `DatasetShares` the proceeds are split between the
+/// shareholders, whose token accounts are passed as remaining accounts. The
+/// seller-side payouts are appended to the listing's `PayoutLedger`.
+pub fn settle_auction<'info>(ctx: Context<'_, '_, '_, 'info, SettleAuction<'info>>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let auction = &ctx.accounts.auction;
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= auction.end_time, MarketplaceError::AuctionNotEnded);
+
+    let amount = auction.highest_bid;
+    let winner = auction.has_bid() && amount >= auction.reserve_price;
+    if winner {
+        let config = &ctx.accounts.marketplace_config;
+        let fee = config.bps_of(amount, config.effective_protocol_fee_bps())?;
+        let royalty = config.bps_of(amount, auction.royalty_bps)?;
+        let proceeds = amount
+            .checked_sub(fee)
+            .and_then(|proceeds| proceeds.checked_sub(royalty))
+            .ok_or(MarketplaceError::InvalidFeeBps)?;
+        if royalty > 0 {
+            let creator_token_account = ctx
+                .accounts
+                .creator_token_account
+                .as_ref()
+                .ok_or(MarketplaceError::InvalidRoyaltyAccounts)?;
+            require_keys_eq!(
+                creator_token_account.owner,
+                auction.creator,
+                MarketplaceError::InvalidRoyaltyAccounts
+            );
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                auction,
+                &ctx.accounts.vault,
+                creator_token_account.to_account_info(),
+                royalty,
+            )?;
+        }
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            auction,
+            &ctx.accounts.vault,
+            ctx.accounts.treasury.to_account_info(),
+            fee,
+        )?;
+        let payouts = pay_seller_proceeds(
+            ctx.accounts.dataset_shares.as_deref(),
+            ctx.remaining_accounts,
+            &ctx.accounts.seller_token_account,
+            &auction.mint,
+            proceeds,
+            |to, amount| {
+                transfer_from_vault(
+                    &ctx.accounts.token_program,
+                    auction,
+                    &ctx.accounts.vault,
+                    to,
+                    amount,
+                )
+            },
+        )?;
+        ctx.accounts.payout_ledger.record_payouts(
+            auction.listing,
+            get_bump(&ctx.bumps, "payout_ledger")?,
+            &payouts,
+            now,
+        );
+
+        let listing = auction.listing;
+        let bidder = auction.highest_bidder;
+        let (address, bump) = Pubkey::find_program_address(
+            &[b"access".as_ref(), listing.as_ref(), bidder.as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(
+            ctx.accounts.access_grant.key(),
+            address,
+            MarketplaceError::InvalidBidderAccount
+        );
+        let access_grant = ctx.accounts.access_grant.to_account_info();
+        if access_grant.owner != ctx.program_id {
+            create_pda_account(
+                &ctx.accounts.payer.to_account_info(),
+                &access_grant,
+                &ctx.accounts.system_program.to_account_info(),
+                8 + AccessGrant::LEN,
+                ctx.program_id,
+                &[
+                    b"access".as_ref(),
+                    listing.as_ref(),
+                    bidder.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            AccessGrant::new(listing, bidder, now, bump)
+                .try_serialize(&mut &mut access_grant.try_borrow_mut_data()?[..])?;
+            assert_rent_exempt(&access_grant)?;
+        }
+
+        ctx.accounts.marketplace_stats.record_sale(amount)?;
+    } else if auction.has_bid() {
+        let bidder_token_account = ctx
+            .accounts
+            .bidder_token_account
+            .as_ref()
+            .ok_or(MarketplaceError::InvalidBidderAccount)?;
+        require_keys_eq!(
+            bidder_token_account.owner,
+            auction.highest_bidder,
+            MarketplaceError::InvalidBidderAccount
+        );
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            auction,
+            &ctx.accounts.vault,
+            bidder_token_account.to_account_info(),
+            amount,
+        )?;
+    }
+
+    emit!(AuctionSettled {
+        listing: auction.listing,
+        winner: winner.then_some(auction.highest_bidder),
+        amount,
+    });
+    ctx.accounts.auction.settled = true;
+
+    Ok(())
+}
+
+/// Account validation for auction conversion
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct ConvertToAuction<'info> {
+    /// The seller that owns the listing
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing to auction
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+        has_one = mint,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + AuctionListing::LEN,
+        seeds = [AUCTION_SEED, listing.key().as_ref()],
+        bump,
+    )]
+    pub auction: Account<'info, AuctionListing>,
+
+    /// Token vault holding the highest bid
+    #[account(
+        init,
+        payer = seller,
+        seeds = [AUCTION_VAULT_SEED, auction.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = auction,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Mint the listing is priced in
+    pub mint: Account<'info, Mint>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for bidding
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+    /// The bidder
+    pub bidder: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The auction bid on
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, auction.listing.as_ref()],
+        bump = auction.bump,
+        constraint = !auction.settled @ MarketplaceError::AuctionAlreadySettled,
+    )]
+    pub auction: Account<'info, AuctionListing>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Token vault holding the highest bid
+    #[account(
+        mut,
+        seeds = [AUCTION_VAULT_SEED, auction.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Bidder's token account
+    #[account(
+        mut,
+        token::mint = auction.mint,
+        token::authority = bidder,
+    )]
+    pub bidder_token_account: Account<'info, TokenAccount>,
+
+    /// Previous high bidder's token account, required once there is a bid
+    #[account(
+        mut,
+        token::mint = auction.mint,
+    )]
+    pub previous_bidder_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for settlement
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    /// Pays for the winner's access grant and the payout ledger
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The auction to settle
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, auction.listing.as_ref()],
+        bump = auction.bump,
+        constraint = !auction.settled @ MarketplaceError::AuctionAlreadySettled,
+    )]
+    pub auction: Account<'info, AuctionListing>,
+
+    /// The auctioned listing
+    #[account(
+        address = auction.listing,
+        constraint = listing.dataset_shares == dataset_shares.as_ref().map(|shares| shares.key())
+            @ MarketplaceError::InvalidShareholderAccounts,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Fractional owners of the listing, required when the listing records them
+    pub dataset_shares: Option<Account<'info, DatasetShares>>,
+
+    /// Token vault holding the highest bid
+    #[account(
+        mut,
+        seeds = [AUCTION_VAULT_SEED, auction.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Treasury token account receiving the protocol fee
+    #[account(
+        mut,
+        address = marketplace_config.treasury,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Seller's token account
+    #[account(
+        mut,
+        token::mint = auction.mint,
+        token::authority = auction.seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Creator's token account, required when a royalty is due
+    #[account(
+        mut,
+        token::mint = auction.mint,
+    )]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Highest bidder's token account, required to refund a bid below the reserve
+    #[account(
+        mut,
+        token::mint = auction.mint,
+    )]
+    pub bidder_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: The winner's access grant, created in the handler at its PDA unless it exists
+    #[account(mut)]
+    pub access_grant: UncheckedAccount<'info>,
+
+    /// Payout history of the listing, created on first payout
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PayoutLedger::LEN,
+        seeds = [b"payout-ledger".as_ref(), auction.listing.as_ref()],
+        bump,
+    )]
+    pub payout_ledger: Account<'info, PayoutLedger>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction() -> AuctionListing {
+        AuctionListing {
+            listing: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            royalty_bps: 0,
+            reserve_price: 100,
+            end_time: 1_000,
+            highest_bid: 0,
+            highest_bidder: Pubkey::default(),
+            settled: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn outbidding_refunds_prior_bidder() {
+        let mut auction = auction();
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+
+        assert_eq!(auction.record_bid(first, 50, 100, 0).unwrap(), None);
+        assert_eq!(
+            auction.record_bid(second, 80, 200, 0).unwrap(),
+            Some((first, 50))
+        );
+        assert_eq!(auction.highest_bidder, second);
+        assert_eq!(auction.highest_bid, 80);
+        assert_eq!(
+            auction.record_bid(first, 80, 300, 0).unwrap_err(),
+            MarketplaceError::BidTooLow.into()
+        );
+    }
+
+    #[test]
+    fn late_bid_extends_auction() {
+        let mut auction = auction();
+        auction
+            .record_bid(Pubkey::new_unique(), 50, 950, 60)
+            .unwrap();
+        assert_eq!(auction.end_time, 1_060);
+        assert_eq!(
+            auction
+                .record_bid(Pubkey::new_unique(), 60, 1_060, 60)
+                .unwrap_err(),
+            MarketplaceError::AuctionEnded.into()
+        );
+    }
+
+    #[test]
+    fn only_bid_in_final_window_extends_auction() {
+        let mut auction = auction();
+        auction
+            .record_bid(Pubkey::new_unique(), 50, 500, 60)
+            .unwrap();
+        assert_eq!(auction.end_time, 1_000);
+
+        auction
+            .record_bid(Pubkey::new_unique(), 60, 995, 60)
+            .unwrap();
+        assert_eq!(auction.end_time, 1_060);
+    }
+}
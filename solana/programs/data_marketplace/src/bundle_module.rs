@@ -0,0 +1,569 @@
+//! bundle_module module for data marketplace
+//!
+//! This module provides atomic purchases of several listings in one
+//! instruction. Each listing in a bundle is escrowed exactly as `purchase`
+//! escrows it, with its own Escrow, token vaults and AccessGrant, and is then
+//! released, refunded or force resolved on its own through the escrow
+//! instructions, which pay the fee, royalty, shareholders and payout ledger.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::access_module::AccessGrant;
+use crate::errors_module::MarketplaceError;
+use crate::escrow_module::{
+    purchase_fee_bps, purchase_terms, BuyerEscrows, Escrow, EscrowStatus, PurchaseTerms,
+};
+use crate::feature_flags_module::{FeatureFlags, ENABLE_BUNDLE_PURCHASES};
+use crate::listing_module::Listing;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::seller_profile_module::SellerProfile;
+use crate::spend_limit_module::record_spend;
+use crate::stats_module::MarketplaceStats;
+use crate::utils_module::{
+    assert_batch_size, assert_not_globally_paused, assert_rent_exempt, create_pda_account, get_bump,
+};
+use crate::vaults_module::{
+    escrow_signer_seeds, find_escrow_address, ESCROW_VAULT_SEED, ROYALTY_VAULT_SEED,
+};
+
+/// Remaining accounts passed per listing: listing, seller profile, escrow,
+/// access grant, escrow vault, royalty vault
+pub const ACCOUNTS_PER_BUNDLE_ITEM: usize = 6;
+
+/// Emitted when a bundle is purchased
+#[event]
+pub struct BundlePurchased {
+    pub buyer: Pubkey,
+    pub listing_ids: Vec<u64>,
+    pub total: u64,
+}
+
+/// Purchase every listing in `listing_ids` or none of them.
+///
+/// For each listing, in order, the remaining accounts must hold the listing,
+/// the seller's profile PDA, which need not exist yet, and the escrow, access
+/// grant, escrow vault and royalty vault PDAs to create for the purchase. The
+/// buyer pays in `mint` and gets the same fee discounts, rebates and
+/// `fee_payer` split as with `purchase`. Listings containing personal data
+/// require an acknowledgement, and royalties in a separate royalty mint or
+/// listings priced in a mint other than `mint` a conversion, so none of them
+/// can be bundled.
+///
+/// Fails with `MaxTotalExceeded` when the buyer would pay more than
+/// `max_total` in all. Any failure aborts the instruction, so no listing is
+/// escrowed unless all are.
+pub fn purchase_bundle<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchaseBundle<'info>>,
+    listing_ids: Vec<u64>,
+    max_total: u64,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts
+        .feature_flags
+        .require_enabled(ENABLE_BUNDLE_PURCHASES)?;
+    validate_bundle(&listing_ids, ctx.remaining_accounts.len())?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let buyer = ctx.accounts.buyer.key();
+    let mint = ctx.accounts.mint.key();
+    let buyer_escrows = &mut ctx.accounts.buyer_escrows;
+    if buyer_escrows.buyer == Pubkey::default() {
+        buyer_escrows.buyer = buyer;
+        buyer_escrows.bump = get_bump(&ctx.bumps, "buyer_escrows")?;
+    }
+
+    let mut total_price: u64 = 0;
+    let mut total: u64 = 0;
+    for (listing_id, accounts) in listing_ids
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(ACCOUNTS_PER_BUNDLE_ITEM))
+    {
+        let [listing_info, profile_info, escrow_info, grant_info, vault_info, royalty_vault_info] =
+            accounts
+        else {
+            return err!(MarketplaceError::InvalidBundle);
+        };
+
+        let mut listing = Account::<Listing>::try_from(listing_info)?;
+        let listing_key = listing.key();
+        let price = bundle_item_price(
+            &mut listing,
+            &listing_key,
+            *listing_id,
+            &mint,
+            now,
+            ctx.program_id,
+        )?;
+
+        let (profile_address, _) = Pubkey::find_program_address(
+            &[b"seller".as_ref(), listing.seller.as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(
+            profile_info.key(),
+            profile_address,
+            MarketplaceError::InvalidBundle
+        );
+        let seller_profile = match profile_info.data_is_empty() {
+            true => None,
+            false => Some(Account::<SellerProfile>::try_from(profile_info)?),
+        };
+
+        let PurchaseTerms {
+            protocol_fee,
+            royalty,
+            escrowed,
+        } = bundle_item_terms(
+            &ctx.accounts.marketplace_config,
+            &buyer,
+            ctx.accounts.buyer_stake_account.as_deref(),
+            seller_profile.as_deref(),
+            &listing,
+            price,
+        )?;
+        total_price = total_price
+            .checked_add(price)
+            .ok_or(MarketplaceError::MathOverflow)?;
+        total = escrowed
+            .checked_add(royalty)
+            .and_then(|paid| total.checked_add(paid))
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        let (escrow_address, escrow_bump) =
+            find_escrow_address(&listing_key, &buyer, ctx.program_id);
+        require_keys_eq!(
+            escrow_info.key(),
+            escrow_address,
+            MarketplaceError::InvalidBundle
+        );
+        let escrow_bump = [escrow_bump];
+        create_pda_account(
+            &ctx.accounts.buyer.to_account_info(),
+            escrow_info,
+            &ctx.accounts.system_program.to_account_info(),
+            8 + Escrow::LEN,
+            ctx.program_id,
+            &escrow_signer_seeds(&listing_key, &buyer, &escrow_bump),
+        )?;
+        let escrow = Escrow {
+            listing: listing_key,
+            buyer,
+            payer: buyer,
+            seller: listing.seller,
+            amount: escrowed,
+            protocol_fee,
+            royalty,
+            referrer: None,
+            referral_fee: 0,
+            released_so_far: 0,
+            status: EscrowStatus::Funded,
+            created_at: now,
+            buyer_evidence_hash: [0; 32],
+            seller_evidence_hash: [0; 32],
+            bump: escrow_bump[0],
+        };
+        escrow.try_serialize(&mut &mut escrow_info.try_borrow_mut_data()?[..])?;
+        assert_rent_exempt(escrow_info)?;
+
+        let grant_seeds = [b"access".as_ref(), listing_key.as_ref(), buyer.as_ref()];
+        let (grant_address, grant_bump) =
+            Pubkey::find_program_address(&grant_seeds, ctx.program_id);
+        require_keys_eq!(
+            grant_info.key(),
+            grant_address,
+            MarketplaceError::InvalidBundle
+        );
+        create_pda_account(
+            &ctx.accounts.buyer.to_account_info(),
+            grant_info,
+            &ctx.accounts.system_program.to_account_info(),
+            8 + AccessGrant::LEN,
+            ctx.program_id,
+            &[
+                grant_seeds[0],
+                grant_seeds[1],
+                grant_seeds[2],
+                &[grant_bump],
+            ],
+        )?;
+        AccessGrant::new(listing_key, buyer, now, grant_bump)
+            .try_serialize(&mut &mut grant_info.try_borrow_mut_data()?[..])?;
+        assert_rent_exempt(grant_info)?;
+
+        for (vault_info, seed, amount) in [
+            (vault_info, ESCROW_VAULT_SEED, escrowed),
+            (royalty_vault_info, ROYALTY_VAULT_SEED, royalty),
+        ] {
+            let (vault_address, vault_bump) =
+                Pubkey::find_program_address(&[seed, escrow_address.as_ref()], ctx.program_id);
+            require_keys_eq!(
+                vault_info.key(),
+                vault_address,
+                MarketplaceError::InvalidBundle
+            );
+            create_pda_account(
+                &ctx.accounts.buyer.to_account_info(),
+                vault_info,
+                &ctx.accounts.system_program.to_account_info(),
+                TokenAccount::LEN,
+                &token::ID,
+                &[seed, escrow_address.as_ref(), &[vault_bump]],
+            )?;
+            token::initialize_account3(CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::InitializeAccount3 {
+                    account: vault_info.clone(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    authority: escrow_info.clone(),
+                },
+            ))?;
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::Transfer {
+                            from: ctx.accounts.buyer_token_account.to_account_info(),
+                            to: vault_info.clone(),
+                            authority: ctx.accounts.buyer.to_account_info(),
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+        }
+
+        ctx.accounts
+            .buyer_escrows
+            .open(ctx.accounts.marketplace_config.max_open_escrows)?;
+        listing.record_escrow_opened()?;
+        listing.exit(ctx.program_id)?;
+        ctx.accounts.marketplace_stats.record_sale(price)?;
+    }
+    require!(total <= max_total, MarketplaceError::MaxTotalExceeded);
+    record_spend(&ctx.accounts.spend_window, total_price, now, ctx.program_id)?;
+
+    emit!(BundlePurchased {
+        buyer,
+        listing_ids,
+        total,
+    });
+
+    Ok(())
+}
+
+/// Check `listing_ids` names at most `MAX_BATCH` listings, none twice, with
+/// `ACCOUNTS_PER_BUNDLE_ITEM` of the `remaining_accounts` for each
+fn validate_bundle(listing_ids: &[u64], remaining_accounts: usize) -> Result<()> {
+    require!(!listing_ids.is_empty(), MarketplaceError::InvalidBundle);
+    assert_batch_size(listing_ids.len())?;
+    require!(
+        remaining_accounts == listing_ids.len() * ACCOUNTS_PER_BUNDLE_ITEM,
+        MarketplaceError::InvalidBundle
+    );
+    for (i, listing_id) in listing_ids.iter().enumerate() {
+        require!(
+            !listing_ids[..i].contains(listing_id),
+            MarketplaceError::InvalidBundle
+        );
+    }
+
+    Ok(())
+}
+
+/// Price in `mint` of bundling the listing at `listing_key` as `listing_id` at
+/// `now`, recording the buyer.
+///
+/// Rejects listings that are not purchasable, need a PII acknowledgement or a
+/// conversion, or pay royalties in a separate royalty mint.
+fn bundle_item_price(
+    listing: &mut Listing,
+    listing_key: &Pubkey,
+    listing_id: u64,
+    mint: &Pubkey,
+    now: i64,
+    program_id: &Pubkey,
+) -> Result<u64> {
+    let expected = Pubkey::create_program_address(
+        &[
+            b"listing".as_ref(),
+            listing_id.to_le_bytes().as_ref(),
+            &[listing.bump],
+        ],
+        program_id,
+    )
+    .map_err(|_| error!(MarketplaceError::InvalidBundle))?;
+    require_keys_eq!(*listing_key, expected, MarketplaceError::InvalidBundle);
+    listing.record_buyer()?;
+    listing.assert_purchasable(now)?;
+    let price = listing.price_in(mint, |_| err!(MarketplaceError::UnacceptedMint))?;
+    listing.assert_pii_acknowledged(false)?;
+    require!(
+        listing.royalty_mint.is_none(),
+        MarketplaceError::InvalidBundle
+    );
+
+    Ok(price)
+}
+
+/// Terms a bundled listing is escrowed on at `price`, with the buyer's stake
+/// discount and the seller's rebate taken off the protocol fee
+fn bundle_item_terms(
+    config: &MarketplaceConfig,
+    buyer: &Pubkey,
+    buyer_stake_account: Option<&AccountInfo>,
+    seller_profile: Option<&SellerProfile>,
+    listing: &Listing,
+    price: u64,
+) -> Result<PurchaseTerms> {
+    let protocol_fee_bps = purchase_fee_bps(config, buyer, buyer_stake_account, seller_profile)?;
+    purchase_terms(config, listing, price, protocol_fee_bps)
+}
+
+/// Account validation for bundle purchase
+#[derive(Accounts)]
+pub struct PurchaseBundle<'info> {
+    /// The buyer paying for the bundle and the accounts it creates
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// Feature flags
+    #[account(
+        seeds = [b"feature-flags".as_ref()],
+        bump = feature_flags.bump,
+    )]
+    pub feature_flags: Account<'info, FeatureFlags>,
+
+    /// Mint every listing in the bundle is priced in
+    pub mint: Account<'info, Mint>,
+
+    /// Buyer's token account
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    /// Open escrow count of the buyer, created on first purchase
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerEscrows::LEN,
+        seeds = [b"buyer-escrows".as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_escrows: Account<'info, BuyerEscrows>,
+
+    /// CHECK: Buyer's spend window PDA, enforced only once the buyer has opted in
+    #[account(
+        mut,
+        seeds = [b"spend-window".as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub spend_window: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer's MCP stake account for the fee discount, verified in the handler
+    pub buyer_stake_account: Option<UncheckedAccount<'info>>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing_module::tests::listing;
+    use crate::marketplace_config_module::tests::marketplace_config;
+    use crate::marketplace_config_module::{FeeDiscountTier, FeePayer, SellerRebateTier};
+    use mcp_token::staking_module::StakeAccount;
+    use mcp_token::utils_module::MAX_BATCH;
+
+    /// `listing()` at the address of listing `listing_id`
+    fn bundled_listing(listing_id: u64) -> (Listing, Pubkey) {
+        let mut listing = listing();
+        let (key, bump) = Pubkey::find_program_address(
+            &[b"listing", listing_id.to_le_bytes().as_ref()],
+            &crate::ID,
+        );
+        listing.listing_id = listing_id;
+        listing.bump = bump;
+        (listing, key)
+    }
+
+    #[test]
+    fn bundle_of_unique_listings_within_batch_accepted() {
+        let ids: Vec<u64> = (0..MAX_BATCH as u64).collect();
+        validate_bundle(&ids, MAX_BATCH * ACCOUNTS_PER_BUNDLE_ITEM).unwrap();
+
+        let too_many: Vec<u64> = (0..=MAX_BATCH as u64).collect();
+        assert_eq!(
+            validate_bundle(&too_many, too_many.len() * ACCOUNTS_PER_BUNDLE_ITEM).unwrap_err(),
+            MarketplaceError::BatchTooLarge.into()
+        );
+        for (ids, accounts) in [
+            (&[][..], 0),
+            (&[1, 2][..], ACCOUNTS_PER_BUNDLE_ITEM),
+            (&[1, 2, 1][..], 3 * ACCOUNTS_PER_BUNDLE_ITEM),
+        ] {
+            assert_eq!(
+                validate_bundle(ids, accounts).unwrap_err(),
+                MarketplaceError::InvalidBundle.into()
+            );
+        }
+    }
+
+    #[test]
+    fn bundle_item_priced_and_buyer_recorded() {
+        let (mut listing, key) = bundled_listing(7);
+        let mint = listing.mint;
+
+        assert_eq!(
+            bundle_item_price(&mut listing, &key, 7, &mint, 0, &crate::ID).unwrap(),
+            1_000
+        );
+        assert_eq!(listing.buyer_count, 1);
+        assert_eq!(
+            bundle_item_price(&mut listing, &key, 8, &mint, 0, &crate::ID).unwrap_err(),
+            MarketplaceError::InvalidBundle.into()
+        );
+        assert_eq!(
+            bundle_item_price(&mut listing, &key, 7, &Pubkey::new_unique(), 0, &crate::ID)
+                .unwrap_err(),
+            MarketplaceError::UnacceptedMint.into()
+        );
+    }
+
+    #[test]
+    fn unbundleable_listing_aborts_bundle() {
+        let (base, key) = bundled_listing(7);
+        let mint = base.mint;
+        let mut inactive = base.clone();
+        inactive.active = false;
+        let mut pii = base.clone();
+        pii.contains_pii = true;
+        let mut royalty_mint = base;
+        royalty_mint.royalty_mint = Some(Pubkey::new_unique());
+
+        for (mut listing, error) in [
+            (inactive, MarketplaceError::ListingInactive),
+            (pii, MarketplaceError::PiiNotAcknowledged),
+            (royalty_mint, MarketplaceError::InvalidBundle),
+        ] {
+            assert_eq!(
+                bundle_item_price(&mut listing, &key, 7, &mint, 0, &crate::ID).unwrap_err(),
+                error.into()
+            );
+        }
+    }
+
+    #[test]
+    fn bundle_item_fee_discounted_for_stake_and_seller_sales() {
+        let mut config = marketplace_config();
+        config.fee_payer = FeePayer::Buyer;
+        config.discount_stake_pool = Pubkey::new_unique();
+        config.fee_discount_tiers = vec![FeeDiscountTier {
+            min_stake: 100,
+            discount_bps: 200,
+        }];
+        config.seller_rebate_tiers = vec![SellerRebateTier {
+            min_sales: 10,
+            rebate_bps: 100,
+        }];
+        let mut listing = listing();
+        listing.creator = Pubkey::new_unique();
+        listing.royalty_bps = 1_000;
+        let buyer = Pubkey::new_unique();
+        let profile = SellerProfile {
+            seller: listing.seller,
+            rating_sum: 0,
+            rating_count: 0,
+            total_sales: 10,
+            bump: 255,
+        };
+        let stake = StakeAccount {
+            owner: buyer,
+            pool: config.discount_stake_pool,
+            amount: 100,
+            staked_at: 0,
+            reward_debt: 0,
+            pending_rewards: 0,
+            auto_compound: false,
+            locked_by: None,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        stake.try_serialize(&mut data).unwrap();
+        let (key, owner, mut lamports) = (Pubkey::new_unique(), mcp_token::ID, 0);
+        let stake_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        let terms = bundle_item_terms(&config, &buyer, None, None, &listing, 1_000).unwrap();
+        assert_eq!(terms.protocol_fee, 50);
+        assert_eq!(terms.royalty, 100);
+        assert_eq!(terms.escrowed, 950);
+
+        // 5% less 2% for the stake and 1% for the seller's sales
+        let terms = bundle_item_terms(
+            &config,
+            &buyer,
+            Some(&stake_info),
+            Some(&profile),
+            &listing,
+            1_000,
+        )
+        .unwrap();
+        assert_eq!(terms.protocol_fee, 20);
+        assert_eq!(terms.royalty, 100);
+        assert_eq!(terms.escrowed, 920);
+
+        assert_eq!(
+            bundle_item_terms(
+                &config,
+                &Pubkey::new_unique(),
+                Some(&stake_info),
+                None,
+                &listing,
+                1_000
+            )
+            .err(),
+            Some(MarketplaceError::InvalidStakeAccount.into())
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! collateral_module module for data marketplace
+//!
+//! This module provides the ways out of a listing's collateral vault. The
+//! seller takes the collateral back once the listing is no longer sold and has
+//! no open escrows; until then the marketplace admin can slash any part of it
+//! into the protocol fee vault of the listing's mint.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::listing_module::Listing;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::utils_module::assert_not_globally_paused;
+use crate::vaults_module::{COLLATERAL_VAULT_SEED, FEE_VAULT_SEED};
+
+/// Emitted when the admin slashes a listing's collateral
+#[event]
+pub struct CollateralSlashed {
+    pub listing: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+/// Move `amount` out of the collateral vault, signed by the listing
+fn transfer_collateral<'info>(
+    listing: &Account<'info, Listing>,
+    collateral_vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let id_bytes = listing.listing_id.to_le_bytes();
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: collateral_vault.to_account_info(),
+                to,
+                authority: listing.to_account_info(),
+            },
+            &[&[b"listing".as_ref(), id_bytes.as_ref(), &[listing.bump]]],
+        ),
+        amount,
+    )
+}
+
+/// Return the whole collateral to the seller once the listing is inactive or
+/// expired and has no open escrows
+pub fn release_collateral(ctx: Context<ReleaseCollateral>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let listing = &mut ctx.accounts.listing;
+    listing.assert_collateral_releasable(Clock::get()?.unix_timestamp)?;
+    let amount = listing.collateral;
+    listing.take_collateral(amount)?;
+
+    transfer_collateral(
+        &ctx.accounts.listing,
+        &ctx.accounts.collateral_vault,
+        ctx.accounts.seller_token_account.to_account_info(),
+        &ctx.accounts.token_program,
+        amount,
+    )
+}
+
+/// Slash `amount` of a listing's collateral into the fee vault of its mint.
+///
+/// Only the marketplace admin may call it, and only while the collateral is
+/// still locked.
+pub fn slash_collateral(ctx: Context<SlashCollateral>, amount: u64) -> Result<()> {
+    let listing = &mut ctx.accounts.listing;
+    listing.slash_collateral(amount, Clock::get()?.unix_timestamp)?;
+
+    emit!(CollateralSlashed {
+        listing: listing.key(),
+        amount,
+        remaining: listing.collateral,
+    });
+
+    transfer_collateral(
+        &ctx.accounts.listing,
+        &ctx.accounts.collateral_vault,
+        ctx.accounts.fee_vault.to_account_info(),
+        &ctx.accounts.token_program,
+        amount,
+    )
+}
+
+/// Account validation for collateral release
+#[derive(Accounts)]
+pub struct ReleaseCollateral<'info> {
+    /// The seller of the listing
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing the collateral was locked for
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing.listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Vault holding the seller collateral
+    #[account(
+        mut,
+        seeds = [COLLATERAL_VAULT_SEED, listing.key().as_ref()],
+        bump,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    /// Seller's token account receiving the collateral
+    #[account(
+        mut,
+        token::mint = collateral_vault.mint,
+        token::authority = seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for collateral slashing
+#[derive(Accounts)]
+pub struct SlashCollateral<'info> {
+    /// The marketplace admin
+    pub admin: Signer<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        has_one = admin,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The listing whose collateral is slashed
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing.listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Vault holding the seller collateral
+    #[account(
+        mut,
+        seeds = [COLLATERAL_VAULT_SEED, listing.key().as_ref()],
+        bump,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    /// Protocol fee vault of the collateral's mint
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED, collateral_vault.mint.as_ref()],
+        bump,
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,179 @@
+//! conversion_module module for data marketplace
+//!
+//! This module provides admin-set conversion rates between mints, used when a
+//! payment priced in one mint is settled in another, such as a royalty paid in
+//! a dedicated royalty mint. Rates are quoted per whole token, so conversion
+//! accounts for the decimals of both mints.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::errors_module::MarketplaceError;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::utils_module::get_bump;
+
+/// Fixed-point scale of `ConversionRate::rate`
+pub const CONVERSION_RATE_PRECISION: u64 = 1_000_000_000;
+
+/// ConversionRate state account
+#[account]
+pub struct ConversionRate {
+    /// Mint amounts are converted from
+    pub from_mint: Pubkey,
+
+    /// Mint amounts are converted to
+    pub to_mint: Pubkey,
+
+    /// Whole `to_mint` tokens per whole `from_mint` token, scaled by `CONVERSION_RATE_PRECISION`
+    pub rate: u64,
+
+    /// Time the rate was last set
+    pub updated_at: i64,
+
+    /// Seconds after `updated_at` during which the rate may be used
+    pub max_staleness: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ConversionRate {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 1;
+
+    /// Convert `amount` base units of `from` into base units of `to`.
+    ///
+    /// Fails when the mints do not match the rate, or the rate is zero or
+    /// older than `max_staleness`. The result is rounded down.
+    pub fn convert(
+        &self,
+        amount: u64,
+        from: &Account<Mint>,
+        to: &Account<Mint>,
+        now: i64,
+    ) -> Result<u64> {
+        require!(
+            self.from_mint == from.key() && self.to_mint == to.key(),
+            MarketplaceError::InvalidConversionRate
+        );
+        self.convert_amount(amount, from.decimals, to.decimals, now)
+    }
+
+    /// Convert `amount` between mints with the given decimals, without checking the mints
+    pub fn convert_amount(
+        &self,
+        amount: u64,
+        from_decimals: u8,
+        to_decimals: u8,
+        now: i64,
+    ) -> Result<u64> {
+        require!(self.rate > 0, MarketplaceError::InvalidConversionRate);
+        let stale_at = self
+            .updated_at
+            .checked_add(self.max_staleness)
+            .ok_or(MarketplaceError::MathOverflow)?;
+        require!(now <= stale_at, MarketplaceError::StaleConversionRate);
+
+        let numerator = 10u128
+            .checked_pow(to_decimals as u32)
+            .and_then(|scale| scale.checked_mul(self.rate as u128))
+            .and_then(|scale| scale.checked_mul(amount as u128))
+            .ok_or(MarketplaceError::MathOverflow)?;
+        let denominator = 10u128
+            .checked_pow(from_decimals as u32)
+            .and_then(|scale| scale.checked_mul(CONVERSION_RATE_PRECISION as u128))
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        u64::try_from(numerator / denominator).map_err(|_| error!(MarketplaceError::MathOverflow))
+    }
+}
+
+/// Set the rate converting `from_mint` amounts into `to_mint`, valid for `max_staleness` seconds
+pub fn set_conversion_rate(
+    ctx: Context<SetConversionRate>,
+    rate: u64,
+    max_staleness: i64,
+) -> Result<()> {
+    require!(
+        rate > 0 && max_staleness > 0,
+        MarketplaceError::InvalidConversionRate
+    );
+
+    let conversion_rate = &mut ctx.accounts.conversion_rate;
+    conversion_rate.from_mint = ctx.accounts.from_mint.key();
+    conversion_rate.to_mint = ctx.accounts.to_mint.key();
+    conversion_rate.rate = rate;
+    conversion_rate.updated_at = Clock::get()?.unix_timestamp;
+    conversion_rate.max_staleness = max_staleness;
+    conversion_rate.bump = get_bump(&ctx.bumps, "conversion_rate")?;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct SetConversionRate<'info> {
+    /// The admin of the marketplace
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        has_one = admin,
+        constraint = !marketplace_config.config_frozen @ MarketplaceError::ConfigFrozen,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Mint amounts are converted from
+    pub from_mint: Account<'info, Mint>,
+
+    /// Mint amounts are converted to
+    pub to_mint: Account<'info, Mint>,
+
+    /// The account to initialize or update
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + ConversionRate::LEN,
+        seeds = [b"conversion-rate".as_ref(), from_mint.key().as_ref(), to_mint.key().as_ref()],
+        bump,
+    )]
+    pub conversion_rate: Account<'info, ConversionRate>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversion_rate() -> ConversionRate {
+        ConversionRate {
+            from_mint: Pubkey::new_unique(),
+            to_mint: Pubkey::new_unique(),
+            rate: 2 * CONVERSION_RATE_PRECISION,
+            updated_at: 1_000,
+            max_staleness: 60,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn royalty_converted_across_decimals() {
+        let rate = conversion_rate();
+        assert_eq!(rate.convert_amount(50, 6, 9, 1_060).unwrap(), 100_000);
+        assert_eq!(rate.convert_amount(100_000, 9, 6, 1_060).unwrap(), 200);
+    }
+
+    #[test]
+    fn stale_rate_rejected() {
+        assert_eq!(
+            conversion_rate()
+                .convert_amount(50, 6, 6, 1_061)
+                .unwrap_err(),
+            MarketplaceError::StaleConversionRate.into()
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! credits_module module for data marketplace
+//!
+//! This module provides marketplace credits bought and redeemed for tokens at
+//! the rate set on `MarketplaceConfig`. Both directions use the rate current at
+//! the time of the call, so a rate change applies equally to everyone holding
+//! credits.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::utils_module::{assert_not_globally_paused, get_bump, mul_div};
+use crate::vaults_module::CREDITS_VAULT_SEED;
+
+/// Fixed-point scale of `MarketplaceConfig::credit_rate`
+pub const CREDIT_RATE_PRECISION: u64 = 1_000_000;
+
+/// Credits state account
+#[account]
+pub struct Credits {
+    /// The owner of the credits
+    pub owner: Pubkey,
+
+    /// Credit balance
+    pub balance: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Credits {
+    pub const LEN: usize = 32 + 8 + 1;
+}
+
+/// Credits bought with `amount` tokens at the current rate of `config`
+pub fn credits_for_tokens(config: &MarketplaceConfig, amount: u64) -> Result<u64> {
+    require!(config.credit_rate > 0, MarketplaceError::CreditsDisabled);
+    let credits = mul_div(amount, CREDIT_RATE_PRECISION, config.credit_rate)?;
+    require!(credits > 0, MarketplaceError::InvalidCreditAmount);
+
+    Ok(credits)
+}
+
+/// Tokens paid out for `credits` at the current rate of `config`
+pub fn tokens_for_credits(config: &MarketplaceConfig, credits: u64) -> Result<u64> {
+    require!(config.credit_rate > 0, MarketplaceError::CreditsDisabled);
+    let amount = mul_div(credits, config.credit_rate, CREDIT_RATE_PRECISION)?;
+    require!(amount > 0, MarketplaceError::InvalidCreditAmount);
+
+    Ok(amount)
+}
+
+/// Buy credits with `amount` tokens at the current rate
+pub fn buy_credits(ctx: Context<BuyCredits>, amount: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let credits = credits_for_tokens(&ctx.accounts.marketplace_config, amount)?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.credits_vault.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let account = &mut ctx.accounts.credits;
+    if account.owner == Pubkey::default() {
+        account.owner = ctx.accounts.owner.key();
+        account.bump = get_bump(&ctx.bumps, "credits")?;
+    }
+    account.balance = account
+        .balance
+        .checked_add(credits)
+        .ok_or(MarketplaceError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Redeem `credits` for tokens at the current rate
+pub fn redeem_credits(ctx: Context<RedeemCredits>, credits: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let amount = tokens_for_credits(&ctx.accounts.marketplace_config, credits)?;
+
+    let account = &mut ctx.accounts.credits;
+    account.balance = account
+        .balance
+        .checked_sub(credits)
+        .ok_or(MarketplaceError::InsufficientCredits)?;
+
+    let bump = [get_bump(&ctx.bumps, "credits_vault")?];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.credits_vault.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.credits_vault.to_account_info(),
+            },
+            &[&[CREDITS_VAULT_SEED, bump.as_ref()]],
+        ),
+        amount,
+    )
+}
+
+/// Create the vault backing marketplace credits
+pub fn initialize_credits_vault(_ctx: Context<InitializeCreditsVault>) -> Result<()> {
+    Ok(())
+}
+
+/// Account validation for buying credits
+#[derive(Accounts)]
+pub struct BuyCredits<'info> {
+    /// The buyer of the credits
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The owner's credits, created on first purchase
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + Credits::LEN,
+        seeds = [b"credits".as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub credits: Account<'info, Credits>,
+
+    /// Vault backing marketplace credits
+    #[account(
+        mut,
+        seeds = [CREDITS_VAULT_SEED],
+        bump,
+    )]
+    pub credits_vault: Account<'info, TokenAccount>,
+
+    /// Owner's token account
+    #[account(
+        mut,
+        token::mint = credits_vault.mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for redeeming credits
+#[derive(Accounts)]
+pub struct RedeemCredits<'info> {
+    /// The owner of the credits
+    pub owner: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The owner's credits
+    #[account(
+        mut,
+        seeds = [b"credits".as_ref(), owner.key().as_ref()],
+        bump = credits.bump,
+        has_one = owner,
+    )]
+    pub credits: Account<'info, Credits>,
+
+    /// Vault backing marketplace credits
+    #[account(
+        mut,
+        seeds = [CREDITS_VAULT_SEED],
+        bump,
+    )]
+    pub credits_vault: Account<'info, TokenAccount>,
+
+    /// Owner's token account
+    #[account(
+        mut,
+        token::mint = credits_vault.mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for credits vault creation
+#[derive(Accounts)]
+pub struct InitializeCreditsVault<'info> {
+    /// The admin of the marketplace
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        has_one = admin,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The vault to initialize; it is its own authority
+    #[account(
+        init,
+        payer = admin,
+        seeds = [CREDITS_VAULT_SEED],
+        bump,
+        token::mint = mint,
+        token::authority = credits_vault,
+    )]
+    pub credits_vault: Account<'info, TokenAccount>,
+
+    /// Mint credits are bought and redeemed in
+    pub mint: Account<'info, Mint>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace_config_module::tests::marketplace_config;
+
+    #[test]
+    fn credits_redeemed_at_rate_current_at_redemption() {
+        let mut config = marketplace_config();
+        assert_eq!(
+            credits_for_tokens(&config, 1_000).unwrap_err(),
+            MarketplaceError::CreditsDisabled.into()
+        );
+
+        config.credit_rate = CREDIT_RATE_PRECISION / 2;
+        let credits = credits_for_tokens(&config, 1_000).unwrap();
+        assert_eq!(credits, 2_000);
+
+        config.credit_rate = CREDIT_RATE_PRECISION;
+        assert_eq!(tokens_for_credits(&config, credits).unwrap(), 2_000);
+    }
+}
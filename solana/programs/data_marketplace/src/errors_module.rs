@@ -0,0 +1,237 @@
+//! errors_module module for data marketplace
+//!
+//! This module provides the error codes returned by data marketplace instructions.
+
+use anchor_lang::prelude::*;
+
+/// Data marketplace errors
+#[error_code]
+pub enum MarketplaceError {
+    #[msg("Escrow has already been settled")]
+    EscrowAlreadySettled,
+
+    #[msg("Escrow has not been stuck long enough to force resolve")]
+    EscrowNotStuck,
+
+    #[msg("Listing is not active")]
+    ListingInactive,
+
+    #[msg("Listing has expired")]
+    ListingExpired,
+
+    #[msg("Listing expiry is invalid")]
+    InvalidExpiry,
+
+    #[msg("Data URI is too long")]
+    DataUriTooLong,
+
+    #[msg("PDA bump not found")]
+    BumpNotFound,
+
+    #[msg("Too many entries")]
+    TooManyEntries,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+
+    #[msg("Fee basis points are out of range")]
+    InvalidFeeBps,
+
+    #[msg("Buyer cannot refer themselves")]
+    SelfReferral,
+
+    #[msg("Referrer token account does not match the referrer")]
+    InvalidReferrer,
+
+    #[msg("Jurisdiction must be an ISO 3166-1 alpha-2 code")]
+    InvalidJurisdiction,
+
+    #[msg("Buyer must acknowledge the dataset contains personal data")]
+    PiiNotAcknowledged,
+
+    #[msg("Seller balance does not cover the listing collateral")]
+    InsufficientCollateral,
+
+    #[msg("Unsupported instruction argument version")]
+    UnsupportedVersion,
+
+    #[msg("Shares must be non-zero, unique and sum to 10000")]
+    InvalidShares,
+
+    #[msg("Signer is not a shareholder")]
+    NotShareholder,
+
+    #[msg("Shareholder token accounts do not match the dataset shares")]
+    InvalidShareholderAccounts,
+
+    #[msg("Milestone amount exceeds the remaining escrow")]
+    InvalidMilestone,
+
+    #[msg("Configuration is frozen")]
+    ConfigFrozen,
+
+    #[msg("Daily spend limit exceeded")]
+    SpendLimitExceeded,
+
+    #[msg("Invalid bundle")]
+    InvalidBundle,
+
+    #[msg("Bundle total exceeds the buyer's maximum")]
+    MaxTotalExceeded,
+
+    #[msg("Listings with fractional owners cannot be bundled")]
+    SharedListingInBundle,
+
+    #[msg("Account is not of the expected type")]
+    AccountTypeMismatch,
+
+    #[msg("Stake account does not belong to the buyer or the discount pool")]
+    InvalidStakeAccount,
+
+    #[msg("Sub-listing prices must be positive and sum to the listing price")]
+    InvalidSplit,
+
+    #[msg("Marketplace is not accepting new listings")]
+    NotAcceptingListings,
+
+    #[msg("Royalty token accounts are missing or invalid")]
+    InvalidRoyaltyAccounts,
+
+    #[msg("Feature is disabled")]
+    FeatureDisabled,
+
+    #[msg("Marketplace credits are disabled")]
+    CreditsDisabled,
+
+    #[msg("Amount converts to zero")]
+    InvalidCreditAmount,
+
+    #[msg("Insufficient credits")]
+    InsufficientCredits,
+
+    #[msg("Account is not a sweepable protocol account")]
+    InvalidDustAccounts,
+
+    #[msg("Mint amount must be greater than zero")]
+    InvalidMintAmount,
+
+    #[msg("Listing does not accept burning for access")]
+    BurnToAccessDisabled,
+
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+
+    #[msg("Only buyers of the seller can rate them")]
+    NotABuyer,
+
+    #[msg("Buyer has too many open escrows")]
+    TooManyOpenEscrows,
+
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+
+    #[msg("Conversion rate is missing, zero or for other mints")]
+    InvalidConversionRate,
+
+    #[msg("Conversion rate is stale")]
+    StaleConversionRate,
+
+    #[msg("Missing or invalid Ed25519 signature")]
+    InvalidSignature,
+
+    #[msg("Data URI scheme is not allowed")]
+    UnsupportedUriScheme,
+
+    #[msg("Listing is not priced in native SOL")]
+    NotSolListing,
+
+    #[msg("Auction has ended")]
+    AuctionEnded,
+
+    #[msg("Auction has not ended")]
+    AuctionNotEnded,
+
+    #[msg("Auction is already settled")]
+    AuctionAlreadySettled,
+
+    #[msg("Bid must exceed the highest bid")]
+    BidTooLow,
+
+    #[msg("Token account does not belong to the bidder")]
+    InvalidBidderAccount,
+
+    #[msg("Listing has reached its maximum number of buyers")]
+    BuyerLimitReached,
+
+    #[msg("Listing metadata is locked")]
+    MetadataLocked,
+
+    #[msg("Account is not a protocol fee vault for the treasury mint")]
+    InvalidFeeVault,
+
+    #[msg("Listing has purchases still in escrow")]
+    ListingHasOpenEscrows,
+
+    #[msg("Payment mint is not accepted by the listing")]
+    UnacceptedMint,
+
+    #[msg("Listing cannot be purchased yet")]
+    ListingNotYetPurchasable,
+
+    #[msg("Listing snapshot violates listing invariants")]
+    InvalidSnapshot,
+
+    #[msg("Account would not be rent exempt")]
+    NotRentExempt,
+
+    #[msg("Access grant cannot be transferred")]
+    GrantNotTransferable,
+
+    #[msg("Signer is not the congestion keeper")]
+    NotCongestionKeeper,
+
+    #[msg("Subscription period has not ended")]
+    SubscriptionActive,
+
+    #[msg("Subscription has already expired")]
+    SubscriptionExpired,
+
+    #[msg("Signer is neither the buyer nor the seller of the escrow")]
+    NotEscrowParty,
+
+    #[msg("Batch exceeds the maximum size")]
+    BatchTooLarge,
+
+    #[msg("Stuck timeout must be positive")]
+    InvalidStuckTimeout,
+
+    #[msg("Token account is not a treasury account for this mint")]
+    InvalidTreasury,
+
+    #[msg("Listing was converted, split or migrated and cannot be relisted")]
+    ListingNotRelistable,
+
+    #[msg("No program version to import listings from is configured")]
+    MigrationSourceNotSet,
+
+    #[msg("Source listing has not been exported")]
+    ListingNotExported,
+
+    #[msg("Listing has no collateral locked")]
+    NoCollateral,
+
+    #[msg("Collateral stays locked while the listing is live or has open escrows")]
+    CollateralLocked,
+
+    #[msg("Slash amount exceeds the locked collateral")]
+    SlashExceedsCollateral,
+
+    #[msg("Listings with dataset shares cannot be exported")]
+    ListingHasShares,
+
+    #[msg("Signer does not hold the access grant")]
+    NotGrantHolder,
+
+    #[msg("Collateral is no longer locked and can only be released")]
+    CollateralUnlocked,
+}
@@ -0,0 +1,2106 @@
+//! escrow_module module for data marketplace
+//!
+//! This module provides escrow settlement for dataset purchases.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+use mcp_token::staking_module::StakeAccount;
+
+use crate::access_module::AccessGrant;
+use crate::conversion_module::ConversionRate;
+use crate::errors_module::MarketplaceError;
+use crate::listing_module::Listing;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::payout_ledger_module::PayoutLedger;
+use crate::seller_profile_module::SellerProfile;
+use crate::shares_module::{pay_seller_proceeds, DatasetShares};
+use crate::spend_limit_module::record_spend;
+use crate::stats_module::MarketplaceStats;
+use crate::utils_module::{
+    assert_batch_size, assert_not_globally_paused, get_bump, load_foreign_account, mul_div,
+    verify_ed25519_signature,
+};
+use crate::vaults_module::{
+    escrow_signer_seeds, ESCROW_SEED, ESCROW_VAULT_SEED, ROYALTY_VAULT_SEED,
+};
+use crate::versioning_module::Versioned;
+
+/// Lifecycle of an escrow
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum EscrowStatus {
+    /// Buyer funds are held in the vault
+    Funded,
+    /// Funds were paid out to the seller
+    Released,
+    /// Funds were returned to the buyer
+    Refunded,
+    /// Funds were routed by the admin break-glass path
+    ForceResolved,
+}
+
+/// Escrow state account
+#[account]
+pub struct Escrow {
+    /// The listing being purchased
+    pub listing: Pubkey,
+
+    /// The buyer the dataset is sold to
+    pub buyer: Pubkey,
+
+    /// The account that funded the escrow and receives any refund; usually `buyer`
+    pub payer: Pubkey,
+
+    /// The seller that receives the funds on release
+    pub seller: Pubkey,
+
+    /// Amount held in the vault
+    pub amount: u64,
+
+    /// Portion of `amount` owed to the treasury on release
+    pub protocol_fee: u64,
+
+    /// Creator royalty held in the royalty vault, in the royalty mint, until
+    /// the escrow is settled
+    pub royalty: u64,
+
+    /// Referrer rewarded out of the protocol fee as the escrow is released to the seller
+    pub referrer: Option<Pubkey>,
+
+    /// Portion of `protocol_fee` owed to `referrer`
+    pub referral_fee: u64,
+
+    /// Amount already released to the seller through milestones
+    pub released_so_far: u64,
+
+    /// Status of the escrow
+    pub status: EscrowStatus,
+
+    /// Creation time
+    pub created_at: i64,
+
+    /// Hash of the buyer's off-chain dispute evidence; zero if none
+    pub buyer_evidence_hash: [u8; 32],
+
+    /// Hash of the seller's off-chain dispute evidence; zero if none
+    pub seller_evidence_hash: [u8; 32],
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Escrow {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 8 + 8 + 33 + 8 + 8 + 1 + 8 + 32 + 32 + 1;
+
+    pub fn is_settled(&self) -> bool {
+        self.status != EscrowStatus::Funded
+    }
+
+    /// Record `party`'s evidence hash, rejecting anyone but the buyer or seller
+    pub fn record_evidence(&mut self, party: Pubkey, evidence_hash: [u8; 32]) -> Result<()> {
+        require!(!self.is_settled(), MarketplaceError::EscrowAlreadySettled);
+        if party == self.buyer {
+            self.buyer_evidence_hash = evidence_hash;
+        } else if party == self.seller {
+            self.seller_evidence_hash = evidence_hash;
+        } else {
+            return err!(MarketplaceError::NotEscrowParty);
+        }
+
+        Ok(())
+    }
+
+    /// Amount still held in the vault
+    pub fn remaining(&self) -> Result<u64> {
+        self.amount
+            .checked_sub(self.released_so_far)
+            .ok_or_else(|| error!(MarketplaceError::MathOverflow))
+    }
+
+    /// Check that a milestone of `amount` is non-zero and within what remains
+    pub fn assert_milestone(&self, amount: u64) -> Result<()> {
+        require!(
+            amount > 0 && amount <= self.remaining()?,
+            MarketplaceError::InvalidMilestone
+        );
+
+        Ok(())
+    }
+
+    /// Message the buyer signs to confirm receipt of `amount` from `escrow`.
+    ///
+    /// It covers the escrow, the amount and the amount released so far, so a
+    /// receipt authorizes exactly one release and cannot be replayed for a
+    /// later milestone of the same size.
+    pub fn receipt_message(&self, escrow: &Pubkey, amount: u64) -> Vec<u8> {
+        let mut message = Vec::with_capacity(32 + 8 + 8);
+        message.extend_from_slice(escrow.as_ref());
+        message.extend_from_slice(&amount.to_le_bytes());
+        message.extend_from_slice(&self.released_so_far.to_le_bytes());
+        message
+    }
+
+    /// Cumulative protocol fee charged once `released` has been paid out.
+    ///
+    /// Equals `protocol_fee` exactly when the whole amount is released, so
+    /// milestone rounding never leaves fee dust behind.
+    pub fn fee_released_at(&self, released: u64) -> Result<u64> {
+        if self.amount == 0 {
+            return Ok(0);
+        }
+        mul_div(self.protocol_fee, released, self.amount)
+    }
+
+    /// Reject break-glass resolution until the escrow has been unsettled for `stuck_timeout`
+    pub fn assert_stuck(&self, stuck_timeout: i64, now: i64) -> Result<()> {
+        let stuck_after = self
+            .created_at
+            .checked_add(stuck_timeout)
+            .ok_or(MarketplaceError::EscrowNotStuck)?;
+        require!(now >= stuck_after, MarketplaceError::EscrowNotStuck);
+
+        Ok(())
+    }
+
+    /// Referral reward earned by releasing up to `released_after`, out of the
+    /// protocol `fee` charged on that release
+    pub fn referral_for_release(&self, released_after: u64, fee: u64) -> Result<u64> {
+        if self.amount == 0 {
+            return Ok(0);
+        }
+        let earned_after = mul_div(self.referral_fee, released_after, self.amount)?;
+        let earned_before = mul_div(self.referral_fee, self.released_so_far, self.amount)?;
+        let referral = earned_after
+            .checked_sub(earned_before)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(referral.min(fee))
+    }
+
+    /// Take the held royalty for payout, leaving none behind to pay twice
+    pub fn take_royalty(&mut self) -> u64 {
+        std::mem::take(&mut self.royalty)
+    }
+
+    /// Unreleased protocol fee the treasury keeps when the escrow is force resolved.
+    ///
+    /// Only a resolution for the buyer can waive it, when `refund_fee_on_dispute` is set.
+    pub fn force_resolve_fee(&self, to_seller: bool, refund_fee_on_dispute: bool) -> Result<u64> {
+        if !to_seller && refund_fee_on_dispute {
+            return Ok(0);
+        }
+        self.fee_released_at(self.amount)?
+            .checked_sub(self.fee_released_at(self.released_so_far)?)
+            .ok_or_else(|| error!(MarketplaceError::MathOverflow))
+    }
+}
+
+/// BuyerEscrows state account, counting the unsettled escrows of a buyer
+#[account]
+pub struct BuyerEscrows {
+    /// The buyer the count applies to
+    pub buyer: Pubkey,
+
+    /// Number of escrows funded by the buyer that are not yet settled
+    pub open_escrows: u32,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl BuyerEscrows {
+    pub const LEN: usize = 32 + 4 + 1;
+
+    /// Count a newly funded escrow, rejecting it once `max_open_escrows` are open
+    pub fn open(&mut self, max_open_escrows: u32) -> Result<()> {
+        require!(
+            max_open_escrows == 0 || self.open_escrows < max_open_escrows,
+            MarketplaceError::TooManyOpenEscrows
+        );
+        self.open_escrows = self
+            .open_escrows
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Count an escrow being settled
+    pub fn settle(&mut self) -> Result<()> {
+        self.open_escrows = self
+            .open_escrows
+            .checked_sub(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// Emitted when the admin force resolves a stuck escrow
+#[event]
+pub struct EscrowForceResolved {
+    pub escrow: Pubkey,
+    pub admin: Pubkey,
+    pub to_seller: bool,
+    pub amount: u64,
+    pub fee_retained: u64,
+    pub buyer_evidence_hash: [u8; 32],
+    pub seller_evidence_hash: [u8; 32],
+}
+
+/// Arguments of `purchase`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PurchaseParams {
+    /// Optional referrer rewarded out of the protocol fee
+    pub referrer: Option<Pubkey>,
+
+    /// Buyer acknowledgement required for listings containing personal data
+    pub pii_acknowledged: bool,
+}
+
+/// Protocol fee rate of a purchase by `buyer`.
+///
+/// A stake account owned by the buyer in the discount pool earns the discount
+/// of the highest tier it qualifies for, and the seller's profile adds the
+/// seller's volume rebate on top; the rate bottoms out at zero.
+pub(crate) fn purchase_fee_bps(
+    config: &MarketplaceConfig,
+    buyer: &Pubkey,
+    buyer_stake_account: Option<&AccountInfo>,
+    seller_profile: Option<&SellerProfile>,
+) -> Result<u16> {
+    let protocol_fee_bps = match buyer_stake_account {
+        Some(info) => {
+            let stake = load_foreign_account::<StakeAccount>(info, &mcp_token::ID)?;
+            require!(
+                stake.owner == *buyer && stake.pool == config.discount_stake_pool,
+                MarketplaceError::InvalidStakeAccount
+            );
+            config.protocol_fee_bps_for_stake(stake.amount)
+        }
+        None => config.effective_protocol_fee_bps(),
+    };
+    let seller_rebate_bps = match seller_profile {
+        Some(profile) => config.seller_rebate_bps(profile.total_sales),
+        None => 0,
+    };
+
+    Ok(protocol_fee_bps.saturating_sub(seller_rebate_bps))
+}
+
+/// Amounts a purchase moves, in the mint it is paid in
+pub(crate) struct PurchaseTerms {
+    /// Protocol fee owed to the treasury on release
+    pub protocol_fee: u64,
+
+    /// Creator royalty held until the escrow is settled
+    pub royalty: u64,
+
+    /// Amount escrowed: the price less the royalty, plus the buyer's share of the fee
+    pub escrowed: u64,
+}
+
+/// Terms of purchasing `listing` at `price` with a `protocol_fee_bps` fee rate
+pub(crate) fn purchase_terms(
+    config: &MarketplaceConfig,
+    listing: &Listing,
+    price: u64,
+    protocol_fee_bps: u16,
+) -> Result<PurchaseTerms> {
+    let protocol_fee = config.bps_of(price, protocol_fee_bps)?;
+    let royalty = listing.royalty_due(price, config.rounding_mode)?;
+    let escrowed = price
+        .checked_sub(royalty)
+        .and_then(|escrowed| escrowed.checked_add(config.fee_payer.buyer_share(protocol_fee)))
+        .ok_or(MarketplaceError::MathOverflow)?;
+    require!(protocol_fee <= escrowed, MarketplaceError::InvalidFeeBps);
+
+    Ok(PurchaseTerms {
+        protocol_fee,
+        royalty,
+        escrowed,
+    })
+}
+
+/// Move `amount` out of a token account owned by the escrow, signed by the escrow
+fn transfer_from_escrow<'info>(
+    token_program: &Program<'info, Token>,
+    escrow: &Account<'info, Escrow>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from,
+                to,
+                authority: escrow.to_account_info(),
+            },
+            &[&escrow_signer_seeds(
+                &escrow.listing,
+                &escrow.buyer,
+                &[escrow.bump],
+            )],
+        ),
+        amount,
+    )
+}
+
+/// The referrer's token account in the escrow mint, checked against `escrow`
+fn referrer_token_account<'a, 'info>(
+    escrow: &Escrow,
+    referrer_token_account: Option<&'a Account<'info, TokenAccount>>,
+    mint: &Pubkey,
+) -> Result<&'a Account<'info, TokenAccount>> {
+    referrer_token_account
+        .filter(|account| Some(account.owner) == escrow.referrer && account.mint == *mint)
+        .ok_or_else(|| error!(MarketplaceError::InvalidReferrer))
+}
+
+/// Pay the royalty held by `escrow` to the listing creator
+fn pay_held_royalty<'info>(
+    token_program: &Program<'info, Token>,
+    escrow: &mut Account<'info, Escrow>,
+    royalty_vault: &Account<'info, TokenAccount>,
+    creator_token_account: Option<&Account<'info, TokenAccount>>,
+    creator: &Pubkey,
+) -> Result<()> {
+    let royalty = escrow.take_royalty();
+    if royalty == 0 {
+        return Ok(());
+    }
+    let creator_token_account = creator_token_account
+        .filter(|account| account.owner == *creator && account.mint == royalty_vault.mint)
+        .ok_or(MarketplaceError::InvalidRoyaltyAccounts)?;
+    transfer_from_escrow(
+        token_program,
+        escrow,
+        royalty_vault.to_account_info(),
+        creator_token_account.to_account_info(),
+        royalty,
+    )
+}
+
+/// Return the royalty held by `escrow` to its payer
+fn refund_held_royalty<'info>(
+    token_program: &Program<'info, Token>,
+    escrow: &mut Account<'info, Escrow>,
+    royalty_vault: &Account<'info, TokenAccount>,
+    payer_royalty_token_account: Option<&Account<'info, TokenAccount>>,
+) -> Result<()> {
+    let royalty = escrow.take_royalty();
+    if royalty == 0 {
+        return Ok(());
+    }
+    let payer_royalty_token_account = payer_royalty_token_account
+        .filter(|account| account.owner == escrow.payer && account.mint == royalty_vault.mint)
+        .ok_or(MarketplaceError::InvalidRoyaltyAccounts)?;
+    transfer_from_escrow(
+        token_program,
+        escrow,
+        royalty_vault.to_account_info(),
+        payer_royalty_token_account.to_account_info(),
+        royalty,
+    )
+}
+
+/// Purchase a listing, moving the price from the buyer into the escrow vault.
+///
+/// When a `referrer` is given, `price * referral_bps / 10000` of the protocol
+/// fee is set aside for them. It stays in escrow and is paid, pro rata, as the
+/// escrow is released to the seller, so refunded purchases earn nothing and
+/// the seller's proceeds are unaffected.
+///
+/// Listings flagged `contains_pii` require the buyer to set `pii_acknowledged`.
+/// Listings with `max_buyers` set stop selling once that many buyers have
+/// purchased. The buyer may pay in any of the listing's `accepted_mints`; a
+/// mint other than the listing's is charged `price` converted at the current
+/// `ConversionRate` from the listing mint.
+///
+/// Resales of a dataset owe the creator's royalty out of the price. It is held
+/// in the escrow's royalty vault and paid to the creator once the escrow is
+/// released to the seller; refunds return it to the payer. When the listing
+/// has a royalty mint the royalty is converted into it at the admin-set
+/// `ConversionRate`, which must be current.
+///
+/// The price may be paid by a third party: `payer` funds the escrow and gets
+/// any refund, while access and receipts stay with `buyer`. The buyer's
+/// AccessGrant is issued as soon as the escrow is funded, so it can check the
+/// dataset before confirming receipt.
+///
+/// Buyers passing their MCP stake account get the protocol fee discount of the
+/// highest tier they qualify for. The seller's volume rebate, read from the
+/// seller's profile, applies on top; the fee bottoms out at zero. The profile
+/// is created on the seller's first sale, so it can never be left out.
+///
+/// The config's `fee_payer` decides who bears the protocol fee. The buyer's
+/// share is added to the amount escrowed, and the whole fee is taken out of
+/// the escrow on release, so with a fee `f` the seller nets:
+///
+/// - `Seller`: buyer pays `price`, seller nets `price - royalty - f`
+/// - `Buyer`: buyer pays `price + f`, seller nets `price - royalty`
+/// - `Split`: buyer pays `price + f / 2`, seller nets `price - royalty - (f - f / 2)`
+///
+/// A refund returns everything left in escrow, including the buyer's share.
+pub fn purchase(ctx: Context<Purchase>, args: Versioned<PurchaseParams>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let PurchaseParams {
+        referrer,
+        pii_acknowledged,
+    } = args.into_v1()?;
+    ctx.accounts.listing.record_buyer()?;
+    let listing = &ctx.accounts.listing;
+    let config = &ctx.accounts.marketplace_config;
+    let now = Clock::get()?.unix_timestamp;
+    listing.assert_purchasable(now)?;
+    let price = listing.price_in(&ctx.accounts.mint.key(), |price| {
+        let listing_mint = ctx
+            .accounts
+            .listing_mint
+            .as_ref()
+            .ok_or(MarketplaceError::InvalidConversionRate)?;
+        ctx.accounts
+            .price_conversion_rate
+            .as_ref()
+            .ok_or(MarketplaceError::InvalidConversionRate)?
+            .convert(price, listing_mint, &ctx.accounts.mint, now)
+    })?;
+    listing.assert_pii_acknowledged(pii_acknowledged)?;
+    record_spend(&ctx.accounts.spend_window, price, now, ctx.program_id)?;
+    ctx.accounts
+        .seller_profile
+        .initialize_if_needed(listing.seller, get_bump(&ctx.bumps, "seller_profile")?);
+
+    let protocol_fee_bps = purchase_fee_bps(
+        config,
+        &ctx.accounts.buyer.key(),
+        ctx.accounts.buyer_stake_account.as_deref(),
+        Some(&ctx.accounts.seller_profile),
+    )?;
+    let PurchaseTerms {
+        protocol_fee,
+        royalty,
+        escrowed,
+    } = purchase_terms(config, listing, price, protocol_fee_bps)?;
+    let referral_fee =
+        config.referral_fee(price, protocol_fee, referrer, &ctx.accounts.buyer.key())?;
+    let royalty_held = if royalty > 0 {
+        let (from, royalty_paid) = match listing.royalty_mint {
+            Some(royalty_mint) => {
+                let payer_royalty_token_account = ctx
+                    .accounts
+                    .payer_royalty_token_account
+                    .as_ref()
+                    .ok_or(MarketplaceError::InvalidRoyaltyAccounts)?;
+                require!(
+                    payer_royalty_token_account.owner == ctx.accounts.payer.key()
+                        && payer_royalty_token_account.mint == royalty_mint,
+                    MarketplaceError::InvalidRoyaltyAccounts
+                );
+                let conversion_rate = ctx
+                    .accounts
+                    .royalty_conversion_rate
+                    .as_ref()
+                    .ok_or(MarketplaceError::InvalidConversionRate)?;
+                let converted = conversion_rate.convert(
+                    royalty,
+                    &ctx.accounts.mint,
+                    &ctx.accounts.royalty_mint,
+                    now,
+                )?;
+                (payer_royalty_token_account.to_account_info(), converted)
+            }
+            None => (ctx.accounts.payer_token_account.to_account_info(), royalty),
+        };
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from,
+                    to: ctx.accounts.royalty_vault.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            royalty_paid,
+        )?;
+        royalty_paid
+    } else {
+        0
+    };
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.payer_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        escrowed,
+    )?;
+
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.listing = listing.key();
+    escrow.buyer = ctx.accounts.buyer.key();
+    escrow.payer = ctx.accounts.payer.key();
+    escrow.seller = listing.seller;
+    escrow.amount = escrowed;
+    escrow.protocol_fee = protocol_fee;
+    escrow.royalty = royalty_held;
+    escrow.referrer = referrer;
+    escrow.referral_fee = referral_fee;
+    escrow.released_so_far = 0;
+    escrow.status = EscrowStatus::Funded;
+    escrow.created_at = now;
+    escrow.buyer_evidence_hash = [0; 32];
+    escrow.seller_evidence_hash = [0; 32];
+    escrow.bump = get_bump(&ctx.bumps, "escrow")?;
+
+    *ctx.accounts.access_grant = AccessGrant::new(
+        escrow.listing,
+        escrow.buyer,
+        now,
+        get_bump(&ctx.bumps, "access_grant")?,
+    );
+
+    let buyer_escrows = &mut ctx.accounts.buyer_escrows;
+    if buyer_escrows.buyer == Pubkey::default() {
+        buyer_escrows.buyer = ctx.accounts.buyer.key();
+        buyer_escrows.bump = get_bump(&ctx.bumps, "buyer_escrows")?;
+    }
+    buyer_escrows.open(config.max_open_escrows)?;
+    ctx.accounts.listing.record_escrow_opened()?;
+
+    ctx.accounts.marketplace_stats.record_sale(price)
+}
+
+/// Release the rest of an escrow to the seller once the buyer confirms delivery.
+///
+/// Requires an Ed25519 instruction just before this one verifying the buyer's
+/// signature over `Escrow::receipt_message`. Fees and the held royalty are
+/// settled, and shareholders' token accounts are the remaining accounts.
+pub fn release_to_seller<'info>(
+    ctx: Context<'_, '_, '_, 'info, ReleaseToSeller<'info>>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let remaining = ctx.accounts.escrow.remaining()?;
+    release(ctx, remaining)
+}
+
+/// Release part of an escrow to the seller as a milestone approved by the buyer.
+///
+/// Requires a buyer receipt for `amount`, as for `release_to_seller`.
+///
+/// The protocol fee is charged pro rata, and the escrow is marked released once
+/// the whole amount has been paid out.
+pub fn release_milestone<'info>(
+    ctx: Context<'_, '_, '_, 'info, ReleaseToSeller<'info>>,
+    amount: u64,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts.escrow.assert_milestone(amount)?;
+    release(ctx, amount)
+}
+
+fn release<'info>(
+    ctx: Context<'_, '_, '_, 'info, ReleaseToSeller<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let escrow = &ctx.accounts.escrow;
+    require!(!escrow.is_settled(), MarketplaceError::EscrowAlreadySettled);
+    verify_ed25519_signature(
+        &ctx.accounts.instructions,
+        &escrow.buyer,
+        &escrow.receipt_message(&escrow.key(), amount),
+    )?;
+
+    let released_after = escrow
+        .released_so_far
+        .checked_add(amount)
+        .ok_or(MarketplaceError::MathOverflow)?;
+    let fee = escrow
+        .fee_released_at(released_after)?
+        .checked_sub(escrow.fee_released_at(escrow.released_so_far)?)
+        .ok_or(MarketplaceError::MathOverflow)?;
+    let referral = escrow.referral_for_release(released_after, fee)?;
+    let now = Clock::get()?.unix_timestamp;
+    let holding_fee = ctx
+        .accounts
+        .marketplace_config
+        .holding_fee(amount, now.saturating_sub(escrow.created_at))?
+        .min(amount.saturating_sub(fee));
+    let fee = fee
+        .checked_add(holding_fee)
+        .ok_or(MarketplaceError::MathOverflow)?;
+    let proceeds = amount
+        .checked_sub(fee)
+        .ok_or(MarketplaceError::MathOverflow)?;
+    let bump = [escrow.bump];
+    let signer_seeds = escrow_signer_seeds(&escrow.listing, &escrow.buyer, &bump);
+    let signer = &[&signer_seeds[..]];
+
+    let transfer_from_vault = |to: AccountInfo<'info>, amount: u64| -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to,
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )
+    };
+
+    if referral > 0 {
+        let referrer_token_account = referrer_token_account(
+            escrow,
+            ctx.accounts.referrer_token_account.as_ref(),
+            &ctx.accounts.vault.mint,
+        )?;
+        transfer_from_vault(referrer_token_account.to_account_info(), referral)?;
+    }
+    transfer_from_vault(ctx.accounts.treasury.to_account_info(), fee - referral)?;
+
+    let payouts = pay_seller_proceeds(
+        ctx.accounts.dataset_shares.as_deref(),
+        ctx.remaining_accounts,
+        &ctx.accounts.seller_token_account,
+        &ctx.accounts.vault.mint,
+        proceeds,
+        transfer_from_vault,
+    )?;
+
+    ctx.accounts.payout_ledger.record_payouts(
+        ctx.accounts.escrow.listing,
+        get_bump(&ctx.bumps, "payout_ledger")?,
+        &payouts,
+        now,
+    );
+
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.released_so_far = released_after;
+    if escrow.released_so_far == escrow.amount {
+        pay_held_royalty(
+            &ctx.accounts.token_program,
+            escrow,
+            &ctx.accounts.royalty_vault,
+            ctx.accounts.creator_token_account.as_ref(),
+            &ctx.accounts.listing.creator,
+        )?;
+        escrow.status = EscrowStatus::Released;
+        ctx.accounts.buyer_escrows.settle()?;
+        ctx.accounts.listing.record_escrow_closed()?;
+        let profile = &mut ctx.accounts.seller_profile;
+        profile.initialize_if_needed(escrow.seller, get_bump(&ctx.bumps, "seller_profile")?);
+        profile.record_sale()?;
+    }
+
+    Ok(())
+}
+
+/// Return the rest of an escrow to its payer, at the seller's discretion.
+///
+/// The held creator royalty is returned too, to the payer's token account in
+/// the royalty mint.
+pub fn refund_to_buyer(ctx: Context<RefundToBuyer>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let escrow = &ctx.accounts.escrow;
+    require!(!escrow.is_settled(), MarketplaceError::EscrowAlreadySettled);
+    let amount = escrow.remaining()?;
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.payer_token_account.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            &[&escrow_signer_seeds(
+                &escrow.listing,
+                &escrow.buyer,
+                &[escrow.bump],
+            )],
+        ),
+        amount,
+    )?;
+    refund_held_royalty(
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.escrow,
+        &ctx.accounts.royalty_vault,
+        ctx.accounts.payer_royalty_token_account.as_ref(),
+    )?;
+
+    ctx.accounts.escrow.status = EscrowStatus::Refunded;
+    ctx.accounts.buyer_escrows.settle()?;
+    ctx.accounts.listing.record_escrow_closed()?;
+
+    Ok(())
+}
+
+/// Collect dust left in the vaults of settled escrows into the treasury.
+///
+/// Remaining accounts are `(escrow, vault)` pairs. Only vaults of escrows that
+/// have been fully settled are touched, so no buyer or seller funds can be
+/// moved; vaults holding `dust_threshold` or more, or nothing, are skipped.
+pub fn sweep_dust<'info>(ctx: Context<'_, '_, '_, 'info, SweepDust<'info>>) -> Result<()> {
+    let config = &ctx.accounts.marketplace_config;
+    require!(
+        ctx.remaining_accounts.len().is_multiple_of(2),
+        MarketplaceError::InvalidDustAccounts
+    );
+    assert_batch_size(ctx.remaining_accounts.len() / 2)?;
+
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let escrow = Account::<Escrow>::try_from(&pair[0])?;
+        require!(escrow.is_settled(), MarketplaceError::InvalidDustAccounts);
+        let (vault_address, _) = Pubkey::find_program_address(
+            &[ESCROW_VAULT_SEED, escrow.key().as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(
+            pair[1].key(),
+            vault_address,
+            MarketplaceError::InvalidDustAccounts
+        );
+
+        let vault = Account::<TokenAccount>::try_from(&pair[1])?;
+        require_keys_eq!(
+            vault.mint,
+            ctx.accounts.treasury.mint,
+            MarketplaceError::InvalidDustAccounts
+        );
+        if !config.is_dust(vault.amount) {
+            continue;
+        }
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: pair[1].clone(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: pair[0].clone(),
+                },
+                &[&escrow_signer_seeds(
+                    &escrow.listing,
+                    &escrow.buyer,
+                    &[escrow.bump],
+                )],
+            ),
+            vault.amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Record the hash of the signer's evidence for an escrow in dispute.
+///
+/// The buyer and the seller each set only their own hash, chosen by who signs;
+/// the evidence itself lives off-chain. A later submission replaces the
+/// earlier one until the escrow is settled.
+pub fn submit_evidence(ctx: Context<SubmitEvidence>, evidence_hash: [u8; 32]) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let party = ctx.accounts.party.key();
+    ctx.accounts.escrow.record_evidence(party, evidence_hash)
+}
+
+/// Break-glass resolution of an escrow that has been stuck past `stuck_timeout`.
+///
+/// Admin only. Resolving for the seller settles like a release; resolving for
+/// the buyer refunds the payer, with the unreleased fee only while
+/// `refund_fee_on_dispute` is set.
+pub fn force_resolve_escrow<'info>(
+    ctx: Context<'_, '_, '_, 'info, ForceResolveEscrow<'info>>,
+    to_seller: bool,
+) -> Result<()> {
+    let escrow = &ctx.accounts.escrow;
+    require!(!escrow.is_settled(), MarketplaceError::EscrowAlreadySettled);
+
+    let now = Clock::get()?.unix_timestamp;
+    escrow.assert_stuck(ctx.accounts.marketplace_config.stuck_timeout, now)?;
+
+    let fee_retained = escrow.force_resolve_fee(
+        to_seller,
+        ctx.accounts.marketplace_config.refund_fee_on_dispute,
+    )?;
+    let amount = escrow
+        .remaining()?
+        .checked_sub(fee_retained)
+        .ok_or(MarketplaceError::MathOverflow)?;
+    let bump = [escrow.bump];
+    let signer_seeds = escrow_signer_seeds(&escrow.listing, &escrow.buyer, &bump);
+    let signer = &[&signer_seeds[..]];
+
+    let transfer_from_vault = |to: AccountInfo<'info>, amount: u64| -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to,
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )
+    };
+
+    let referral = if to_seller {
+        escrow.referral_for_release(escrow.amount, fee_retained)?
+    } else {
+        0
+    };
+    if referral > 0 {
+        let referrer_token_account = referrer_token_account(
+            escrow,
+            ctx.accounts.referrer_token_account.as_ref(),
+            &ctx.accounts.vault.mint,
+        )?;
+        transfer_from_vault(referrer_token_account.to_account_info(), referral)?;
+    }
+    transfer_from_vault(
+        ctx.accounts.treasury.to_account_info(),
+        fee_retained - referral,
+    )?;
+    if to_seller {
+        let payouts = pay_seller_proceeds(
+            ctx.accounts.dataset_shares.as_deref(),
+            ctx.remaining_accounts,
+            &ctx.accounts.seller_token_account,
+            &ctx.accounts.vault.mint,
+            amount,
+            transfer_from_vault,
+        )?;
+        ctx.accounts.payout_ledger.record_payouts(
+            ctx.accounts.escrow.listing,
+            get_bump(&ctx.bumps, "payout_ledger")?,
+            &payouts,
+            now,
+        );
+    } else {
+        transfer_from_vault(ctx.accounts.payer_token_account.to_account_info(), amount)?;
+    }
+
+    let escrow = &mut ctx.accounts.escrow;
+    if to_seller {
+        pay_held_royalty(
+            &ctx.accounts.token_program,
+            escrow,
+            &ctx.accounts.royalty_vault,
+            ctx.accounts.creator_token_account.as_ref(),
+            &ctx.accounts.listing.creator,
+        )?;
+    } else {
+        refund_held_royalty(
+            &ctx.accounts.token_program,
+            escrow,
+            &ctx.accounts.royalty_vault,
+            ctx.accounts.payer_royalty_token_account.as_ref(),
+        )?;
+    }
+    escrow.status = EscrowStatus::ForceResolved;
+    ctx.accounts.buyer_escrows.settle()?;
+    ctx.accounts.listing.record_escrow_closed()?;
+
+    emit!(EscrowForceResolved {
+        escrow: escrow.key(),
+        admin: ctx.accounts.admin.key(),
+        to_seller,
+        amount,
+        fee_retained,
+        buyer_evidence_hash: escrow.buyer_evidence_hash,
+        seller_evidence_hash: escrow.seller_evidence_hash,
+    });
+
+    Ok(())
+}
+
+/// Account validation for purchase
+#[derive(Accounts)]
+pub struct Purchase<'info> {
+    /// The buyer receiving access, paying for the accounts created
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// The account funding the purchase; the buyer itself or a third party
+    pub payer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing being purchased
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing.listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// The escrow to initialize
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + Escrow::LEN,
+        seeds = [ESCROW_SEED, listing.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// The buyer's access to the listing
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + AccessGrant::LEN,
+        seeds = [b"access".as_ref(), listing.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub access_grant: Account<'info, AccessGrant>,
+
+    /// Token vault holding the escrowed funds
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [ESCROW_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Open escrow count of the buyer, created on first purchase
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerEscrows::LEN,
+        seeds = [b"buyer-escrows".as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_escrows: Account<'info, BuyerEscrows>,
+
+    /// Mint the buyer pays in, one of the listing's accepted mints
+    pub mint: Account<'info, Mint>,
+
+    /// Mint the listing is priced in, required when paying in another mint
+    #[account(address = listing.mint @ MarketplaceError::InvalidConversionRate)]
+    pub listing_mint: Option<Account<'info, Mint>>,
+
+    /// Rate converting the listing price into `mint`, required when paying in another mint
+    pub price_conversion_rate: Option<Account<'info, ConversionRate>>,
+
+    /// Payer's token account funding the purchase
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+    )]
+    pub payer_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Buyer's spend window PDA, enforced only once the buyer has opted in
+    #[account(
+        mut,
+        seeds = [b"spend-window".as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub spend_window: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer's MCP stake account for the fee discount, verified in the handler
+    pub buyer_stake_account: Option<UncheckedAccount<'info>>,
+
+    /// Seller's profile for the volume rebate, created on the seller's first sale
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + SellerProfile::LEN,
+        seeds = [b"seller".as_ref(), listing.seller.as_ref()],
+        bump,
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+
+    /// Payer's token account in the royalty mint, required when it differs from the listing mint
+    #[account(mut)]
+    pub payer_royalty_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Mint the royalty is held in: the listing's royalty mint, or `mint` when it has none
+    #[account(
+        address = listing.royalty_mint.unwrap_or(mint.key())
+            @ MarketplaceError::InvalidRoyaltyAccounts,
+    )]
+    pub royalty_mint: Account<'info, Mint>,
+
+    /// Token vault holding the creator royalty until the escrow is settled
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [ROYALTY_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::mint = royalty_mint,
+        token::authority = escrow,
+    )]
+    pub royalty_vault: Account<'info, TokenAccount>,
+
+    /// Rate converting the royalty into the royalty mint, required with `royalty_mint`
+    pub royalty_conversion_rate: Option<Account<'info, ConversionRate>>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for release
+#[derive(Accounts)]
+pub struct ReleaseToSeller<'info> {
+    /// The seller submitting the buyer's receipt, paying for accounts created on first release
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The escrow to release
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.listing.as_ref(), escrow.buyer.as_ref()],
+        bump = escrow.bump,
+        has_one = seller,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// The escrow's listing
+    #[account(
+        mut,
+        address = escrow.listing,
+        constraint = listing.dataset_shares == dataset_shares.as_ref().map(|shares| shares.key())
+            @ MarketplaceError::InvalidShareholderAccounts,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Token vault holding the escrowed funds
+    #[account(
+        mut,
+        seeds = [ESCROW_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::authority = escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Seller's token account
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == escrow.seller,
+        constraint = seller_token_account.mint == vault.mint,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Token vault holding the escrow's creator royalty
+    #[account(
+        mut,
+        seeds = [ROYALTY_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::authority = escrow,
+    )]
+    pub royalty_vault: Account<'info, TokenAccount>,
+
+    /// Creator's token account in the royalty mint, required when a royalty is held
+    #[account(mut)]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Treasury token account in the escrow mint receiving the protocol fee
+    #[account(
+        mut,
+        constraint = treasury.owner == marketplace_config.treasury_owner
+            @ MarketplaceError::InvalidTreasury,
+        constraint = treasury.mint == vault.mint,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Referrer's token account in the escrow mint, required when a referral reward is due
+    #[account(mut)]
+    pub referrer_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Fractional owners of the listing, required when the listing records them
+    pub dataset_shares: Option<Account<'info, DatasetShares>>,
+
+    /// Payout history of the listing, created on first release
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + PayoutLedger::LEN,
+        seeds = [b"payout-ledger".as_ref(), escrow.listing.as_ref()],
+        bump,
+    )]
+    pub payout_ledger: Account<'info, PayoutLedger>,
+
+    /// Reputation of the seller, created on first release
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + SellerProfile::LEN,
+        seeds = [b"seller".as_ref(), escrow.seller.as_ref()],
+        bump,
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+
+    /// Open escrow count of the buyer
+    #[account(
+        mut,
+        seeds = [b"buyer-escrows".as_ref(), escrow.buyer.as_ref()],
+        bump = buyer_escrows.bump,
+    )]
+    pub buyer_escrows: Account<'info, BuyerEscrows>,
+
+    /// CHECK: Instructions sysvar, used to find the buyer's receipt
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for refund
+#[derive(Accounts)]
+pub struct RefundToBuyer<'info> {
+    /// The seller giving up the funds
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The escrow to refund
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.listing.as_ref(), escrow.buyer.as_ref()],
+        bump = escrow.bump,
+        has_one = seller,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// The escrow's listing
+    #[account(
+        mut,
+        address = escrow.listing,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Token vault holding the escrowed funds
+    #[account(
+        mut,
+        seeds = [ESCROW_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::authority = escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Token account of the escrow's payer, receiving the refund
+    #[account(
+        mut,
+        constraint = payer_token_account.owner == escrow.payer,
+        constraint = payer_token_account.mint == vault.mint,
+    )]
+    pub payer_token_account: Account<'info, TokenAccount>,
+
+    /// Token vault holding the escrow's creator royalty
+    #[account(
+        mut,
+        seeds = [ROYALTY_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::authority = escrow,
+    )]
+    pub royalty_vault: Account<'info, TokenAccount>,
+
+    /// Payer's token account in the royalty mint, required when a royalty is held
+    #[account(mut)]
+    pub payer_royalty_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Open escrow count of the buyer
+    #[account(
+        mut,
+        seeds = [b"buyer-escrows".as_ref(), escrow.buyer.as_ref()],
+        bump = buyer_escrows.bump,
+    )]
+    pub buyer_escrows: Account<'info, BuyerEscrows>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for dust sweep
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    /// The marketplace admin
+    pub admin: Signer<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        has_one = admin,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Treasury token account receiving the dust, in the mint of the swept vaults
+    #[account(
+        mut,
+        constraint = treasury.owner == marketplace_config.treasury_owner
+            @ MarketplaceError::InvalidTreasury,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for evidence submission
+#[derive(Accounts)]
+pub struct SubmitEvidence<'info> {
+    /// The buyer or seller of the escrow
+    pub party: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The disputed escrow
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.listing.as_ref(), escrow.buyer.as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+/// Account validation for force resolve
+#[derive(Accounts)]
+pub struct ForceResolveEscrow<'info> {
+    /// The marketplace admin, paying for the payout ledger if needed
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        has_one = admin,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The escrow to resolve
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.listing.as_ref(), escrow.buyer.as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// The escrow's listing
+    #[account(
+        mut,
+        address = escrow.listing,
+        constraint = listing.dataset_shares == dataset_shares.as_ref().map(|shares| shares.key())
+            @ MarketplaceError::InvalidShareholderAccounts,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Token vault holding the escrowed funds
+    #[account(
+        mut,
+        seeds = [ESCROW_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::authority = escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Seller's token account
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == escrow.seller,
+        constraint = seller_token_account.mint == vault.mint,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Token account of the escrow's payer, receiving the refund
+    #[account(
+        mut,
+        constraint = payer_token_account.owner == escrow.payer,
+        constraint = payer_token_account.mint == vault.mint,
+    )]
+    pub payer_token_account: Account<'info, TokenAccount>,
+
+    /// Token vault holding the escrow's creator royalty
+    #[account(
+        mut,
+        seeds = [ROYALTY_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::authority = escrow,
+    )]
+    pub royalty_vault: Account<'info, TokenAccount>,
+
+    /// Creator's token account in the royalty mint, required when a royalty is held
+    #[account(mut)]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Payer's token account in the royalty mint, required when a royalty is held
+    #[account(mut)]
+    pub payer_royalty_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Fractional owners of the listing, required when the listing records them
+    pub dataset_shares: Option<Account<'info, DatasetShares>>,
+
+    /// Treasury token account in the escrow mint receiving a retained protocol fee
+    #[account(
+        mut,
+        constraint = treasury.owner == marketplace_config.treasury_owner
+            @ MarketplaceError::InvalidTreasury,
+        constraint = treasury.mint == vault.mint,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Referrer's token account in the escrow mint, required when a referral reward is due
+    #[account(mut)]
+    pub referrer_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Open escrow count of the buyer
+    #[account(
+        mut,
+        seeds = [b"buyer-escrows".as_ref(), escrow.buyer.as_ref()],
+        bump = buyer_escrows.bump,
+    )]
+    pub buyer_escrows: Account<'info, BuyerEscrows>,
+
+    /// Payout history of the listing, created on first payout
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PayoutLedger::LEN,
+        seeds = [b"payout-ledger".as_ref(), escrow.listing.as_ref()],
+        bump,
+    )]
+    pub payout_ledger: Account<'info, PayoutLedger>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::listing_module::tests::listing;
+    use crate::listing_module::CreateListing;
+    use crate::marketplace_config_module::tests::marketplace_config;
+    use crate::marketplace_config_module::{FeeDiscountTier, FeePayer, SellerRebateTier};
+    use crate::utils_module::tests::{
+        global_config_data, install_test_syscalls, take_cpis, TestAccount, TEST_NOW,
+    };
+    use anchor_lang::solana_program::ed25519_program;
+    use anchor_lang::solana_program::sysvar::{
+        self,
+        instructions::{
+            self, construct_instructions_data, store_current_index, BorrowedInstruction,
+        },
+    };
+    use anchor_spl::token::spl_token::instruction::TokenInstruction;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// A funded escrow of 1000 carrying a 50 protocol fee
+    pub(crate) fn escrow() -> Escrow {
+        Escrow {
+            listing: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            amount: 1_000,
+            protocol_fee: 50,
+            royalty: 0,
+            referrer: None,
+            referral_fee: 0,
+            released_so_far: 0,
+            status: EscrowStatus::Funded,
+            created_at: 1_000,
+            buyer_evidence_hash: [0; 32],
+            seller_evidence_hash: [0; 32],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn force_resolve_rejected_before_stuck_timeout() {
+        let escrow = escrow();
+        assert_eq!(
+            escrow.assert_stuck(500, 1_499).unwrap_err(),
+            MarketplaceError::EscrowNotStuck.into()
+        );
+        assert!(escrow.assert_stuck(500, 1_500).is_ok());
+    }
+
+    #[test]
+    fn milestones_released_up_to_total_only() {
+        let mut escrow = escrow();
+
+        for amount in [400, 600] {
+            escrow.assert_milestone(amount).unwrap();
+            escrow.released_so_far += amount;
+        }
+        assert_eq!(escrow.remaining().unwrap(), 0);
+        assert_eq!(
+            escrow.assert_milestone(1).unwrap_err(),
+            MarketplaceError::InvalidMilestone.into()
+        );
+        assert!(escrow.assert_milestone(0).is_err());
+    }
+
+    #[test]
+    fn referral_paid_pro_rata_from_released_fee() {
+        let mut escrow = escrow();
+        escrow.referrer = Some(Pubkey::new_unique());
+        escrow.referral_fee = 20;
+
+        assert_eq!(escrow.referral_for_release(400, 20).unwrap(), 8);
+        escrow.released_so_far = 400;
+        assert_eq!(escrow.referral_for_release(1_000, 30).unwrap(), 12);
+        assert_eq!(escrow.referral_for_release(1_000, 5).unwrap(), 5);
+    }
+
+    #[test]
+    fn held_royalty_is_paid_out_once() {
+        let mut escrow = escrow();
+        escrow.royalty = 75;
+
+        assert_eq!(escrow.take_royalty(), 75);
+        assert_eq!(escrow.take_royalty(), 0);
+    }
+
+    /// Accounts of a purchase of a listing paying its creator a 5% royalty, in
+    /// `Purchase` order, with
+    /// `payer` funding it from a token account of `funding_owner`
+    fn purchase_accounts(buyer: Pubkey, payer: Pubkey, funding_owner: Pubkey) -> Vec<TestAccount> {
+        let program_id = crate::ID;
+        let mut listing = listing();
+        listing.creator = Pubkey::new_unique();
+        listing.royalty_bps = 500;
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+        let (listing_key, bump) = pda(&[b"listing", &listing.listing_id.to_le_bytes()]);
+        listing.bump = bump;
+        let mut config = marketplace_config();
+        (_, config.bump) = pda(&[b"marketplace-config"]);
+        let (stats_key, stats_bump) = pda(&[b"marketplace-stats"]);
+        let stats = MarketplaceStats {
+            total_volume: 0,
+            total_sales: 0,
+            active_listings: 1,
+            bump: stats_bump,
+        };
+        let (escrow_key, _) = pda(&[ESCROW_SEED, listing_key.as_ref(), buyer.as_ref()]);
+        let (buyer_escrows_key, bump) = pda(&[b"buyer-escrows", buyer.as_ref()]);
+        let buyer_escrows = BuyerEscrows {
+            buyer,
+            open_escrows: 0,
+            bump,
+        };
+        let (seller_profile_key, bump) = pda(&[b"seller", listing.seller.as_ref()]);
+        let seller_profile = SellerProfile {
+            seller: listing.seller,
+            rating_sum: 0,
+            rating_count: 0,
+            total_sales: 0,
+            bump,
+        };
+        // CPIs are not executed, so accounts to be created are laid out as
+        // the system and token programs would leave them
+        let uninitialized = |key: Pubkey, space: usize| {
+            TestAccount::new(key, program_id, vec![0; space]).writable()
+        };
+        let new_vault = |seed: &[u8]| {
+            let key = pda(&[seed, escrow_key.as_ref()]).0;
+            TestAccount::token_account(key, listing.mint, escrow_key, 0).writable()
+        };
+        let none = || TestAccount::program(program_id);
+
+        vec![
+            TestAccount::wallet(buyer).signer().writable(),
+            TestAccount::wallet(payer).signer(),
+            TestAccount::new(
+                Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &mcp_token::ID).0,
+                mcp_token::ID,
+                global_config_data(false),
+            ),
+            TestAccount::state(listing_key, &listing, 8 + Listing::LEN).writable(),
+            TestAccount::state(
+                pda(&[b"marketplace-config"]).0,
+                &config,
+                8 + MarketplaceConfig::LEN,
+            ),
+            TestAccount::state(stats_key, &stats, 8 + MarketplaceStats::LEN).writable(),
+            uninitialized(escrow_key, 8 + Escrow::LEN),
+            uninitialized(
+                pda(&[b"access", listing_key.as_ref(), buyer.as_ref()]).0,
+                8 + AccessGrant::LEN,
+            ),
+            new_vault(ESCROW_VAULT_SEED),
+            TestAccount::state(buyer_escrows_key, &buyer_escrows, 8 + BuyerEscrows::LEN).writable(),
+            TestAccount::mint(listing.mint),
+            none(),
+            none(),
+            TestAccount::token_account(Pubkey::new_unique(), listing.mint, funding_owner, 10_000)
+                .writable(),
+            TestAccount::wallet(pda(&[b"spend-window", buyer.as_ref()]).0).writable(),
+            none(),
+            TestAccount::state(seller_profile_key, &seller_profile, 8 + SellerProfile::LEN)
+                .writable(),
+            none(),
+            TestAccount::mint(listing.mint),
+            new_vault(ROYALTY_VAULT_SEED),
+            none(),
+            TestAccount::program(token::ID),
+            TestAccount::program(System::id()),
+            TestAccount::rent_sysvar(),
+        ]
+    }
+
+    #[test]
+    fn third_party_payer_funds_while_buyer_gets_access() {
+        install_test_syscalls();
+        let program_id = crate::ID;
+        let (buyer, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // The purchase can only be funded from a token account of the payer
+        let mut accounts = purchase_accounts(buyer, payer, buyer);
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let result = Purchase::try_accounts(
+            &program_id,
+            &mut &infos[..],
+            &[],
+            &mut BTreeMap::new(),
+            &mut BTreeSet::new(),
+        );
+        assert_eq!(
+            result
+                .err()
+                .map(|err| err == ErrorCode::ConstraintTokenOwner.into()),
+            Some(true)
+        );
+
+        let mut accounts = purchase_accounts(buyer, payer, payer);
+        // `payer_token_account` is the 14th account of `Purchase`
+        let payer_token_account = accounts[13].key;
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let mut bumps = BTreeMap::new();
+        let mut purchase_accounts = Purchase::try_accounts(
+            &program_id,
+            &mut &infos[..],
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .unwrap();
+        purchase(
+            Context::new(&program_id, &mut purchase_accounts, &[], bumps),
+            Versioned::V1(PurchaseParams {
+                referrer: None,
+                pii_acknowledged: false,
+            }),
+        )
+        .unwrap();
+
+        let transfers: Vec<_> = take_cpis()
+            .into_iter()
+            .filter(|cpi| {
+                cpi.program_id == token::ID
+                    && matches!(
+                        TokenInstruction::unpack(&cpi.data),
+                        Ok(TokenInstruction::Transfer { .. })
+                    )
+            })
+            .collect();
+        // The royalty and the escrowed amount
+        assert_eq!(transfers.len(), 2);
+        for transfer in &transfers {
+            assert_eq!(transfer.accounts[0].pubkey, payer_token_account);
+            assert_eq!(transfer.accounts[2].pubkey, payer);
+        }
+        assert_eq!(purchase_accounts.escrow.payer, payer);
+        assert_eq!(purchase_accounts.escrow.buyer, buyer);
+        assert_eq!(purchase_accounts.access_grant.buyer, buyer);
+        assert_eq!(purchase_accounts.access_grant.granted_at, TEST_NOW);
+        assert_eq!(purchase_accounts.buyer_escrows.open_escrows, 1);
+    }
+
+    #[test]
+    fn force_resolve_to_seller_keeps_fee_for_treasury() {
+        let mut escrow = escrow();
+        escrow.released_so_far = 400;
+
+        assert_eq!(escrow.force_resolve_fee(true, true).unwrap(), 30);
+        assert_eq!(escrow.force_resolve_fee(true, false).unwrap(), 30);
+        assert_eq!(escrow.force_resolve_fee(false, false).unwrap(), 30);
+        assert_eq!(escrow.force_resolve_fee(false, true).unwrap(), 0);
+    }
+
+    #[test]
+    fn seller_past_rebate_threshold_charged_lower_fee() {
+        let mut config = marketplace_config();
+        config.seller_rebate_tiers = vec![SellerRebateTier {
+            min_sales: 10,
+            rebate_bps: 200,
+        }];
+        let buyer = Pubkey::new_unique();
+        let mut profile = SellerProfile {
+            seller: Pubkey::new_unique(),
+            rating_sum: 0,
+            rating_count: 0,
+            total_sales: 9,
+            bump: 255,
+        };
+
+        assert_eq!(
+            purchase_fee_bps(&config, &buyer, None, Some(&profile)).unwrap(),
+            500
+        );
+        profile.total_sales = 10;
+        assert_eq!(
+            purchase_fee_bps(&config, &buyer, None, Some(&profile)).unwrap(),
+            300
+        );
+
+        config.seller_rebate_tiers[0].rebate_bps = 900;
+        assert_eq!(
+            purchase_fee_bps(&config, &buyer, None, Some(&profile)).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn qualifying_staker_charged_reduced_fee() {
+        let mut config = marketplace_config();
+        config.discount_stake_pool = Pubkey::new_unique();
+        config.fee_discount_tiers = vec![FeeDiscountTier {
+            min_stake: 1_000,
+            discount_bps: 200,
+        }];
+        let buyer = Pubkey::new_unique();
+        let fee_for = |amount: u64, pool: Pubkey| {
+            let stake = StakeAccount {
+                owner: buyer,
+                pool,
+                amount,
+                staked_at: 0,
+                reward_debt: 0,
+                pending_rewards: 0,
+                auto_compound: false,
+                locked_by: None,
+                bump: 255,
+            };
+            let mut data = Vec::new();
+            stake.try_serialize(&mut data).unwrap();
+            let (key, owner, mut lamports) = (Pubkey::new_unique(), mcp_token::ID, 0);
+            let info = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+            purchase_fee_bps(&config, &buyer, Some(&info), None)
+        };
+
+        assert_eq!(fee_for(999, config.discount_stake_pool).unwrap(), 500);
+        assert_eq!(fee_for(1_000, config.discount_stake_pool).unwrap(), 300);
+        assert_eq!(
+            fee_for(1_000, Pubkey::new_unique()).unwrap_err(),
+            MarketplaceError::InvalidStakeAccount.into()
+        );
+    }
+
+    /// Accounts of `create_listing` for listing 2 of `seller` under `config`
+    fn create_listing_accounts(seller: Pubkey, config: &MarketplaceConfig) -> Vec<TestAccount> {
+        let program_id = crate::ID;
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+        let (stats_key, stats_bump) = pda(&[b"marketplace-stats"]);
+        let stats = MarketplaceStats {
+            total_volume: 0,
+            total_sales: 0,
+            active_listings: 1,
+            bump: stats_bump,
+        };
+
+        vec![
+            TestAccount::wallet(seller).signer().writable(),
+            TestAccount::new(
+                Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &mcp_token::ID).0,
+                mcp_token::ID,
+                global_config_data(false),
+            ),
+            TestAccount::new(
+                pda(&[b"listing", &2u64.to_le_bytes()]).0,
+                program_id,
+                vec![0; 8 + Listing::LEN],
+            )
+            .writable(),
+            TestAccount::state(stats_key, &stats, 8 + MarketplaceStats::LEN).writable(),
+            TestAccount::state(
+                pda(&[b"marketplace-config"]).0,
+                config,
+                8 + MarketplaceConfig::LEN,
+            ),
+            TestAccount::mint(Pubkey::new_unique()),
+            TestAccount::program(System::id()),
+        ]
+    }
+
+    /// Accounts of `release_to_seller` for `escrow` of `listing`, preceded by
+    /// the buyer's receipt for the whole amount
+    fn release_accounts(
+        escrow: &Escrow,
+        listing: &Listing,
+        config: &MarketplaceConfig,
+    ) -> Vec<TestAccount> {
+        let program_id = crate::ID;
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+        let (escrow_key, _) = pda(&[ESCROW_SEED, escrow.listing.as_ref(), escrow.buyer.as_ref()]);
+        let mint = listing.mint;
+        let vault = |seed: &[u8], amount: u64| {
+            let key = pda(&[seed, escrow_key.as_ref()]).0;
+            TestAccount::token_account(key, mint, escrow_key, amount).writable()
+        };
+        let (buyer_escrows_key, bump) = pda(&[b"buyer-escrows", escrow.buyer.as_ref()]);
+        let buyer_escrows = BuyerEscrows {
+            buyer: escrow.buyer,
+            open_escrows: 1,
+            bump,
+        };
+        let ledger = PayoutLedger {
+            listing: Pubkey::default(),
+            entries: Vec::new(),
+            total_entries: 0,
+            bump: 0,
+        };
+        let profile = SellerProfile {
+            seller: escrow.seller,
+            rating_sum: 0,
+            rating_count: 0,
+            total_sales: 0,
+            bump: 0,
+        };
+        let message = escrow.receipt_message(&escrow_key, escrow.amount);
+        let receipt = ed25519_instruction_data(&escrow.buyer, &message);
+        let ixs = [
+            BorrowedInstruction {
+                program_id: &ed25519_program::ID,
+                accounts: Vec::new(),
+                data: &receipt,
+            },
+            BorrowedInstruction {
+                program_id: &program_id,
+                accounts: Vec::new(),
+                data: &[],
+            },
+        ];
+        let mut instructions_data = construct_instructions_data(&ixs);
+        store_current_index(&mut instructions_data, 1);
+        let none = || TestAccount::program(program_id);
+
+        vec![
+            TestAccount::wallet(escrow.seller).signer().writable(),
+            TestAccount::new(
+                Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &mcp_token::ID).0,
+                mcp_token::ID,
+                global_config_data(false),
+            ),
+            TestAccount::state(
+                pda(&[b"marketplace-config"]).0,
+                config,
+                8 + MarketplaceConfig::LEN,
+            ),
+            TestAccount::state(escrow_key, escrow, 8 + Escrow::LEN).writable(),
+            TestAccount::state(escrow.listing, listing, 8 + Listing::LEN).writable(),
+            vault(ESCROW_VAULT_SEED, escrow.amount),
+            TestAccount::token_account(Pubkey::new_unique(), mint, escrow.seller, 0).writable(),
+            vault(ROYALTY_VAULT_SEED, 0),
+            none(),
+            TestAccount::token_account(Pubkey::new_unique(), mint, config.treasury_owner, 0)
+                .writable(),
+            none(),
+            none(),
+            TestAccount::state(
+                pda(&[b"payout-ledger", escrow.listing.as_ref()]).0,
+                &ledger,
+                8 + PayoutLedger::LEN,
+            )
+            .writable(),
+            TestAccount::state(
+                pda(&[b"seller", escrow.seller.as_ref()]).0,
+                &profile,
+                8 + SellerProfile::LEN,
+            )
+            .writable(),
+            TestAccount::state(buyer_escrows_key, &buyer_escrows, 8 + BuyerEscrows::LEN).writable(),
+            TestAccount::new(instructions::ID, sysvar::ID, instructions_data),
+            TestAccount::program(token::ID),
+            TestAccount::program(System::id()),
+        ]
+    }
+
+    #[test]
+    fn new_listings_rejected_while_escrow_releasable() {
+        install_test_syscalls();
+        let program_id = crate::ID;
+        let mut config = marketplace_config();
+        (_, config.bump) = Pubkey::find_program_address(&[b"marketplace-config"], &program_id);
+        config.accepting_new_listings = false;
+        let mut listing = listing();
+        listing.open_escrows = 1;
+        let mut escrow = escrow();
+        escrow.seller = listing.seller;
+        (escrow.listing, listing.bump) = Pubkey::find_program_address(
+            &[b"listing", &listing.listing_id.to_le_bytes()],
+            &program_id,
+        );
+        (_, escrow.bump) = Pubkey::find_program_address(
+            &[ESCROW_SEED, escrow.listing.as_ref(), escrow.buyer.as_ref()],
+            &program_id,
+        );
+        let create_listing = |config: &MarketplaceConfig| {
+            let mut accounts = create_listing_accounts(listing.seller, config);
+            let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+            CreateListing::try_accounts(
+                &program_id,
+                &mut &infos[..],
+                &2u64.to_le_bytes(),
+                &mut BTreeMap::new(),
+                &mut BTreeSet::new(),
+            )
+            .err()
+        };
+
+        assert_eq!(
+            create_listing(&config),
+            Some(MarketplaceError::NotAcceptingListings.into())
+        );
+
+        let mut accounts = release_accounts(&escrow, &listing, &config);
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let mut bumps = BTreeMap::new();
+        let mut release_accounts = ReleaseToSeller::try_accounts(
+            &program_id,
+            &mut &infos[..],
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .unwrap();
+        release_to_seller(Context::new(&program_id, &mut release_accounts, &[], bumps)).unwrap();
+        assert!(release_accounts.escrow.status == EscrowStatus::Released);
+        assert_eq!(release_accounts.listing.open_escrows, 0);
+
+        assert_eq!(
+            create_listing(&config),
+            Some(MarketplaceError::NotAcceptingListings.into())
+        );
+        config.accepting_new_listings = true;
+        assert!(create_listing(&config).is_none());
+    }
+
+    #[test]
+    fn buyer_blocked_at_open_escrow_cap_until_one_settles() {
+        let mut buyer_escrows = BuyerEscrows {
+            buyer: Pubkey::new_unique(),
+            open_escrows: 0,
+            bump: 255,
+        };
+
+        buyer_escrows.open(2).unwrap();
+        buyer_escrows.open(2).unwrap();
+        assert_eq!(
+            buyer_escrows.open(2).unwrap_err(),
+            MarketplaceError::TooManyOpenEscrows.into()
+        );
+        buyer_escrows.settle().unwrap();
+        buyer_escrows.open(2).unwrap();
+        assert_eq!(buyer_escrows.open_escrows, 2);
+    }
+
+    /// Ed25519 program instruction data carrying `signer`'s signature over `message`
+    fn ed25519_instruction_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let (public_key_offset, signature_offset) = (16u16, 48u16);
+        let message_offset = signature_offset + 64;
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            u16::MAX,
+            public_key_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn release_requires_buyer_signed_receipt() {
+        let escrow = escrow();
+        let escrow_key = Pubkey::new_unique();
+        let message = escrow.receipt_message(&escrow_key, escrow.amount);
+        let program_id = crate::ID;
+        let ed25519_program_id = ed25519_program::ID;
+        let receipt_data = ed25519_instruction_data(&escrow.buyer, &message);
+        let forged_data = ed25519_instruction_data(&escrow.seller, &message);
+
+        let cases = [
+            (None, false),
+            (Some(&forged_data), false),
+            (Some(&receipt_data), true),
+        ];
+        for (receipt, valid) in cases {
+            let mut ixs = Vec::new();
+            if let Some(data) = receipt {
+                ixs.push(BorrowedInstruction {
+                    program_id: &ed25519_program_id,
+                    accounts: Vec::new(),
+                    data,
+                });
+            }
+            ixs.push(BorrowedInstruction {
+                program_id: &program_id,
+                accounts: Vec::new(),
+                data: &[],
+            });
+            let mut data = construct_instructions_data(&ixs);
+            store_current_index(&mut data, ixs.len() as u16 - 1);
+            let key = instructions::ID;
+            let owner = anchor_lang::solana_program::sysvar::ID;
+            let mut lamports = 0;
+            let info = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+
+            let result = verify_ed25519_signature(&info, &escrow.buyer, &message);
+            if valid {
+                assert!(result.is_ok());
+            } else {
+                assert_eq!(
+                    result.unwrap_err(),
+                    MarketplaceError::InvalidSignature.into()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn each_party_sets_only_own_evidence_hash() {
+        let mut target = escrow();
+        target.record_evidence(target.buyer, [1; 32]).unwrap();
+        target.record_evidence(target.seller, [2; 32]).unwrap();
+        assert_eq!(target.buyer_evidence_hash, [1; 32]);
+        assert_eq!(target.seller_evidence_hash, [2; 32]);
+
+        assert_eq!(
+            target
+                .record_evidence(Pubkey::new_unique(), [3; 32])
+                .unwrap_err(),
+            MarketplaceError::NotEscrowParty.into()
+        );
+        assert_eq!(target.buyer_evidence_hash, [1; 32]);
+        assert_eq!(target.seller_evidence_hash, [2; 32]);
+    }
+
+    #[test]
+    fn each_fee_payer_mode_nets_correct_amounts() {
+        let mut config = marketplace_config();
+        for (fee_payer, buyer_pays, seller_nets) in [
+            (FeePayer::Buyer, 1_051, 1_000),
+            (FeePayer::Seller, 1_000, 949),
+            (FeePayer::Split, 1_025, 974),
+        ] {
+            config.fee_payer = fee_payer;
+            let terms = purchase_terms(&config, &listing(), 1_000, 510).unwrap();
+            assert_eq!(terms.protocol_fee, 51);
+            assert_eq!(terms.escrowed, buyer_pays);
+            assert_eq!(terms.escrowed - terms.protocol_fee, seller_nets);
+        }
+    }
+}
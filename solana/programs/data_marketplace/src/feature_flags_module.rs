@@ -0,0 +1,231 @@
+//! feature_flags_module module for data marketplace
+//!
+//! This module provides per-deployment feature flags, so new instructions can
+//! ship disabled and be switched on by the admin when ready.
+
+use anchor_lang::prelude::*;
+
+use crate::errors_module::MarketplaceError;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::utils_module::get_bump;
+
+/// Enables `purchase_bundle`
+pub const ENABLE_BUNDLE_PURCHASES: u64 = 1 << 0;
+
+/// Enables `split_listing`
+pub const ENABLE_SPLIT_LISTINGS: u64 = 1 << 1;
+
+/// FeatureFlags state account
+#[account]
+pub struct FeatureFlags {
+    /// The admin that can update this account
+    pub admin: Pubkey,
+
+    /// Bit set of enabled features
+    pub flags: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl FeatureFlags {
+    pub const LEN: usize = 32 + 8 + 1;
+
+    pub fn is_enabled(&self, feature: u64) -> bool {
+        self.flags & feature == feature
+    }
+
+    /// Fail with `FeatureDisabled` unless `feature` is enabled
+    pub fn require_enabled(&self, feature: u64) -> Result<()> {
+        require!(self.is_enabled(feature), MarketplaceError::FeatureDisabled);
+
+        Ok(())
+    }
+
+    /// Enable or disable the features in `features`
+    pub fn set(&mut self, features: u64, enabled: bool) {
+        if enabled {
+            self.flags |= features;
+        } else {
+            self.flags &= !features;
+        }
+    }
+}
+
+/// Initialize the FeatureFlags with every feature disabled
+pub fn initialize_feature_flags(ctx: Context<InitializeFeatureFlags>) -> Result<()> {
+    let feature_flags = &mut ctx.accounts.feature_flags;
+    feature_flags.admin = ctx.accounts.admin.key();
+    feature_flags.flags = 0;
+    feature_flags.bump = get_bump(&ctx.bumps, "feature_flags")?;
+
+    Ok(())
+}
+
+/// Enable or disable the features in `features`
+pub fn set_feature_flags(
+    ctx: Context<UpdateFeatureFlags>,
+    features: u64,
+    enabled: bool,
+) -> Result<()> {
+    ctx.accounts.feature_flags.set(features, enabled);
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeFeatureFlags<'info> {
+    /// The admin of the marketplace
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + FeatureFlags::LEN,
+        seeds = [b"feature-flags".as_ref()],
+        bump,
+    )]
+    pub feature_flags: Account<'info, FeatureFlags>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateFeatureFlags<'info> {
+    /// The admin of the marketplace
+    pub admin: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"feature-flags".as_ref()],
+        bump = feature_flags.bump,
+        has_one = admin,
+    )]
+    pub feature_flags: Account<'info, FeatureFlags>,
+
+    /// Marketplace configuration, checked for the config freeze
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        constraint = !marketplace_config.config_frozen @ MarketplaceError::ConfigFrozen,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::marketplace_config_module::tests::marketplace_config;
+
+    #[test]
+    fn disabled_feature_rejected_until_enabled() {
+        let mut feature_flags = FeatureFlags {
+            admin: Pubkey::new_unique(),
+            flags: 0,
+            bump: 255,
+        };
+        assert_eq!(
+            feature_flags
+                .require_enabled(ENABLE_BUNDLE_PURCHASES)
+                .unwrap_err(),
+            MarketplaceError::FeatureDisabled.into()
+        );
+
+        feature_flags.set(ENABLE_BUNDLE_PURCHASES, true);
+        feature_flags
+            .require_enabled(ENABLE_BUNDLE_PURCHASES)
+            .unwrap();
+        assert_eq!(
+            feature_flags
+                .require_enabled(ENABLE_SPLIT_LISTINGS)
+                .unwrap_err(),
+            MarketplaceError::FeatureDisabled.into()
+        );
+
+        feature_flags.set(ENABLE_BUNDLE_PURCHASES, false);
+        assert!(!feature_flags.is_enabled(ENABLE_BUNDLE_PURCHASES));
+    }
+
+    #[test]
+    fn flags_locked_once_config_frozen() {
+        let program_id = crate::ID;
+        let admin = Pubkey::new_unique();
+        let (flags_key, flags_bump) =
+            Pubkey::find_program_address(&[b"feature-flags"], &program_id);
+        let (config_key, config_bump) =
+            Pubkey::find_program_address(&[b"marketplace-config"], &program_id);
+        let mut flags_data = Vec::new();
+        FeatureFlags {
+            admin,
+            flags: 0,
+            bump: flags_bump,
+        }
+        .try_serialize(&mut flags_data)
+        .unwrap();
+
+        for frozen in [false, true] {
+            let mut config = marketplace_config();
+            config.bump = config_bump;
+            config.config_frozen = frozen;
+            let mut config_data = Vec::new();
+            config.try_serialize(&mut config_data).unwrap();
+            let mut flags_data = flags_data.clone();
+            let (mut admin_lamports, mut flags_lamports, mut config_lamports) = (0, 0, 0);
+            let mut admin_data = [];
+            let system_program = System::id();
+            let accounts = [
+                AccountInfo::new(
+                    &admin,
+                    true,
+                    false,
+                    &mut admin_lamports,
+                    &mut admin_data,
+                    &system_program,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &flags_key,
+                    false,
+                    true,
+                    &mut flags_lamports,
+                    &mut flags_data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &config_key,
+                    false,
+                    false,
+                    &mut config_lamports,
+                    &mut config_data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+            ];
+
+            let result = UpdateFeatureFlags::try_accounts(
+                &program_id,
+                &mut &accounts[..],
+                &[],
+                &mut BTreeMap::new(),
+                &mut BTreeSet::new(),
+            );
+            if frozen {
+                assert_eq!(result.err(), Some(MarketplaceError::ConfigFrozen.into()));
+            } else {
+                assert!(result.is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! fee_vault_module module for data marketplace
+//!
+//! This module provides per-mint protocol fee vaults and their consolidation
+//! into the treasury. Each vault is its own authority, so a keeper can sweep
+//! them without holding any key. The model registry pays its fees straight to
+//! model owners and keeps no fee vaults, so there is nothing of it to sweep.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::utils_module::{assert_batch_size, assert_not_globally_paused};
+use crate::vaults_module::{find_fee_vault_address, FEE_VAULT_SEED};
+
+/// Create the protocol fee vault for a mint
+pub fn initialize_fee_vault(_ctx: Context<InitializeFeeVault>) -> Result<()> {
+    Ok(())
+}
+
+/// Balance of `info`, rejecting any account other than `fee_vault`
+fn fee_vault_balance(info: &AccountInfo, fee_vault: &Pubkey) -> Result<u64> {
+    require_keys_eq!(info.key(), *fee_vault, MarketplaceError::InvalidFeeVault);
+
+    Ok(Account::<TokenAccount>::try_from(info)?.amount)
+}
+
+/// Move the balance of every fee vault passed as a remaining account into the
+/// treasury; callable by anyone.
+///
+/// Each account must be the fee vault PDA of the treasury mint, so no other
+/// account can be drained through this instruction. Empty vaults are skipped.
+pub fn sweep_all_fees<'info>(ctx: Context<'_, '_, '_, 'info, SweepAllFees<'info>>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let mint = ctx.accounts.treasury.mint;
+    let (fee_vault, bump) = find_fee_vault_address(&mint, ctx.program_id);
+    assert_batch_size(ctx.remaining_accounts.len())?;
+
+    for info in ctx.remaining_accounts.iter() {
+        let amount = fee_vault_balance(info, &fee_vault)?;
+        if amount == 0 {
+            continue;
+        }
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: info.clone(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: info.clone(),
+                },
+                &[&[FEE_VAULT_SEED, mint.as_ref(), &[bump]]],
+            ),
+            amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Account validation for fee vault creation
+#[derive(Accounts)]
+pub struct InitializeFeeVault<'info> {
+    /// The admin of the marketplace
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        has_one = admin,
+        constraint = !marketplace_config.config_frozen @ MarketplaceError::ConfigFrozen,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The vault to initialize; it is its own authority
+    #[account(
+        init,
+        payer = admin,
+        seeds = [FEE_VAULT_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = fee_vault,
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+
+    /// Mint the vault collects fees in
+    pub mint: Account<'info, Mint>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for fee sweep
+#[derive(Accounts)]
+pub struct SweepAllFees<'info> {
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Treasury token account receiving the fees
+    #[account(
+        mut,
+        address = marketplace_config.treasury,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+
+    #[test]
+    fn non_fee_vault_account_rejected() {
+        let mint = Pubkey::new_unique();
+        let (fee_vault, _) = find_fee_vault_address(&mint, &crate::ID);
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner: fee_vault,
+            amount: 500,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+
+        for (key, expected) in [
+            (fee_vault, Ok(500)),
+            (
+                Pubkey::new_unique(),
+                Err(MarketplaceError::InvalidFeeVault.into()),
+            ),
+        ] {
+            let (mut lamports, mut data) = (0, data.clone());
+            let info = AccountInfo::new(
+                &key,
+                false,
+                true,
+                &mut lamports,
+                &mut data,
+                &spl_token::ID,
+                false,
+                0,
+            );
+            assert_eq!(fee_vault_balance(&info, &fee_vault), expected);
+        }
+    }
+}
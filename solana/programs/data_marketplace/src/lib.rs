@@ -0,0 +1,561 @@
+use anchor_lang::prelude::*;
+
+pub mod access_module;
+pub mod auction_module;
+pub mod bundle_module;
+pub mod collateral_module;
+pub mod conversion_module;
+pub mod credits_module;
+pub mod errors_module;
+pub mod escrow_module;
+pub mod feature_flags_module;
+pub mod fee_vault_module;
+pub mod listing_module;
+pub mod marketplace_config_module;
+pub mod migration_module;
+pub mod payout_ledger_module;
+pub mod seller_profile_module;
+pub mod shares_module;
+pub mod sol_escrow_module;
+pub mod spend_limit_module;
+pub mod stats_module;
+pub mod subscription_module;
+pub mod utils_module;
+pub mod vaults_module;
+pub mod versioning_module;
+
+use access_module::*;
+use auction_module::*;
+use bundle_module::*;
+use collateral_module::*;
+use conversion_module::*;
+use credits_module::*;
+use escrow_module::*;
+use feature_flags_module::*;
+use fee_vault_module::*;
+use listing_module::*;
+use marketplace_config_module::*;
+use migration_module::*;
+use seller_profile_module::*;
+use shares_module::*;
+use sol_escrow_module::*;
+use spend_limit_module::*;
+use stats_module::*;
+use subscription_module::*;
+use utils_module::RoundingMode;
+use versioning_module::*;
+
+declare_id!("AnSaB4X6DvLhdQwppF59v7ojV1DCH9NHck759iqfvjTV");
+
+#[program]
+pub mod data_marketplace {
+    use super::*;
+
+    /// Burn the listing price from the buyer and grant them access to the dataset.
+    pub fn burn_for_access(
+        ctx: Context<BurnForAccess>,
+        listing_id: u64,
+        pii_acknowledged: bool,
+    ) -> Result<()> {
+        access_module::burn_for_access(ctx, listing_id, pii_acknowledged)
+    }
+
+    /// Hand an access grant to `to`, paying the creator's royalty on the listing's
+    /// secondary price, floored at the listing price.
+    pub fn transfer_access_grant(ctx: Context<TransferAccessGrant>, to: Pubkey) -> Result<()> {
+        access_module::transfer_access_grant(ctx, to)
+    }
+
+    /// Convert an active fixed-price Listing into an auction ending `duration`
+    /// seconds from now.
+    pub fn convert_to_auction(
+        ctx: Context<ConvertToAuction>,
+        listing_id: u64,
+        reserve_price: u64,
+        duration: i64,
+    ) -> Result<()> {
+        auction_module::convert_to_auction(ctx, listing_id, reserve_price, duration)
+    }
+
+    /// Bid `amount` on an auction, refunding the previous high bidder.
+    pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+        auction_module::place_bid(ctx, amount)
+    }
+
+    /// Settle an ended auction; callable by anyone.
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleAuction<'info>>,
+    ) -> Result<()> {
+        auction_module::settle_auction(ctx)
+    }
+
+    /// Purchase every listing in `listing_ids` or none of them.
+    pub fn purchase_bundle<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseBundle<'info>>,
+        listing_ids: Vec<u64>,
+        max_total: u64,
+    ) -> Result<()> {
+        bundle_module::purchase_bundle(ctx, listing_ids, max_total)
+    }
+
+    /// Set the rate converting `from_mint` amounts into `to_mint`, valid for
+    /// `max_staleness` seconds
+    pub fn set_conversion_rate(
+        ctx: Context<SetConversionRate>,
+        rate: u64,
+        max_staleness: i64,
+    ) -> Result<()> {
+        conversion_module::set_conversion_rate(ctx, rate, max_staleness)
+    }
+
+    /// Buy credits with `amount` tokens at the current rate
+    pub fn buy_credits(ctx: Context<BuyCredits>, amount: u64) -> Result<()> {
+        credits_module::buy_credits(ctx, amount)
+    }
+
+    /// Redeem `credits` for tokens at the current rate
+    pub fn redeem_credits(ctx: Context<RedeemCredits>, credits: u64) -> Result<()> {
+        credits_module::redeem_credits(ctx, credits)
+    }
+
+    /// Create the vault backing marketplace credits
+    pub fn initialize_credits_vault(ctx: Context<InitializeCreditsVault>) -> Result<()> {
+        credits_module::initialize_credits_vault(ctx)
+    }
+
+    /// Purchase a listing, moving the price from the buyer into the escrow vault.
+    pub fn purchase(ctx: Context<Purchase>, args: Versioned<PurchaseParams>) -> Result<()> {
+        escrow_module::purchase(ctx, args)
+    }
+
+    /// Release the rest of an escrow to the seller once the buyer confirms delivery.
+    pub fn release_to_seller<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleaseToSeller<'info>>,
+    ) -> Result<()> {
+        escrow_module::release_to_seller(ctx)
+    }
+
+    /// Release part of an escrow to the seller as a milestone approved by the buyer.
+    pub fn release_milestone<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleaseToSeller<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        escrow_module::release_milestone(ctx, amount)
+    }
+
+    /// Return the rest of an escrow to its payer, at the seller's discretion
+    pub fn refund_to_buyer(ctx: Context<RefundToBuyer>) -> Result<()> {
+        escrow_module::refund_to_buyer(ctx)
+    }
+
+    /// Collect dust left in the vaults of settled escrows into the treasury.
+    pub fn sweep_dust<'info>(ctx: Context<'_, '_, '_, 'info, SweepDust<'info>>) -> Result<()> {
+        escrow_module::sweep_dust(ctx)
+    }
+
+    /// Record the hash of the signer's evidence for an escrow in dispute.
+    pub fn submit_evidence(ctx: Context<SubmitEvidence>, evidence_hash: [u8; 32]) -> Result<()> {
+        escrow_module::submit_evidence(ctx, evidence_hash)
+    }
+
+    /// Break-glass resolution of an escrow that has been stuck past `stuck_timeout`.
+    pub fn force_resolve_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForceResolveEscrow<'info>>,
+        to_seller: bool,
+    ) -> Result<()> {
+        escrow_module::force_resolve_escrow(ctx, to_seller)
+    }
+
+    /// Initialize the FeatureFlags with every feature disabled
+    pub fn initialize_feature_flags(ctx: Context<InitializeFeatureFlags>) -> Result<()> {
+        feature_flags_module::initialize_feature_flags(ctx)
+    }
+
+    /// Enable or disable the features in `features`
+    pub fn set_feature_flags(
+        ctx: Context<UpdateFeatureFlags>,
+        features: u64,
+        enabled: bool,
+    ) -> Result<()> {
+        feature_flags_module::set_feature_flags(ctx, features, enabled)
+    }
+
+    /// Create the protocol fee vault for a mint
+    pub fn initialize_fee_vault(ctx: Context<InitializeFeeVault>) -> Result<()> {
+        fee_vault_module::initialize_fee_vault(ctx)
+    }
+
+    /// Move the balance of every fee vault passed as a remaining account into the
+    /// treasury; callable by anyone.
+    pub fn sweep_all_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, SweepAllFees<'info>>,
+    ) -> Result<()> {
+        fee_vault_module::sweep_all_fees(ctx)
+    }
+
+    /// Create a new Listing that expires `params.duration` seconds from now
+    pub fn create_listing(
+        ctx: Context<CreateListing>,
+        listing_id: u64,
+        args: Versioned<CreateListingParams>,
+    ) -> Result<()> {
+        listing_module::create_listing(ctx, listing_id, args)
+    }
+
+    /// Create a new Listing and lock the configured seller collateral in one step.
+    pub fn create_listing_with_collateral(
+        ctx: Context<CreateListingWithCollateral>,
+        listing_id: u64,
+        args: Versioned<CreateListingParams>,
+    ) -> Result<()> {
+        listing_module::create_listing_with_collateral(ctx, listing_id, args)
+    }
+
+    /// Return a listing's collateral to its seller once the listing is
+    /// inactive or expired and has no open escrows
+    pub fn release_collateral(ctx: Context<ReleaseCollateral>) -> Result<()> {
+        collateral_module::release_collateral(ctx)
+    }
+
+    /// Slash part of a listing's locked collateral into the protocol fee vault
+    pub fn slash_collateral(ctx: Context<SlashCollateral>, amount: u64) -> Result<()> {
+        collateral_module::slash_collateral(ctx, amount)
+    }
+
+    /// Mint `amount` dataset-access tokens to the creator and list the dataset in
+    /// one step.
+    pub fn mint_and_list(
+        ctx: Context<MintAndList>,
+        listing_id: u64,
+        amount: u64,
+        args: Versioned<CreateListingParams>,
+    ) -> Result<()> {
+        listing_module::mint_and_list(ctx, listing_id, amount, args)
+    }
+
+    /// Renew a Listing with a new expiry time
+    pub fn relist(ctx: Context<Relist>, listing_id: u64, new_expiry: i64) -> Result<()> {
+        listing_module::relist(ctx, listing_id, new_expiry)
+    }
+
+    /// Grow a Listing created before its latest fields to the current size
+    pub fn resize_listing(ctx: Context<ResizeListing>, listing_id: u64) -> Result<()> {
+        listing_module::resize_listing(ctx, listing_id)
+    }
+
+    /// Change the price of an active Listing and restart its lifetime in one step.
+    pub fn reprice(
+        ctx: Context<UpdateListing>,
+        listing_id: u64,
+        new_price: u64,
+        duration: i64,
+    ) -> Result<()> {
+        listing_module::reprice(ctx, listing_id, new_price, duration)
+    }
+
+    /// Point a Listing at a new data URI, unless its metadata is locked
+    pub fn update_data_uri(
+        ctx: Context<UpdateListingMetadata>,
+        listing_id: u64,
+        data_uri: String,
+    ) -> Result<()> {
+        listing_module::update_data_uri(ctx, listing_id, data_uri)
+    }
+
+    /// Permanently lock the metadata of a Listing.
+    pub fn lock_metadata(ctx: Context<UpdateListing>, listing_id: u64) -> Result<()> {
+        listing_module::lock_metadata(ctx, listing_id)
+    }
+
+    /// Replace the mints buyers may pay for a Listing in.
+    pub fn set_accepted_mints(
+        ctx: Context<UpdateListing>,
+        listing_id: u64,
+        accepted_mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        listing_module::set_accepted_mints(ctx, listing_id, accepted_mints)
+    }
+
+    /// Allow or forbid transferring the access grants of a Listing.
+    pub fn set_grant_transfer_terms(
+        ctx: Context<UpdateListing>,
+        listing_id: u64,
+        grants_transferable: bool,
+        secondary_price: u64,
+    ) -> Result<()> {
+        listing_module::set_grant_transfer_terms(
+            ctx,
+            listing_id,
+            grants_transferable,
+            secondary_price,
+        )
+    }
+
+    /// Deactivate a Listing
+    pub fn cancel_listing(ctx: Context<ChangeListingStatus>, listing_id: u64) -> Result<()> {
+        listing_module::cancel_listing(ctx, listing_id)
+    }
+
+    /// Split an active Listing into sub-listings priced at `parts`.
+    pub fn split_listing<'info>(
+        ctx: Context<'_, '_, '_, 'info, SplitListing<'info>>,
+        listing_id: u64,
+        parts: Vec<u64>,
+    ) -> Result<()> {
+        listing_module::split_listing(ctx, listing_id, parts)
+    }
+
+    /// Record that the signer is interested in a listing.
+    pub fn express_interest(ctx: Context<ExpressInterest>, listing_id: u64) -> Result<()> {
+        listing_module::express_interest(ctx, listing_id)
+    }
+
+    /// Initialize the MarketplaceConfig
+    pub fn initialize_marketplace_config(
+        ctx: Context<InitializeMarketplaceConfig>,
+        protocol_fee_bps: u16,
+        stuck_timeout: i64,
+    ) -> Result<()> {
+        marketplace_config_module::initialize_marketplace_config(
+            ctx,
+            protocol_fee_bps,
+            stuck_timeout,
+        )
+    }
+
+    /// Update the protocol fee
+    pub fn set_protocol_fee(
+        ctx: Context<UpdateMarketplaceConfig>,
+        protocol_fee_bps: u16,
+    ) -> Result<()> {
+        marketplace_config_module::set_protocol_fee(ctx, protocol_fee_bps)
+    }
+
+    /// Update the collateral required by `create_listing_with_collateral`
+    pub fn set_listing_collateral(
+        ctx: Context<UpdateMarketplaceConfig>,
+        listing_collateral: u64,
+    ) -> Result<()> {
+        marketplace_config_module::set_listing_collateral(ctx, listing_collateral)
+    }
+
+    /// Update the referral reward.
+    pub fn set_referral_bps(
+        ctx: Context<UpdateMarketplaceConfig>,
+        referral_bps: u16,
+    ) -> Result<()> {
+        marketplace_config_module::set_referral_bps(ctx, referral_bps)
+    }
+
+    /// Update the rate credits are bought and redeemed at.
+    pub fn set_credit_rate(ctx: Context<UpdateMarketplaceConfig>, credit_rate: u64) -> Result<()> {
+        marketplace_config_module::set_credit_rate(ctx, credit_rate)
+    }
+
+    /// Update the balance below which protocol accounts may be swept
+    pub fn set_dust_threshold(
+        ctx: Context<UpdateMarketplaceConfig>,
+        dust_threshold: u64,
+    ) -> Result<()> {
+        marketplace_config_module::set_dust_threshold(ctx, dust_threshold)
+    }
+
+    /// Turn listing creation on or off, e.g. during a wind-down
+    pub fn set_accepting_new_listings(
+        ctx: Context<UpdateMarketplaceConfig>,
+        accepting_new_listings: bool,
+    ) -> Result<()> {
+        marketplace_config_module::set_accepting_new_listings(ctx, accepting_new_listings)
+    }
+
+    /// Update how many escrows a buyer may have open at once
+    pub fn set_max_open_escrows(
+        ctx: Context<UpdateMarketplaceConfig>,
+        max_open_escrows: u32,
+    ) -> Result<()> {
+        marketplace_config_module::set_max_open_escrows(ctx, max_open_escrows)
+    }
+
+    /// Update the auction anti-snipe window; zero disables extensions
+    pub fn set_anti_snipe_window(
+        ctx: Context<UpdateMarketplaceConfig>,
+        anti_snipe_window: i64,
+    ) -> Result<()> {
+        marketplace_config_module::set_anti_snipe_window(ctx, anti_snipe_window)
+    }
+
+    /// Replace the staker fee discount tiers.
+    pub fn set_fee_discount_tiers(
+        ctx: Context<UpdateMarketplaceConfig>,
+        stake_pool: Pubkey,
+        tiers: Vec<FeeDiscountTier>,
+    ) -> Result<()> {
+        marketplace_config_module::set_fee_discount_tiers(ctx, stake_pool, tiers)
+    }
+
+    /// Replace the high-volume seller fee rebate tiers.
+    pub fn set_seller_rebate_tiers(
+        ctx: Context<UpdateMarketplaceConfig>,
+        tiers: Vec<SellerRebateTier>,
+    ) -> Result<()> {
+        marketplace_config_module::set_seller_rebate_tiers(ctx, tiers)
+    }
+
+    /// Choose how protocol fees and royalties round
+    pub fn set_rounding_mode(
+        ctx: Context<UpdateMarketplaceConfig>,
+        rounding_mode: RoundingMode,
+    ) -> Result<()> {
+        marketplace_config_module::set_rounding_mode(ctx, rounding_mode)
+    }
+
+    /// Choose which party bears the protocol fee of new purchases
+    pub fn set_fee_payer(ctx: Context<UpdateMarketplaceConfig>, fee_payer: FeePayer) -> Result<()> {
+        marketplace_config_module::set_fee_payer(ctx, fee_payer)
+    }
+
+    /// Set the daily escrow holding fee and its cap
+    pub fn set_holding_fee(
+        ctx: Context<UpdateMarketplaceConfig>,
+        holding_fee_bps_per_day: u16,
+        max_holding_fee_bps: u16,
+    ) -> Result<()> {
+        marketplace_config_module::set_holding_fee(
+            ctx,
+            holding_fee_bps_per_day,
+            max_holding_fee_bps,
+        )
+    }
+
+    /// Choose whether buyer-favourable dispute resolutions refund the protocol fee
+    pub fn set_refund_fee_on_dispute(
+        ctx: Context<UpdateMarketplaceConfig>,
+        refund_fee_on_dispute: bool,
+    ) -> Result<()> {
+        marketplace_config_module::set_refund_fee_on_dispute(ctx, refund_fee_on_dispute)
+    }
+
+    /// Set the previous program version listings are imported from
+    pub fn set_migration_source(
+        ctx: Context<UpdateMarketplaceConfig>,
+        migration_source: Pubkey,
+    ) -> Result<()> {
+        marketplace_config_module::set_migration_source(ctx, migration_source)
+    }
+
+    /// Set the keeper reporting network congestion and the bounds the scaled
+    /// protocol fee is clamped to
+    pub fn set_congestion_fee_bounds(
+        ctx: Context<UpdateMarketplaceConfig>,
+        congestion_keeper: Pubkey,
+        min_protocol_fee_bps: u16,
+        max_protocol_fee_bps: u16,
+    ) -> Result<()> {
+        marketplace_config_module::set_congestion_fee_bounds(
+            ctx,
+            congestion_keeper,
+            min_protocol_fee_bps,
+            max_protocol_fee_bps,
+        )
+    }
+
+    /// Report the current congestion multiplier.
+    pub fn update_congestion_multiplier(
+        ctx: Context<UpdateCongestionMultiplier>,
+        congestion_multiplier_bps: u16,
+    ) -> Result<()> {
+        marketplace_config_module::update_congestion_multiplier(ctx, congestion_multiplier_bps)
+    }
+
+    /// Replace the schemes listing data URIs may use
+    pub fn set_allowed_uri_schemes(
+        ctx: Context<UpdateMarketplaceConfig>,
+        schemes: Vec<String>,
+    ) -> Result<()> {
+        marketplace_config_module::set_allowed_uri_schemes(ctx, schemes)
+    }
+
+    /// Permanently freeze the configuration.
+    pub fn freeze_config(ctx: Context<UpdateMarketplaceConfig>) -> Result<()> {
+        marketplace_config_module::freeze_config(ctx)
+    }
+
+    /// Deactivate a Listing for import into another program version, returning its snapshot
+    pub fn export_listing(ctx: Context<ExportListing>, listing_id: u64) -> Result<ListingSnapshot> {
+        migration_module::export_listing(ctx, listing_id)
+    }
+
+    /// Re-create a Listing exported by the previous program version.
+    pub fn import_listing(ctx: Context<ImportListing>, listing_id: u64) -> Result<()> {
+        migration_module::import_listing(ctx, listing_id)
+    }
+
+    /// Rate the seller of an escrow funded by the signer
+    pub fn rate_seller(ctx: Context<RateSeller>, rating: u8) -> Result<()> {
+        seller_profile_module::rate_seller(ctx, rating)
+    }
+
+    /// Split ownership of a listing's proceeds between shareholders
+    pub fn setup_dataset_shares(
+        ctx: Context<SetupDatasetShares>,
+        holders: Vec<Shareholder>,
+    ) -> Result<()> {
+        shares_module::setup_dataset_shares(ctx, holders)
+    }
+
+    /// Move `bps` of the signer's share to `to`
+    pub fn transfer_shares(ctx: Context<TransferShares>, to: Pubkey, bps: u16) -> Result<()> {
+        shares_module::transfer_shares(ctx, to, bps)
+    }
+
+    /// Purchase a listing priced in the native mint, escrowing the price in lamports.
+    pub fn purchase_with_sol(
+        ctx: Context<PurchaseWithSol>,
+        listing_id: u64,
+        pii_acknowledged: bool,
+    ) -> Result<()> {
+        sol_escrow_module::purchase_with_sol(ctx, listing_id, pii_acknowledged)
+    }
+
+    /// Release a SOL escrow to the seller once the buyer confirms delivery.
+    pub fn release_sol<'info>(ctx: Context<'_, '_, '_, 'info, ReleaseSol<'info>>) -> Result<()> {
+        sol_escrow_module::release_sol(ctx)
+    }
+
+    /// Return a SOL escrow to the buyer, at the seller's discretion.
+    pub fn refund_sol(ctx: Context<RefundSol>) -> Result<()> {
+        sol_escrow_module::refund_sol(ctx)
+    }
+
+    /// Opt in to a daily spending limit
+    pub fn set_daily_spend_limit(
+        ctx: Context<SetDailySpendLimit>,
+        daily_spend_limit: u64,
+    ) -> Result<()> {
+        spend_limit_module::set_daily_spend_limit(ctx, daily_spend_limit)
+    }
+
+    /// Initialize the MarketplaceStats
+    pub fn initialize_marketplace_stats(ctx: Context<InitializeMarketplaceStats>) -> Result<()> {
+        stats_module::initialize_marketplace_stats(ctx)
+    }
+
+    /// Subscribe to a listing for `period_duration` seconds at the listing price.
+    pub fn subscribe(ctx: Context<Subscribe>, listing_id: u64, period_duration: i64) -> Result<()> {
+        subscription_module::subscribe(ctx, listing_id, period_duration)
+    }
+
+    /// Cancel an active subscription, refunding `price * remaining / duration`.
+    pub fn cancel_subscription<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelSubscription<'info>>,
+    ) -> Result<()> {
+        subscription_module::cancel_subscription(ctx)
+    }
+
+    /// Pay the seller for a subscription period that has run out.
+    pub fn settle_subscription<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleSubscription<'info>>,
+    ) -> Result<()> {
+        subscription_module::settle_subscription(ctx)
+    }
+}
@@ -0,0 +1,1851 @@
+//! listing_module module for data marketplace
+//!
+//! This module provides dataset listings offered for sale on the marketplace.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::feature_flags_module::{FeatureFlags, ENABLE_SPLIT_LISTINGS};
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::stats_module::MarketplaceStats;
+use crate::utils_module::{
+    assert_not_globally_paused, assert_rent_exempt, create_pda_account, get_bump, grow_account,
+    mul_div_rounded, validate_bounded_vec, verify_discriminator, RoundingMode, BPS_DENOMINATOR,
+};
+use crate::vaults_module::COLLATERAL_VAULT_SEED;
+use crate::versioning_module::Versioned;
+
+/// Maximum length of a listing data URI
+pub const MAX_DATA_URI_LEN: usize = 200;
+
+/// Maximum number of sub-listings a listing can be split into
+pub const MAX_SUB_LISTINGS: usize = 5;
+
+/// Maximum number of extra mints a listing can accept payment in
+pub const MAX_ACCEPTED_MINTS: usize = 4;
+
+/// Why a listing stopped being active
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeactivationReason {
+    /// Cancelled by the seller; the listing may be relisted
+    Cancelled,
+    /// Converted into an auction, which now sells the dataset
+    ConvertedToAuction,
+    /// Split into sub-listings, which now sell the dataset
+    Split,
+    /// Exported to another program version
+    Migrated,
+}
+
+/// Listing state account
+#[account]
+pub struct Listing {
+    /// The seller that owns this listing
+    pub seller: Pubkey,
+
+    /// Unique listing identifier
+    pub listing_id: u64,
+
+    /// Mint the listing is priced in
+    pub mint: Pubkey,
+
+    /// Price in base units of `mint`
+    pub price: u64,
+
+    /// Whether the dataset contains personal data. Stored at a fixed offset
+    /// (byte 88) ahead of `data_uri` so clients can filter with memcmp.
+    pub contains_pii: bool,
+
+    /// ISO 3166-1 alpha-2 country code governing the dataset (byte 89)
+    pub jurisdiction: [u8; 2],
+
+    /// Location of the dataset
+    pub data_uri: String,
+
+    /// Whether the listing can be purchased
+    pub active: bool,
+
+    /// Creation time
+    pub created_at: i64,
+
+    /// Time after which the listing can no longer be purchased
+    pub expires_at: i64,
+
+    /// Seller collateral locked in the collateral vault
+    pub collateral: u64,
+
+    /// Original creator of the dataset, kept when the listing is split
+    pub creator: Pubkey,
+
+    /// Royalty owed to `creator` on resale, in basis points
+    pub royalty_bps: u16,
+
+    /// Mint the royalty is paid in, when different from `mint`
+    pub royalty_mint: Option<Pubkey>,
+
+    /// Number of distinct users that expressed interest
+    pub interest_count: u64,
+
+    /// Dataset-access token minted alongside the listing, if any
+    pub access_mint: Option<Pubkey>,
+
+    /// Whether access is bought by burning `price` tokens instead of paying the seller
+    pub burn_to_access: bool,
+
+    /// Incremented on every price change, so clients can tell a quote is stale
+    pub nonce: u64,
+
+    /// Most buyers the listing may be sold to; `None` is unlimited
+    pub max_buyers: Option<u32>,
+
+    /// Number of buyers the listing has been sold to
+    pub buyer_count: u32,
+
+    /// Once set, the data URI can never change again
+    pub metadata_locked: bool,
+
+    /// Number of escrows for this listing not yet settled
+    pub open_escrows: u32,
+
+    /// Mints buyers may pay in, priced from `price`; when empty only `mint` is accepted
+    pub accepted_mints: Vec<Pubkey>,
+
+    /// Seconds after `created_at` before the listing can be purchased
+    pub purchase_delay: i64,
+
+    /// Whether holders may pass their access grants on with `transfer_access_grant`
+    pub grants_transferable: bool,
+
+    /// Price the creator royalty is charged on when an access grant is transferred
+    pub secondary_price: u64,
+
+    /// The `DatasetShares` splitting the seller proceeds, once set up
+    pub dataset_shares: Option<Pubkey>,
+
+    /// Why the listing was deactivated; `None` while it has never been
+    pub deactivation_reason: Option<DeactivationReason>,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+/// InterestTracker state account, the receipt of a user's interest in a listing
+#[account]
+pub struct InterestTracker {
+    /// The listing of interest
+    pub listing: Pubkey,
+
+    /// The interested user
+    pub user: Pubkey,
+
+    /// Time interest was expressed
+    pub created_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl InterestTracker {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+/// Arguments shared by every instruction that creates a Listing
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateListingParams {
+    /// Price in base units of the listing mint
+    pub price: u64,
+
+    /// Location of the dataset
+    pub data_uri: String,
+
+    /// Seconds from now until the listing expires
+    pub duration: i64,
+
+    /// Whether the dataset contains personal data
+    pub contains_pii: bool,
+
+    /// ISO 3166-1 alpha-2 country code governing the dataset
+    pub jurisdiction: [u8; 2],
+
+    /// Original creator of a dataset being resold; `None` when the seller created it
+    pub creator: Option<Pubkey>,
+
+    /// Royalty owed to the creator on resale, in basis points
+    pub royalty_bps: u16,
+
+    /// Mint the royalty is paid in; `None` pays it in the listing mint
+    pub royalty_mint: Option<Pubkey>,
+
+    /// Whether access is bought by burning `price` tokens via `burn_for_access`
+    pub burn_to_access: bool,
+
+    /// Most buyers the listing may be sold to; `None` is unlimited
+    pub max_buyers: Option<u32>,
+
+    /// Seconds after creation before the listing can be purchased; zero for none
+    pub purchase_delay: i64,
+}
+
+impl Listing {
+    pub const LEN: usize = 32
+        + 8
+        + 32
+        + 8
+        + 1
+        + 2
+        + (4 + MAX_DATA_URI_LEN)
+        + 1
+        + 8
+        + 8
+        + 8
+        + 32
+        + 2
+        + 33
+        + 8
+        + 33
+        + 1
+        + 8
+        + 5
+        + 4
+        + 1
+        + 4
+        + (4 + MAX_ACCEPTED_MINTS * 32)
+        + 8
+        + 1
+        + 8
+        + 33
+        + 2
+        + 1;
+
+    /// Count `user` as interested unless `tracker` already records them
+    pub fn record_interest(
+        &mut self,
+        tracker: &mut InterestTracker,
+        user: Pubkey,
+        now: i64,
+    ) -> Result<()> {
+        if tracker.user == user {
+            return Ok(());
+        }
+        self.interest_count = self
+            .interest_count
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+        tracker.user = user;
+        tracker.created_at = now;
+
+        Ok(())
+    }
+
+    /// Check that the listing can be purchased at `now`
+    pub fn assert_purchasable(&self, now: i64) -> Result<()> {
+        require!(self.active, MarketplaceError::ListingInactive);
+        require!(now <= self.expires_at, MarketplaceError::ListingExpired);
+        require!(
+            now >= self.created_at.saturating_add(self.purchase_delay),
+            MarketplaceError::ListingNotYetPurchasable
+        );
+
+        Ok(())
+    }
+
+    /// Reject buying a dataset with personal data unless the buyer acknowledged it
+    pub fn assert_pii_acknowledged(&self, pii_acknowledged: bool) -> Result<()> {
+        require!(
+            !self.contains_pii || pii_acknowledged,
+            MarketplaceError::PiiNotAcknowledged
+        );
+
+        Ok(())
+    }
+
+    /// Tokens to burn for access at `now`, for listings sold by burning
+    pub fn burn_price(&self, now: i64, pii_acknowledged: bool) -> Result<u64> {
+        self.assert_purchasable(now)?;
+        require!(self.burn_to_access, MarketplaceError::BurnToAccessDisabled);
+        self.assert_pii_acknowledged(pii_acknowledged)?;
+
+        Ok(self.price)
+    }
+
+    /// Price of the listing when paid in `mint`, rejecting mints it does not accept.
+    ///
+    /// A mint other than the listing's own is charged `price` converted by
+    /// `convert`, so repricing the listing reprices every accepted mint.
+    pub fn price_in(&self, mint: &Pubkey, convert: impl FnOnce(u64) -> Result<u64>) -> Result<u64> {
+        let accepted = if self.accepted_mints.is_empty() {
+            *mint == self.mint
+        } else {
+            self.accepted_mints.contains(mint)
+        };
+        require!(accepted, MarketplaceError::UnacceptedMint);
+
+        if *mint == self.mint {
+            Ok(self.price)
+        } else {
+            convert(self.price)
+        }
+    }
+
+    /// Mark the listing inactive for `reason`
+    pub fn deactivate(&mut self, reason: DeactivationReason) {
+        self.active = false;
+        self.deactivation_reason = Some(reason);
+    }
+
+    /// Reject relisting a listing that another listing, an auction or
+    /// another program version now sells
+    pub fn assert_relistable(&self) -> Result<()> {
+        require!(
+            matches!(
+                self.deactivation_reason,
+                None | Some(DeactivationReason::Cancelled)
+            ),
+            MarketplaceError::ListingNotRelistable
+        );
+
+        Ok(())
+    }
+
+    /// Check the listing can be taken off fixed-price sale for an auction at
+    /// `now`: it is still on sale, no purchase is mid-flight and its royalty
+    /// is paid in the listing mint, as auctions pay it out of the winning bid
+    pub fn assert_auctionable(&self, now: i64) -> Result<()> {
+        self.assert_purchasable(now)?;
+        require!(
+            self.open_escrows == 0,
+            MarketplaceError::ListingHasOpenEscrows
+        );
+        require!(
+            self.royalty_mint.is_none(),
+            MarketplaceError::InvalidRoyaltyAccounts
+        );
+
+        Ok(())
+    }
+
+    /// Check the listing can move to another program version: no escrow is
+    /// open, nothing else sells it and no shareholders would lose their cut
+    pub fn assert_exportable(&self) -> Result<()> {
+        require!(
+            self.open_escrows == 0,
+            MarketplaceError::ListingHasOpenEscrows
+        );
+        require!(
+            self.dataset_shares.is_none(),
+            MarketplaceError::ListingHasShares
+        );
+        self.assert_relistable()
+    }
+
+    /// Check the seller can take back the collateral at `now`: only once the
+    /// listing is inactive or expired and every escrow for it has settled, so
+    /// it can still be slashed over any sale
+    pub fn assert_collateral_releasable(&self, now: i64) -> Result<()> {
+        require!(self.collateral > 0, MarketplaceError::NoCollateral);
+        require!(
+            !self.collateral_locked(now),
+            MarketplaceError::CollateralLocked
+        );
+
+        Ok(())
+    }
+
+    /// Whether the collateral is still locked at `now`: the listing is live or
+    /// has open escrows
+    fn collateral_locked(&self, now: i64) -> bool {
+        (self.active && now <= self.expires_at) || self.open_escrows > 0
+    }
+
+    /// Slash `amount` of the collateral at `now`, only while it is still locked
+    pub fn slash_collateral(&mut self, amount: u64, now: i64) -> Result<()> {
+        require!(
+            self.collateral_locked(now),
+            MarketplaceError::CollateralUnlocked
+        );
+        self.take_collateral(amount)
+    }
+
+    /// Remove `amount` from the locked collateral
+    pub fn take_collateral(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, MarketplaceError::NoCollateral);
+        self.collateral = self
+            .collateral
+            .checked_sub(amount)
+            .ok_or(MarketplaceError::SlashExceedsCollateral)?;
+
+        Ok(())
+    }
+
+    /// Reactivate the listing until `new_expiry`, which must be after `now`
+    pub fn renew(&mut self, new_expiry: i64, now: i64) -> Result<()> {
+        require!(new_expiry > now, MarketplaceError::InvalidExpiry);
+        self.assert_relistable()?;
+        self.expires_at = new_expiry;
+        self.active = true;
+        self.deactivation_reason = None;
+
+        Ok(())
+    }
+
+    /// Change the price of an active listing and restart its expiry `duration`
+    /// seconds after `now`, invalidating quotes for the old terms
+    pub fn reprice(&mut self, new_price: u64, duration: i64, now: i64) -> Result<()> {
+        require!(duration > 0, MarketplaceError::InvalidExpiry);
+        require!(self.active, MarketplaceError::ListingInactive);
+        self.price = new_price;
+        self.nonce = self
+            .nonce
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+        self.expires_at = now
+            .checked_add(duration)
+            .ok_or(MarketplaceError::InvalidExpiry)?;
+
+        Ok(())
+    }
+
+    /// Point the listing at `data_uri` unless its metadata is locked
+    pub fn set_data_uri(&mut self, data_uri: String) -> Result<()> {
+        require!(
+            data_uri.len() <= MAX_DATA_URI_LEN,
+            MarketplaceError::DataUriTooLong
+        );
+        require!(!self.metadata_locked, MarketplaceError::MetadataLocked);
+        self.data_uri = data_uri;
+
+        Ok(())
+    }
+
+    /// Count a new buyer, rejecting them once `max_buyers` is reached
+    pub fn record_buyer(&mut self) -> Result<()> {
+        if let Some(max_buyers) = self.max_buyers {
+            require!(
+                self.buyer_count < max_buyers,
+                MarketplaceError::BuyerLimitReached
+            );
+        }
+        self.buyer_count = self
+            .buyer_count
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Count an escrow opened for this listing
+    pub fn record_escrow_opened(&mut self) -> Result<()> {
+        self.open_escrows = self
+            .open_escrows
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Count an escrow of this listing being settled
+    pub fn record_escrow_closed(&mut self) -> Result<()> {
+        self.open_escrows = self
+            .open_escrows
+            .checked_sub(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Validate `params` and populate a freshly initialized listing
+    pub fn initialize(
+        &mut self,
+        seller: Pubkey,
+        listing_id: u64,
+        mint: Pubkey,
+        params: CreateListingParams,
+        bump: u8,
+    ) -> Result<()> {
+        require!(
+            params.data_uri.len() <= MAX_DATA_URI_LEN,
+            MarketplaceError::DataUriTooLong
+        );
+        require!(
+            params.jurisdiction.iter().all(u8::is_ascii_uppercase),
+            MarketplaceError::InvalidJurisdiction
+        );
+        require!(params.duration > 0, MarketplaceError::InvalidExpiry);
+        require!(params.purchase_delay >= 0, MarketplaceError::InvalidExpiry);
+        require!(
+            params.royalty_bps as u64 <= BPS_DENOMINATOR,
+            MarketplaceError::InvalidFeeBps
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        self.seller = seller;
+        self.listing_id = listing_id;
+        self.mint = mint;
+        self.price = params.price;
+        self.contains_pii = params.contains_pii;
+        self.jurisdiction = params.jurisdiction;
+        self.data_uri = params.data_uri;
+        self.active = true;
+        self.created_at = now;
+        self.expires_at = now
+            .checked_add(params.duration)
+            .ok_or(MarketplaceError::InvalidExpiry)?;
+        self.collateral = 0;
+        self.creator = params.creator.unwrap_or(seller);
+        self.royalty_bps = params.royalty_bps;
+        self.royalty_mint = params.royalty_mint;
+        self.interest_count = 0;
+        self.access_mint = None;
+        self.burn_to_access = params.burn_to_access;
+        self.nonce = 0;
+        self.max_buyers = params.max_buyers;
+        self.buyer_count = 0;
+        self.metadata_locked = false;
+        self.open_escrows = 0;
+        self.accepted_mints = Vec::new();
+        self.purchase_delay = params.purchase_delay;
+        self.grants_transferable = false;
+        self.secondary_price = 0;
+        self.dataset_shares = None;
+        self.deactivation_reason = None;
+        self.bump = bump;
+
+        Ok(())
+    }
+
+    /// Royalty owed to the creator on a sale at `price`.
+    ///
+    /// Royalties only apply when the dataset is sold by someone other than its
+    /// creator. The amount is in base units of `mint`; when `royalty_mint` is
+    /// set it is converted at the marketplace `ConversionRate` before payment.
+    pub fn royalty_due(&self, price: u64, rounding_mode: RoundingMode) -> Result<u64> {
+        if self.creator == self.seller {
+            return Ok(0);
+        }
+        mul_div_rounded(
+            price,
+            self.royalty_bps as u64,
+            BPS_DENOMINATOR,
+            rounding_mode,
+        )
+    }
+
+    /// Royalty `holder` owes the creator to hand an access grant to `to`, on the
+    /// secondary price floored at the listing price, rejecting transfers of
+    /// non-transferable grants
+    pub fn grant_transfer_royalty(
+        &self,
+        holder: Pubkey,
+        to: Pubkey,
+        rounding_mode: RoundingMode,
+    ) -> Result<u64> {
+        require!(
+            self.grants_transferable,
+            MarketplaceError::GrantNotTransferable
+        );
+        require_keys_neq!(to, holder, MarketplaceError::GrantNotTransferable);
+        if holder == self.creator {
+            return Ok(0);
+        }
+        mul_div_rounded(
+            self.secondary_price.max(self.price),
+            self.royalty_bps as u64,
+            BPS_DENOMINATOR,
+            rounding_mode,
+        )
+    }
+
+    /// Check `parts` splits the listing into 2 to `MAX_SUB_LISTINGS` non-zero
+    /// prices summing to its price
+    pub fn validate_split(&self, parts: &[u64]) -> Result<()> {
+        validate_bounded_vec(parts, MAX_SUB_LISTINGS)?;
+        require!(parts.len() >= 2, MarketplaceError::InvalidSplit);
+        let total = parts.iter().try_fold(0u64, |total, part| {
+            require!(*part > 0, MarketplaceError::InvalidSplit);
+            total
+                .checked_add(*part)
+                .ok_or_else(|| error!(MarketplaceError::MathOverflow))
+        })?;
+        require!(total == self.price, MarketplaceError::InvalidSplit);
+
+        Ok(())
+    }
+
+    /// Identifier of the `index`-th sub-listing created by splitting `listing_id`
+    pub fn sub_listing_id(listing_id: u64, index: u8) -> u64 {
+        let hash = keccak::hashv(&[b"sub-listing", &listing_id.to_le_bytes(), &[index]]).0;
+        u64::from_le_bytes(hash[..8].try_into().unwrap())
+    }
+}
+
+/// Create a new Listing that expires `params.duration` seconds from now
+pub fn create_listing(
+    ctx: Context<CreateListing>,
+    listing_id: u64,
+    args: Versioned<CreateListingParams>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let params = args.into_v1()?;
+    ctx.accounts
+        .marketplace_config
+        .assert_allowed_uri(&params.data_uri)?;
+    let bump = get_bump(&ctx.bumps, "listing")?;
+    ctx.accounts.listing.initialize(
+        ctx.accounts.seller.key(),
+        listing_id,
+        ctx.accounts.mint.key(),
+        params,
+        bump,
+    )?;
+    ctx.accounts.marketplace_stats.record_listing_opened()
+}
+
+/// Create a new Listing and lock the configured seller collateral in one step.
+///
+/// Both happen in a single instruction, so a failed collateral transfer leaves
+/// no listing behind. The collateral comes back through `release_collateral`.
+pub fn create_listing_with_collateral(
+    ctx: Context<CreateListingWithCollateral>,
+    listing_id: u64,
+    args: Versioned<CreateListingParams>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let params = args.into_v1()?;
+    ctx.accounts
+        .marketplace_config
+        .assert_allowed_uri(&params.data_uri)?;
+    let collateral = ctx.accounts.marketplace_config.listing_collateral;
+    require!(
+        ctx.accounts.seller_token_account.amount >= collateral,
+        MarketplaceError::InsufficientCollateral
+    );
+
+    let bump = get_bump(&ctx.bumps, "listing")?;
+    ctx.accounts.listing.initialize(
+        ctx.accounts.seller.key(),
+        listing_id,
+        ctx.accounts.mint.key(),
+        params,
+        bump,
+    )?;
+    ctx.accounts.marketplace_stats.record_listing_opened()?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.seller_token_account.to_account_info(),
+                to: ctx.accounts.collateral_vault.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        ),
+        collateral,
+    )?;
+    ctx.accounts.listing.collateral = collateral;
+
+    Ok(())
+}
+
+/// Mint `amount` dataset-access tokens to the creator and list the dataset in
+/// one step.
+///
+/// Both happen in a single instruction, so a listing that fails validation
+/// leaves no tokens minted.
+pub fn mint_and_list(
+    ctx: Context<MintAndList>,
+    listing_id: u64,
+    amount: u64,
+    args: Versioned<CreateListingParams>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    require!(amount > 0, MarketplaceError::InvalidMintAmount);
+    let params = args.into_v1()?;
+    ctx.accounts
+        .marketplace_config
+        .assert_allowed_uri(&params.data_uri)?;
+
+    let bump = get_bump(&ctx.bumps, "listing")?;
+    ctx.accounts.listing.initialize(
+        ctx.accounts.seller.key(),
+        listing_id,
+        ctx.accounts.mint.key(),
+        params,
+        bump,
+    )?;
+    ctx.accounts.listing.access_mint = Some(ctx.accounts.access_mint.key());
+    ctx.accounts.marketplace_stats.record_listing_opened()?;
+
+    token::mint_to(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::MintTo {
+                mint: ctx.accounts.access_mint.to_account_info(),
+                to: ctx.accounts.seller_access_token_account.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Renew a Listing with a new expiry time.
+///
+/// Cancelled listings can be reopened this way, but not listings converted to
+/// an auction, split or migrated. Like listing creation, relisting is refused
+/// while the marketplace is not accepting new listings.
+pub fn relist(ctx: Context<Relist>, _listing_id: u64, new_expiry: i64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let listing = &mut ctx.accounts.listing;
+    let was_active = listing.active;
+    listing.renew(new_expiry, Clock::get()?.unix_timestamp)?;
+    if !was_active {
+        ctx.accounts.marketplace_stats.record_listing_opened()?;
+    }
+
+    Ok(())
+}
+
+/// Grow a Listing created before its latest fields to the current size, so it
+/// can be loaded again; callable by anyone, who pays the added rent.
+///
+/// Listings already at the current size are left unchanged.
+pub fn resize_listing(ctx: Context<ResizeListing>, _listing_id: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let info = ctx.accounts.listing.to_account_info();
+    verify_discriminator::<Listing>(&info)?;
+    if info.data_len() >= 8 + Listing::LEN {
+        return Ok(());
+    }
+
+    grow_account(
+        &ctx.accounts.payer.to_account_info(),
+        &info,
+        &ctx.accounts.system_program.to_account_info(),
+        8 + Listing::LEN,
+    )
+}
+
+/// Change the price of an active Listing and restart its lifetime in one step.
+///
+/// Unlike `relist`, which only moves the expiry, this sets the new price,
+/// bumps `nonce` and expires the listing `duration` seconds from now. The
+/// listing stays active throughout, so there is no window in which it cannot
+/// be bought.
+pub fn reprice(
+    ctx: Context<UpdateListing>,
+    _listing_id: u64,
+    new_price: u64,
+    duration: i64,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts
+        .listing
+        .reprice(new_price, duration, Clock::get()?.unix_timestamp)
+}
+
+/// Point a Listing at a new data URI, unless its metadata is locked
+pub fn update_data_uri(
+    ctx: Context<UpdateListingMetadata>,
+    _listing_id: u64,
+    data_uri: String,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts
+        .marketplace_config
+        .assert_allowed_uri(&data_uri)?;
+    ctx.accounts.listing.set_data_uri(data_uri)
+}
+
+/// Permanently lock the metadata of a Listing.
+///
+/// Afterwards `update_data_uri` fails with `MetadataLocked`, letting sellers
+/// commit to the dataset buyers pay for. The price is not metadata and can
+/// still be changed with `reprice`.
+pub fn lock_metadata(ctx: Context<UpdateListing>, _listing_id: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts.listing.metadata_locked = true;
+
+    Ok(())
+}
+
+/// Replace the mints buyers may pay for a Listing in.
+///
+/// Other mints are charged `price` converted at the admin-set
+/// `ConversionRate` from the listing mint; include the listing mint to keep
+/// accepting it. An empty list restores the default of accepting only the
+/// listing mint. Bumps `nonce`, since quotes in the replaced mints go stale.
+pub fn set_accepted_mints(
+    ctx: Context<UpdateListing>,
+    _listing_id: u64,
+    accepted_mints: Vec<Pubkey>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    validate_bounded_vec(&accepted_mints, MAX_ACCEPTED_MINTS)?;
+    let listing = &mut ctx.accounts.listing;
+    listing.accepted_mints = accepted_mints;
+    listing.nonce = listing
+        .nonce
+        .checked_add(1)
+        .ok_or(MarketplaceError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Allow or forbid transferring the access grants of a Listing.
+///
+/// Each transfer pays the creator `royalty_bps` of `secondary_price`, or of the
+/// price if that is higher.
+pub fn set_grant_transfer_terms(
+    ctx: Context<UpdateListing>,
+    _listing_id: u64,
+    grants_transferable: bool,
+    secondary_price: u64,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let listing = &mut ctx.accounts.listing;
+    listing.grants_transferable = grants_transferable;
+    listing.secondary_price = secondary_price;
+
+    Ok(())
+}
+
+/// Deactivate a Listing
+pub fn cancel_listing(ctx: Context<ChangeListingStatus>, _listing_id: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let listing = &mut ctx.accounts.listing;
+    require!(listing.active, MarketplaceError::ListingInactive);
+    listing.deactivate(DeactivationReason::Cancelled);
+    ctx.accounts.marketplace_stats.record_listing_closed()
+}
+
+/// Split an active Listing into sub-listings priced at `parts`.
+///
+/// The parts must sum to the parent price. Sub-listing `i` is created at the
+/// listing PDA of `Listing::sub_listing_id(listing_id, i)`, passed in order as
+/// remaining accounts, and copies every attribute of the parent, including its
+/// creator, royalty, accepted mints and `DatasetShares`, so the parent's
+/// shareholders are paid for sales of every part. The parent is deactivated;
+/// its collateral stays with it and is released like that of any inactive
+/// listing.
+pub fn split_listing<'info>(
+    ctx: Context<'_, '_, '_, 'info, SplitListing<'info>>,
+    listing_id: u64,
+    parts: Vec<u64>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts
+        .feature_flags
+        .require_enabled(ENABLE_SPLIT_LISTINGS)?;
+    require!(
+        ctx.remaining_accounts.len() == parts.len(),
+        MarketplaceError::InvalidSplit
+    );
+    let parent = &ctx.accounts.listing;
+    require!(parent.active, MarketplaceError::ListingInactive);
+    parent.validate_split(&parts)?;
+
+    for (index, (price, info)) in parts.iter().zip(ctx.remaining_accounts.iter()).enumerate() {
+        let sub_listing_id = Listing::sub_listing_id(listing_id, index as u8);
+        let id_bytes = sub_listing_id.to_le_bytes();
+        let (address, bump) =
+            Pubkey::find_program_address(&[b"listing".as_ref(), id_bytes.as_ref()], ctx.program_id);
+        require_keys_eq!(info.key(), address, MarketplaceError::InvalidSplit);
+
+        require!(info.owner != ctx.program_id, MarketplaceError::InvalidSplit);
+        create_pda_account(
+            &ctx.accounts.seller.to_account_info(),
+            info,
+            &ctx.accounts.system_program.to_account_info(),
+            8 + Listing::LEN,
+            ctx.program_id,
+            &[b"listing".as_ref(), id_bytes.as_ref(), &[bump]],
+        )?;
+
+        let sub_listing = Listing {
+            listing_id: sub_listing_id,
+            price: *price,
+            collateral: 0,
+            interest_count: 0,
+            nonce: 0,
+            buyer_count: 0,
+            open_escrows: 0,
+            deactivation_reason: None,
+            bump,
+            ..(**parent).clone()
+        };
+        sub_listing.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        assert_rent_exempt(info)?;
+        ctx.accounts.marketplace_stats.record_listing_opened()?;
+    }
+
+    ctx.accounts.listing.deactivate(DeactivationReason::Split);
+    ctx.accounts.marketplace_stats.record_listing_closed()
+}
+
+/// Record that the signer is interested in a listing.
+///
+/// Each user is counted once: their `InterestTracker` receipt is created on the
+/// first call and any repeat call leaves the count unchanged.
+pub fn express_interest(ctx: Context<ExpressInterest>, _listing_id: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let listing = &mut ctx.accounts.listing;
+    let tracker = &mut ctx.accounts.interest_tracker;
+    listing.record_interest(
+        tracker,
+        ctx.accounts.user.key(),
+        Clock::get()?.unix_timestamp,
+    )?;
+    tracker.listing = listing.key();
+    tracker.bump = get_bump(&ctx.bumps, "interest_tracker")?;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct CreateListing<'info> {
+    /// The seller creating the listing
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Listing::LEN,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        constraint = marketplace_config.accepting_new_listings
+            @ MarketplaceError::NotAcceptingListings,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Mint the listing is priced in
+    pub mint: Account<'info, Mint>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for create with collateral
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct CreateListingWithCollateral<'info> {
+    /// The seller creating the listing
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Listing::LEN,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        constraint = marketplace_config.accepting_new_listings
+            @ MarketplaceError::NotAcceptingListings,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Vault holding the seller collateral
+    #[account(
+        init,
+        payer = seller,
+        seeds = [COLLATERAL_VAULT_SEED, listing.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = listing,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    /// Mint the listing is priced in
+    pub mint: Account<'info, Mint>,
+
+    /// Seller's token account
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for mint and list
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct MintAndList<'info> {
+    /// The creator minting and listing the dataset
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Listing::LEN,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        constraint = marketplace_config.accepting_new_listings
+            @ MarketplaceError::NotAcceptingListings,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Mint the listing is priced in
+    pub mint: Account<'info, Mint>,
+
+    /// Dataset-access token mint; the seller must be its mint authority
+    #[account(
+        mut,
+        mint::authority = seller,
+    )]
+    pub access_mint: Account<'info, Mint>,
+
+    /// Seller's access token account receiving the minted tokens
+    #[account(
+        mut,
+        token::mint = access_mint,
+        token::authority = seller,
+    )]
+    pub seller_access_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct UpdateListing<'info> {
+    /// The seller that owns the listing
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+}
+
+/// Account validation for metadata update
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct UpdateListingMetadata<'info> {
+    /// The seller that owns the listing
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+}
+
+/// Account validation for split
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct SplitListing<'info> {
+    /// The seller that owns the listing, paying for the sub-listings
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing to split
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        constraint = marketplace_config.accepting_new_listings
+            @ MarketplaceError::NotAcceptingListings,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Feature flags
+    #[account(
+        seeds = [b"feature-flags".as_ref()],
+        bump = feature_flags.bump,
+    )]
+    pub feature_flags: Account<'info, FeatureFlags>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for interest
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct ExpressInterest<'info> {
+    /// The interested user
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing of interest
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// The user's receipt, created on first interest so they are not counted twice
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + InterestTracker::LEN,
+        seeds = [b"interest".as_ref(), listing.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub interest_tracker: Account<'info, InterestTracker>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for relist
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct Relist<'info> {
+    /// The seller that owns the listing
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        constraint = marketplace_config.accepting_new_listings
+            @ MarketplaceError::NotAcceptingListings,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+}
+
+/// Account validation for resize
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct ResizeListing<'info> {
+    /// Pays the rent of the added space
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// CHECK: Listing to grow; it may be too short to deserialize, so only its
+    /// address, owner and discriminator are checked
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub listing: UncheckedAccount<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for cancel
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct ChangeListingStatus<'info> {
+    /// The seller that owns the listing
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::utils_module::tests::global_config_data;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// An active listing priced at 1000, sold by its creator
+    pub(crate) fn listing() -> Listing {
+        let seller = Pubkey::new_unique();
+        Listing {
+            seller,
+            listing_id: 1,
+            mint: Pubkey::new_unique(),
+            price: 1_000,
+            contains_pii: false,
+            jurisdiction: *b"US",
+            data_uri: "ipfs://dataset".to_string(),
+            active: true,
+            created_at: 0,
+            expires_at: 10_000,
+            collateral: 0,
+            creator: seller,
+            royalty_bps: 0,
+            royalty_mint: None,
+            interest_count: 0,
+            access_mint: None,
+            burn_to_access: false,
+            nonce: 0,
+            max_buyers: None,
+            buyer_count: 0,
+            metadata_locked: false,
+            open_escrows: 0,
+            accepted_mints: Vec::new(),
+            purchase_delay: 0,
+            grants_transferable: false,
+            secondary_price: 0,
+            dataset_shares: None,
+            deactivation_reason: None,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn split_into_two_parts() {
+        assert!(listing().validate_split(&[600, 400]).is_ok());
+    }
+
+    #[test]
+    fn split_with_mismatched_sum_rejected() {
+        let listing = listing();
+        for parts in [&[600, 300][..], &[600, 500], &[1_000], &[1_000, 0]] {
+            assert_eq!(
+                listing.validate_split(parts).unwrap_err(),
+                MarketplaceError::InvalidSplit.into()
+            );
+        }
+    }
+
+    #[test]
+    fn split_over_bound_rejected() {
+        let listing = listing();
+        listing.validate_split(&[200; MAX_SUB_LISTINGS]).unwrap();
+        assert_eq!(
+            listing
+                .validate_split(&[100, 100, 100, 100, 100, 500])
+                .unwrap_err(),
+            MarketplaceError::TooManyEntries.into()
+        );
+    }
+
+    #[test]
+    fn accepted_mints_priced_from_listing_price() {
+        let mut listing = listing();
+        let usdc = Pubkey::new_unique();
+        let quarter = |price: u64| Ok(price / 4);
+        assert_eq!(listing.price_in(&listing.mint, quarter).unwrap(), 1_000);
+        assert_eq!(
+            listing.price_in(&usdc, quarter).unwrap_err(),
+            MarketplaceError::UnacceptedMint.into()
+        );
+
+        listing.accepted_mints = vec![listing.mint, usdc];
+        assert_eq!(listing.price_in(&usdc, quarter).unwrap(), 250);
+        listing.price = 2_000;
+        assert_eq!(listing.price_in(&usdc, quarter).unwrap(), 500);
+        assert_eq!(listing.price_in(&listing.mint, quarter).unwrap(), 2_000);
+        assert_eq!(
+            listing
+                .price_in(&Pubkey::new_unique(), quarter)
+                .unwrap_err(),
+            MarketplaceError::UnacceptedMint.into()
+        );
+    }
+
+    #[test]
+    fn resale_owes_creator_royalty() {
+        let mut listing = listing();
+        listing.royalty_bps = 500;
+        assert_eq!(listing.royalty_due(1_000, RoundingMode::Floor).unwrap(), 0);
+
+        listing.creator = Pubkey::new_unique();
+        assert_eq!(listing.royalty_due(1_000, RoundingMode::Floor).unwrap(), 50);
+    }
+
+    #[test]
+    fn expired_listing_purchasable_again_once_relisted() {
+        let mut listing = listing();
+        listing.assert_purchasable(10_000).unwrap();
+        assert_eq!(
+            listing.assert_purchasable(10_001).unwrap_err(),
+            MarketplaceError::ListingExpired.into()
+        );
+
+        assert_eq!(
+            listing.renew(10_001, 10_001).unwrap_err(),
+            MarketplaceError::InvalidExpiry.into()
+        );
+        listing.renew(20_000, 10_001).unwrap();
+        listing.assert_purchasable(10_001).unwrap();
+    }
+
+    #[test]
+    fn pii_purchase_requires_acknowledgement() {
+        let mut listing = listing();
+        listing.assert_pii_acknowledged(false).unwrap();
+
+        listing.contains_pii = true;
+        assert_eq!(
+            listing.assert_pii_acknowledged(false).unwrap_err(),
+            MarketplaceError::PiiNotAcknowledged.into()
+        );
+        listing.assert_pii_acknowledged(true).unwrap();
+    }
+
+    #[test]
+    fn only_cancelled_listings_relist() {
+        let mut listing = listing();
+        listing.deactivate(DeactivationReason::Cancelled);
+        assert!(listing.assert_relistable().is_ok());
+
+        for reason in [
+            DeactivationReason::ConvertedToAuction,
+            DeactivationReason::Split,
+            DeactivationReason::Migrated,
+        ] {
+            listing.deactivate(reason);
+            assert_eq!(
+                listing.assert_relistable().unwrap_err(),
+                MarketplaceError::ListingNotRelistable.into()
+            );
+        }
+    }
+
+    #[test]
+    fn collateral_released_only_once_listing_closed() {
+        let mut cancelled = listing();
+        cancelled.collateral = 500;
+        cancelled.open_escrows = 1;
+
+        assert_eq!(
+            cancelled.assert_collateral_releasable(5_000).unwrap_err(),
+            MarketplaceError::CollateralLocked.into()
+        );
+        cancelled.deactivate(DeactivationReason::Cancelled);
+        assert_eq!(
+            cancelled.assert_collateral_releasable(5_000).unwrap_err(),
+            MarketplaceError::CollateralLocked.into()
+        );
+        cancelled.open_escrows = 0;
+        cancelled.assert_collateral_releasable(5_000).unwrap();
+
+        let mut expired = listing();
+        expired.collateral = 500;
+        assert!(expired.assert_collateral_releasable(10_000).is_err());
+        expired.assert_collateral_releasable(10_001).unwrap();
+    }
+
+    #[test]
+    fn slash_limited_to_locked_collateral() {
+        let mut listing = listing();
+        listing.collateral = 500;
+
+        listing.take_collateral(200).unwrap();
+        assert_eq!(listing.collateral, 300);
+        assert_eq!(
+            listing.take_collateral(301).unwrap_err(),
+            MarketplaceError::SlashExceedsCollateral.into()
+        );
+        listing.take_collateral(300).unwrap();
+        assert_eq!(
+            listing.assert_collateral_releasable(10_001).unwrap_err(),
+            MarketplaceError::NoCollateral.into()
+        );
+    }
+
+    #[test]
+    fn collateral_slashed_only_while_locked() {
+        let mut listing = listing();
+        listing.collateral = 500;
+
+        listing.slash_collateral(100, 10_000).unwrap();
+        assert_eq!(listing.collateral, 400);
+        assert_eq!(
+            listing.slash_collateral(100, 10_001).unwrap_err(),
+            MarketplaceError::CollateralUnlocked.into()
+        );
+
+        listing.deactivate(DeactivationReason::Cancelled);
+        listing.open_escrows = 1;
+        listing.slash_collateral(100, 5_000).unwrap();
+        listing.open_escrows = 0;
+        assert_eq!(
+            listing.slash_collateral(100, 5_000).unwrap_err(),
+            MarketplaceError::CollateralUnlocked.into()
+        );
+        assert_eq!(listing.collateral, 300);
+    }
+
+    #[test]
+    fn repeated_interest_counted_once() {
+        let mut target = listing();
+        let mut tracker = InterestTracker {
+            listing: Pubkey::default(),
+            user: Pubkey::default(),
+            created_at: 0,
+            bump: 0,
+        };
+        let user = Pubkey::new_unique();
+
+        target.record_interest(&mut tracker, user, 10).unwrap();
+        target.record_interest(&mut tracker, user, 20).unwrap();
+        assert_eq!(target.interest_count, 1);
+        assert_eq!(tracker.created_at, 10);
+    }
+
+    #[test]
+    fn failed_listing_step_stops_before_minting() {
+        let mut target = listing();
+        let params = CreateListingParams {
+            price: 1_000,
+            data_uri: "ipfs://dataset".to_string(),
+            duration: 0,
+            contains_pii: false,
+            jurisdiction: *b"US",
+            creator: None,
+            royalty_bps: 0,
+            royalty_mint: None,
+            burn_to_access: false,
+            max_buyers: None,
+            purchase_delay: 0,
+        };
+
+        // `mint_and_list` only mints once the listing step has succeeded
+        assert_eq!(
+            target
+                .initialize(target.seller, 2, target.mint, params, 255)
+                .unwrap_err(),
+            MarketplaceError::InvalidExpiry.into()
+        );
+        assert_eq!(target.listing_id, 1);
+        assert_eq!(target.access_mint, None);
+    }
+
+    #[test]
+    fn burn_to_access_listing_burns_its_price() {
+        let mut target = listing();
+        assert_eq!(
+            target.burn_price(100, false).unwrap_err(),
+            MarketplaceError::BurnToAccessDisabled.into()
+        );
+
+        target.burn_to_access = true;
+        assert_eq!(target.burn_price(100, false).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn reprice_updates_price_nonce_and_expiry_together() {
+        let mut target = listing();
+        target.reprice(1_500, 600, 5_000).unwrap();
+        assert_eq!(
+            (target.price, target.nonce, target.expires_at),
+            (1_500, 1, 5_600)
+        );
+
+        target.active = false;
+        assert_eq!(
+            target.reprice(2_000, 600, 5_000).unwrap_err(),
+            MarketplaceError::ListingInactive.into()
+        );
+        assert_eq!(target.price, 1_500);
+    }
+
+    #[test]
+    fn listing_sold_up_to_buyer_cap() {
+        let mut target = listing();
+        target.max_buyers = Some(2);
+
+        target.record_buyer().unwrap();
+        target.record_buyer().unwrap();
+        assert_eq!(
+            target.record_buyer().unwrap_err(),
+            MarketplaceError::BuyerLimitReached.into()
+        );
+        assert_eq!(target.buyer_count, 2);
+
+        target.max_buyers = None;
+        target.record_buyer().unwrap();
+    }
+
+    #[test]
+    fn locked_listing_uri_cannot_change() {
+        let mut target = listing();
+        target.set_data_uri("ipfs://updated".to_string()).unwrap();
+        assert_eq!(target.data_uri, "ipfs://updated");
+
+        target.metadata_locked = true;
+        assert_eq!(
+            target
+                .set_data_uri("ipfs://swapped".to_string())
+                .unwrap_err(),
+            MarketplaceError::MetadataLocked.into()
+        );
+        assert_eq!(target.data_uri, "ipfs://updated");
+        target.reprice(200, 100, 0).unwrap();
+        assert_eq!(target.price, 200);
+    }
+
+    #[test]
+    fn purchase_rejected_until_delay_passes() {
+        let mut target = listing();
+        target.created_at = 100;
+        target.purchase_delay = 60;
+        assert_eq!(
+            target.assert_purchasable(159).unwrap_err(),
+            MarketplaceError::ListingNotYetPurchasable.into()
+        );
+        target.assert_purchasable(160).unwrap();
+    }
+
+    #[test]
+    fn secondary_transfer_pays_creator_unless_not_transferable() {
+        let mut target = listing();
+        target.creator = Pubkey::new_unique();
+        target.royalty_bps = 500;
+        target.secondary_price = 2_000;
+        let (holder, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            target
+                .grant_transfer_royalty(holder, to, RoundingMode::Floor)
+                .unwrap_err(),
+            MarketplaceError::GrantNotTransferable.into()
+        );
+
+        target.grants_transferable = true;
+        assert_eq!(
+            target
+                .grant_transfer_royalty(holder, to, RoundingMode::Floor)
+                .unwrap(),
+            100
+        );
+        assert_eq!(
+            target
+                .grant_transfer_royalty(target.creator, to, RoundingMode::Floor)
+                .unwrap(),
+            0
+        );
+
+        target.secondary_price = 0;
+        assert_eq!(
+            target
+                .grant_transfer_royalty(holder, to, RoundingMode::Floor)
+                .unwrap(),
+            50
+        );
+    }
+
+    /// Run `handler` on `listing` updated by its seller, with the
+    /// protocol-wide pause set or not, after validating the `UpdateListing`
+    /// accounts
+    fn update_listing(
+        listing: &Listing,
+        global_paused: bool,
+        handler: impl FnOnce(Context<UpdateListing>) -> Result<()>,
+    ) -> Result<()> {
+        let program_id = crate::ID;
+        let (listing_key, bump) = Pubkey::find_program_address(
+            &[b"listing", listing.listing_id.to_le_bytes().as_ref()],
+            &program_id,
+        );
+        let (global_config_key, _) =
+            Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &mcp_token::ID);
+        let mut listing = listing.clone();
+        listing.bump = bump;
+        let mut listing_data = Vec::new();
+        listing.try_serialize(&mut listing_data)?;
+        let mut config_data = global_config_data(global_paused);
+        let (mut seller_lamports, mut config_lamports, mut listing_lamports) = (0, 0, 0);
+        let mut seller_data = [];
+        let system_program = System::id();
+        let accounts = [
+            AccountInfo::new(
+                &listing.seller,
+                true,
+                false,
+                &mut seller_lamports,
+                &mut seller_data,
+                &system_program,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &global_config_key,
+                false,
+                false,
+                &mut config_lamports,
+                &mut config_data,
+                &mcp_token::ID,
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &listing_key,
+                false,
+                true,
+                &mut listing_lamports,
+                &mut listing_data,
+                &program_id,
+                false,
+                0,
+            ),
+        ];
+
+        let mut bumps = BTreeMap::new();
+        let mut update_accounts = UpdateListing::try_accounts(
+            &program_id,
+            &mut &accounts[..],
+            &listing.listing_id.to_le_bytes(),
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        handler(Context::new(&program_id, &mut update_accounts, &[], bumps))
+    }
+
+    #[test]
+    fn accepted_mints_over_bound_rejected() {
+        let listing = listing();
+        let id = listing.listing_id;
+        let mints = |count: usize| (0..count).map(|_| Pubkey::new_unique()).collect();
+
+        update_listing(&listing, false, |ctx| {
+            set_accepted_mints(ctx, id, mints(MAX_ACCEPTED_MINTS))
+        })
+        .unwrap();
+        assert_eq!(
+            update_listing(&listing, false, |ctx| {
+                set_accepted_mints(ctx, id, mints(MAX_ACCEPTED_MINTS + 1))
+            })
+            .unwrap_err(),
+            MarketplaceError::TooManyEntries.into()
+        );
+    }
+
+    #[test]
+    fn global_pause_blocks_listing_updates() {
+        let listing = listing();
+        let id = listing.listing_id;
+
+        update_listing(&listing, false, |ctx| lock_metadata(ctx, id)).unwrap();
+        assert_eq!(
+            update_listing(&listing, true, |ctx| lock_metadata(ctx, id)).unwrap_err(),
+            MarketplaceError::ProtocolPaused.into()
+        );
+        assert_eq!(
+            update_listing(&listing, true, |ctx| reprice(ctx, id, 500, 60)).unwrap_err(),
+            MarketplaceError::ProtocolPaused.into()
+        );
+    }
+
+    #[test]
+    fn listing_in_escrow_not_auctionable() {
+        let mut listing = listing();
+        listing.assert_auctionable(1_000).unwrap();
+
+        listing.open_escrows = 1;
+        assert_eq!(
+            listing.assert_auctionable(1_000).unwrap_err(),
+            MarketplaceError::ListingHasOpenEscrows.into()
+        );
+        listing.open_escrows = 0;
+        listing.royalty_mint = Some(Pubkey::new_unique());
+        assert_eq!(
+            listing.assert_auctionable(1_000).unwrap_err(),
+            MarketplaceError::InvalidRoyaltyAccounts.into()
+        );
+        listing.royalty_mint = None;
+        assert!(listing.assert_auctionable(listing.expires_at + 1).is_err());
+    }
+}
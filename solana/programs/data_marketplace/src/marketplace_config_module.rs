@@ -0,0 +1,914 @@
+//! marketplace_config_module module for data marketplace
+//!
+//! This module provides the global configuration account for the data marketplace.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::utils_module::{
+    assert_not_globally_paused, get_bump, mul_div, mul_div_rounded, validate_bounded_vec,
+    RoundingMode, BPS_DENOMINATOR, MAX_TIERS,
+};
+
+/// Maximum number of allowed data URI schemes
+pub const MAX_URI_SCHEMES: usize = 8;
+
+/// Maximum length of an allowed data URI scheme, including `://`
+pub const MAX_URI_SCHEME_LEN: usize = 16;
+
+/// Length of the period the escrow holding fee accrues per
+pub const HOLDING_FEE_PERIOD_SECONDS: i64 = 24 * 60 * 60;
+
+/// A protocol fee discount for buyers staking at least `min_stake`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FeeDiscountTier {
+    /// Minimum staked MCP tokens to qualify
+    pub min_stake: u64,
+
+    /// Reduction of the protocol fee in basis points
+    pub discount_bps: u16,
+}
+
+/// A protocol fee rebate for sellers with at least `min_sales` completed sales
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SellerRebateTier {
+    /// Minimum `SellerProfile::total_sales` to qualify
+    pub min_sales: u64,
+
+    /// Reduction of the protocol fee in basis points
+    pub rebate_bps: u16,
+}
+
+/// Party bearing the protocol fee of a purchase
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FeePayer {
+    /// The buyer pays `price + fee`; the seller receives the price
+    Buyer,
+    /// The buyer pays `price`; the seller receives `price - fee`
+    Seller,
+    /// The buyer pays `price + fee / 2`, rounded down; the seller bears the rest
+    Split,
+}
+
+impl FeePayer {
+    /// Part of `fee` charged to the buyer on top of the price
+    pub fn buyer_share(&self, fee: u64) -> u64 {
+        match self {
+            FeePayer::Buyer => fee,
+            FeePayer::Seller => 0,
+            FeePayer::Split => fee / 2,
+        }
+    }
+}
+
+/// MarketplaceConfig state account
+#[account]
+pub struct MarketplaceConfig {
+    /// The admin that can update this account
+    pub admin: Pubkey,
+
+    /// Token account receiving protocol fees
+    pub treasury: Pubkey,
+
+    /// Owner of `treasury`; fees in other mints go to its token accounts in those mints
+    pub treasury_owner: Pubkey,
+
+    /// Protocol fee in basis points
+    pub protocol_fee_bps: u16,
+
+    /// Share of the sale price paid to a referrer out of the protocol fee, in basis points
+    pub referral_bps: u16,
+
+    /// Collateral a seller locks when listing with collateral
+    pub listing_collateral: u64,
+
+    /// Seconds an escrow must be unsettled before the admin may force resolve it
+    pub stuck_timeout: i64,
+
+    /// Once set, no configuration can be changed again
+    pub config_frozen: bool,
+
+    /// Tokens per credit, scaled by `CREDIT_RATE_PRECISION`; zero disables credits
+    pub credit_rate: u64,
+
+    /// Balances below this, in base units, are dust that `sweep_dust` may collect
+    pub dust_threshold: u64,
+
+    /// Kill switch for listing creation; settlement of existing escrows is unaffected
+    pub accepting_new_listings: bool,
+
+    /// Most escrows a buyer may have open at once; zero disables the cap
+    pub max_open_escrows: u32,
+
+    /// Bids placed this close to an auction's end extend it by the same amount
+    pub anti_snipe_window: i64,
+
+    /// MCP stake pool whose stakes qualify for fee discounts
+    pub discount_stake_pool: Pubkey,
+
+    /// Staker fee discount tiers
+    pub fee_discount_tiers: Vec<FeeDiscountTier>,
+
+    /// Prefixes a listing data URI must start with, such as `ipfs://`
+    pub allowed_uri_schemes: Vec<String>,
+
+    /// High-volume seller fee rebate tiers
+    pub seller_rebate_tiers: Vec<SellerRebateTier>,
+
+    /// Rounding of protocol fees and royalties. With `Floor` the remainder of a
+    /// split stays with the seller; with `Ceil` it goes to the treasury or the
+    /// creator, at the seller's expense.
+    pub rounding_mode: RoundingMode,
+
+    /// Keeper allowed to update `congestion_multiplier_bps`
+    pub congestion_keeper: Pubkey,
+
+    /// Scale applied to the protocol fee during network congestion, in basis
+    /// points; `BPS_DENOMINATOR` leaves the fee unchanged
+    pub congestion_multiplier_bps: u16,
+
+    /// Lowest protocol fee the congestion multiplier can produce
+    pub min_protocol_fee_bps: u16,
+
+    /// Highest protocol fee the congestion multiplier can produce
+    pub max_protocol_fee_bps: u16,
+
+    /// Party bearing the protocol fee
+    pub fee_payer: FeePayer,
+
+    /// Holding fee charged per full day an escrow stays open, in basis points
+    /// of the released amount; zero disables the fee
+    pub holding_fee_bps_per_day: u16,
+
+    /// Highest holding fee, in basis points of the released amount
+    pub max_holding_fee_bps: u16,
+
+    /// Whether an escrow force resolved in the buyer's favour refunds the
+    /// unreleased protocol fee too, rather than paying it to the treasury
+    pub refund_fee_on_dispute: bool,
+
+    /// Previous marketplace program version listings are imported from;
+    /// the default key disables imports
+    pub migration_source: Pubkey,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl MarketplaceConfig {
+    pub const LEN: usize = 32
+        + 32
+        + 32
+        + 2
+        + 2
+        + 8
+        + 8
+        + 1
+        + 8
+        + 8
+        + 1
+        + 4
+        + 8
+        + 32
+        + (4 + MAX_TIERS * (8 + 2))
+        + (4 + MAX_URI_SCHEMES * (4 + MAX_URI_SCHEME_LEN))
+        + (4 + MAX_TIERS * (8 + 2))
+        + 1
+        + 32
+        + 2
+        + 2
+        + 2
+        + 1
+        + 1
+        + 2
+        + 2
+        + 1
+        + 32
+        + 1;
+
+    /// Protocol fee after the congestion multiplier, clamped to the configured bounds
+    pub fn effective_protocol_fee_bps(&self) -> u16 {
+        let scaled =
+            self.protocol_fee_bps as u64 * self.congestion_multiplier_bps as u64 / BPS_DENOMINATOR;
+        scaled.clamp(
+            self.min_protocol_fee_bps as u64,
+            self.max_protocol_fee_bps as u64,
+        ) as u16
+    }
+
+    /// Protocol fee charged to a buyer with `staked` tokens in the discount pool.
+    ///
+    /// The largest discount among the tiers the buyer qualifies for applies.
+    pub fn protocol_fee_bps_for_stake(&self, staked: u64) -> u16 {
+        let discount = self
+            .fee_discount_tiers
+            .iter()
+            .filter(|tier| staked >= tier.min_stake)
+            .map(|tier| tier.discount_bps)
+            .max()
+            .unwrap_or(0);
+        self.effective_protocol_fee_bps().saturating_sub(discount)
+    }
+
+    /// Protocol fee rebate for a seller with `total_sales` completed sales.
+    ///
+    /// The largest rebate among the tiers the seller qualifies for applies.
+    pub fn seller_rebate_bps(&self, total_sales: u64) -> u16 {
+        self.seller_rebate_tiers
+            .iter()
+            .filter(|tier| total_sales >= tier.min_sales)
+            .map(|tier| tier.rebate_bps)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Share of `amount` given by `bps`, rounded by the configured mode
+    pub fn bps_of(&self, amount: u64, bps: u16) -> Result<u64> {
+        mul_div_rounded(amount, bps as u64, BPS_DENOMINATOR, self.rounding_mode)
+    }
+
+    /// Whether a non-empty protocol account holding `amount` is dust
+    pub fn is_dust(&self, amount: u64) -> bool {
+        amount > 0 && amount < self.dust_threshold
+    }
+
+    /// Referral reward set aside out of `protocol_fee` for `referrer` on a
+    /// purchase at `price`, rejecting buyers referring themselves
+    pub fn referral_fee(
+        &self,
+        price: u64,
+        protocol_fee: u64,
+        referrer: Option<Pubkey>,
+        buyer: &Pubkey,
+    ) -> Result<u64> {
+        let Some(referrer) = referrer else {
+            return Ok(0);
+        };
+        require_keys_neq!(referrer, *buyer, MarketplaceError::SelfReferral);
+
+        Ok(self.bps_of(price, self.referral_bps)?.min(protocol_fee))
+    }
+
+    /// Holding fee on `amount` released from an escrow opened `held_seconds` ago.
+    ///
+    /// Accrues `holding_fee_bps_per_day` for each full day, capped at
+    /// `max_holding_fee_bps`, and rounds down.
+    pub fn holding_fee(&self, amount: u64, held_seconds: i64) -> Result<u64> {
+        let days = (held_seconds.max(0) / HOLDING_FEE_PERIOD_SECONDS) as u64;
+        let fee_bps = (self.holding_fee_bps_per_day as u64)
+            .checked_mul(days)
+            .ok_or(MarketplaceError::MathOverflow)?
+            .min(self.max_holding_fee_bps as u64);
+        mul_div(amount, fee_bps, BPS_DENOMINATOR)
+    }
+
+    /// Reject a data URI that does not start with one of the allowed schemes
+    pub fn assert_allowed_uri(&self, data_uri: &str) -> Result<()> {
+        require!(
+            self.allowed_uri_schemes
+                .iter()
+                .any(|scheme| data_uri.starts_with(scheme.as_str())),
+            MarketplaceError::UnsupportedUriScheme
+        );
+
+        Ok(())
+    }
+}
+
+/// Initialize the MarketplaceConfig
+pub fn initialize_marketplace_config(
+    ctx: Context<InitializeMarketplaceConfig>,
+    protocol_fee_bps: u16,
+    stuck_timeout: i64,
+) -> Result<()> {
+    require!(
+        protocol_fee_bps as u64 <= BPS_DENOMINATOR,
+        MarketplaceError::InvalidFeeBps
+    );
+    require!(stuck_timeout > 0, MarketplaceError::InvalidStuckTimeout);
+
+    let config = &mut ctx.accounts.marketplace_config;
+    config.admin = ctx.accounts.admin.key();
+    config.treasury = ctx.accounts.treasury.key();
+    config.treasury_owner = ctx.accounts.treasury.owner;
+    config.protocol_fee_bps = protocol_fee_bps;
+    config.referral_bps = 0;
+    config.listing_collateral = 0;
+    config.stuck_timeout = stuck_timeout;
+    config.config_frozen = false;
+    config.credit_rate = 0;
+    config.dust_threshold = 0;
+    config.accepting_new_listings = true;
+    config.max_open_escrows = 0;
+    config.anti_snipe_window = 0;
+    config.discount_stake_pool = Pubkey::default();
+    config.fee_discount_tiers = Vec::new();
+    config.allowed_uri_schemes = ["ipfs://", "ar://", "https://"]
+        .iter()
+        .map(|scheme| scheme.to_string())
+        .collect();
+    config.seller_rebate_tiers = Vec::new();
+    config.rounding_mode = RoundingMode::Floor;
+    config.congestion_keeper = Pubkey::default();
+    config.congestion_multiplier_bps = BPS_DENOMINATOR as u16;
+    config.min_protocol_fee_bps = 0;
+    config.max_protocol_fee_bps = BPS_DENOMINATOR as u16;
+    config.fee_payer = FeePayer::Seller;
+    config.holding_fee_bps_per_day = 0;
+    config.max_holding_fee_bps = 0;
+    config.refund_fee_on_dispute = true;
+    config.migration_source = Pubkey::default();
+    config.bump = get_bump(&ctx.bumps, "marketplace_config")?;
+
+    Ok(())
+}
+
+/// Update the protocol fee
+pub fn set_protocol_fee(
+    ctx: Context<UpdateMarketplaceConfig>,
+    protocol_fee_bps: u16,
+) -> Result<()> {
+    let config = &mut ctx.accounts.marketplace_config;
+    require!(
+        protocol_fee_bps as u64 <= BPS_DENOMINATOR && protocol_fee_bps >= config.referral_bps,
+        MarketplaceError::InvalidFeeBps
+    );
+    require!(
+        config
+            .fee_discount_tiers
+            .iter()
+            .all(|tier| tier.discount_bps <= protocol_fee_bps),
+        MarketplaceError::InvalidFeeBps
+    );
+    require!(
+        config
+            .seller_rebate_tiers
+            .iter()
+            .all(|tier| tier.rebate_bps <= protocol_fee_bps),
+        MarketplaceError::InvalidFeeBps
+    );
+    config.protocol_fee_bps = protocol_fee_bps;
+
+    Ok(())
+}
+
+/// Update the collateral required by `create_listing_with_collateral`
+pub fn set_listing_collateral(
+    ctx: Context<UpdateMarketplaceConfig>,
+    listing_collateral: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.marketplace_config;
+    config.listing_collateral = listing_collateral;
+
+    Ok(())
+}
+
+/// Update the referral reward.
+///
+/// Referrals are paid out of the protocol fee, so the reward can never exceed it.
+pub fn set_referral_bps(ctx: Context<UpdateMarketplaceConfig>, referral_bps: u16) -> Result<()> {
+    let config = &mut ctx.accounts.marketplace_config;
+    require!(
+        referral_bps <= config.protocol_fee_bps,
+        MarketplaceError::InvalidFeeBps
+    );
+    config.referral_bps = referral_bps;
+
+    Ok(())
+}
+
+/// Update the rate credits are bought and redeemed at.
+///
+/// Existing balances redeem at the new rate; the admin is expected to be the
+/// governance authority.
+pub fn set_credit_rate(ctx: Context<UpdateMarketplaceConfig>, credit_rate: u64) -> Result<()> {
+    let config = &mut ctx.accounts.marketplace_config;
+    config.credit_rate = credit_rate;
+
+    Ok(())
+}
+
+/// Update the balance below which protocol accounts may be swept
+pub fn set_dust_threshold(
+    ctx: Context<UpdateMarketplaceConfig>,
+    dust_threshold: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.marketplace_config;
+    config.dust_threshold = dust_threshold;
+
+    Ok(())
+}
+
+/// Turn listing creation on or off, e.g. during a wind-down
+pub fn set_accepting_new_listings(
+    ctx: Context<UpdateMarketplaceConfig>,
+    accepting_new_listings: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.marketplace_config;
+    config.accepting_new_listings = accepting_new_listings;
+
+    Ok(())
+}
+
+/// Update how many escrows a buyer may have open at once
+pub fn set_max_open_escrows(
+    ctx: Context<UpdateMarketplaceConfig>,
+    max_open_escrows: u32,
+) -> Result<()> {
+    let config = &mut ctx.accounts.marketplace_config;
+    config.max_open_escrows = max_open_escrows;
+
+    Ok(())
+}
+
+/// Update the auction anti-snipe window; zero disables extensions
+pub fn set_anti_snipe_window(
+    ctx: Context<UpdateMarketplaceConfig>,
+    anti_snipe_window: i64,
+) -> Result<()> {
+    require!(anti_snipe_window >= 0, MarketplaceError::InvalidExpiry);
+    let config = &mut ctx.accounts.marketplace_config;
+    config.anti_snipe_window = anti_snipe_window;
+
+    Ok(())
+}
+
+/// Replace the staker fee discount tiers.
+///
+/// Stakes in `stake_pool` qualify; no discount may exceed the protocol fee.
+pub fn set_fee_discount_tiers(
+    ctx: Context<UpdateMarketplaceConfig>,
+    stake_pool: Pubkey,
+    tiers: Vec<FeeDiscountTier>,
+) -> Result<()> {
+    validate_bounded_vec(&tiers, MAX_TIERS)?;
+    let config = &mut ctx.accounts.marketplace_config;
+    require!(
+        tiers
+            .iter()
+            .all(|tier| tier.discount_bps <= config.protocol_fee_bps),
+        MarketplaceError::InvalidFeeBps
+    );
+    config.discount_stake_pool = stake_pool;
+    config.fee_discount_tiers = tiers;
+
+    Ok(())
+}
+
+/// Replace the high-volume seller fee rebate tiers.
+///
+/// No rebate may exceed the protocol fee.
+pub fn set_seller_rebate_tiers(
+    ctx: Context<UpdateMarketplaceConfig>,
+    tiers: Vec<SellerRebateTier>,
+) -> Result<()> {
+    validate_bounded_vec(&tiers, MAX_TIERS)?;
+    let config = &mut ctx.accounts.marketplace_config;
+    require!(
+        tiers
+            .iter()
+            .all(|tier| tier.rebate_bps <= config.protocol_fee_bps),
+        MarketplaceError::InvalidFeeBps
+    );
+    config.seller_rebate_tiers = tiers;
+
+    Ok(())
+}
+
+/// Choose how protocol fees and royalties round
+pub fn set_rounding_mode(
+    ctx: Context<UpdateMarketplaceConfig>,
+    rounding_mode: RoundingMode,
+) -> Result<()> {
+    ctx.accounts.marketplace_config.rounding_mode = rounding_mode;
+
+    Ok(())
+}
+
+/// Choose which party bears the protocol fee of new purchases
+pub fn set_fee_payer(ctx: Context<UpdateMarketplaceConfig>, fee_payer: FeePayer) -> Result<()> {
+    ctx.accounts.marketplace_config.fee_payer = fee_payer;
+
+    Ok(())
+}
+
+/// Set the daily escrow holding fee and its cap
+pub fn set_holding_fee(
+    ctx: Context<UpdateMarketplaceConfig>,
+    holding_fee_bps_per_day: u16,
+    max_holding_fee_bps: u16,
+) -> Result<()> {
+    require!(
+        holding_fee_bps_per_day <= max_holding_fee_bps
+            && max_holding_fee_bps as u64 <= BPS_DENOMINATOR,
+        MarketplaceError::InvalidFeeBps
+    );
+    let config = &mut ctx.accounts.marketplace_config;
+    config.holding_fee_bps_per_day = holding_fee_bps_per_day;
+    config.max_holding_fee_bps = max_holding_fee_bps;
+
+    Ok(())
+}
+
+/// Choose whether buyer-favourable dispute resolutions refund the protocol fee
+pub fn set_refund_fee_on_dispute(
+    ctx: Context<UpdateMarketplaceConfig>,
+    refund_fee_on_dispute: bool,
+) -> Result<()> {
+    ctx.accounts.marketplace_config.refund_fee_on_dispute = refund_fee_on_dispute;
+
+    Ok(())
+}
+
+/// Set the previous program version listings are imported from
+pub fn set_migration_source(
+    ctx: Context<UpdateMarketplaceConfig>,
+    migration_source: Pubkey,
+) -> Result<()> {
+    ctx.accounts.marketplace_config.migration_source = migration_source;
+
+    Ok(())
+}
+
+/// Set the keeper reporting network congestion and the bounds the scaled
+/// protocol fee is clamped to
+pub fn set_congestion_fee_bounds(
+    ctx: Context<UpdateMarketplaceConfig>,
+    congestion_keeper: Pubkey,
+    min_protocol_fee_bps: u16,
+    max_protocol_fee_bps: u16,
+) -> Result<()> {
+    require!(
+        min_protocol_fee_bps <= max_protocol_fee_bps
+            && max_protocol_fee_bps as u64 <= BPS_DENOMINATOR,
+        MarketplaceError::InvalidFeeBps
+    );
+    let config = &mut ctx.accounts.marketplace_config;
+    config.congestion_keeper = congestion_keeper;
+    config.min_protocol_fee_bps = min_protocol_fee_bps;
+    config.max_protocol_fee_bps = max_protocol_fee_bps;
+
+    Ok(())
+}
+
+/// Report the current congestion multiplier.
+///
+/// Only the congestion keeper may call this. It keeps working once the
+/// configuration is frozen, since the frozen bounds still clamp the fee.
+pub fn update_congestion_multiplier(
+    ctx: Context<UpdateCongestionMultiplier>,
+    congestion_multiplier_bps: u16,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts.marketplace_config.congestion_multiplier_bps = congestion_multiplier_bps;
+
+    Ok(())
+}
+
+/// Replace the schemes listing data URIs may use
+pub fn set_allowed_uri_schemes(
+    ctx: Context<UpdateMarketplaceConfig>,
+    schemes: Vec<String>,
+) -> Result<()> {
+    validate_bounded_vec(&schemes, MAX_URI_SCHEMES)?;
+    require!(
+        schemes
+            .iter()
+            .all(|scheme| !scheme.is_empty() && scheme.len() <= MAX_URI_SCHEME_LEN),
+        MarketplaceError::UnsupportedUriScheme
+    );
+    ctx.accounts.marketplace_config.allowed_uri_schemes = schemes;
+
+    Ok(())
+}
+
+/// Permanently freeze the configuration.
+///
+/// The admin is expected to be the governance authority; after this call every
+/// setter fails with `ConfigFrozen`, letting deployments commit to fixed
+/// parameters.
+pub fn freeze_config(ctx: Context<UpdateMarketplaceConfig>) -> Result<()> {
+    ctx.accounts.marketplace_config.config_frozen = true;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeMarketplaceConfig<'info> {
+    /// The admin of the marketplace
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + MarketplaceConfig::LEN,
+        seeds = [b"marketplace-config".as_ref()],
+        bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Token account receiving protocol fees
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateMarketplaceConfig<'info> {
+    /// The admin of the marketplace
+    pub admin: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        has_one = admin,
+        constraint = !marketplace_config.config_frozen @ MarketplaceError::ConfigFrozen,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+}
+
+/// Account validation for congestion updates
+#[derive(Accounts)]
+pub struct UpdateCongestionMultiplier<'info> {
+    /// The congestion keeper
+    pub keeper: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        constraint = marketplace_config.congestion_keeper == keeper.key()
+            @ MarketplaceError::NotCongestionKeeper,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::utils_module::tests::TestAccount;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// A configuration with a 5% protocol fee borne by the seller
+    pub(crate) fn marketplace_config() -> MarketplaceConfig {
+        MarketplaceConfig {
+            admin: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            treasury_owner: Pubkey::new_unique(),
+            protocol_fee_bps: 500,
+            referral_bps: 0,
+            listing_collateral: 0,
+            stuck_timeout: 86_400,
+            config_frozen: false,
+            credit_rate: 0,
+            dust_threshold: 0,
+            accepting_new_listings: true,
+            max_open_escrows: 0,
+            anti_snipe_window: 0,
+            discount_stake_pool: Pubkey::default(),
+            fee_discount_tiers: Vec::new(),
+            allowed_uri_schemes: vec!["ipfs://".to_string()],
+            seller_rebate_tiers: Vec::new(),
+            rounding_mode: RoundingMode::Floor,
+            congestion_keeper: Pubkey::default(),
+            congestion_multiplier_bps: BPS_DENOMINATOR as u16,
+            min_protocol_fee_bps: 0,
+            max_protocol_fee_bps: BPS_DENOMINATOR as u16,
+            fee_payer: FeePayer::Seller,
+            holding_fee_bps_per_day: 0,
+            max_holding_fee_bps: 0,
+            refund_fee_on_dispute: true,
+            migration_source: Pubkey::default(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn referred_purchase_sets_aside_referral_fee() {
+        let mut config = marketplace_config();
+        config.referral_bps = 200;
+        let buyer = Pubkey::new_unique();
+
+        assert_eq!(
+            config
+                .referral_fee(1_000, 50, Some(Pubkey::new_unique()), &buyer)
+                .unwrap(),
+            20
+        );
+        assert_eq!(config.referral_fee(1_000, 50, None, &buyer).unwrap(), 0);
+    }
+
+    #[test]
+    fn self_referral_rejected() {
+        let config = marketplace_config();
+        let buyer = Pubkey::new_unique();
+
+        assert_eq!(
+            config
+                .referral_fee(1_000, 50, Some(buyer), &buyer)
+                .unwrap_err(),
+            MarketplaceError::SelfReferral.into()
+        );
+    }
+
+    #[test]
+    fn setters_rejected_once_config_frozen() {
+        let program_id = crate::ID;
+        let admin = Pubkey::new_unique();
+        let (config_key, bump) =
+            Pubkey::find_program_address(&[b"marketplace-config"], &program_id);
+
+        for frozen in [false, true] {
+            let mut config = marketplace_config();
+            config.admin = admin;
+            config.bump = bump;
+            config.config_frozen = frozen;
+            let mut data = Vec::new();
+            config.try_serialize(&mut data).unwrap();
+            let (mut admin_lamports, mut config_lamports) = (0, 0);
+            let mut admin_data = [];
+            let system_program = System::id();
+            let accounts = [
+                AccountInfo::new(
+                    &admin,
+                    true,
+                    false,
+                    &mut admin_lamports,
+                    &mut admin_data,
+                    &system_program,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &config_key,
+                    false,
+                    true,
+                    &mut config_lamports,
+                    &mut data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+            ];
+
+            let result = UpdateMarketplaceConfig::try_accounts(
+                &program_id,
+                &mut &accounts[..],
+                &[],
+                &mut BTreeMap::new(),
+                &mut BTreeSet::new(),
+            );
+            if frozen {
+                assert_eq!(result.err(), Some(MarketplaceError::ConfigFrozen.into()));
+            } else {
+                assert!(result.is_ok());
+            }
+        }
+    }
+
+    /// Run `handler` on the default configuration updated by its admin, after
+    /// validating the `UpdateMarketplaceConfig` accounts
+    fn update_config(
+        handler: impl FnOnce(Context<UpdateMarketplaceConfig>) -> Result<()>,
+    ) -> Result<()> {
+        let program_id = crate::ID;
+        let (config_key, bump) =
+            Pubkey::find_program_address(&[b"marketplace-config"], &program_id);
+        let config = MarketplaceConfig {
+            bump,
+            ..marketplace_config()
+        };
+        let mut accounts = [
+            TestAccount::wallet(config.admin).signer(),
+            TestAccount::state(config_key, &config, 8 + MarketplaceConfig::LEN).writable(),
+        ];
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let mut bumps = BTreeMap::new();
+        let mut update_accounts = UpdateMarketplaceConfig::try_accounts(
+            &program_id,
+            &mut &infos[..],
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        handler(Context::new(&program_id, &mut update_accounts, &[], bumps))
+    }
+
+    #[test]
+    fn config_vectors_over_bound_rejected() {
+        let discount_tiers = |count: usize| {
+            (0..count)
+                .map(|_| FeeDiscountTier {
+                    min_stake: 1,
+                    discount_bps: 100,
+                })
+                .collect()
+        };
+        let rebate_tiers = |count: usize| {
+            (0..count)
+                .map(|_| SellerRebateTier {
+                    min_sales: 1,
+                    rebate_bps: 100,
+                })
+                .collect()
+        };
+        let schemes = |count: usize| vec!["ipfs://".to_string(); count];
+        let stake_pool = Pubkey::new_unique();
+
+        update_config(|ctx| set_fee_discount_tiers(ctx, stake_pool, discount_tiers(MAX_TIERS)))
+            .unwrap();
+        update_config(|ctx| set_seller_rebate_tiers(ctx, rebate_tiers(MAX_TIERS))).unwrap();
+        update_config(|ctx| set_allowed_uri_schemes(ctx, schemes(MAX_URI_SCHEMES))).unwrap();
+        for result in [
+            update_config(|ctx| {
+                set_fee_discount_tiers(ctx, stake_pool, discount_tiers(MAX_TIERS + 1))
+            }),
+            update_config(|ctx| set_seller_rebate_tiers(ctx, rebate_tiers(MAX_TIERS + 1))),
+            update_config(|ctx| set_allowed_uri_schemes(ctx, schemes(MAX_URI_SCHEMES + 1))),
+        ] {
+            assert_eq!(result.unwrap_err(), MarketplaceError::TooManyEntries.into());
+        }
+    }
+
+    #[test]
+    fn only_balances_below_dust_threshold_swept() {
+        let mut config = marketplace_config();
+        config.dust_threshold = 10;
+
+        assert!(config.is_dust(9));
+        assert!(!config.is_dust(10));
+        assert!(!config.is_dust(11));
+        assert!(!config.is_dust(0));
+    }
+
+    #[test]
+    fn ipfs_uri_accepted_and_file_uri_rejected() {
+        let config = marketplace_config();
+
+        config.assert_allowed_uri("ipfs://bafy").unwrap();
+        assert_eq!(
+            config.assert_allowed_uri("file:///etc/passwd").unwrap_err(),
+            MarketplaceError::UnsupportedUriScheme.into()
+        );
+    }
+
+    #[test]
+    fn each_rounding_mode_places_split_remainder() {
+        let mut config = marketplace_config();
+        // 2.5% of 1_001 is 25.025 and of 1_020 is 25.5
+        for (mode, expected) in [
+            (RoundingMode::Floor, [25, 25]),
+            (RoundingMode::Ceil, [26, 26]),
+            (RoundingMode::Round, [25, 26]),
+        ] {
+            config.rounding_mode = mode;
+            assert_eq!(config.bps_of(1_001, 250).unwrap(), expected[0]);
+            assert_eq!(config.bps_of(1_020, 250).unwrap(), expected[1]);
+        }
+    }
+
+    #[test]
+    fn effective_fee_tracks_multiplier_within_clamps() {
+        let mut config = marketplace_config();
+        config.min_protocol_fee_bps = 300;
+        config.max_protocol_fee_bps = 1_000;
+        for (multiplier_bps, expected) in
+            [(10_000, 500), (15_000, 750), (5_000, 300), (30_000, 1_000)]
+        {
+            config.congestion_multiplier_bps = multiplier_bps;
+            assert_eq!(config.effective_protocol_fee_bps(), expected);
+        }
+    }
+
+    #[test]
+    fn longer_held_escrow_pays_larger_capped_fee() {
+        let mut config = marketplace_config();
+        config.holding_fee_bps_per_day = 10;
+        config.max_holding_fee_bps = 50;
+        let day = HOLDING_FEE_PERIOD_SECONDS;
+
+        assert_eq!(config.holding_fee(10_000, day - 1).unwrap(), 0);
+        assert_eq!(config.holding_fee(10_000, day).unwrap(), 10);
+        assert_eq!(config.holding_fee(10_000, 3 * day).unwrap(), 30);
+        assert_eq!(config.holding_fee(10_000, 30 * day).unwrap(), 50);
+    }
+}
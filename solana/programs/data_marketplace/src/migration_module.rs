@@ -0,0 +1,524 @@
+//! migration_module module for data marketplace
+//!
+//! This module provides moving listings between marketplace program versions.
+//! The seller exports the listing from the old program, which deactivates it
+//! and stores a versioned `ListingSnapshot` in a `ListingExport` account, also
+//! returned as return data. The new program then reads that account itself,
+//! from the old program id set as the config's `migration_source`, and
+//! re-creates the listing with its original creation time and royalty terms.
+//! Nothing the caller passes in is trusted, and the snapshot layout does not
+//! depend on the `Listing` layout of either version.
+//!
+//! Imported listings arrive inactive; the seller reopens them with `relist`.
+//! Collateral, open escrows and dataset shares do not move: listings with
+//! open escrows or shares cannot be exported, and collateral is reclaimed from
+//! the program that holds it.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::listing_module::{DeactivationReason, Listing, MAX_ACCEPTED_MINTS, MAX_DATA_URI_LEN};
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::stats_module::MarketplaceStats;
+use crate::utils_module::{
+    assert_not_globally_paused, get_bump, load_foreign_account, validate_bounded_vec,
+    BPS_DENOMINATOR,
+};
+use crate::versioning_module::Versioned;
+
+/// Seed prefix of the `ListingExport` account of a listing
+pub const LISTING_EXPORT_SEED: &[u8] = b"listing-export";
+
+/// Portable copy of a Listing, independent of the program version
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ListingSnapshot {
+    /// Unique listing identifier
+    pub listing_id: u64,
+
+    /// The seller that owns the listing
+    pub seller: Pubkey,
+
+    /// Mint the listing is priced in
+    pub mint: Pubkey,
+
+    /// Price in base units of `mint`
+    pub price: u64,
+
+    /// Whether the dataset contains personal data
+    pub contains_pii: bool,
+
+    /// ISO 3166-1 alpha-2 country code governing the dataset
+    pub jurisdiction: [u8; 2],
+
+    /// Location of the dataset
+    pub data_uri: String,
+
+    /// Whether the listing can be purchased
+    pub active: bool,
+
+    /// Original creation time
+    pub created_at: i64,
+
+    /// Time after which the listing can no longer be purchased
+    pub expires_at: i64,
+
+    /// Original creator of the dataset
+    pub creator: Pubkey,
+
+    /// Royalty owed to `creator` on resale, in basis points
+    pub royalty_bps: u16,
+
+    /// Mint the royalty is paid in, when different from `mint`
+    pub royalty_mint: Option<Pubkey>,
+
+    /// Number of distinct users that expressed interest
+    pub interest_count: u64,
+
+    /// Dataset-access token minted alongside the listing, if any
+    pub access_mint: Option<Pubkey>,
+
+    /// Whether access is bought by burning `price` tokens
+    pub burn_to_access: bool,
+
+    /// Price change counter
+    pub nonce: u64,
+
+    /// Most buyers the listing may be sold to; `None` is unlimited
+    pub max_buyers: Option<u32>,
+
+    /// Number of buyers the listing has been sold to
+    pub buyer_count: u32,
+
+    /// Whether the data URI is locked
+    pub metadata_locked: bool,
+
+    /// Mints buyers may pay in
+    pub accepted_mints: Vec<Pubkey>,
+
+    /// Seconds after `created_at` before the listing can be purchased
+    pub purchase_delay: i64,
+
+    /// Whether access grants may be transferred
+    pub grants_transferable: bool,
+
+    /// Price the creator royalty is charged on when a grant is transferred
+    pub secondary_price: u64,
+}
+
+impl ListingSnapshot {
+    pub const LEN: usize = 8
+        + 32
+        + 32
+        + 8
+        + 1
+        + 2
+        + (4 + MAX_DATA_URI_LEN)
+        + 1
+        + 8
+        + 8
+        + 32
+        + 2
+        + 33
+        + 8
+        + 33
+        + 1
+        + 8
+        + 5
+        + 4
+        + 1
+        + (4 + MAX_ACCEPTED_MINTS * 32)
+        + 8
+        + 1
+        + 8;
+
+    /// Check the invariants every listing created by this program upholds
+    pub fn validate(&self, now: i64) -> Result<()> {
+        require!(
+            self.data_uri.len() <= MAX_DATA_URI_LEN,
+            MarketplaceError::DataUriTooLong
+        );
+        require!(
+            self.jurisdiction.iter().all(u8::is_ascii_uppercase),
+            MarketplaceError::InvalidJurisdiction
+        );
+        require!(
+            self.royalty_bps as u64 <= BPS_DENOMINATOR,
+            MarketplaceError::InvalidFeeBps
+        );
+        require!(
+            self.created_at <= now && self.created_at < self.expires_at,
+            MarketplaceError::InvalidSnapshot
+        );
+        require!(self.purchase_delay >= 0, MarketplaceError::InvalidSnapshot);
+        if let Some(max_buyers) = self.max_buyers {
+            require!(
+                self.buyer_count <= max_buyers,
+                MarketplaceError::InvalidSnapshot
+            );
+        }
+        validate_bounded_vec(&self.accepted_mints, MAX_ACCEPTED_MINTS)?;
+
+        Ok(())
+    }
+
+    /// Re-create the listing, inactive and without collateral, open escrows or shares
+    pub fn into_listing(self, bump: u8) -> Listing {
+        Listing {
+            seller: self.seller,
+            listing_id: self.listing_id,
+            mint: self.mint,
+            price: self.price,
+            contains_pii: self.contains_pii,
+            jurisdiction: self.jurisdiction,
+            data_uri: self.data_uri,
+            active: false,
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            collateral: 0,
+            creator: self.creator,
+            royalty_bps: self.royalty_bps,
+            royalty_mint: self.royalty_mint,
+            interest_count: self.interest_count,
+            access_mint: self.access_mint,
+            burn_to_access: self.burn_to_access,
+            nonce: self.nonce,
+            max_buyers: self.max_buyers,
+            buyer_count: self.buyer_count,
+            metadata_locked: self.metadata_locked,
+            open_escrows: 0,
+            accepted_mints: self.accepted_mints,
+            purchase_delay: self.purchase_delay,
+            grants_transferable: self.grants_transferable,
+            secondary_price: self.secondary_price,
+            dataset_shares: None,
+            deactivation_reason: None,
+            bump,
+        }
+    }
+}
+
+impl From<&Listing> for ListingSnapshot {
+    fn from(listing: &Listing) -> Self {
+        Self {
+            listing_id: listing.listing_id,
+            seller: listing.seller,
+            mint: listing.mint,
+            price: listing.price,
+            contains_pii: listing.contains_pii,
+            jurisdiction: listing.jurisdiction,
+            data_uri: listing.data_uri.clone(),
+            active: listing.active,
+            created_at: listing.created_at,
+            expires_at: listing.expires_at,
+            creator: listing.creator,
+            royalty_bps: listing.royalty_bps,
+            royalty_mint: listing.royalty_mint,
+            interest_count: listing.interest_count,
+            access_mint: listing.access_mint,
+            burn_to_access: listing.burn_to_access,
+            nonce: listing.nonce,
+            max_buyers: listing.max_buyers,
+            buyer_count: listing.buyer_count,
+            metadata_locked: listing.metadata_locked,
+            accepted_mints: listing.accepted_mints.clone(),
+            purchase_delay: listing.purchase_delay,
+            grants_transferable: listing.grants_transferable,
+            secondary_price: listing.secondary_price,
+        }
+    }
+}
+
+/// Exported snapshot of a listing, read by the next program version
+#[account]
+pub struct ListingExport {
+    /// The listing as exported, tagged with its snapshot version
+    pub snapshot: Versioned<ListingSnapshot>,
+}
+
+impl ListingExport {
+    pub const LEN: usize = 1 + ListingSnapshot::LEN;
+
+    /// Load the snapshot of `listing_id` exported by the `migration_source` program
+    pub fn load(
+        info: &AccountInfo,
+        migration_source: &Pubkey,
+        listing_id: u64,
+    ) -> Result<ListingSnapshot> {
+        let (address, _) = Pubkey::find_program_address(
+            &[LISTING_EXPORT_SEED, listing_id.to_le_bytes().as_ref()],
+            migration_source,
+        );
+        require_keys_eq!(info.key(), address, MarketplaceError::InvalidSnapshot);
+        require_keys_eq!(
+            *info.owner,
+            *migration_source,
+            MarketplaceError::ListingNotExported
+        );
+        let snapshot = load_foreign_account::<ListingExport>(info, migration_source)?
+            .snapshot
+            .into_v1()?;
+        require!(
+            snapshot.listing_id == listing_id,
+            MarketplaceError::InvalidSnapshot
+        );
+
+        Ok(snapshot)
+    }
+}
+
+/// Deactivate a Listing for import into another program version, returning its snapshot.
+///
+/// Listings with open escrows or dataset shares, converted to an auction,
+/// split or already exported cannot be exported. The snapshot is also stored
+/// in the listing's `ListingExport` account for the next version to read.
+pub fn export_listing(ctx: Context<ExportListing>, _listing_id: u64) -> Result<ListingSnapshot> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let listing = &mut ctx.accounts.listing;
+    listing.assert_exportable()?;
+
+    let snapshot = ListingSnapshot::from(&**listing);
+    if listing.active {
+        ctx.accounts.marketplace_stats.record_listing_closed()?;
+    }
+    listing.deactivate(DeactivationReason::Migrated);
+    ctx.accounts.listing_export.snapshot = Versioned::V1(snapshot.clone());
+
+    Ok(snapshot)
+}
+
+/// Re-create a Listing exported by the previous program version.
+///
+/// The snapshot is loaded from the `ListingExport` account of `listing_id` in
+/// the `migration_source` program, so only exported listings can be imported.
+/// It must satisfy the invariants enforced on listing creation, and its seller
+/// must sign. The creation time and royalty terms are kept, so resale
+/// royalties survive the migration.
+pub fn import_listing(ctx: Context<ImportListing>, listing_id: u64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let migration_source = ctx.accounts.marketplace_config.migration_source;
+    require_keys_neq!(
+        migration_source,
+        Pubkey::default(),
+        MarketplaceError::MigrationSourceNotSet
+    );
+    let snapshot =
+        ListingExport::load(&ctx.accounts.listing_export, &migration_source, listing_id)?;
+    require_keys_eq!(
+        snapshot.seller,
+        ctx.accounts.seller.key(),
+        MarketplaceError::InvalidSnapshot
+    );
+    snapshot.validate(Clock::get()?.unix_timestamp)?;
+    ctx.accounts
+        .marketplace_config
+        .assert_allowed_uri(&snapshot.data_uri)?;
+
+    *ctx.accounts.listing = snapshot.into_listing(get_bump(&ctx.bumps, "listing")?);
+
+    Ok(())
+}
+
+/// Account validation for export
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct ExportListing<'info> {
+    /// The seller that owns the listing, paying for the export account
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing to export
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// The snapshot read by the next program version
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + ListingExport::LEN,
+        seeds = [LISTING_EXPORT_SEED, listing_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub listing_export: Account<'info, ListingExport>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for import
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct ImportListing<'info> {
+    /// The seller of the source listing
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// CHECK: `ListingExport` of the `migration_source` program, verified in the handler
+    pub listing_export: UncheckedAccount<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Listing::LEN,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        constraint = marketplace_config.accepting_new_listings
+            @ MarketplaceError::NotAcceptingListings,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing_module::tests::listing;
+
+    #[test]
+    fn snapshot_round_trip_keeps_creation_time_and_royalty() {
+        let mut listing = listing();
+        listing.creator = Pubkey::new_unique();
+        listing.royalty_bps = 500;
+        listing.accepted_mints = vec![Pubkey::new_unique()];
+        let snapshot = ListingSnapshot::from(&listing);
+        assert!(snapshot.validate(listing.created_at).is_ok());
+
+        let imported = snapshot.into_listing(254);
+        assert_eq!(imported.created_at, listing.created_at);
+        assert_eq!(imported.creator, listing.creator);
+        assert_eq!(imported.royalty_bps, 500);
+        assert!(imported.accepted_mints == listing.accepted_mints);
+        assert!(!imported.active);
+        assert_eq!(imported.collateral, 0);
+        assert_eq!(imported.bump, 254);
+    }
+
+    #[test]
+    fn export_loaded_by_next_version_only_if_supported() {
+        let migration_source = Pubkey::new_unique();
+        let listing = listing();
+        let (address, _) = Pubkey::find_program_address(
+            &[
+                LISTING_EXPORT_SEED,
+                listing.listing_id.to_le_bytes().as_ref(),
+            ],
+            &migration_source,
+        );
+        for (version, owner) in [
+            (
+                Versioned::V1(ListingSnapshot::from(&listing)),
+                migration_source,
+            ),
+            (Versioned::Unsupported(2), migration_source),
+            (Versioned::V1(ListingSnapshot::from(&listing)), System::id()),
+        ] {
+            let mut data = Vec::new();
+            ListingExport { snapshot: version }
+                .try_serialize(&mut data)
+                .unwrap();
+            let mut lamports = 0;
+            let info = AccountInfo::new(
+                &address,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+
+            let loaded = ListingExport::load(&info, &migration_source, listing.listing_id);
+            if owner != migration_source {
+                assert_eq!(
+                    loaded.err(),
+                    Some(MarketplaceError::ListingNotExported.into())
+                );
+            } else if let Ok(snapshot) = loaded {
+                assert_eq!(snapshot.seller, listing.seller);
+                assert_eq!(snapshot.created_at, listing.created_at);
+                assert_eq!(snapshot.price, listing.price);
+            } else {
+                assert_eq!(
+                    loaded.err(),
+                    Some(MarketplaceError::UnsupportedVersion.into())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn listing_with_shares_not_exportable() {
+        let mut listing = listing();
+        listing.assert_exportable().unwrap();
+
+        listing.dataset_shares = Some(Pubkey::new_unique());
+        assert_eq!(
+            listing.assert_exportable().unwrap_err(),
+            MarketplaceError::ListingHasShares.into()
+        );
+    }
+
+    #[test]
+    fn snapshot_accepted_mints_over_bound_rejected() {
+        let mut snapshot = ListingSnapshot::from(&listing());
+        snapshot.accepted_mints = vec![Pubkey::new_unique(); MAX_ACCEPTED_MINTS];
+        snapshot.validate(snapshot.created_at).unwrap();
+
+        snapshot.accepted_mints.push(Pubkey::new_unique());
+        assert_eq!(
+            snapshot.validate(snapshot.created_at).unwrap_err(),
+            MarketplaceError::TooManyEntries.into()
+        );
+    }
+
+    #[test]
+    fn tampered_snapshot_rejected() {
+        let mut snapshot = ListingSnapshot::from(&listing());
+        snapshot.royalty_bps = 10_001;
+        assert_eq!(
+            snapshot.validate(snapshot.created_at).unwrap_err(),
+            MarketplaceError::InvalidFeeBps.into()
+        );
+    }
+}
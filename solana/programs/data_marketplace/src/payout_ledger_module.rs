@@ -0,0 +1,133 @@
+//! payout_ledger_module module for data marketplace
+//!
+//! This module provides an on-chain history of the seller-side payouts of a
+//! listing, so revenue splits can be audited without replaying events. Only
+//! the most recent `MAX_PAYOUT_ENTRIES` payouts are kept.
+
+use anchor_lang::prelude::*;
+
+/// Maximum number of payouts kept per listing
+pub const MAX_PAYOUT_ENTRIES: usize = 16;
+
+/// A single payout
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PayoutEntry {
+    /// Wallet that received the payout
+    pub recipient: Pubkey,
+
+    /// Amount paid in base units of the listing mint
+    pub amount: u64,
+
+    /// Time of the payout
+    pub ts: i64,
+}
+
+/// PayoutLedger state account
+#[account]
+pub struct PayoutLedger {
+    /// The listing whose payouts are recorded
+    pub listing: Pubkey,
+
+    /// Most recent payouts, oldest first
+    pub entries: Vec<PayoutEntry>,
+
+    /// Number of payouts ever recorded, including evicted ones
+    pub total_entries: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl PayoutLedger {
+    pub const LEN: usize = 32 + (4 + MAX_PAYOUT_ENTRIES * (32 + 8 + 8)) + 8 + 1;
+
+    /// Append a payout, evicting the oldest entry when the ledger is full
+    pub fn record(&mut self, recipient: Pubkey, amount: u64, ts: i64) {
+        if self.entries.len() == MAX_PAYOUT_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(PayoutEntry {
+            recipient,
+            amount,
+            ts,
+        });
+        self.total_entries = self.total_entries.saturating_add(1);
+    }
+
+    /// Record the non-zero `payouts` of `listing`, setting up a ledger created
+    /// by this instruction with the given `bump`
+    pub fn record_payouts(
+        &mut self,
+        listing: Pubkey,
+        bump: u8,
+        payouts: &[(Pubkey, u64)],
+        ts: i64,
+    ) {
+        if self.listing == Pubkey::default() {
+            self.listing = listing;
+            self.bump = bump;
+        }
+        for &(recipient, amount) in payouts.iter().filter(|(_, amount)| *amount > 0) {
+            self.record(recipient, amount, ts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_sales_append_two_entries() {
+        let mut ledger = PayoutLedger {
+            listing: Pubkey::new_unique(),
+            entries: Vec::new(),
+            total_entries: 0,
+            bump: 255,
+        };
+        let seller = Pubkey::new_unique();
+
+        ledger.record(seller, 950, 10);
+        ledger.record(seller, 475, 20);
+        let amounts: Vec<u64> = ledger.entries.iter().map(|entry| entry.amount).collect();
+        assert_eq!(amounts, [950, 475]);
+        assert!(ledger.entries.iter().all(|entry| entry.recipient == seller));
+        assert_eq!(ledger.total_entries, 2);
+    }
+
+    #[test]
+    fn oldest_entry_evicted_when_full() {
+        let mut ledger = PayoutLedger {
+            listing: Pubkey::new_unique(),
+            entries: Vec::new(),
+            total_entries: 0,
+            bump: 255,
+        };
+        for ts in 0..=MAX_PAYOUT_ENTRIES as i64 {
+            ledger.record(Pubkey::new_unique(), 1, ts);
+        }
+        assert_eq!(ledger.entries.len(), MAX_PAYOUT_ENTRIES);
+        assert_eq!(ledger.entries[0].ts, 1);
+        assert_eq!(ledger.total_entries, MAX_PAYOUT_ENTRIES as u64 + 1);
+    }
+
+    #[test]
+    fn new_ledger_set_up_and_zero_payouts_skipped() {
+        let mut ledger = PayoutLedger {
+            listing: Pubkey::default(),
+            entries: Vec::new(),
+            total_entries: 0,
+            bump: 0,
+        };
+        let listing = Pubkey::new_unique();
+        let holders = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        ledger.record_payouts(listing, 254, &[(holders[0], 600), (holders[1], 0)], 10);
+        ledger.record_payouts(Pubkey::new_unique(), 1, &[(holders[1], 400)], 20);
+        assert_eq!(ledger.listing, listing);
+        assert_eq!(ledger.bump, 254);
+        let recipients: Vec<Pubkey> = ledger.entries.iter().map(|entry| entry.recipient).collect();
+        assert_eq!(recipients, holders);
+        assert_eq!(ledger.total_entries, 2);
+    }
+}
@@ -0,0 +1,184 @@
+//! seller_profile_module module for data marketplace
+//!
+//! This module provides seller reputation aggregated across all of a seller's
+//! listings. Only buyers holding an escrow with the seller can rate them, once
+//! per escrow.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::escrow_module::Escrow;
+use crate::utils_module::{assert_not_globally_paused, get_bump};
+use crate::vaults_module::ESCROW_SEED;
+
+/// Highest rating a buyer can give
+pub const MAX_RATING: u8 = 5;
+
+/// SellerProfile state account
+#[account]
+pub struct SellerProfile {
+    /// The seller the profile describes
+    pub seller: Pubkey,
+
+    /// Sum of all ratings received
+    pub rating_sum: u64,
+
+    /// Number of ratings received
+    pub rating_count: u64,
+
+    /// Number of escrows fully released to the seller
+    pub total_sales: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl SellerProfile {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 1;
+
+    /// Populate a profile created with `init_if_needed`, leaving existing ones untouched
+    pub fn initialize_if_needed(&mut self, seller: Pubkey, bump: u8) {
+        if self.seller == Pubkey::default() {
+            self.seller = seller;
+            self.bump = bump;
+        }
+    }
+
+    /// Add `rating` by `rater`, who must be the buyer of `escrow`
+    pub fn record_rating(&mut self, escrow: &Escrow, rater: &Pubkey, rating: u8) -> Result<()> {
+        require_keys_eq!(escrow.buyer, *rater, MarketplaceError::NotABuyer);
+        require!(
+            (1..=MAX_RATING).contains(&rating),
+            MarketplaceError::InvalidRating
+        );
+        self.rating_sum = self
+            .rating_sum
+            .checked_add(rating as u64)
+            .ok_or(MarketplaceError::MathOverflow)?;
+        self.rating_count = self
+            .rating_count
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Record an escrow fully released to the seller
+    pub fn record_sale(&mut self) -> Result<()> {
+        self.total_sales = self
+            .total_sales
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// RatingReceipt state account, marking an escrow whose buyer already rated
+#[account]
+pub struct RatingReceipt {
+    /// The escrow the rating was given for
+    pub escrow: Pubkey,
+
+    /// Rating given, from 1 to `MAX_RATING`
+    pub rating: u8,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl RatingReceipt {
+    pub const LEN: usize = 32 + 1 + 1;
+}
+
+/// Rate the seller of an escrow funded by the signer
+pub fn rate_seller(ctx: Context<RateSeller>, rating: u8) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let escrow = &ctx.accounts.escrow;
+    let profile = &mut ctx.accounts.seller_profile;
+    profile.initialize_if_needed(escrow.seller, get_bump(&ctx.bumps, "seller_profile")?);
+    profile.record_rating(escrow, &ctx.accounts.buyer.key(), rating)?;
+
+    let receipt = &mut ctx.accounts.rating_receipt;
+    receipt.escrow = ctx.accounts.escrow.key();
+    receipt.rating = rating;
+    receipt.bump = get_bump(&ctx.bumps, "rating_receipt")?;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct RateSeller<'info> {
+    /// The buyer giving the rating
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The buyer's escrow, proving the purchase
+    #[account(
+        seeds = [ESCROW_SEED, escrow.listing.as_ref(), buyer.key().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.buyer == buyer.key() @ MarketplaceError::NotABuyer,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Profile of the escrow's seller, created on first use
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + SellerProfile::LEN,
+        seeds = [b"seller".as_ref(), escrow.seller.as_ref()],
+        bump,
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+
+    /// Receipt preventing a second rating for the same escrow
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + RatingReceipt::LEN,
+        seeds = [b"rating".as_ref(), escrow.key().as_ref()],
+        bump,
+    )]
+    pub rating_receipt: Account<'info, RatingReceipt>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escrow_module::tests::escrow;
+
+    #[test]
+    fn only_buyer_rating_updates_profile() {
+        let escrow = escrow();
+        let mut profile = SellerProfile {
+            seller: escrow.seller,
+            rating_sum: 0,
+            rating_count: 0,
+            total_sales: 0,
+            bump: 255,
+        };
+
+        assert_eq!(
+            profile
+                .record_rating(&escrow, &Pubkey::new_unique(), 5)
+                .unwrap_err(),
+            MarketplaceError::NotABuyer.into()
+        );
+        profile.record_rating(&escrow, &escrow.buyer, 4).unwrap();
+        assert_eq!((profile.rating_sum, profile.rating_count), (4, 1));
+    }
+}
@@ -0,0 +1,311 @@
+//! shares_module module for data marketplace
+//!
+//! This module provides fractional ownership of a dataset listing. Each
+//! shareholder holds a basis-point share of the seller proceeds, and the shares
+//! always sum to 10000.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::listing_module::Listing;
+use crate::utils_module::{
+    assert_not_globally_paused, get_bump, mul_div, validate_bounded_vec, BPS_DENOMINATOR,
+};
+
+/// Maximum number of shareholders of a dataset
+pub const MAX_SHAREHOLDERS: usize = 10;
+
+/// A single shareholder entry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Shareholder {
+    /// Wallet receiving this share of the proceeds
+    pub owner: Pubkey,
+
+    /// Share of the proceeds in basis points
+    pub bps: u16,
+}
+
+/// DatasetShares state account
+#[account]
+pub struct DatasetShares {
+    /// The listing whose proceeds are shared
+    pub listing: Pubkey,
+
+    /// Shareholders, in the order their token accounts are passed on release
+    pub holders: Vec<Shareholder>,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl DatasetShares {
+    pub const LEN: usize = 32 + (4 + MAX_SHAREHOLDERS * (32 + 2)) + 1;
+
+    /// Check the shares are bounded, non-zero, unique and sum to 10000
+    pub fn validate(holders: &[Shareholder]) -> Result<()> {
+        validate_bounded_vec(holders, MAX_SHAREHOLDERS)?;
+
+        let mut total: u64 = 0;
+        for (i, holder) in holders.iter().enumerate() {
+            require!(holder.bps > 0, MarketplaceError::InvalidShares);
+            require!(
+                holders[..i].iter().all(|other| other.owner != holder.owner),
+                MarketplaceError::InvalidShares
+            );
+            total += holder.bps as u64;
+        }
+        require!(total == BPS_DENOMINATOR, MarketplaceError::InvalidShares);
+
+        Ok(())
+    }
+
+    /// Split `amount` across the holders in order.
+    ///
+    /// Each share is rounded down and the last holder receives the remainder, so
+    /// the parts always sum to `amount`.
+    pub fn split(&self, amount: u64) -> Result<Vec<u64>> {
+        let mut parts = Vec::with_capacity(self.holders.len());
+        let mut distributed: u64 = 0;
+        for (i, holder) in self.holders.iter().enumerate() {
+            let part = if i + 1 == self.holders.len() {
+                amount - distributed
+            } else {
+                mul_div(amount, holder.bps as u64, BPS_DENOMINATOR)?
+            };
+            distributed += part;
+            parts.push(part);
+        }
+
+        Ok(parts)
+    }
+
+    /// Move `bps` of the share of `from` to `to`, dropping holders left with nothing
+    pub fn transfer(&mut self, from: &Pubkey, to: Pubkey, bps: u16) -> Result<()> {
+        let from_index = self
+            .holders
+            .iter()
+            .position(|holder| holder.owner == *from)
+            .ok_or(MarketplaceError::NotShareholder)?;
+        require!(
+            bps > 0 && bps <= self.holders[from_index].bps,
+            MarketplaceError::InvalidShares
+        );
+
+        self.holders[from_index].bps -= bps;
+        match self.holders.iter_mut().find(|holder| holder.owner == to) {
+            Some(holder) => holder.bps += bps,
+            None => self.holders.push(Shareholder { owner: to, bps }),
+        }
+        self.holders.retain(|holder| holder.bps > 0);
+
+        Self::validate(&self.holders)
+    }
+}
+
+/// Pay `proceeds` out of a program-owned vault to the sellers of a listing.
+///
+/// With `dataset_shares`, the proceeds are split between the shareholders,
+/// whose token accounts in `mint` are `holder_accounts` in the order of
+/// `holders`; otherwise everything goes to `seller_token_account`. Callers
+/// check `dataset_shares` is the account recorded on the listing. Returns each
+/// recipient with the amount paid.
+pub fn pay_seller_proceeds<'info>(
+    dataset_shares: Option<&DatasetShares>,
+    holder_accounts: &[AccountInfo<'info>],
+    seller_token_account: &Account<'info, TokenAccount>,
+    mint: &Pubkey,
+    proceeds: u64,
+    mut transfer: impl FnMut(AccountInfo<'info>, u64) -> Result<()>,
+) -> Result<Vec<(Pubkey, u64)>> {
+    let shares = match dataset_shares {
+        Some(shares) => shares,
+        None => {
+            transfer(seller_token_account.to_account_info(), proceeds)?;
+            return Ok(vec![(seller_token_account.owner, proceeds)]);
+        }
+    };
+    require!(
+        holder_accounts.len() == shares.holders.len(),
+        MarketplaceError::InvalidShareholderAccounts
+    );
+
+    let mut payouts = Vec::with_capacity(shares.holders.len());
+    for ((holder, info), part) in shares
+        .holders
+        .iter()
+        .zip(holder_accounts)
+        .zip(shares.split(proceeds)?)
+    {
+        let token_account = Account::<TokenAccount>::try_from(info)?;
+        require!(
+            token_account.owner == holder.owner && token_account.mint == *mint,
+            MarketplaceError::InvalidShareholderAccounts
+        );
+        transfer(info.clone(), part)?;
+        payouts.push((holder.owner, part));
+    }
+
+    Ok(payouts)
+}
+
+/// Split ownership of a listing's proceeds between shareholders.
+///
+/// The shares are recorded on the listing, and every later payout of its
+/// proceeds has to go through them.
+pub fn setup_dataset_shares(
+    ctx: Context<SetupDatasetShares>,
+    holders: Vec<Shareholder>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    DatasetShares::validate(&holders)?;
+
+    let shares = &mut ctx.accounts.dataset_shares;
+    shares.listing = ctx.accounts.listing.key();
+    shares.holders = holders;
+    shares.bump = get_bump(&ctx.bumps, "dataset_shares")?;
+    ctx.accounts.listing.dataset_shares = Some(shares.key());
+
+    Ok(())
+}
+
+/// Move `bps` of the signer's share to `to`
+pub fn transfer_shares(ctx: Context<TransferShares>, to: Pubkey, bps: u16) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let from = ctx.accounts.holder.key();
+    ctx.accounts.dataset_shares.transfer(&from, to, bps)
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct SetupDatasetShares<'info> {
+    /// The seller that owns the listing
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing whose proceeds are shared
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing.listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+        constraint = listing.dataset_shares.is_none() @ MarketplaceError::InvalidShares,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + DatasetShares::LEN,
+        seeds = [b"shares".as_ref(), listing.key().as_ref()],
+        bump,
+    )]
+    pub dataset_shares: Account<'info, DatasetShares>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for share transfer
+#[derive(Accounts)]
+pub struct TransferShares<'info> {
+    /// The shareholder giving up part of their share
+    pub holder: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"shares".as_ref(), dataset_shares.listing.as_ref()],
+        bump = dataset_shares.bump,
+    )]
+    pub dataset_shares: Account<'info, DatasetShares>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shares(bps: &[u16]) -> DatasetShares {
+        DatasetShares {
+            listing: Pubkey::new_unique(),
+            holders: bps
+                .iter()
+                .map(|bps| Shareholder {
+                    owner: Pubkey::new_unique(),
+                    bps: *bps,
+                })
+                .collect(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn revenue_split_across_three_shareholders() {
+        let shares = shares(&[5_000, 3_000, 2_000]);
+        assert!(DatasetShares::validate(&shares.holders).is_ok());
+        assert_eq!(shares.split(1_001).unwrap(), vec![500, 300, 201]);
+    }
+
+    #[test]
+    fn shares_must_sum_to_10000() {
+        assert_eq!(
+            DatasetShares::validate(&shares(&[5_000, 3_000]).holders).unwrap_err(),
+            MarketplaceError::InvalidShares.into()
+        );
+    }
+
+    #[test]
+    fn shareholders_over_bound_rejected() {
+        let holders = shares(&[1_000; MAX_SHAREHOLDERS]).holders;
+        DatasetShares::validate(&holders).unwrap();
+        let holders = shares(&[1_000; MAX_SHAREHOLDERS + 1]).holders;
+        assert_eq!(
+            DatasetShares::validate(&holders).unwrap_err(),
+            MarketplaceError::TooManyEntries.into()
+        );
+    }
+
+    #[test]
+    fn transfer_moves_share_to_new_holder() {
+        let mut shares = shares(&[5_000, 3_000, 2_000]);
+        let from = shares.holders[2].owner;
+        let to = Pubkey::new_unique();
+
+        shares.transfer(&from, to, 500).unwrap();
+        assert_eq!(shares.holders[2].bps, 1_500);
+        assert!(
+            shares.holders[3]
+                == Shareholder {
+                    owner: to,
+                    bps: 500
+                }
+        );
+
+        shares.transfer(&from, to, 1_500).unwrap();
+        assert_eq!(shares.holders.len(), 3);
+        assert!(shares.holders.iter().all(|holder| holder.owner != from));
+        assert_eq!(
+            shares.transfer(&from, to, 1).unwrap_err(),
+            MarketplaceError::NotShareholder.into()
+        );
+    }
+}
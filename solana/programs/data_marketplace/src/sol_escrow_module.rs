@@ -0,0 +1,579 @@
+//! sol_escrow_module module for data marketplace
+//!
+//! This module provides escrow in native SOL for listings priced in the native
+//! mint, so buyers without wrapped SOL can still purchase. The escrow account
+//! holds the lamports itself: it is created rent exempt by the buyer and the
+//! price is transferred on top, so paying out the price never takes it below
+//! the rent-exempt minimum. Closing the escrow returns the rent to the buyer.
+//!
+//! SOL purchases follow the same rules as token purchases: the protocol fee,
+//! its discounts and `fee_payer` split, the creator royalty and the buyer's
+//! spend limit all apply. The fee is paid in lamports to the treasury owner's
+//! wallet and the royalty to the creator's wallet, so listings with a separate
+//! royalty mint cannot be bought with SOL.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::escrow_module::{purchase_fee_bps, purchase_terms, BuyerEscrows, PurchaseTerms};
+use crate::listing_module::Listing;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::payout_ledger_module::PayoutLedger;
+use crate::seller_profile_module::SellerProfile;
+use crate::shares_module::DatasetShares;
+use crate::spend_limit_module::record_spend;
+use crate::stats_module::MarketplaceStats;
+use crate::utils_module::{assert_not_globally_paused, get_bump};
+use crate::vaults_module::SOL_ESCROW_SEED;
+
+/// SolEscrow state account, also holding the escrowed lamports
+#[account]
+pub struct SolEscrow {
+    /// The listing purchased
+    pub listing: Pubkey,
+
+    /// The buyer that funded the escrow
+    pub buyer: Pubkey,
+
+    /// The seller receiving the funds on release
+    pub seller: Pubkey,
+
+    /// Escrowed lamports, on top of the account's rent and the held royalty
+    pub amount: u64,
+
+    /// Portion of `amount` owed to the treasury on release
+    pub protocol_fee: u64,
+
+    /// Creator royalty held in lamports until the escrow is settled
+    pub royalty: u64,
+
+    /// Funding time
+    pub created_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl SolEscrow {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// Split `amount` on release into the treasury's fee, including a
+    /// `holding_fee` capped at what the protocol fee leaves, and the seller proceeds
+    pub fn payouts(&self, holding_fee: u64) -> Result<(u64, u64)> {
+        let fee = self
+            .amount
+            .checked_sub(self.protocol_fee)
+            .and_then(|rest| self.protocol_fee.checked_add(holding_fee.min(rest)))
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok((fee, self.amount - fee))
+    }
+
+    /// Lamports held on top of the account's rent
+    pub fn held(&self) -> Result<u64> {
+        self.amount
+            .checked_add(self.royalty)
+            .ok_or_else(|| error!(MarketplaceError::MathOverflow))
+    }
+}
+
+/// Purchase a listing priced in the native mint, escrowing the price in lamports.
+///
+/// Listings flagged `contains_pii` require the buyer to set `pii_acknowledged`.
+/// The protocol fee, royalty and spend limit apply as for `purchase`: the
+/// escrow holds `price - royalty` plus the buyer's share of the fee, and the
+/// royalty alongside it.
+pub fn purchase_with_sol(
+    ctx: Context<PurchaseWithSol>,
+    _listing_id: u64,
+    pii_acknowledged: bool,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts.listing.record_buyer()?;
+    let listing = &ctx.accounts.listing;
+    let config = &ctx.accounts.marketplace_config;
+    let now = Clock::get()?.unix_timestamp;
+    listing.assert_purchasable(now)?;
+    require_keys_eq!(
+        listing.mint,
+        native_mint::ID,
+        MarketplaceError::NotSolListing
+    );
+    let price = listing.price_in(&native_mint::ID, |_| err!(MarketplaceError::NotSolListing))?;
+    listing.assert_pii_acknowledged(pii_acknowledged)?;
+    record_spend(&ctx.accounts.spend_window, price, now, ctx.program_id)?;
+    ctx.accounts
+        .seller_profile
+        .initialize_if_needed(listing.seller, get_bump(&ctx.bumps, "seller_profile")?);
+
+    let protocol_fee_bps = purchase_fee_bps(
+        config,
+        &ctx.accounts.buyer.key(),
+        ctx.accounts.buyer_stake_account.as_deref(),
+        Some(&ctx.accounts.seller_profile),
+    )?;
+    let PurchaseTerms {
+        protocol_fee,
+        royalty,
+        escrowed: amount,
+    } = purchase_terms(config, listing, price, protocol_fee_bps)?;
+    require!(
+        royalty == 0 || listing.royalty_mint.is_none(),
+        MarketplaceError::InvalidRoyaltyAccounts
+    );
+
+    let escrow = &mut ctx.accounts.sol_escrow;
+    escrow.listing = listing.key();
+    escrow.buyer = ctx.accounts.buyer.key();
+    escrow.seller = listing.seller;
+    escrow.amount = amount;
+    escrow.protocol_fee = protocol_fee;
+    escrow.royalty = royalty;
+    escrow.created_at = now;
+    escrow.bump = get_bump(&ctx.bumps, "sol_escrow")?;
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.buyer.to_account_info(),
+                to: ctx.accounts.sol_escrow.to_account_info(),
+            },
+        ),
+        ctx.accounts.sol_escrow.held()?,
+    )?;
+
+    let buyer_escrows = &mut ctx.accounts.buyer_escrows;
+    if buyer_escrows.buyer == Pubkey::default() {
+        buyer_escrows.buyer = ctx.accounts.buyer.key();
+        buyer_escrows.bump = get_bump(&ctx.bumps, "buyer_escrows")?;
+    }
+    buyer_escrows.open(config.max_open_escrows)?;
+    ctx.accounts.listing.record_escrow_opened()?;
+
+    ctx.accounts.marketplace_stats.record_sale(price)
+}
+
+/// Release a SOL escrow to the seller once the buyer confirms delivery.
+///
+/// The protocol fee, plus the holding fee for escrows left open for days, goes
+/// to the treasury owner and the held royalty to the listing creator. When the
+/// listing has `DatasetShares` the seller proceeds are split between the
+/// shareholders, whose wallets are passed as remaining accounts in the order of
+/// `holders`, and every seller-side payout is appended to the listing's
+/// `PayoutLedger`. The escrow is closed and its rent returned to the buyer.
+pub fn release_sol<'info>(ctx: Context<'_, '_, '_, 'info, ReleaseSol<'info>>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let escrow = &ctx.accounts.sol_escrow;
+    let now = Clock::get()?.unix_timestamp;
+    let holding_fee = ctx
+        .accounts
+        .marketplace_config
+        .holding_fee(escrow.amount, now.saturating_sub(escrow.created_at))?;
+    let (fee, amount) = escrow.payouts(holding_fee)?;
+    let royalty = escrow.royalty;
+    let escrow_info = escrow.to_account_info();
+    **escrow_info.try_borrow_mut_lamports()? -= escrow.held()?;
+    **ctx.accounts.treasury.try_borrow_mut_lamports()? += fee;
+    **ctx.accounts.creator.try_borrow_mut_lamports()? += royalty;
+    let mut payouts = Vec::new();
+    match &ctx.accounts.dataset_shares {
+        Some(shares) => {
+            require!(
+                ctx.remaining_accounts.len() == shares.holders.len(),
+                MarketplaceError::InvalidShareholderAccounts
+            );
+            for ((holder, info), part) in shares
+                .holders
+                .iter()
+                .zip(ctx.remaining_accounts)
+                .zip(shares.split(amount)?)
+            {
+                require_keys_eq!(
+                    info.key(),
+                    holder.owner,
+                    MarketplaceError::InvalidShareholderAccounts
+                );
+                **info.try_borrow_mut_lamports()? += part;
+                payouts.push((holder.owner, part));
+            }
+        }
+        None => {
+            **ctx.accounts.seller.try_borrow_mut_lamports()? += amount;
+            payouts.push((ctx.accounts.seller.key(), amount));
+        }
+    }
+    ctx.accounts.payout_ledger.record_payouts(
+        ctx.accounts.sol_escrow.listing,
+        get_bump(&ctx.bumps, "payout_ledger")?,
+        &payouts,
+        now,
+    );
+
+    let profile = &mut ctx.accounts.seller_profile;
+    profile.initialize_if_needed(
+        ctx.accounts.sol_escrow.seller,
+        get_bump(&ctx.bumps, "seller_profile")?,
+    );
+    profile.record_sale()?;
+    ctx.accounts.buyer_escrows.settle()?;
+    ctx.accounts.listing.record_escrow_closed()
+}
+
+/// Return a SOL escrow to the buyer, at the seller's discretion.
+///
+/// The escrow is closed, returning everything it holds to the buyer: the
+/// escrowed amount with the buyer's share of the fee, the royalty and the rent.
+pub fn refund_sol(ctx: Context<RefundSol>) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    ctx.accounts.buyer_escrows.settle()?;
+    ctx.accounts.listing.record_escrow_closed()
+}
+
+/// Account validation for SOL purchases
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct PurchaseWithSol<'info> {
+    /// The buyer funding the escrow
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing being purchased
+    #[account(
+        mut,
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// Marketplace statistics
+    #[account(
+        mut,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump = marketplace_stats.bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// CHECK: Buyer's spend window PDA, enforced only once the buyer has opted in
+    #[account(
+        mut,
+        seeds = [b"spend-window".as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub spend_window: UncheckedAccount<'info>,
+
+    /// CHECK: Buyer's MCP stake account for the fee discount, verified in the handler
+    pub buyer_stake_account: Option<UncheckedAccount<'info>>,
+
+    /// Seller's profile for the volume rebate, created on the seller's first sale
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + SellerProfile::LEN,
+        seeds = [b"seller".as_ref(), listing.seller.as_ref()],
+        bump,
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+
+    /// The escrow to initialize
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + SolEscrow::LEN,
+        seeds = [SOL_ESCROW_SEED, listing.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub sol_escrow: Account<'info, SolEscrow>,
+
+    /// Open escrow count of the buyer, created on first purchase
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerEscrows::LEN,
+        seeds = [b"buyer-escrows".as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_escrows: Account<'info, BuyerEscrows>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for SOL release
+#[derive(Accounts)]
+pub struct ReleaseSol<'info> {
+    /// The buyer confirming delivery
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// CHECK: The seller receiving the lamports, checked against the escrow
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// CHECK: Treasury owner's wallet receiving the protocol fee in lamports
+    #[account(
+        mut,
+        address = marketplace_config.treasury_owner @ MarketplaceError::InvalidTreasury,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Listing creator's wallet receiving the held royalty
+    #[account(
+        mut,
+        address = listing.creator @ MarketplaceError::InvalidRoyaltyAccounts,
+    )]
+    pub creator: UncheckedAccount<'info>,
+
+    /// The escrow to release
+    #[account(
+        mut,
+        seeds = [SOL_ESCROW_SEED, sol_escrow.listing.as_ref(), buyer.key().as_ref()],
+        bump = sol_escrow.bump,
+        has_one = buyer,
+        has_one = seller,
+        close = buyer,
+    )]
+    pub sol_escrow: Account<'info, SolEscrow>,
+
+    /// The escrow's listing
+    #[account(
+        mut,
+        address = sol_escrow.listing,
+        constraint = listing.dataset_shares == dataset_shares.as_ref().map(|shares| shares.key())
+            @ MarketplaceError::InvalidShareholderAccounts,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Fractional owners of the listing, required when the listing records them
+    pub dataset_shares: Option<Account<'info, DatasetShares>>,
+
+    /// Payout history of the listing, created on first payout
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + PayoutLedger::LEN,
+        seeds = [b"payout-ledger".as_ref(), sol_escrow.listing.as_ref()],
+        bump,
+    )]
+    pub payout_ledger: Account<'info, PayoutLedger>,
+
+    /// Seller's sales record, created on the seller's first completed sale
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + SellerProfile::LEN,
+        seeds = [b"seller".as_ref(), sol_escrow.seller.as_ref()],
+        bump,
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+
+    /// Open escrow count of the buyer
+    #[account(
+        mut,
+        seeds = [b"buyer-escrows".as_ref(), buyer.key().as_ref()],
+        bump = buyer_escrows.bump,
+    )]
+    pub buyer_escrows: Account<'info, BuyerEscrows>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for SOL refund
+#[derive(Accounts)]
+pub struct RefundSol<'info> {
+    /// The seller giving up the funds
+    pub seller: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// CHECK: The buyer receiving the lamports, checked against the escrow
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// The escrow to refund
+    #[account(
+        mut,
+        seeds = [SOL_ESCROW_SEED, sol_escrow.listing.as_ref(), buyer.key().as_ref()],
+        bump = sol_escrow.bump,
+        has_one = buyer,
+        has_one = seller,
+        close = buyer,
+    )]
+    pub sol_escrow: Account<'info, SolEscrow>,
+
+    /// The escrow's listing
+    #[account(
+        mut,
+        address = sol_escrow.listing,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Open escrow count of the buyer
+    #[account(
+        mut,
+        seeds = [b"buyer-escrows".as_ref(), buyer.key().as_ref()],
+        bump = buyer_escrows.bump,
+    )]
+    pub buyer_escrows: Account<'info, BuyerEscrows>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing_module::tests::listing;
+    use crate::utils_module::tests::{global_config_data, TestAccount};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn sol_escrow() -> SolEscrow {
+        SolEscrow {
+            listing: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            amount: 950,
+            protocol_fee: 50,
+            royalty: 50,
+            created_at: 1000,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn sol_release_pays_fee_royalty_and_seller() {
+        let escrow = sol_escrow();
+        assert_eq!(escrow.held().unwrap(), 1000);
+        assert_eq!(escrow.payouts(0).unwrap(), (50, 900));
+        assert_eq!(escrow.payouts(10).unwrap(), (60, 890));
+        assert_eq!(escrow.payouts(u64::MAX).unwrap(), (950, 0));
+    }
+
+    /// `RefundSol` accounts for a funded SOL escrow of `buyer`, signed by `signer`
+    fn refund_accounts(escrow: &SolEscrow, signer: Pubkey) -> Vec<TestAccount> {
+        let program_id = crate::ID;
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+        let (escrow_key, bump) = pda(&[
+            SOL_ESCROW_SEED,
+            escrow.listing.as_ref(),
+            escrow.buyer.as_ref(),
+        ]);
+        let escrow = SolEscrow {
+            bump,
+            ..escrow.clone()
+        };
+        let mut listing = listing();
+        listing.open_escrows = 1;
+        let (buyer_escrows_key, bump) = pda(&[b"buyer-escrows", escrow.buyer.as_ref()]);
+        let buyer_escrows = BuyerEscrows {
+            buyer: escrow.buyer,
+            open_escrows: 1,
+            bump,
+        };
+        let mut escrow_account =
+            TestAccount::state(escrow_key, &escrow, 8 + SolEscrow::LEN).writable();
+        escrow_account.lamports += escrow.held().unwrap();
+
+        vec![
+            TestAccount::wallet(signer).signer(),
+            TestAccount::new(
+                Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &mcp_token::ID).0,
+                mcp_token::ID,
+                global_config_data(false),
+            ),
+            TestAccount::wallet(escrow.buyer).writable(),
+            escrow_account,
+            TestAccount::state(escrow.listing, &listing, 8 + Listing::LEN).writable(),
+            TestAccount::state(buyer_escrows_key, &buyer_escrows, 8 + BuyerEscrows::LEN).writable(),
+        ]
+    }
+
+    #[test]
+    fn only_seller_refunds_and_buyer_gets_everything_back() {
+        let program_id = crate::ID;
+        let escrow = sol_escrow();
+
+        let mut accounts = refund_accounts(&escrow, Pubkey::new_unique());
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let result = RefundSol::try_accounts(
+            &program_id,
+            &mut &infos[..],
+            &[],
+            &mut BTreeMap::new(),
+            &mut BTreeSet::new(),
+        );
+        assert_eq!(
+            result
+                .err()
+                .map(|err| err == ErrorCode::ConstraintHasOne.into()),
+            Some(true)
+        );
+
+        let mut accounts = refund_accounts(&escrow, escrow.seller);
+        let buyer_lamports = accounts[2].lamports;
+        let escrow_lamports = accounts[3].lamports;
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(TestAccount::info).collect();
+        let mut bumps = BTreeMap::new();
+        let mut refund_accounts = RefundSol::try_accounts(
+            &program_id,
+            &mut &infos[..],
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .unwrap();
+        refund_sol(Context::new(&program_id, &mut refund_accounts, &[], bumps)).unwrap();
+        assert_eq!(refund_accounts.listing.open_escrows, 0);
+        assert_eq!(refund_accounts.buyer_escrows.open_escrows, 0);
+        refund_accounts.exit(&program_id).unwrap();
+        drop(refund_accounts);
+        drop(infos);
+
+        // The escrowed amount with the fee, the royalty and the rent
+        assert_eq!(
+            escrow_lamports,
+            Rent::default().minimum_balance(8 + SolEscrow::LEN) + 1_000
+        );
+        assert_eq!(accounts[2].lamports, buyer_lamports + escrow_lamports);
+        assert_eq!(accounts[3].lamports, 0);
+        assert_eq!(accounts[3].owner, System::id());
+    }
+}
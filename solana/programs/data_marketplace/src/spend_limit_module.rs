@@ -0,0 +1,152 @@
+//! spend_limit_module module for data marketplace
+//!
+//! This module provides opt-in daily spending caps for buyers. A buyer creates
+//! a `SpendWindow` with their limit; from then on every purchase is counted
+//! against the current day (`unix_timestamp / 86400`) and the window resets
+//! when a new day starts.
+
+use anchor_lang::prelude::*;
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::utils_module::{assert_not_globally_paused, get_bump};
+
+/// Length of a spending window in seconds
+pub const SPEND_WINDOW_SECONDS: i64 = 86_400;
+
+/// SpendWindow state account
+#[account]
+pub struct SpendWindow {
+    /// The consumer the limit applies to
+    pub consumer: Pubkey,
+
+    /// Most the consumer may spend per day
+    pub daily_spend_limit: u64,
+
+    /// Day index (`unix_timestamp / 86400`) that `spent` refers to
+    pub day: i64,
+
+    /// Amount spent during `day`
+    pub spent: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl SpendWindow {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 1;
+
+    /// Count `amount` against the window for `now`, rolling over to a new day if needed
+    pub fn record_spend(&mut self, amount: u64, now: i64) -> Result<()> {
+        let day = now / SPEND_WINDOW_SECONDS;
+        if day != self.day {
+            self.day = day;
+            self.spent = 0;
+        }
+
+        let spent = self
+            .spent
+            .checked_add(amount)
+            .ok_or(MarketplaceError::MathOverflow)?;
+        require!(
+            spent <= self.daily_spend_limit,
+            MarketplaceError::SpendLimitExceeded
+        );
+        self.spent = spent;
+
+        Ok(())
+    }
+}
+
+/// Count a spend against the consumer's window if they have opted in.
+///
+/// `info` must be the consumer's spend window PDA; when it has not been
+/// created the consumer has no limit and the spend is not tracked.
+pub fn record_spend<'info>(
+    info: &AccountInfo<'info>,
+    amount: u64,
+    now: i64,
+    program_id: &Pubkey,
+) -> Result<()> {
+    if info.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut window = Account::<SpendWindow>::try_from(info)?;
+    window.record_spend(amount, now)?;
+    window.exit(program_id)
+}
+
+/// Opt in to a daily spending limit
+pub fn set_daily_spend_limit(
+    ctx: Context<SetDailySpendLimit>,
+    daily_spend_limit: u64,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let window = &mut ctx.accounts.spend_window;
+    if window.consumer == Pubkey::default() {
+        window.consumer = ctx.accounts.consumer.key();
+        window.bump = get_bump(&ctx.bumps, "spend_window")?;
+    }
+    window.daily_spend_limit = daily_spend_limit;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct SetDailySpendLimit<'info> {
+    /// The consumer setting their limit
+    #[account(mut)]
+    pub consumer: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The account to initialize or update
+    #[account(
+        init_if_needed,
+        payer = consumer,
+        space = 8 + SpendWindow::LEN,
+        seeds = [b"spend-window".as_ref(), consumer.key().as_ref()],
+        bump,
+    )]
+    pub spend_window: Account<'info, SpendWindow>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spend_over_daily_limit_rejected_until_next_day() {
+        let mut window = SpendWindow {
+            consumer: Pubkey::new_unique(),
+            daily_spend_limit: 1_000,
+            day: 0,
+            spent: 0,
+            bump: 255,
+        };
+        let now = 3 * SPEND_WINDOW_SECONDS;
+
+        window.record_spend(600, now).unwrap();
+        window.record_spend(400, now + 10).unwrap();
+        assert_eq!(
+            window.record_spend(1, now + 20).unwrap_err(),
+            MarketplaceError::SpendLimitExceeded.into()
+        );
+        assert_eq!(window.spent, 1_000);
+
+        window.record_spend(1, now + SPEND_WINDOW_SECONDS).unwrap();
+        assert_eq!(window.spent, 1);
+    }
+}
@@ -0,0 +1,133 @@
+//! stats_module module for data marketplace
+//!
+//! This module provides aggregate marketplace statistics maintained incrementally
+//! by listing and purchase instructions, so dashboards can read totals from a
+//! single account instead of scanning every listing and escrow.
+
+use anchor_lang::prelude::*;
+
+use crate::errors_module::MarketplaceError;
+use crate::marketplace_config_module::MarketplaceConfig;
+use crate::utils_module::get_bump;
+
+/// MarketplaceStats state account
+#[account]
+pub struct MarketplaceStats {
+    /// Sum of all purchase prices
+    pub total_volume: u64,
+
+    /// Number of purchases
+    pub total_sales: u64,
+
+    /// Number of listings currently active
+    pub active_listings: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl MarketplaceStats {
+    pub const LEN: usize = 8 + 8 + 8 + 1;
+
+    /// Record a listing becoming active
+    pub fn record_listing_opened(&mut self) -> Result<()> {
+        self.active_listings = self
+            .active_listings
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Record an active listing being deactivated
+    pub fn record_listing_closed(&mut self) -> Result<()> {
+        self.active_listings = self
+            .active_listings
+            .checked_sub(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Record a purchase of `amount`
+    pub fn record_sale(&mut self, amount: u64) -> Result<()> {
+        self.total_sales = self
+            .total_sales
+            .checked_add(1)
+            .ok_or(MarketplaceError::MathOverflow)?;
+        self.total_volume = self
+            .total_volume
+            .checked_add(amount)
+            .ok_or(MarketplaceError::MathOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// Initialize the MarketplaceStats
+pub fn initialize_marketplace_stats(ctx: Context<InitializeMarketplaceStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.marketplace_stats;
+    stats.total_volume = 0;
+    stats.total_sales = 0;
+    stats.active_listings = 0;
+    stats.bump = get_bump(&ctx.bumps, "marketplace_stats")?;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeMarketplaceStats<'info> {
+    /// The admin of the marketplace
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Marketplace configuration
+    #[account(
+        seeds = [b"marketplace-config".as_ref()],
+        bump = marketplace_config.bump,
+        has_one = admin,
+        constraint = !marketplace_config.config_frozen @ MarketplaceError::ConfigFrozen,
+    )]
+    pub marketplace_config: Account<'info, MarketplaceConfig>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + MarketplaceStats::LEN,
+        seeds = [b"marketplace-stats".as_ref()],
+        bump,
+    )]
+    pub marketplace_stats: Account<'info, MarketplaceStats>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opening_and_cancelling_listings_keeps_active_count() {
+        let mut stats = MarketplaceStats {
+            total_volume: 0,
+            total_sales: 0,
+            active_listings: 0,
+            bump: 255,
+        };
+
+        stats.record_listing_opened().unwrap();
+        stats.record_listing_opened().unwrap();
+        stats.record_listing_closed().unwrap();
+        assert_eq!(stats.active_listings, 1);
+
+        stats.record_listing_closed().unwrap();
+        assert_eq!(
+            stats.record_listing_closed().unwrap_err(),
+            MarketplaceError::MathOverflow.into()
+        );
+        assert_eq!(stats.active_listings, 0);
+    }
+}
@@ -0,0 +1,474 @@
+//! subscription_module module for data marketplace
+//!
+//! This module provides time-boxed subscriptions to a listing. A subscriber
+//! pays the listing price for one period into a vault; the seller collects it
+//! once the period is over. Subscribers cancelling mid-period get the unused
+//! share of the price back and the seller is paid for the time used.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use mcp_token::global_config_module::GLOBAL_CONFIG_SEED;
+
+use crate::errors_module::MarketplaceError;
+use crate::listing_module::Listing;
+use crate::payout_ledger_module::PayoutLedger;
+use crate::shares_module::{pay_seller_proceeds, DatasetShares};
+use crate::utils_module::{assert_not_globally_paused, get_bump, mul_div};
+use crate::vaults_module::{SUBSCRIPTION_SEED, SUBSCRIPTION_VAULT_SEED};
+
+/// Subscription state account, also the authority of the subscription vault
+#[account]
+pub struct Subscription {
+    /// The listing subscribed to
+    pub listing: Pubkey,
+
+    /// The subscriber
+    pub subscriber: Pubkey,
+
+    /// The seller paid for the period
+    pub seller: Pubkey,
+
+    /// Price paid for the current period
+    pub price: u64,
+
+    /// Start of the current period
+    pub period_start: i64,
+
+    /// Length of the current period in seconds
+    pub period_duration: i64,
+
+    /// End of access; moved to the cancellation time on cancel
+    pub expires_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Subscription {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// Whether the subscription grants access at `now`
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
+
+    /// Share of `price` for the part of the period left at `now`.
+    ///
+    /// Rounds down, so the seller keeps any remainder and a cancellation in the
+    /// last moments of a period can refund nothing.
+    pub fn unused_refund(&self, now: i64) -> Result<u64> {
+        let remaining = self.expires_at.saturating_sub(now).max(0);
+        mul_div(self.price, remaining as u64, self.period_duration as u64)
+    }
+}
+
+/// Move `amount` out of the subscription vault, signed by the subscription
+fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    subscription: &Account<'info, Subscription>,
+    vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: vault.to_account_info(),
+                to,
+                authority: subscription.to_account_info(),
+            },
+            &[&[
+                SUBSCRIPTION_SEED,
+                subscription.listing.as_ref(),
+                subscription.subscriber.as_ref(),
+                &[subscription.bump],
+            ]],
+        ),
+        amount,
+    )
+}
+
+/// Subscribe to a listing for `period_duration` seconds at the listing price.
+///
+/// An expired subscription whose payment has been settled can be renewed the
+/// same way.
+pub fn subscribe(ctx: Context<Subscribe>, _listing_id: u64, period_duration: i64) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    require!(period_duration > 0, MarketplaceError::InvalidExpiry);
+    let listing = &ctx.accounts.listing;
+    let now = Clock::get()?.unix_timestamp;
+    listing.assert_purchasable(now)?;
+
+    let subscription = &ctx.accounts.subscription;
+    if subscription.subscriber != Pubkey::default() {
+        require!(
+            !subscription.is_active(now) && ctx.accounts.vault.amount == 0,
+            MarketplaceError::SubscriptionActive
+        );
+    }
+
+    let price = listing.price;
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.subscriber_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.subscriber.to_account_info(),
+            },
+        ),
+        price,
+    )?;
+
+    let subscription = &mut ctx.accounts.subscription;
+    subscription.listing = listing.key();
+    subscription.subscriber = ctx.accounts.subscriber.key();
+    subscription.seller = listing.seller;
+    subscription.price = price;
+    subscription.period_start = now;
+    subscription.period_duration = period_duration;
+    subscription.expires_at = now
+        .checked_add(period_duration)
+        .ok_or(MarketplaceError::InvalidExpiry)?;
+    subscription.bump = get_bump(&ctx.bumps, "subscription")?;
+
+    Ok(())
+}
+
+/// Cancel an active subscription, refunding `price * remaining / duration`.
+///
+/// The rest of the period payment goes to the seller and access ends now.
+/// When the listing has `DatasetShares` the seller's part is split between the
+/// shareholders, whose token accounts are passed as remaining accounts. The
+/// seller-side payouts are appended to the listing's `PayoutLedger`.
+pub fn cancel_subscription<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelSubscription<'info>>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let subscription = &ctx.accounts.subscription;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        subscription.is_active(now),
+        MarketplaceError::SubscriptionExpired
+    );
+
+    let refund = subscription.unused_refund(now)?;
+    let earned = ctx
+        .accounts
+        .vault
+        .amount
+        .checked_sub(refund)
+        .ok_or(MarketplaceError::MathOverflow)?;
+    let pay_from_vault = |to: AccountInfo<'info>, amount: u64| {
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.subscription,
+            &ctx.accounts.vault,
+            to,
+            amount,
+        )
+    };
+    pay_from_vault(
+        ctx.accounts.subscriber_token_account.to_account_info(),
+        refund,
+    )?;
+    let payouts = pay_seller_proceeds(
+        ctx.accounts.dataset_shares.as_deref(),
+        ctx.remaining_accounts,
+        &ctx.accounts.seller_token_account,
+        &ctx.accounts.vault.mint,
+        earned,
+        pay_from_vault,
+    )?;
+    ctx.accounts.payout_ledger.record_payouts(
+        ctx.accounts.subscription.listing,
+        get_bump(&ctx.bumps, "payout_ledger")?,
+        &payouts,
+        now,
+    );
+
+    ctx.accounts.subscription.expires_at = now;
+
+    Ok(())
+}
+
+/// Pay the seller for a subscription period that has run out.
+///
+/// Callable by anyone, so the seller is paid without the subscriber's help.
+/// Listings with `DatasetShares` pay the shareholders, and the payouts are
+/// recorded, as on cancellation.
+pub fn settle_subscription<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleSubscription<'info>>,
+) -> Result<()> {
+    assert_not_globally_paused(&ctx.accounts.global_config)?;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !ctx.accounts.subscription.is_active(now),
+        MarketplaceError::SubscriptionActive
+    );
+
+    let payouts = pay_seller_proceeds(
+        ctx.accounts.dataset_shares.as_deref(),
+        ctx.remaining_accounts,
+        &ctx.accounts.seller_token_account,
+        &ctx.accounts.vault.mint,
+        ctx.accounts.vault.amount,
+        |to, amount| {
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.subscription,
+                &ctx.accounts.vault,
+                to,
+                amount,
+            )
+        },
+    )?;
+    ctx.accounts.payout_ledger.record_payouts(
+        ctx.accounts.subscription.listing,
+        get_bump(&ctx.bumps, "payout_ledger")?,
+        &payouts,
+        now,
+    );
+
+    Ok(())
+}
+
+/// Account validation for subscribe
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct Subscribe<'info> {
+    /// The subscriber paying for the period
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The listing subscribed to
+    #[account(
+        seeds = [b"listing".as_ref(), listing_id.to_le_bytes().as_ref()],
+        bump = listing.bump,
+        has_one = mint,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// The subscription, created on first subscribe
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + Subscription::LEN,
+        seeds = [SUBSCRIPTION_SEED, listing.key().as_ref(), subscriber.key().as_ref()],
+        bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// Token vault holding the period payment
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        seeds = [SUBSCRIPTION_VAULT_SEED, subscription.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = subscription,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Mint the listing is priced in
+    pub mint: Account<'info, Mint>,
+
+    /// Subscriber's token account
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = subscriber,
+    )]
+    pub subscriber_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for cancellation
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    /// The subscriber cancelling, paying for the payout ledger if needed
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The subscription to cancel
+    #[account(
+        mut,
+        seeds = [SUBSCRIPTION_SEED, subscription.listing.as_ref(), subscriber.key().as_ref()],
+        bump = subscription.bump,
+        has_one = subscriber,
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// The subscribed listing
+    #[account(
+        address = subscription.listing,
+        constraint = listing.dataset_shares == dataset_shares.as_ref().map(|shares| shares.key())
+            @ MarketplaceError::InvalidShareholderAccounts,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Fractional owners of the listing, required when the listing records them
+    pub dataset_shares: Option<Account<'info, DatasetShares>>,
+
+    /// Token vault holding the period payment
+    #[account(
+        mut,
+        seeds = [SUBSCRIPTION_VAULT_SEED, subscription.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Subscriber's token account receiving the refund
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = subscriber,
+    )]
+    pub subscriber_token_account: Account<'info, TokenAccount>,
+
+    /// Seller's token account receiving payment for the time used
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = subscription.seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Payout history of the listing, created on first payout
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + PayoutLedger::LEN,
+        seeds = [b"payout-ledger".as_ref(), subscription.listing.as_ref()],
+        bump,
+    )]
+    pub payout_ledger: Account<'info, PayoutLedger>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for settlement
+#[derive(Accounts)]
+pub struct SettleSubscription<'info> {
+    /// Pays for the payout ledger if needed
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The subscription whose period ended
+    #[account(
+        seeds = [
+            SUBSCRIPTION_SEED,
+            subscription.listing.as_ref(),
+            subscription.subscriber.as_ref(),
+        ],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// CHECK: MCP token global config, checked for the protocol-wide pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        seeds::program = mcp_token::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// The subscribed listing
+    #[account(
+        address = subscription.listing,
+        constraint = listing.dataset_shares == dataset_shares.as_ref().map(|shares| shares.key())
+            @ MarketplaceError::InvalidShareholderAccounts,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// Fractional owners of the listing, required when the listing records them
+    pub dataset_shares: Option<Account<'info, DatasetShares>>,
+
+    /// Token vault holding the period payment
+    #[account(
+        mut,
+        seeds = [SUBSCRIPTION_VAULT_SEED, subscription.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Seller's token account
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = subscription.seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    /// Payout history of the listing, created on first payout
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PayoutLedger::LEN,
+        seeds = [b"payout-ledger".as_ref(), subscription.listing.as_ref()],
+        bump,
+    )]
+    pub payout_ledger: Account<'info, PayoutLedger>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_at_half_period_refunds_half() {
+        let subscription = Subscription {
+            listing: Pubkey::new_unique(),
+            subscriber: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            price: 1_001,
+            period_start: 1_000,
+            period_duration: 3_000,
+            expires_at: 4_000,
+            bump: 255,
+        };
+
+        assert_eq!(subscription.unused_refund(2_500).unwrap(), 500);
+        assert_eq!(subscription.unused_refund(3_999).unwrap(), 0);
+        assert_eq!(subscription.unused_refund(5_000).unwrap(), 0);
+    }
+}
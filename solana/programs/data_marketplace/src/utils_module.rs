@@ -0,0 +1,587 @@
+//! utils_module module for data marketplace
+//!
+//! This module provides helpers shared by data marketplace instructions.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use std::collections::BTreeMap;
+
+use mcp_token::global_config_module::GlobalConfig;
+use mcp_token::utils_module::MAX_BATCH;
+
+use crate::errors_module::MarketplaceError;
+
+/// Denominator for basis point amounts
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Maximum number of pricing tiers on an account
+pub const MAX_TIERS: usize = 8;
+
+/// Maximum number of royalty recipients on an account
+pub const MAX_ROYALTY_RECIPIENTS: usize = 5;
+
+/// Maximum number of tags on an account
+pub const MAX_TAGS: usize = 10;
+
+/// Fail with `BatchTooLarge` when a batch has more than the MCP-wide
+/// `MAX_BATCH` entries.
+///
+/// Called before a batch instruction touches any state.
+pub fn assert_batch_size(entries: usize) -> Result<()> {
+    require!(entries <= MAX_BATCH, MarketplaceError::BatchTooLarge);
+
+    Ok(())
+}
+
+/// Look up a PDA bump recorded by Anchor during account validation.
+///
+/// Returns `BumpNotFound` instead of panicking when `name` is missing.
+pub fn get_bump(bumps: &BTreeMap<String, u8>, name: &str) -> Result<u8> {
+    bumps
+        .get(name)
+        .copied()
+        .ok_or_else(|| error!(MarketplaceError::BumpNotFound))
+}
+
+/// Check that a vector stored on an account stays within its fixed bound.
+///
+/// Account sizes are computed from these bounds, so every vector field must be
+/// validated here before it is written.
+pub fn validate_bounded_vec<T>(entries: &[T], max: usize) -> Result<()> {
+    require!(entries.len() <= max, MarketplaceError::TooManyEntries);
+
+    Ok(())
+}
+
+/// Check that an account still holds enough lamports to be rent exempt.
+///
+/// Anchor verifies this for accounts it creates with `init`. Accounts this
+/// program creates, resizes or fills by hand must call it once mutated, so a
+/// payer that under-funds them fails cleanly instead of leaving an account the
+/// runtime can reclaim.
+pub fn assert_rent_exempt(info: &AccountInfo) -> Result<()> {
+    check_rent_exempt(&Rent::get()?, info.lamports(), info.data_len())
+}
+
+/// Check that `lamports` keep a `data_len`-byte account rent exempt under `rent`
+pub fn check_rent_exempt(rent: &Rent, lamports: u64, data_len: usize) -> Result<()> {
+    require!(
+        rent.is_exempt(lamports, data_len),
+        MarketplaceError::NotRentExempt
+    );
+
+    Ok(())
+}
+
+/// Grow a program-owned account to `new_len` bytes, first topping its
+/// lamports up from `payer` to the rent-exempt minimum of the new size
+pub fn grow_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let top_up = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(target.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    target.realloc(new_len, true)?;
+
+    assert_rent_exempt(target)
+}
+
+/// Create a `space`-byte account owned by `owner` at the PDA signed for by `seeds`.
+///
+/// Like Anchor's `init`, an address that already holds lamports is topped up
+/// to the rent-exempt minimum, allocated and assigned rather than created, so
+/// anyone pre-funding the address cannot block its creation.
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(space);
+    if target.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                &[seeds],
+            ),
+            required,
+            space as u64,
+            owner,
+        );
+    }
+
+    let top_up = required.saturating_sub(target.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: target.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: target.clone(),
+            },
+            &[seeds],
+        ),
+        owner,
+    )
+}
+
+/// Compute `amount * numerator / denominator` without intermediate overflow
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    require!(denominator > 0, MarketplaceError::MathOverflow);
+    let result = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(MarketplaceError::MathOverflow)?
+        / denominator as u128;
+
+    u64::try_from(result).map_err(|_| error!(MarketplaceError::MathOverflow))
+}
+
+/// How fee and royalty splits round a fractional result
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round down, leaving the remainder with the party receiving the rest
+    Floor,
+    /// Round up, giving the remainder to the fee or royalty recipient
+    Ceil,
+    /// Round half up to the nearest unit
+    Round,
+}
+
+/// Compute `amount * numerator / denominator` rounded according to `mode`
+pub fn mul_div_rounded(
+    amount: u64,
+    numerator: u64,
+    denominator: u64,
+    mode: RoundingMode,
+) -> Result<u64> {
+    require!(denominator > 0, MarketplaceError::MathOverflow);
+    let product = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(MarketplaceError::MathOverflow)?;
+    let denominator = denominator as u128;
+    let result = match mode {
+        RoundingMode::Floor => product / denominator,
+        RoundingMode::Ceil => product.div_ceil(denominator),
+        RoundingMode::Round => (product + denominator / 2) / denominator,
+    };
+
+    u64::try_from(result).map_err(|_| error!(MarketplaceError::MathOverflow))
+}
+
+/// Check that `info` holds an account of type `T` by its 8-byte discriminator
+pub fn verify_discriminator<T: Discriminator>(info: &AccountInfo) -> Result<()> {
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == T::discriminator(),
+        MarketplaceError::AccountTypeMismatch
+    );
+
+    Ok(())
+}
+
+/// Deserialize an account of type `T` owned by `program_id`, verifying its
+/// owner and discriminator first
+pub fn load_foreign_account<T: AccountDeserialize + Discriminator>(
+    info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<T> {
+    require_keys_eq!(
+        *info.owner,
+        *program_id,
+        MarketplaceError::AccountTypeMismatch
+    );
+    verify_discriminator::<T>(info)?;
+
+    let data = info.try_borrow_data()?;
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Fail with `ProtocolPaused` while the MCP token global config has the
+/// protocol-wide pause set. `info` must already be validated as that PDA.
+pub fn assert_not_globally_paused(info: &AccountInfo) -> Result<()> {
+    let config = load_foreign_account::<GlobalConfig>(info, &mcp_token::ID)?;
+    require!(!config.global_paused, MarketplaceError::ProtocolPaused);
+
+    Ok(())
+}
+
+/// Size of the header of an Ed25519 program instruction: signature count and padding
+const ED25519_HEADER_LEN: usize = 2;
+
+/// Size of one `Ed25519SignatureOffsets` entry
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Check that the instruction right before the current one is an Ed25519
+/// program instruction verifying a signature by `signer` over `message`.
+///
+/// The Ed25519 program has already rejected the transaction if the signature
+/// is invalid; this only checks that it covered the expected key and message,
+/// both stored in the Ed25519 instruction's own data.
+pub fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, MarketplaceError::InvalidSignature);
+    let ix = load_instruction_at_checked(current as usize - 1, instructions)?;
+    require_keys_eq!(
+        ix.program_id,
+        ed25519_program::ID,
+        MarketplaceError::InvalidSignature
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        MarketplaceError::InvalidSignature
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_HEADER_LEN;
+    let public_key_offset = read_u16(offsets + 4) as usize;
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_size = read_u16(offsets + 10) as usize;
+    // Every part must come from this instruction's data, not another instruction
+    require!(
+        [2, 6, 12]
+            .iter()
+            .all(|at| read_u16(offsets + at) == u16::MAX),
+        MarketplaceError::InvalidSignature
+    );
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(MarketplaceError::InvalidSignature)?;
+    let signed = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(MarketplaceError::InvalidSignature)?;
+    require!(
+        public_key == signer.as_ref() && signed == message,
+        MarketplaceError::InvalidSignature
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use anchor_lang::solana_program::entrypoint::{ProgramResult, SUCCESS};
+    use anchor_lang::solana_program::instruction::Instruction;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+    use anchor_lang::solana_program::sysvar::{self, Sysvar};
+    use anchor_spl::token::spl_token::state::{
+        Account as SplAccount, AccountState, Mint as SplMint,
+    };
+    use std::cell::RefCell;
+    use std::sync::Once;
+
+    #[test]
+    fn missing_bump_returns_bump_not_found() {
+        let mut bumps = BTreeMap::new();
+        bumps.insert("mint_authority".to_string(), 254);
+
+        assert_eq!(get_bump(&bumps, "mint_authority").unwrap(), 254);
+        assert_eq!(
+            get_bump(&bumps, "missing").unwrap_err(),
+            MarketplaceError::BumpNotFound.into()
+        );
+    }
+
+    #[test]
+    fn vec_over_bound_rejected() {
+        for max in [MAX_TIERS, MAX_ROYALTY_RECIPIENTS, MAX_TAGS] {
+            validate_bounded_vec(&vec![0u8; max], max).unwrap();
+            assert_eq!(
+                validate_bounded_vec(&vec![0u8; max + 1], max).unwrap_err(),
+                MarketplaceError::TooManyEntries.into()
+            );
+        }
+    }
+
+    #[test]
+    fn grown_account_without_top_up_not_rent_exempt() {
+        let rent = Rent::default();
+        let lamports = rent.minimum_balance(100);
+
+        check_rent_exempt(&rent, lamports, 100).unwrap();
+        assert_eq!(
+            check_rent_exempt(&rent, lamports, 200).unwrap_err(),
+            MarketplaceError::NotRentExempt.into()
+        );
+        check_rent_exempt(&rent, rent.minimum_balance(200), 200).unwrap();
+    }
+
+    /// Serialized MCP token global config with the protocol-wide pause set or not
+    pub(crate) fn global_config_data(global_paused: bool) -> Vec<u8> {
+        let config = GlobalConfig {
+            admin: Pubkey::new_unique(),
+            mint_limit_per_window: 0,
+            window_seconds: 0,
+            global_paused,
+            clock_skew_tolerance: 0,
+            global_freeze: false,
+            freeze_admins: Vec::new(),
+            config_frozen: false,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn global_pause_blocks_purchases() {
+        for global_paused in [false, true] {
+            let mut data = global_config_data(global_paused);
+            let key = Pubkey::new_unique();
+            let owner = mcp_token::ID;
+            let mut lamports = 0;
+            let info = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+
+            let result = assert_not_globally_paused(&info);
+            if global_paused {
+                assert_eq!(result.unwrap_err(), MarketplaceError::ProtocolPaused.into());
+            } else {
+                assert!(result.is_ok());
+            }
+        }
+    }
+
+    /// Time `Clock::get` returns to handlers under test
+    pub(crate) const TEST_NOW: i64 = 1_000;
+
+    thread_local! {
+        static CPIS: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Syscalls for running handlers off-chain: the default rent, a clock at
+    /// `TEST_NOW`, and CPIs recorded per thread instead of executed
+    struct TestSyscalls;
+
+    impl SyscallStubs for TestSyscalls {
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            _account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            CPIS.with(|cpis| cpis.borrow_mut().push(instruction.clone()));
+            Ok(())
+        }
+
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                unix_timestamp: TEST_NOW,
+                ..Clock::default()
+            };
+            // SAFETY: `Clock::get` passes a pointer to a `Clock`
+            unsafe { *(var_addr as *mut Clock) = clock };
+            SUCCESS
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            // SAFETY: `Rent::get` passes a pointer to a `Rent`
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+    }
+
+    /// Install `TestSyscalls` and forget the CPIs recorded so far on this thread
+    pub(crate) fn install_test_syscalls() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(TestSyscalls));
+        });
+        take_cpis();
+    }
+
+    /// CPIs made on this thread since the last call
+    pub(crate) fn take_cpis() -> Vec<Instruction> {
+        CPIS.with(|cpis| cpis.take())
+    }
+
+    /// Bytes ahead of an account's data where the runtime keeps its length
+    const DATA_LEN_HEADER: usize = 8;
+
+    /// Account state owned by a test and lent to handlers as an `AccountInfo`.
+    ///
+    /// Laid out like the runtime's input, with the original data length just
+    /// ahead of `key` and the data length ahead of the data, for
+    /// `AccountInfo::realloc` and so Anchor's `close` on exit
+    #[repr(C)]
+    pub(crate) struct TestAccount {
+        original_data_len: u32,
+        pub(crate) key: Pubkey,
+        pub(crate) owner: Pubkey,
+        pub(crate) lamports: u64,
+        data: Vec<u8>,
+        pub(crate) is_signer: bool,
+        pub(crate) is_writable: bool,
+        pub(crate) executable: bool,
+    }
+
+    impl TestAccount {
+        /// A rent-exempt, read-only account holding `data`
+        pub(crate) fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+            let lamports = Rent::default().minimum_balance(data.len());
+            let original_data_len = data.len() as u32;
+            let mut buffer = (data.len() as u64).to_le_bytes().to_vec();
+            buffer.extend(data);
+            Self {
+                original_data_len,
+                key,
+                owner,
+                lamports,
+                data: buffer,
+                is_signer: false,
+                is_writable: false,
+                executable: false,
+            }
+        }
+
+        /// A program state account serialized into `space` bytes
+        pub(crate) fn state(key: Pubkey, account: &impl AccountSerialize, space: usize) -> Self {
+            let mut data = Vec::new();
+            account.try_serialize(&mut data).unwrap();
+            data.resize(space, 0);
+            Self::new(key, crate::ID, data)
+        }
+
+        /// A wallet without data
+        pub(crate) fn wallet(key: Pubkey) -> Self {
+            Self::new(key, System::id(), Vec::new())
+        }
+
+        /// An executable program
+        pub(crate) fn program(key: Pubkey) -> Self {
+            Self {
+                executable: true,
+                ..Self::new(key, Pubkey::default(), Vec::new())
+            }
+        }
+
+        pub(crate) fn signer(self) -> Self {
+            Self {
+                is_signer: true,
+                ..self
+            }
+        }
+
+        pub(crate) fn writable(self) -> Self {
+            Self {
+                is_writable: true,
+                ..self
+            }
+        }
+
+        pub(crate) fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                self.is_signer,
+                self.is_writable,
+                &mut self.lamports,
+                &mut self.data[DATA_LEN_HEADER..],
+                &self.owner,
+                self.executable,
+                0,
+            )
+        }
+    }
+
+    impl TestAccount {
+        /// An SPL token account of `owner` holding `amount` of `mint`
+        pub(crate) fn token_account(key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Self {
+            let mut data = vec![0; SplAccount::LEN];
+            SplAccount {
+                mint,
+                owner,
+                amount,
+                state: AccountState::Initialized,
+                ..SplAccount::default()
+            }
+            .pack_into_slice(&mut data);
+            Self::new(key, anchor_spl::token::ID, data)
+        }
+
+        /// An SPL mint with 6 decimals
+        pub(crate) fn mint(key: Pubkey) -> Self {
+            let mut data = vec![0; SplMint::LEN];
+            SplMint {
+                mint_authority: COption::None,
+                supply: 0,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            }
+            .pack_into_slice(&mut data);
+            Self::new(key, anchor_spl::token::ID, data)
+        }
+
+        /// The rent sysvar holding the default rent
+        pub(crate) fn rent_sysvar() -> Self {
+            let mut account = Self::new(sysvar::rent::ID, sysvar::ID, vec![0; Rent::size_of()]);
+            Rent::default()
+                .to_account_info(&mut account.info())
+                .unwrap();
+            account
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! vaults_module module for data marketplace
+//!
+//! This module provides the canonical PDA seeds for marketplace token vaults.
+//! Instructions and off-chain clients must derive vault addresses through these
+//! helpers so the seeds cannot drift between call sites.
+
+use anchor_lang::prelude::*;
+
+/// Seed prefix of the escrow state account, which is also the vault authority
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// Seed prefix of the token vault holding an escrow's funds
+pub const ESCROW_VAULT_SEED: &[u8] = b"escrow-vault";
+
+/// Seed prefix of the token vault holding an escrow's creator royalty
+pub const ROYALTY_VAULT_SEED: &[u8] = b"royalty-vault";
+
+/// Seed prefix of the SOL escrow, which holds the escrowed lamports itself
+pub const SOL_ESCROW_SEED: &[u8] = b"sol-escrow";
+
+/// Seed prefix of the auction state account, which is also the bid vault authority
+pub const AUCTION_SEED: &[u8] = b"auction";
+
+/// Seed prefix of the token vault holding an auction's highest bid
+pub const AUCTION_VAULT_SEED: &[u8] = b"auction-vault";
+
+/// Seed prefix of the subscription state account, which is also the vault authority
+pub const SUBSCRIPTION_SEED: &[u8] = b"subscription";
+
+/// Seed prefix of the token vault holding a subscription's current period payment
+pub const SUBSCRIPTION_VAULT_SEED: &[u8] = b"subscription-vault";
+
+/// Seed prefix of the token vault holding a listing's seller collateral
+pub const COLLATERAL_VAULT_SEED: &[u8] = b"collateral-vault";
+
+/// Seed prefix of the token vault collecting protocol fees for a mint
+pub const FEE_VAULT_SEED: &[u8] = b"fee-vault";
+
+/// Seed of the token vault backing marketplace credits, which is its own authority
+pub const CREDITS_VAULT_SEED: &[u8] = b"credits-vault";
+
+/// Seeds of the escrow for `buyer`'s purchase of `listing`
+pub fn escrow_seeds<'a>(listing: &'a Pubkey, buyer: &'a Pubkey) -> [&'a [u8]; 3] {
+    [ESCROW_SEED, listing.as_ref(), buyer.as_ref()]
+}
+
+/// Seeds used when the escrow signs for its vault
+pub fn escrow_signer_seeds<'a>(
+    listing: &'a Pubkey,
+    buyer: &'a Pubkey,
+    bump: &'a [u8; 1],
+) -> [&'a [u8]; 4] {
+    [ESCROW_SEED, listing.as_ref(), buyer.as_ref(), bump.as_ref()]
+}
+
+/// Seeds of the token vault owned by `escrow`
+pub fn escrow_vault_seeds(escrow: &Pubkey) -> [&[u8]; 2] {
+    [ESCROW_VAULT_SEED, escrow.as_ref()]
+}
+
+/// Seeds of the royalty vault owned by `escrow`
+pub fn royalty_vault_seeds(escrow: &Pubkey) -> [&[u8]; 2] {
+    [ROYALTY_VAULT_SEED, escrow.as_ref()]
+}
+
+/// Seeds of the collateral vault owned by `listing`
+pub fn collateral_vault_seeds(listing: &Pubkey) -> [&[u8]; 2] {
+    [COLLATERAL_VAULT_SEED, listing.as_ref()]
+}
+
+/// Seeds of the protocol fee vault for `mint`
+pub fn fee_vault_seeds(mint: &Pubkey) -> [&[u8]; 2] {
+    [FEE_VAULT_SEED, mint.as_ref()]
+}
+
+/// Derive the escrow address for `buyer`'s purchase of `listing`
+pub fn find_escrow_address(listing: &Pubkey, buyer: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&escrow_seeds(listing, buyer), program_id)
+}
+
+/// Derive the token vault address owned by `escrow`
+pub fn find_escrow_vault_address(escrow: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&escrow_vault_seeds(escrow), program_id)
+}
+
+/// Derive the collateral vault address owned by `listing`
+pub fn find_collateral_vault_address(listing: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&collateral_vault_seeds(listing), program_id)
+}
+
+/// Derive the protocol fee vault address for `mint`
+pub fn find_fee_vault_address(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&fee_vault_seeds(mint), program_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_addresses_match_off_chain_derivation() {
+        let program_id = crate::ID;
+        let listing = Pubkey::new_unique();
+        let buyer = Pubkey::new_unique();
+        let derive = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id);
+
+        let (escrow, bump) = find_escrow_address(&listing, &buyer, &program_id);
+        assert_eq!(
+            (escrow, bump),
+            derive(&[b"escrow", listing.as_ref(), buyer.as_ref()])
+        );
+        assert_eq!(
+            Pubkey::create_program_address(
+                &escrow_signer_seeds(&listing, &buyer, &[bump]),
+                &program_id
+            )
+            .unwrap(),
+            escrow
+        );
+        assert_eq!(
+            find_escrow_vault_address(&escrow, &program_id),
+            derive(&[b"escrow-vault", escrow.as_ref()])
+        );
+        assert_eq!(
+            find_collateral_vault_address(&listing, &program_id),
+            derive(&[b"collateral-vault", listing.as_ref()])
+        );
+        assert_eq!(
+            find_fee_vault_address(&buyer, &program_id),
+            derive(&[b"fee-vault", buyer.as_ref()])
+        );
+    }
+}
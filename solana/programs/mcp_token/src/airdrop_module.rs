@@ -0,0 +1,523 @@
+//! airdrop_module module for MCP token
+//!
+//! This module provides Merkle airdrops distributed in waves. Each wave is a
+//! generation with its own Merkle root and its own claim bitmap, so indices
+//! claimed under an earlier root never block claims under a later one.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use crate::errors_module::McpTokenError;
+use crate::global_config_module::{GlobalConfig, GLOBAL_CONFIG_SEED};
+use crate::utils_module::get_bump;
+use crate::vaults_module::AIRDROP_VAULT_SEED;
+
+/// Number of leaves a single generation can hold
+pub const MAX_CLAIMS_PER_GENERATION: u64 = 8192;
+
+/// AirdropDistributor state account
+#[account]
+pub struct AirdropDistributor {
+    /// The admin that can update this account
+    pub admin: Pubkey,
+
+    /// Unique distributor identifier
+    pub distributor_id: u64,
+
+    /// Mint being distributed
+    pub mint: Pubkey,
+
+    /// Merkle root of the current generation
+    pub merkle_root: [u8; 32],
+
+    /// Current generation, incremented every time the root is replaced
+    pub generation: u64,
+
+    /// Time after which claims are rejected and the vault can be swept
+    pub claim_deadline: i64,
+
+    /// Set once unclaimed funds have been swept; no further claims are accepted
+    pub closed: bool,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl AirdropDistributor {
+    pub const LEN: usize = 32 + 8 + 32 + 32 + 8 + 8 + 1 + 1;
+
+    /// Check that claims are accepted at `now`
+    pub fn assert_claimable(&self, now: i64) -> Result<()> {
+        require!(
+            !self.closed && now <= self.claim_deadline,
+            McpTokenError::ClaimWindowClosed
+        );
+
+        Ok(())
+    }
+
+    /// Check that the unclaimed funds can be swept at `now`
+    pub fn assert_sweepable(&self, now: i64) -> Result<()> {
+        require!(
+            now > self.claim_deadline,
+            McpTokenError::ClaimDeadlineNotReached
+        );
+
+        Ok(())
+    }
+
+    /// Replace the Merkle root and move on to the next generation
+    pub fn advance_generation(&mut self, new_root: [u8; 32]) -> Result<()> {
+        require!(!self.closed, McpTokenError::ClaimWindowClosed);
+        self.merkle_root = new_root;
+        self.generation = self
+            .generation
+            .checked_add(1)
+            .ok_or(McpTokenError::MathOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// Claim bitmap for a single generation of a distributor
+#[account]
+pub struct ClaimBitmap {
+    /// The distributor this bitmap belongs to
+    pub distributor: Pubkey,
+
+    /// The generation this bitmap tracks
+    pub generation: u64,
+
+    /// One bit per leaf index, set once claimed
+    pub bits: [u8; (MAX_CLAIMS_PER_GENERATION / 8) as usize],
+}
+
+impl ClaimBitmap {
+    pub const LEN: usize = 32 + 8 + (MAX_CLAIMS_PER_GENERATION / 8) as usize;
+
+    pub fn is_claimed(&self, index: u64) -> bool {
+        self.bits[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u64) {
+        self.bits[(index / 8) as usize] |= 1 << (index % 8);
+    }
+}
+
+/// Emitted when a claim is paid out
+#[event]
+pub struct AirdropClaimed {
+    pub distributor: Pubkey,
+    pub generation: u64,
+    pub index: u64,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+/// Verify a Merkle proof of `leaf` against `root` using sorted pair hashing
+pub fn verify_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).0
+        } else {
+            keccak::hashv(&[sibling, &node]).0
+        }
+    });
+    computed == root
+}
+
+/// Merkle leaf entitling `claimant` to `amount` at `index`
+pub fn claim_leaf(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &index.to_le_bytes(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Initialize a new AirdropDistributor and the bitmap of its first generation
+pub fn initialize_distributor(
+    ctx: Context<InitializeDistributor>,
+    distributor_id: u64,
+    merkle_root: [u8; 32],
+    claim_deadline: i64,
+) -> Result<()> {
+    require!(
+        claim_deadline > Clock::get()?.unix_timestamp,
+        McpTokenError::InvalidDeadline
+    );
+
+    let distributor = &mut ctx.accounts.distributor;
+    distributor.admin = ctx.accounts.admin.key();
+    distributor.distributor_id = distributor_id;
+    distributor.mint = ctx.accounts.mint.key();
+    distributor.merkle_root = merkle_root;
+    distributor.generation = 0;
+    distributor.claim_deadline = claim_deadline;
+    distributor.closed = false;
+    distributor.bump = get_bump(&ctx.bumps, "distributor")?;
+
+    let bitmap = &mut ctx.accounts.claim_bitmap;
+    bitmap.distributor = distributor.key();
+    bitmap.generation = 0;
+
+    Ok(())
+}
+
+/// Start a new airdrop wave.
+///
+/// Replaces the Merkle root and bumps `generation`. Claims are tracked in a
+/// fresh bitmap for the new generation, so leaf indices can be reused freely
+/// across waves; bitmaps of earlier generations are left untouched.
+pub fn update_merkle_root(ctx: Context<UpdateMerkleRoot>, new_root: [u8; 32]) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+    distributor.advance_generation(new_root)?;
+
+    let bitmap = &mut ctx.accounts.claim_bitmap;
+    bitmap.distributor = distributor.key();
+    bitmap.generation = distributor.generation;
+
+    Ok(())
+}
+
+/// Claim `amount` for leaf `index` of the current generation
+pub fn claim_airdrop(
+    ctx: Context<ClaimAirdrop>,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    require!(
+        index < MAX_CLAIMS_PER_GENERATION,
+        McpTokenError::InvalidProof
+    );
+    let distributor = &ctx.accounts.distributor;
+    let claimant = ctx.accounts.claimant.key();
+    distributor.assert_claimable(Clock::get()?.unix_timestamp)?;
+
+    let leaf = claim_leaf(index, &claimant, amount);
+    require!(
+        verify_proof(&proof, distributor.merkle_root, leaf),
+        McpTokenError::InvalidProof
+    );
+    require!(
+        !ctx.accounts.claim_bitmap.is_claimed(index),
+        McpTokenError::AlreadyClaimed
+    );
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.airdrop_vault.to_account_info(),
+                to: ctx.accounts.claimant_token_account.to_account_info(),
+                authority: distributor.to_account_info(),
+            },
+            &[&[
+                b"distributor".as_ref(),
+                distributor.distributor_id.to_le_bytes().as_ref(),
+                &[distributor.bump],
+            ]],
+        ),
+        amount,
+    )?;
+
+    ctx.accounts.claim_bitmap.set_claimed(index);
+
+    emit!(AirdropClaimed {
+        distributor: distributor.key(),
+        generation: distributor.generation,
+        index,
+        claimant,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Return unclaimed tokens to the admin once the claim deadline has passed.
+///
+/// Closes the distributor: no further claims or root updates are accepted.
+pub fn sweep_unclaimed(ctx: Context<SweepUnclaimed>) -> Result<()> {
+    let distributor = &ctx.accounts.distributor;
+    distributor.assert_sweepable(Clock::get()?.unix_timestamp)?;
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.airdrop_vault.to_account_info(),
+                to: ctx.accounts.admin_token_account.to_account_info(),
+                authority: distributor.to_account_info(),
+            },
+            &[&[
+                b"distributor".as_ref(),
+                distributor.distributor_id.to_le_bytes().as_ref(),
+                &[distributor.bump],
+            ]],
+        ),
+        ctx.accounts.airdrop_vault.amount,
+    )?;
+
+    ctx.accounts.distributor.closed = true;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+#[instruction(distributor_id: u64)]
+pub struct InitializeDistributor<'info> {
+    /// The admin of the distributor
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AirdropDistributor::LEN,
+        seeds = [b"distributor".as_ref(), distributor_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub distributor: Account<'info, AirdropDistributor>,
+
+    /// Claim bitmap of generation 0
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ClaimBitmap::LEN,
+        seeds = [b"claim-bitmap".as_ref(), distributor.key().as_ref(), 0u64.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub claim_bitmap: Box<Account<'info, ClaimBitmap>>,
+
+    /// Vault holding the tokens to distribute
+    #[account(
+        init,
+        payer = admin,
+        seeds = [AIRDROP_VAULT_SEED, distributor.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = distributor,
+    )]
+    pub airdrop_vault: Account<'info, TokenAccount>,
+
+    /// Mint being distributed
+    pub mint: Account<'info, Mint>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for root update
+#[derive(Accounts)]
+pub struct UpdateMerkleRoot<'info> {
+    /// The admin of the distributor
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"distributor".as_ref(), distributor.distributor_id.to_le_bytes().as_ref()],
+        bump = distributor.bump,
+        has_one = admin,
+    )]
+    pub distributor: Account<'info, AirdropDistributor>,
+
+    /// Claim bitmap of the next generation
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ClaimBitmap::LEN,
+        seeds = [
+            b"claim-bitmap".as_ref(),
+            distributor.key().as_ref(),
+            (distributor.generation + 1).to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub claim_bitmap: Box<Account<'info, ClaimBitmap>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for claim
+#[derive(Accounts)]
+pub struct ClaimAirdrop<'info> {
+    /// The wallet named in the Merkle leaf
+    pub claimant: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The distributor being claimed from
+    #[account(
+        seeds = [b"distributor".as_ref(), distributor.distributor_id.to_le_bytes().as_ref()],
+        bump = distributor.bump,
+    )]
+    pub distributor: Account<'info, AirdropDistributor>,
+
+    /// Claim bitmap of the current generation
+    #[account(
+        mut,
+        seeds = [
+            b"claim-bitmap".as_ref(),
+            distributor.key().as_ref(),
+            distributor.generation.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub claim_bitmap: Box<Account<'info, ClaimBitmap>>,
+
+    /// Vault holding the tokens to distribute
+    #[account(
+        mut,
+        seeds = [AIRDROP_VAULT_SEED, distributor.key().as_ref()],
+        bump,
+    )]
+    pub airdrop_vault: Account<'info, TokenAccount>,
+
+    /// Claimant's token account
+    #[account(
+        mut,
+        token::mint = distributor.mint,
+        token::authority = claimant,
+    )]
+    pub claimant_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for sweep
+#[derive(Accounts)]
+pub struct SweepUnclaimed<'info> {
+    /// The admin of the distributor
+    pub admin: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"distributor".as_ref(), distributor.distributor_id.to_le_bytes().as_ref()],
+        bump = distributor.bump,
+        has_one = admin,
+    )]
+    pub distributor: Account<'info, AirdropDistributor>,
+
+    /// Vault holding the tokens to distribute
+    #[account(
+        mut,
+        seeds = [AIRDROP_VAULT_SEED, distributor.key().as_ref()],
+        bump,
+    )]
+    pub airdrop_vault: Account<'info, TokenAccount>,
+
+    /// Admin's token account receiving the unclaimed tokens
+    #[account(
+        mut,
+        token::mint = distributor.mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distributor() -> AirdropDistributor {
+        AirdropDistributor {
+            admin: Pubkey::new_unique(),
+            distributor_id: 1,
+            mint: Pubkey::new_unique(),
+            merkle_root: [0; 32],
+            generation: 0,
+            claim_deadline: 1_000,
+            closed: false,
+            bump: 255,
+        }
+    }
+
+    fn bitmap(generation: u64) -> ClaimBitmap {
+        ClaimBitmap {
+            distributor: Pubkey::new_unique(),
+            generation,
+            bits: [0; (MAX_CLAIMS_PER_GENERATION / 8) as usize],
+        }
+    }
+
+    /// Root of a two-leaf tree and the proof of each leaf
+    fn tree(a: [u8; 32], b: [u8; 32]) -> ([u8; 32], Vec<[u8; 32]>, Vec<[u8; 32]>) {
+        let root = if a <= b {
+            keccak::hashv(&[&a, &b]).0
+        } else {
+            keccak::hashv(&[&b, &a]).0
+        };
+        (root, vec![b], vec![a])
+    }
+
+    #[test]
+    fn new_leaf_claimable_after_root_update() {
+        let claimant = Pubkey::new_unique();
+        let mut distributor = distributor();
+        let old_leaf = claim_leaf(0, &claimant, 100);
+        let (old_root, old_proof, _) = tree(old_leaf, claim_leaf(1, &claimant, 5));
+        distributor.merkle_root = old_root;
+        let mut old_bitmap = bitmap(0);
+        assert!(verify_proof(&old_proof, distributor.merkle_root, old_leaf));
+        old_bitmap.set_claimed(0);
+
+        let new_leaf = claim_leaf(0, &claimant, 250);
+        let (new_root, new_proof, _) = tree(new_leaf, claim_leaf(1, &claimant, 7));
+        distributor.advance_generation(new_root).unwrap();
+        let new_bitmap = bitmap(distributor.generation);
+
+        assert_eq!(distributor.generation, 1);
+        assert!(verify_proof(&new_proof, distributor.merkle_root, new_leaf));
+        assert!(!verify_proof(&old_proof, distributor.merkle_root, old_leaf));
+        assert!(old_bitmap.is_claimed(0));
+        assert!(!new_bitmap.is_claimed(0));
+    }
+
+    #[test]
+    fn claims_accepted_until_deadline_then_swept() {
+        let mut distributor = distributor();
+
+        distributor.assert_claimable(1_000).unwrap();
+        assert_eq!(
+            distributor.assert_sweepable(1_000).unwrap_err(),
+            McpTokenError::ClaimDeadlineNotReached.into()
+        );
+
+        distributor.assert_sweepable(1_001).unwrap();
+        assert!(distributor.assert_claimable(1_001).is_err());
+        distributor.closed = true;
+        assert_eq!(
+            distributor.assert_claimable(500).unwrap_err(),
+            McpTokenError::ClaimWindowClosed.into()
+        );
+        assert_eq!(
+            distributor.advance_generation([1; 32]).unwrap_err(),
+            McpTokenError::ClaimWindowClosed.into()
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! blacklist_module module for MCP token
+//!
+//! This module provides an opt-in per-mint blacklist enforced by
+//! `transfer_tokens` on both the sending and receiving side. Unlike an SPL
+//! freeze it is checked by this program, so it also blocks incoming transfers.
+//! Entries are managed by a compliance authority appointed by the mint
+//! authority; mints without a Blacklist transfer freely.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::errors_module::McpTokenError;
+use crate::utils_module::get_bump;
+use crate::MintAuthority;
+
+/// Maximum number of blacklisted keys per mint
+pub const MAX_BLACKLIST_ENTRIES: usize = 64;
+
+/// Blacklist state account
+#[account]
+pub struct Blacklist {
+    /// The compliance authority that manages entries
+    pub compliance_authority: Pubkey,
+
+    /// The mint the blacklist applies to
+    pub mint: Pubkey,
+
+    /// Blacklisted wallets and token accounts
+    pub entries: Vec<Pubkey>,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Blacklist {
+    pub const LEN: usize = 32 + 32 + (4 + 32 * MAX_BLACKLIST_ENTRIES) + 1;
+
+    pub fn is_blacklisted(&self, key: &Pubkey) -> bool {
+        self.entries.contains(key)
+    }
+
+    /// Reject a token account that is blacklisted itself or owned by a
+    /// blacklisted wallet
+    pub fn assert_not_blacklisted(&self, account: &Account<TokenAccount>) -> Result<()> {
+        require!(
+            !self.is_blacklisted(&account.key()) && !self.is_blacklisted(&account.owner),
+            McpTokenError::Blacklisted
+        );
+
+        Ok(())
+    }
+}
+
+/// Reject a transfer from or to a blacklisted account.
+///
+/// Does nothing while the mint has no Blacklist.
+pub fn assert_transfer_not_blacklisted(
+    blacklist: &AccountInfo,
+    from: &Account<TokenAccount>,
+    to: &Account<TokenAccount>,
+) -> Result<()> {
+    if blacklist.data_is_empty() {
+        return Ok(());
+    }
+    let blacklist = Account::<Blacklist>::try_from(blacklist)?;
+    blacklist.assert_not_blacklisted(from)?;
+    blacklist.assert_not_blacklisted(to)
+}
+
+/// Initialize the Blacklist of a mint under `compliance_authority`
+pub fn initialize_blacklist(
+    ctx: Context<InitializeBlacklist>,
+    compliance_authority: Pubkey,
+) -> Result<()> {
+    let blacklist = &mut ctx.accounts.blacklist;
+    blacklist.compliance_authority = compliance_authority;
+    blacklist.mint = ctx.accounts.mint.key();
+    blacklist.entries = Vec::new();
+    blacklist.bump = get_bump(&ctx.bumps, "blacklist")?;
+
+    Ok(())
+}
+
+/// Blacklist `key`; adding an existing entry is a no-op
+pub fn add_to_blacklist(ctx: Context<UpdateBlacklist>, key: Pubkey) -> Result<()> {
+    let blacklist = &mut ctx.accounts.blacklist;
+    if blacklist.is_blacklisted(&key) {
+        return Ok(());
+    }
+    require!(
+        blacklist.entries.len() < MAX_BLACKLIST_ENTRIES,
+        McpTokenError::TooManyEntries
+    );
+    blacklist.entries.push(key);
+
+    Ok(())
+}
+
+/// Remove `key` from the blacklist; removing a missing entry is a no-op
+pub fn remove_from_blacklist(ctx: Context<UpdateBlacklist>, key: Pubkey) -> Result<()> {
+    ctx.accounts.blacklist.entries.retain(|entry| *entry != key);
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeBlacklist<'info> {
+    /// The mint authority appointing the compliance authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The mint the blacklist applies to
+    pub mint: Account<'info, Mint>,
+
+    /// Mint authority of `mint`
+    #[account(
+        seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
+        bump = mint_authority.bump,
+        has_one = authority,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Blacklist::LEN,
+        seeds = [b"blacklist".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateBlacklist<'info> {
+    /// The compliance authority of the blacklist
+    pub compliance_authority: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"blacklist".as_ref(), blacklist.mint.as_ref()],
+        bump = blacklist.bump,
+        has_one = compliance_authority,
+    )]
+    pub blacklist: Account<'info, Blacklist>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+
+    /// Packed SPL token account of `owner` holding `mint`
+    fn token_account_data(mint: Pubkey, owner: Pubkey) -> Vec<u8> {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    #[test]
+    fn transfers_to_and_from_blacklisted_account_rejected() {
+        let (clean_owner, blacklisted_owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let blacklist = Blacklist {
+            compliance_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            entries: vec![blacklisted_owner],
+            bump: 255,
+        };
+        let mut blacklist_data = Vec::new();
+        blacklist.try_serialize(&mut blacklist_data).unwrap();
+        let (blacklist_key, clean_key, blacklisted_key) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut clean_data = token_account_data(blacklist.mint, clean_owner);
+        let mut blacklisted_data = token_account_data(blacklist.mint, blacklisted_owner);
+        let (mut blacklist_lamports, mut clean_lamports, mut blacklisted_lamports) = (0, 0, 0);
+        let (program_id, token_program) = (crate::ID, spl_token::ID);
+        let blacklist_info = AccountInfo::new(
+            &blacklist_key,
+            false,
+            false,
+            &mut blacklist_lamports,
+            &mut blacklist_data,
+            &program_id,
+            false,
+            0,
+        );
+        let clean_info = AccountInfo::new(
+            &clean_key,
+            false,
+            true,
+            &mut clean_lamports,
+            &mut clean_data,
+            &token_program,
+            false,
+            0,
+        );
+        let blacklisted_info = AccountInfo::new(
+            &blacklisted_key,
+            false,
+            true,
+            &mut blacklisted_lamports,
+            &mut blacklisted_data,
+            &token_program,
+            false,
+            0,
+        );
+        let clean = Account::<TokenAccount>::try_from(&clean_info).unwrap();
+        let blacklisted = Account::<TokenAccount>::try_from(&blacklisted_info).unwrap();
+
+        assert_transfer_not_blacklisted(&blacklist_info, &clean, &clean).unwrap();
+        for (from, to) in [(&blacklisted, &clean), (&clean, &blacklisted)] {
+            assert_eq!(
+                assert_transfer_not_blacklisted(&blacklist_info, from, to).unwrap_err(),
+                McpTokenError::Blacklisted.into()
+            );
+        }
+    }
+
+    #[test]
+    fn mint_without_blacklist_transfers_freely() {
+        let (blacklist_key, from_key, to_key) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mint = Pubkey::new_unique();
+        let mut blacklist_data = [];
+        let mut from_data = token_account_data(mint, Pubkey::new_unique());
+        let mut to_data = token_account_data(mint, Pubkey::new_unique());
+        let (mut blacklist_lamports, mut from_lamports, mut to_lamports) = (0, 0, 0);
+        let (system_program, token_program) = (System::id(), spl_token::ID);
+        let blacklist_info = AccountInfo::new(
+            &blacklist_key,
+            false,
+            false,
+            &mut blacklist_lamports,
+            &mut blacklist_data,
+            &system_program,
+            false,
+            0,
+        );
+        let from_info = AccountInfo::new(
+            &from_key,
+            false,
+            true,
+            &mut from_lamports,
+            &mut from_data,
+            &token_program,
+            false,
+            0,
+        );
+        let to_info = AccountInfo::new(
+            &to_key,
+            false,
+            true,
+            &mut to_lamports,
+            &mut to_data,
+            &token_program,
+            false,
+            0,
+        );
+        let from = Account::<TokenAccount>::try_from(&from_info).unwrap();
+        let to = Account::<TokenAccount>::try_from(&to_info).unwrap();
+
+        assert_transfer_not_blacklisted(&blacklist_info, &from, &to).unwrap();
+    }
+}
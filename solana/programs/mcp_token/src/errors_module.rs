@@ -0,0 +1,147 @@
+//! errors_module module for MCP token
+//!
+//! This module provides the error codes returned by MCP token instructions.
+
+use anchor_lang::prelude::*;
+
+/// MCP token errors
+#[error_code]
+pub enum McpTokenError {
+    #[msg("PDA bump not found")]
+    BumpNotFound,
+
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Insufficient stake")]
+    InsufficientStake,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+
+    #[msg("Authority was changed too recently")]
+    AuthorityChangeCooldown,
+
+    #[msg("Cooldown must not be negative")]
+    InvalidCooldown,
+
+    #[msg("Invalid Merkle proof")]
+    InvalidProof,
+
+    #[msg("Leaf has already been claimed")]
+    AlreadyClaimed,
+
+    #[msg("Deadline must be in the future")]
+    InvalidDeadline,
+
+    #[msg("Airdrop claims are closed")]
+    ClaimWindowClosed,
+
+    #[msg("Claim deadline has not passed")]
+    ClaimDeadlineNotReached,
+
+    #[msg("Pool has no stake to reward")]
+    NoStakers,
+
+    #[msg("Vesting schedule times must satisfy start <= cliff <= end")]
+    InvalidVestingSchedule,
+
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+
+    #[msg("Invalid vesting accounts")]
+    InvalidVestingAccounts,
+
+    #[msg("Voting on this proposal is closed")]
+    VotingClosed,
+
+    #[msg("Voting has not ended")]
+    VotingNotEnded,
+
+    #[msg("Proposal is not active")]
+    ProposalNotActive,
+
+    #[msg("Caller program is not allowed")]
+    UnauthorizedCaller,
+
+    #[msg("Too many entries")]
+    TooManyEntries,
+
+    #[msg("Proposal already has votes")]
+    ProposalHasVotes,
+
+    #[msg("Account is blacklisted")]
+    Blacklisted,
+
+    #[msg("Mint window must be positive when a limit is set")]
+    InvalidMintWindow,
+
+    #[msg("Mint would exceed the limit for the current window")]
+    MintLimitExceeded,
+
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+
+    #[msg("Treasury account is missing or does not match")]
+    InvalidTreasury,
+
+    #[msg("Penalty cannot exceed 10000 basis points")]
+    InvalidPenalty,
+
+    #[msg("Clock skew tolerance is out of range")]
+    InvalidClockSkewTolerance,
+
+    #[msg("Signer is not the backup authority")]
+    NotBackupAuthority,
+
+    #[msg("Authority has not been inactive long enough")]
+    AuthorityStillActive,
+
+    #[msg("Transfer fee is out of bounds")]
+    InvalidTransferFee,
+
+    #[msg("Received tokens are still within the hold period")]
+    HoldPeriodNotMet,
+
+    #[msg("Freeze admins must be distinct")]
+    InvalidFreezeAdmins,
+
+    #[msg("Not enough freeze admins signed")]
+    InsufficientFreezeSignatures,
+
+    #[msg("Token transfers are frozen")]
+    TransfersFrozen,
+
+    #[msg("No voting power recorded before the proposal")]
+    NoVotingSnapshot,
+
+    #[msg("Destination is not on the mint allowlist")]
+    MintDestinationNotAllowed,
+
+    #[msg("Batch exceeds the maximum size")]
+    BatchTooLarge,
+
+    #[msg("Voting period is shorter than the minimum")]
+    VotingPeriodTooShort,
+
+    #[msg("Voting period is longer than the maximum")]
+    VotingPeriodTooLong,
+
+    #[msg("Invalid voting period bounds")]
+    InvalidVotingPeriod,
+
+    #[msg("Stake is locked")]
+    StakeLocked,
+
+    #[msg("Signer does not hold the stake lock")]
+    NotStakeLocker,
+
+    #[msg("Configuration is frozen")]
+    ConfigFrozen,
+
+    #[msg("Inactivity period must be positive")]
+    InvalidInactivityPeriod,
+}
@@ -0,0 +1,551 @@
+//! global_config_module module for MCP token
+//!
+//! This module provides protocol-wide settings administered by a single
+//! admin: the limit on how much can be minted per time window, and the
+//! emergency pause that the marketplace and model registry read from here
+//! instead of keeping flags of their own.
+//!
+//! The global freeze, which also stops plain token transfers, is not under
+//! the single admin's control: toggling it, or replacing the freeze admins
+//! once they are set, takes two of the current freeze admins.
+//!
+//! `freeze_config` permanently fixes every setting except the pause, which
+//! must stay available for emergencies.
+
+use anchor_lang::prelude::*;
+
+use crate::errors_module::McpTokenError;
+use crate::utils_module::{get_bump, validate_bounded_vec};
+
+/// Seed of the GlobalConfig PDA, also used by other programs to locate it
+pub const GLOBAL_CONFIG_SEED: &[u8] = b"global-config";
+
+/// Upper bound on `GlobalConfig::clock_skew_tolerance`, in seconds
+pub const MAX_CLOCK_SKEW_TOLERANCE: i64 = 30;
+
+/// Maximum number of freeze admins
+pub const MAX_FREEZE_ADMINS: usize = 5;
+
+/// Distinct freeze admin signatures needed to toggle the global freeze
+pub const FREEZE_SIGNATURE_THRESHOLD: usize = 2;
+
+/// GlobalConfig state account
+#[account]
+pub struct GlobalConfig {
+    /// The admin that can update this account
+    pub admin: Pubkey,
+
+    /// Maximum amount `mint_tokens` can mint per window; zero disables the limit
+    pub mint_limit_per_window: u64,
+
+    /// Length of a mint window in seconds
+    pub window_seconds: i64,
+
+    /// Emergency pause honoured by every program of the protocol
+    pub global_paused: bool,
+
+    /// Seconds of validator clock skew forgiven when checking whether a
+    /// cooldown or window has elapsed
+    pub clock_skew_tolerance: i64,
+
+    /// Break-glass switch rejecting every token transfer
+    pub global_freeze: bool,
+
+    /// Keys allowed to co-sign toggling `global_freeze`
+    pub freeze_admins: Vec<Pubkey>,
+
+    /// Whether the settings are permanently frozen
+    pub config_frozen: bool,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl GlobalConfig {
+    pub const LEN: usize = 32 + 8 + 8 + 1 + 8 + 1 + (4 + MAX_FREEZE_ADMINS * 32) + 1 + 1;
+
+    /// Whether `deadline` has passed at `now`, allowing for clock skew
+    pub fn has_elapsed(&self, now: i64, deadline: i64) -> bool {
+        now.saturating_add(self.clock_skew_tolerance) >= deadline
+    }
+
+    /// Check that at least `FREEZE_SIGNATURE_THRESHOLD` distinct freeze admins
+    /// signed among `accounts`
+    pub fn assert_freeze_signers(&self, accounts: &[AccountInfo]) -> Result<()> {
+        let mut signers: Vec<Pubkey> = Vec::with_capacity(FREEZE_SIGNATURE_THRESHOLD);
+        for info in accounts {
+            if info.is_signer
+                && self.freeze_admins.contains(info.key)
+                && !signers.contains(info.key)
+            {
+                signers.push(*info.key);
+            }
+        }
+        require!(
+            signers.len() >= FREEZE_SIGNATURE_THRESHOLD,
+            McpTokenError::InsufficientFreezeSignatures
+        );
+
+        Ok(())
+    }
+
+    /// Check that the freeze admins may be replaced: the first set is chosen
+    /// by the admin, later changes need `FREEZE_SIGNATURE_THRESHOLD` of the
+    /// current freeze admins among `accounts`
+    pub fn assert_freeze_admins_change(&self, accounts: &[AccountInfo]) -> Result<()> {
+        if self.freeze_admins.is_empty() {
+            return Ok(());
+        }
+
+        self.assert_freeze_signers(accounts)
+    }
+
+    /// Check that the protocol-wide pause is not set
+    pub fn assert_not_paused(&self) -> Result<()> {
+        require!(!self.global_paused, McpTokenError::ProtocolPaused);
+
+        Ok(())
+    }
+
+    /// Check that token transfers are not frozen
+    pub fn assert_transfers_allowed(&self) -> Result<()> {
+        require!(!self.global_freeze, McpTokenError::TransfersFrozen);
+
+        Ok(())
+    }
+}
+
+/// Initialize the GlobalConfig with minting unlimited
+pub fn initialize_global_config(ctx: Context<InitializeGlobalConfig>) -> Result<()> {
+    let config = &mut ctx.accounts.global_config;
+    config.admin = ctx.accounts.admin.key();
+    config.mint_limit_per_window = 0;
+    config.window_seconds = 0;
+    config.global_paused = false;
+    config.clock_skew_tolerance = 0;
+    config.global_freeze = false;
+    config.freeze_admins = Vec::new();
+    config.config_frozen = false;
+    config.bump = get_bump(&ctx.bumps, "global_config")?;
+
+    Ok(())
+}
+
+/// Limit minting to `mint_limit_per_window` every `window_seconds`
+pub fn set_mint_rate_limit(
+    ctx: Context<UpdateGlobalConfig>,
+    mint_limit_per_window: u64,
+    window_seconds: i64,
+) -> Result<()> {
+    require!(
+        mint_limit_per_window == 0 || window_seconds > 0,
+        McpTokenError::InvalidMintWindow
+    );
+    let config = &mut ctx.accounts.global_config;
+    config.mint_limit_per_window = mint_limit_per_window;
+    config.window_seconds = window_seconds;
+
+    Ok(())
+}
+
+/// Pause or unpause the whole protocol.
+///
+/// Every mutating instruction of the token, marketplace and registry is
+/// rejected while paused, except the admins' own configuration. Plain token
+/// transfers stay available so holders are never locked out of their funds;
+/// halting them takes the global freeze.
+pub fn set_global_paused(ctx: Context<SetGlobalPaused>, global_paused: bool) -> Result<()> {
+    ctx.accounts.global_config.global_paused = global_paused;
+
+    Ok(())
+}
+
+/// Update the clock skew forgiven at time boundaries, up to `MAX_CLOCK_SKEW_TOLERANCE`
+pub fn set_clock_skew_tolerance(
+    ctx: Context<UpdateGlobalConfig>,
+    clock_skew_tolerance: i64,
+) -> Result<()> {
+    require!(
+        (0..=MAX_CLOCK_SKEW_TOLERANCE).contains(&clock_skew_tolerance),
+        McpTokenError::InvalidClockSkewTolerance
+    );
+    ctx.accounts.global_config.clock_skew_tolerance = clock_skew_tolerance;
+
+    Ok(())
+}
+
+/// Replace the keys allowed to co-sign the global freeze.
+///
+/// Once freeze admins are set, at least `FREEZE_SIGNATURE_THRESHOLD` of them
+/// must co-sign, passed as remaining accounts, so the admin alone cannot
+/// swap them for keys of their own.
+pub fn set_freeze_admins(
+    ctx: Context<UpdateGlobalConfig>,
+    freeze_admins: Vec<Pubkey>,
+) -> Result<()> {
+    validate_bounded_vec(&freeze_admins, MAX_FREEZE_ADMINS)?;
+    require!(
+        freeze_admins
+            .iter()
+            .enumerate()
+            .all(|(i, admin)| !freeze_admins[..i].contains(admin)),
+        McpTokenError::InvalidFreezeAdmins
+    );
+    let config = &mut ctx.accounts.global_config;
+    config.assert_freeze_admins_change(ctx.remaining_accounts)?;
+    config.freeze_admins = freeze_admins;
+
+    Ok(())
+}
+
+/// Freeze or unfreeze all token transfers.
+///
+/// At least `FREEZE_SIGNATURE_THRESHOLD` distinct freeze admins must sign,
+/// passed as remaining accounts, so a single compromised key cannot halt the
+/// token.
+pub fn set_global_freeze_multisig(
+    ctx: Context<SetGlobalFreeze>,
+    global_freeze: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.global_config;
+    config.assert_freeze_signers(ctx.remaining_accounts)?;
+    config.global_freeze = global_freeze;
+
+    Ok(())
+}
+
+/// Permanently freeze the configuration.
+///
+/// Every setter but `set_global_paused` fails with `ConfigFrozen` afterwards.
+pub fn freeze_config(ctx: Context<UpdateGlobalConfig>) -> Result<()> {
+    ctx.accounts.global_config.config_frozen = true;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    /// The protocol admin
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GlobalConfig::LEN,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateGlobalConfig<'info> {
+    /// The protocol admin
+    pub admin: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        has_one = admin,
+        constraint = !global_config.config_frozen @ McpTokenError::ConfigFrozen,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Account validation for the pause toggle, allowed even once frozen
+#[derive(Accounts)]
+pub struct SetGlobalPaused<'info> {
+    /// The protocol admin
+    pub admin: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        has_one = admin,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Account validation for the multisig freeze toggle
+#[derive(Accounts)]
+pub struct SetGlobalFreeze<'info> {
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+
+    /// A config without mint limit, pause, freeze or clock skew tolerance
+    pub(crate) fn global_config() -> GlobalConfig {
+        GlobalConfig {
+            admin: Pubkey::new_unique(),
+            mint_limit_per_window: 0,
+            window_seconds: 0,
+            global_paused: false,
+            clock_skew_tolerance: 0,
+            global_freeze: false,
+            freeze_admins: Vec::new(),
+            config_frozen: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn boundary_forgiven_within_clock_skew_tolerance() {
+        let mut config = global_config();
+        assert!(config.has_elapsed(1_000, 1_000));
+        assert!(!config.has_elapsed(999, 1_000));
+
+        config.clock_skew_tolerance = 5;
+        assert!(config.has_elapsed(995, 1_000));
+        assert!(!config.has_elapsed(994, 1_000));
+    }
+
+    #[test]
+    fn one_freeze_signature_fails_and_two_succeed() {
+        let mut config = global_config();
+        let admins = [Pubkey::new_unique(), Pubkey::new_unique()];
+        config.freeze_admins = admins.to_vec();
+        let (mut first_lamports, mut second_lamports) = (0, 0);
+        let (mut first_data, mut second_data) = ([], []);
+        let owner = System::id();
+        let first = AccountInfo::new(
+            &admins[0],
+            true,
+            false,
+            &mut first_lamports,
+            &mut first_data,
+            &owner,
+            false,
+            0,
+        );
+        let second = AccountInfo::new(
+            &admins[1],
+            true,
+            false,
+            &mut second_lamports,
+            &mut second_data,
+            &owner,
+            false,
+            0,
+        );
+
+        assert_eq!(
+            config
+                .assert_freeze_signers(std::slice::from_ref(&first))
+                .unwrap_err(),
+            McpTokenError::InsufficientFreezeSignatures.into()
+        );
+        assert_eq!(
+            config
+                .assert_freeze_signers(&[first.clone(), first.clone()])
+                .unwrap_err(),
+            McpTokenError::InsufficientFreezeSignatures.into()
+        );
+        config.assert_freeze_signers(&[first, second]).unwrap();
+    }
+
+    #[test]
+    fn freeze_admins_replaced_only_with_two_current_signatures() {
+        let mut config = global_config();
+        let admins = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let (mut first_lamports, mut second_lamports) = (0, 0);
+        let (mut first_data, mut second_data) = ([], []);
+        let owner = System::id();
+        let first = AccountInfo::new(
+            &admins[0],
+            true,
+            false,
+            &mut first_lamports,
+            &mut first_data,
+            &owner,
+            false,
+            0,
+        );
+        let second = AccountInfo::new(
+            &admins[1],
+            true,
+            false,
+            &mut second_lamports,
+            &mut second_data,
+            &owner,
+            false,
+            0,
+        );
+
+        config.assert_freeze_admins_change(&[]).unwrap();
+        config.freeze_admins = admins.to_vec();
+        assert_eq!(
+            config.assert_freeze_admins_change(&[]).unwrap_err(),
+            McpTokenError::InsufficientFreezeSignatures.into()
+        );
+        assert_eq!(
+            config
+                .assert_freeze_admins_change(std::slice::from_ref(&first))
+                .unwrap_err(),
+            McpTokenError::InsufficientFreezeSignatures.into()
+        );
+        config
+            .assert_freeze_admins_change(&[first, second])
+            .unwrap();
+    }
+
+    #[test]
+    fn pause_rejects_mutations_but_not_transfers() {
+        let mut config = global_config();
+        config.assert_not_paused().unwrap();
+
+        config.global_paused = true;
+        assert_eq!(
+            config.assert_not_paused().unwrap_err(),
+            McpTokenError::ProtocolPaused.into()
+        );
+        config.assert_transfers_allowed().unwrap();
+    }
+
+    #[test]
+    fn freeze_admins_over_bound_rejected() {
+        let program_id = crate::ID;
+        let (config_key, bump) = Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &program_id);
+        let admins = |count: usize| (0..count).map(|_| Pubkey::new_unique()).collect();
+
+        for (count, result) in [
+            (MAX_FREEZE_ADMINS, Ok(())),
+            (
+                MAX_FREEZE_ADMINS + 1,
+                Err(McpTokenError::TooManyEntries.into()),
+            ),
+        ] {
+            let config = GlobalConfig {
+                bump,
+                ..global_config()
+            };
+            let mut data = Vec::new();
+            config.try_serialize(&mut data).unwrap();
+            let (mut admin_lamports, mut config_lamports) = (0, 0);
+            let mut admin_data = [];
+            let system_program = System::id();
+            let accounts = [
+                AccountInfo::new(
+                    &config.admin,
+                    true,
+                    false,
+                    &mut admin_lamports,
+                    &mut admin_data,
+                    &system_program,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &config_key,
+                    false,
+                    true,
+                    &mut config_lamports,
+                    &mut data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+            ];
+
+            let mut bumps = BTreeMap::new();
+            let mut update = UpdateGlobalConfig::try_accounts(
+                &program_id,
+                &mut &accounts[..],
+                &[],
+                &mut bumps,
+                &mut BTreeSet::new(),
+            )
+            .unwrap();
+            assert_eq!(
+                set_freeze_admins(
+                    Context::new(&program_id, &mut update, &[], bumps),
+                    admins(count),
+                ),
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn frozen_config_still_pausable() {
+        let program_id = crate::ID;
+        let admin = Pubkey::new_unique();
+        let (config_key, bump) = Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &program_id);
+
+        for frozen in [false, true] {
+            let mut config = global_config();
+            config.admin = admin;
+            config.bump = bump;
+            config.config_frozen = frozen;
+            let mut data = Vec::new();
+            config.try_serialize(&mut data).unwrap();
+            let (mut admin_lamports, mut config_lamports) = (0, 0);
+            let mut admin_data = [];
+            let system_program = System::id();
+            let accounts = [
+                AccountInfo::new(
+                    &admin,
+                    true,
+                    false,
+                    &mut admin_lamports,
+                    &mut admin_data,
+                    &system_program,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &config_key,
+                    false,
+                    true,
+                    &mut config_lamports,
+                    &mut data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+            ];
+
+            let update = UpdateGlobalConfig::try_accounts(
+                &program_id,
+                &mut &accounts[..],
+                &[],
+                &mut BTreeMap::new(),
+                &mut BTreeSet::new(),
+            );
+            if frozen {
+                assert_eq!(update.err(), Some(McpTokenError::ConfigFrozen.into()));
+            } else {
+                assert!(update.is_ok());
+            }
+            SetGlobalPaused::try_accounts(
+                &program_id,
+                &mut &accounts[..],
+                &[],
+                &mut BTreeMap::new(),
+                &mut BTreeSet::new(),
+            )
+            .unwrap();
+        }
+    }
+}
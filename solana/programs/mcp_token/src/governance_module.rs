@@ -0,0 +1,971 @@
+//! governance_module module for MCP token
+//!
+//! This module provides token-weighted governance proposals. Holders vote with
+//! their MCP balance until the proposal deadline, after which anyone can
+//! execute the proposal to record its outcome.
+//!
+//! Voting power is fixed before a proposal exists: holders record their balance
+//! in a `SnapshotEntry`, and votes count the balance recorded before the
+//! proposal's snapshot slot. Tokens borrowed or bought once a proposal is up
+//! add no weight to it.
+//!
+//! The tokens a vote counts are locked in a vault owned by the `VoteRecord`
+//! until the proposal's deadline, so they cannot be passed to another wallet
+//! with its own snapshot and counted again.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use crate::errors_module::McpTokenError;
+use crate::global_config_module::{GlobalConfig, GLOBAL_CONFIG_SEED};
+use crate::utils_module::{get_bump, isqrt};
+use crate::vaults_module::VOTE_VAULT_SEED;
+
+/// Shortest voting period of new governance configs, in seconds
+pub const DEFAULT_MIN_VOTING_PERIOD: i64 = 24 * 60 * 60;
+
+/// Longest voting period of new governance configs, in seconds
+pub const DEFAULT_MAX_VOTING_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+/// How a proposal with exactly as many yes as no votes is resolved
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// A tie rejects the proposal
+    RejectOnTie,
+    /// A tie passes the proposal
+    PassOnTie,
+}
+
+/// How a voter's token balance translates into vote weight
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VotingMode {
+    /// Weight equals the balance
+    Linear,
+    /// Weight is the integer square root of the balance.
+    ///
+    /// This dampens large holders, but only while each holder uses a single
+    /// wallet: splitting a balance across many wallets recovers linear power,
+    /// so quadratic mode is only meaningful alongside sybil resistance.
+    Quadratic,
+}
+
+impl VotingMode {
+    /// Vote weight of `balance` under this mode
+    pub fn weight(&self, balance: u64) -> u64 {
+        match self {
+            VotingMode::Linear => balance,
+            VotingMode::Quadratic => isqrt(balance),
+        }
+    }
+}
+
+/// Lifecycle of a proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    /// Voting is open or the proposal awaits execution
+    Active,
+    /// Executed with more support than opposition, or a tie under `PassOnTie`
+    Passed,
+    /// Executed without enough support
+    Rejected,
+    /// Withdrawn by the proposer before any vote
+    Cancelled,
+}
+
+/// GovernanceConfig state account
+#[account]
+pub struct GovernanceConfig {
+    /// The authority that can update this account
+    pub authority: Pubkey,
+
+    /// Mint whose holders vote
+    pub mint: Pubkey,
+
+    /// Resolution of tied votes
+    pub tie_break: TieBreak,
+
+    /// Translation of balances into vote weight
+    pub voting_mode: VotingMode,
+
+    /// Shortest time between proposal creation and its deadline, in seconds
+    pub min_voting_period: i64,
+
+    /// Longest time between proposal creation and its deadline, in seconds
+    pub max_voting_period: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl GovernanceConfig {
+    pub const LEN: usize = 32 + 32 + 1 + 1 + 8 + 8 + 1;
+
+    /// Whether a proposal with these totals passes.
+    ///
+    /// More yes than no votes always passes and fewer always fails; an exact
+    /// tie, including no votes at all, is decided by `tie_break`.
+    pub fn resolve(&self, yes_votes: u64, no_votes: u64) -> bool {
+        match yes_votes.cmp(&no_votes) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => self.tie_break == TieBreak::PassOnTie,
+        }
+    }
+
+    /// Check that a proposal created at `now` and closing at `deadline` runs
+    /// for a voting period within the configured bounds
+    pub fn assert_voting_period(&self, deadline: i64, now: i64) -> Result<()> {
+        let voting_period = deadline.saturating_sub(now);
+        require!(
+            voting_period >= self.min_voting_period,
+            McpTokenError::VotingPeriodTooShort
+        );
+        require!(
+            voting_period <= self.max_voting_period,
+            McpTokenError::VotingPeriodTooLong
+        );
+
+        Ok(())
+    }
+}
+
+/// Proposal state account
+#[account]
+pub struct Proposal {
+    /// The account that created the proposal
+    pub proposer: Pubkey,
+
+    /// The governance the proposal belongs to
+    pub governance_config: Pubkey,
+
+    /// Unique proposal identifier within the governance
+    pub proposal_id: u64,
+
+    /// Hash of the off-chain proposal description
+    pub description_hash: [u8; 32],
+
+    /// Total weight voting in favour
+    pub yes_votes: u64,
+
+    /// Total weight voting against
+    pub no_votes: u64,
+
+    /// Time voting closes
+    pub deadline: i64,
+
+    /// Status of the proposal
+    pub status: ProposalStatus,
+
+    /// Slot the proposal was created in; only balances recorded earlier count
+    pub power_snapshot_slot: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub const LEN: usize = 32 + 32 + 8 + 32 + 8 + 8 + 8 + 1 + 8 + 1;
+
+    /// Check that votes can still be cast at `now`
+    pub fn assert_voting_open(&self, now: i64) -> Result<()> {
+        require!(
+            self.status == ProposalStatus::Active && now <= self.deadline,
+            McpTokenError::VotingClosed
+        );
+
+        Ok(())
+    }
+
+    /// Add `weight` to the yes or no tally
+    pub fn add_votes(&mut self, approve: bool, weight: u64) -> Result<()> {
+        let tally = if approve {
+            &mut self.yes_votes
+        } else {
+            &mut self.no_votes
+        };
+        *tally = tally
+            .checked_add(weight)
+            .ok_or(McpTokenError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Withdraw the proposal at `now`, allowed only before its first vote
+    pub fn cancel(&mut self, now: i64) -> Result<()> {
+        require!(
+            self.status == ProposalStatus::Active,
+            McpTokenError::ProposalNotActive
+        );
+        require!(now <= self.deadline, McpTokenError::VotingClosed);
+        require!(
+            self.yes_votes == 0 && self.no_votes == 0,
+            McpTokenError::ProposalHasVotes
+        );
+        self.status = ProposalStatus::Cancelled;
+
+        Ok(())
+    }
+}
+
+/// SnapshotEntry state account, the recorded voting balance of a holder.
+///
+/// The previous recording is kept so a holder updating their balance while a
+/// proposal is open can still vote on it with the balance recorded before.
+#[account]
+pub struct SnapshotEntry {
+    /// The governance the balance counts in
+    pub governance_config: Pubkey,
+
+    /// The holder
+    pub voter: Pubkey,
+
+    /// Latest recorded balance
+    pub balance: u64,
+
+    /// Slot `balance` was recorded in
+    pub slot: u64,
+
+    /// Balance recorded before the latest one
+    pub previous_balance: u64,
+
+    /// Slot `previous_balance` was recorded in; zero if never recorded
+    pub previous_slot: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl SnapshotEntry {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// Balance recorded strictly before `snapshot_slot`, if any
+    pub fn balance_before(&self, snapshot_slot: u64) -> Option<u64> {
+        if self.slot < snapshot_slot {
+            Some(self.balance)
+        } else if self.previous_slot != 0 && self.previous_slot < snapshot_slot {
+            Some(self.previous_balance)
+        } else {
+            None
+        }
+    }
+
+    /// Tokens a vote on a proposal snapshotted at `snapshot_slot` counts: the
+    /// balance recorded before the snapshot, capped by the `current` balance
+    pub fn voting_balance(&self, snapshot_slot: u64, current: u64) -> Result<u64> {
+        let recorded = self
+            .balance_before(snapshot_slot)
+            .ok_or(McpTokenError::NoVotingSnapshot)?;
+
+        Ok(recorded.min(current))
+    }
+}
+
+/// VoteRecord state account, preventing a voter from voting twice
+#[account]
+pub struct VoteRecord {
+    /// The voter
+    pub voter: Pubkey,
+
+    /// The proposal voted on
+    pub proposal: Pubkey,
+
+    /// Whether the vote was in favour
+    pub approve: bool,
+
+    /// Weight of the vote
+    pub weight: u64,
+
+    /// Tokens locked in the vote vault until the deadline; zero once withdrawn
+    pub locked: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 8 + 1;
+}
+
+/// Emitted when a proposal is executed
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub tie_break: TieBreak,
+    pub passed: bool,
+}
+
+/// Initialize the GovernanceConfig of a mint
+pub fn initialize_governance_config(
+    ctx: Context<InitializeGovernanceConfig>,
+    tie_break: TieBreak,
+    voting_mode: VotingMode,
+) -> Result<()> {
+    let config = &mut ctx.accounts.governance_config;
+    config.authority = ctx.accounts.authority.key();
+    config.mint = ctx.accounts.mint.key();
+    config.tie_break = tie_break;
+    config.voting_mode = voting_mode;
+    config.min_voting_period = DEFAULT_MIN_VOTING_PERIOD;
+    config.max_voting_period = DEFAULT_MAX_VOTING_PERIOD;
+    config.bump = get_bump(&ctx.bumps, "governance_config")?;
+
+    Ok(())
+}
+
+/// Update how tied proposals are resolved
+pub fn set_tie_break(ctx: Context<UpdateGovernanceConfig>, tie_break: TieBreak) -> Result<()> {
+    ctx.accounts.governance_config.tie_break = tie_break;
+
+    Ok(())
+}
+
+/// Switch between linear and quadratic vote weighting
+pub fn set_voting_mode(
+    ctx: Context<UpdateGovernanceConfig>,
+    voting_mode: VotingMode,
+) -> Result<()> {
+    ctx.accounts.governance_config.voting_mode = voting_mode;
+
+    Ok(())
+}
+
+/// Update the bounds on how long proposals stay open for voting
+pub fn set_voting_period_bounds(
+    ctx: Context<UpdateGovernanceConfig>,
+    min_voting_period: i64,
+    max_voting_period: i64,
+) -> Result<()> {
+    require!(
+        min_voting_period > 0 && min_voting_period <= max_voting_period,
+        McpTokenError::InvalidVotingPeriod
+    );
+    let config = &mut ctx.accounts.governance_config;
+    config.min_voting_period = min_voting_period;
+    config.max_voting_period = max_voting_period;
+
+    Ok(())
+}
+
+/// Create a new Proposal open for voting until `deadline`.
+///
+/// The voting period, `deadline - now`, must lie within the config's
+/// `min_voting_period` and `max_voting_period`.
+pub fn create_proposal(
+    ctx: Context<CreateProposal>,
+    proposal_id: u64,
+    description_hash: [u8; 32],
+    deadline: i64,
+) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    ctx.accounts
+        .governance_config
+        .assert_voting_period(deadline, Clock::get()?.unix_timestamp)?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.governance_config = ctx.accounts.governance_config.key();
+    proposal.proposal_id = proposal_id;
+    proposal.description_hash = description_hash;
+    proposal.yes_votes = 0;
+    proposal.no_votes = 0;
+    proposal.deadline = deadline;
+    proposal.status = ProposalStatus::Active;
+    proposal.power_snapshot_slot = Clock::get()?.slot;
+    proposal.bump = get_bump(&ctx.bumps, "proposal")?;
+
+    Ok(())
+}
+
+/// Record the voter's current token balance as their voting power for
+/// proposals created from the next slot on
+pub fn record_snapshot(ctx: Context<RecordSnapshot>) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    let slot = Clock::get()?.slot;
+    let entry = &mut ctx.accounts.snapshot_entry;
+    if entry.voter == Pubkey::default() {
+        entry.governance_config = ctx.accounts.governance_config.key();
+        entry.voter = ctx.accounts.voter.key();
+        entry.bump = get_bump(&ctx.bumps, "snapshot_entry")?;
+    } else if entry.slot != slot {
+        entry.previous_balance = entry.balance;
+        entry.previous_slot = entry.slot;
+    }
+    entry.balance = ctx.accounts.voter_token_account.amount;
+    entry.slot = slot;
+
+    Ok(())
+}
+
+/// Vote on a proposal with the voter's balance recorded before the proposal's
+/// snapshot slot, weighted by the governance's voting mode.
+///
+/// The balance counted is also capped by the current one, so tokens sold after
+/// the snapshot do not count either. The counted tokens move into the vote
+/// vault and stay there until `withdraw_vote_tokens` after the deadline.
+pub fn cast_vote(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.assert_voting_open(Clock::get()?.unix_timestamp)?;
+
+    let locked = ctx.accounts.snapshot_entry.voting_balance(
+        proposal.power_snapshot_slot,
+        ctx.accounts.voter_token_account.amount,
+    )?;
+    let weight = ctx.accounts.governance_config.voting_mode.weight(locked);
+    require!(weight > 0, McpTokenError::InvalidAmount);
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.voter_token_account.to_account_info(),
+                to: ctx.accounts.vote_vault.to_account_info(),
+                authority: ctx.accounts.voter.to_account_info(),
+            },
+        ),
+        locked,
+    )?;
+    proposal.add_votes(approve, weight)?;
+
+    let record = &mut ctx.accounts.vote_record;
+    record.voter = ctx.accounts.voter.key();
+    record.proposal = proposal.key();
+    record.approve = approve;
+    record.weight = weight;
+    record.locked = locked;
+    record.bump = get_bump(&ctx.bumps, "vote_record")?;
+
+    Ok(())
+}
+
+/// Return the tokens locked by a vote once the proposal's deadline has passed,
+/// closing the vote vault. The vote record stays, so the vote still counts.
+pub fn withdraw_vote_tokens(ctx: Context<WithdrawVoteTokens>) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    require!(
+        Clock::get()?.unix_timestamp > ctx.accounts.proposal.deadline,
+        McpTokenError::VotingNotEnded
+    );
+
+    let record = &ctx.accounts.vote_record;
+    let bump = [record.bump];
+    let signer_seeds = [
+        b"vote".as_ref(),
+        record.proposal.as_ref(),
+        record.voter.as_ref(),
+        &bump[..],
+    ];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.vote_vault.to_account_info(),
+                to: ctx.accounts.voter_token_account.to_account_info(),
+                authority: ctx.accounts.vote_record.to_account_info(),
+            },
+            &[&signer_seeds],
+        ),
+        ctx.accounts.vote_vault.amount,
+    )?;
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::CloseAccount {
+            account: ctx.accounts.vote_vault.to_account_info(),
+            destination: ctx.accounts.voter.to_account_info(),
+            authority: ctx.accounts.vote_record.to_account_info(),
+        },
+        &[&signer_seeds],
+    ))?;
+    ctx.accounts.vote_record.locked = 0;
+
+    Ok(())
+}
+
+/// Withdraw a proposal that has not received any vote yet.
+///
+/// Only the proposer can cancel, and only before the deadline. The proposal is
+/// closed and its rent returned to the proposer, so it can never be executed.
+pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    ctx.accounts.proposal.cancel(Clock::get()?.unix_timestamp)
+}
+
+/// Resolve a proposal once voting has closed.
+///
+/// The outcome is deterministic: see `GovernanceConfig::resolve`.
+pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        proposal.status == ProposalStatus::Active,
+        McpTokenError::ProposalNotActive
+    );
+    require!(
+        Clock::get()?.unix_timestamp > proposal.deadline,
+        McpTokenError::VotingNotEnded
+    );
+
+    let config = &ctx.accounts.governance_config;
+    let passed = config.resolve(proposal.yes_votes, proposal.no_votes);
+    proposal.status = if passed {
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Rejected
+    };
+
+    emit!(ProposalExecuted {
+        proposal: proposal.key(),
+        yes_votes: proposal.yes_votes,
+        no_votes: proposal.no_votes,
+        tie_break: config.tie_break,
+        passed,
+    });
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeGovernanceConfig<'info> {
+    /// The authority of the governance
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GovernanceConfig::LEN,
+        seeds = [b"governance-config".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+
+    /// Mint whose holders vote
+    pub mint: Account<'info, Mint>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateGovernanceConfig<'info> {
+    /// The authority of the governance
+    pub authority: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"governance-config".as_ref(), governance_config.mint.as_ref()],
+        bump = governance_config.bump,
+        has_one = authority,
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+}
+
+/// Account validation for proposal creation
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct CreateProposal<'info> {
+    /// The account creating the proposal
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The governance the proposal belongs to
+    #[account(
+        seeds = [b"governance-config".as_ref(), governance_config.mint.as_ref()],
+        bump = governance_config.bump,
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::LEN,
+        seeds = [
+            b"proposal".as_ref(),
+            governance_config.key().as_ref(),
+            proposal_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for vote
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    /// The voter
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The governance the proposal belongs to
+    #[account(
+        seeds = [b"governance-config".as_ref(), governance_config.mint.as_ref()],
+        bump = governance_config.bump,
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+
+    /// The proposal voted on
+    #[account(
+        mut,
+        seeds = [
+            b"proposal".as_ref(),
+            governance_config.key().as_ref(),
+            proposal.proposal_id.to_le_bytes().as_ref(),
+        ],
+        bump = proposal.bump,
+        has_one = governance_config,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// The voter's record, whose existence blocks a second vote
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + VoteRecord::LEN,
+        seeds = [b"vote".as_ref(), proposal.key().as_ref(), voter.key().as_ref()],
+        bump,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// The voter's recorded voting power
+    #[account(
+        seeds = [b"snapshot".as_ref(), governance_config.key().as_ref(), voter.key().as_ref()],
+        bump = snapshot_entry.bump,
+    )]
+    pub snapshot_entry: Account<'info, SnapshotEntry>,
+
+    /// Voter's token account capping the vote weight, funding the vote vault
+    #[account(
+        mut,
+        token::mint = governance_config.mint,
+        token::authority = voter,
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    /// Token vault locking the counted tokens until the deadline
+    #[account(
+        init,
+        payer = voter,
+        seeds = [VOTE_VAULT_SEED, vote_record.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vote_record,
+    )]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Mint whose holders vote
+    #[account(address = governance_config.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for withdrawing locked vote tokens
+#[derive(Accounts)]
+pub struct WithdrawVoteTokens<'info> {
+    /// The voter, receiving the vault rent
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The proposal voted on
+    #[account(
+        seeds = [
+            b"proposal".as_ref(),
+            proposal.governance_config.as_ref(),
+            proposal.proposal_id.to_le_bytes().as_ref(),
+        ],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// The voter's record, the authority of the vote vault
+    #[account(
+        mut,
+        seeds = [b"vote".as_ref(), proposal.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump,
+        has_one = voter,
+        has_one = proposal,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// Token vault holding the locked tokens
+    #[account(
+        mut,
+        seeds = [VOTE_VAULT_SEED, vote_record.key().as_ref()],
+        bump,
+    )]
+    pub vote_vault: Account<'info, TokenAccount>,
+
+    /// Voter's token account receiving the tokens
+    #[account(
+        mut,
+        token::mint = vote_vault.mint,
+        token::authority = voter,
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for recording voting power
+#[derive(Accounts)]
+pub struct RecordSnapshot<'info> {
+    /// The holder recording their balance
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The governance the balance counts in
+    #[account(
+        seeds = [b"governance-config".as_ref(), governance_config.mint.as_ref()],
+        bump = governance_config.bump,
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+
+    /// The voter's entry, created on first recording
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = 8 + SnapshotEntry::LEN,
+        seeds = [b"snapshot".as_ref(), governance_config.key().as_ref(), voter.key().as_ref()],
+        bump,
+    )]
+    pub snapshot_entry: Account<'info, SnapshotEntry>,
+
+    /// Voter's token account whose balance is recorded
+    #[account(
+        token::mint = governance_config.mint,
+        token::authority = voter,
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for cancellation
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    /// The proposer, receiving the rent
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The account to close
+    #[account(
+        mut,
+        seeds = [
+            b"proposal".as_ref(),
+            proposal.governance_config.as_ref(),
+            proposal.proposal_id.to_le_bytes().as_ref(),
+        ],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+/// Account validation for execution
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    /// The governance the proposal belongs to
+    #[account(
+        seeds = [b"governance-config".as_ref(), governance_config.mint.as_ref()],
+        bump = governance_config.bump,
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The proposal to execute
+    #[account(
+        mut,
+        seeds = [
+            b"proposal".as_ref(),
+            governance_config.key().as_ref(),
+            proposal.proposal_id.to_le_bytes().as_ref(),
+        ],
+        bump = proposal.bump,
+        has_one = governance_config,
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(balance: u64, slot: u64) -> SnapshotEntry {
+        SnapshotEntry {
+            governance_config: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+            balance,
+            slot,
+            previous_balance: 0,
+            previous_slot: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn tokens_acquired_after_snapshot_do_not_count() {
+        let entry = snapshot(100, 10);
+        assert_eq!(entry.voting_balance(20, 1_000).unwrap(), 100);
+        assert_eq!(entry.voting_balance(20, 40).unwrap(), 40);
+    }
+
+    #[test]
+    fn balance_recorded_after_proposal_falls_back_to_previous() {
+        let mut entry = snapshot(1_000, 30);
+        entry.previous_balance = 100;
+        entry.previous_slot = 10;
+        assert_eq!(entry.voting_balance(20, 1_000).unwrap(), 100);
+
+        entry.previous_slot = 0;
+        assert_eq!(
+            entry.voting_balance(20, 1_000).unwrap_err(),
+            McpTokenError::NoVotingSnapshot.into()
+        );
+    }
+
+    #[test]
+    fn exact_tie_resolved_by_tie_break() {
+        let mut config = GovernanceConfig {
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            tie_break: TieBreak::RejectOnTie,
+            voting_mode: VotingMode::Linear,
+            min_voting_period: DEFAULT_MIN_VOTING_PERIOD,
+            max_voting_period: DEFAULT_MAX_VOTING_PERIOD,
+            bump: 255,
+        };
+        assert!(!config.resolve(50, 50));
+        assert!(config.resolve(51, 50));
+
+        config.tie_break = TieBreak::PassOnTie;
+        assert!(config.resolve(50, 50));
+        assert!(!config.resolve(49, 50));
+    }
+
+    #[test]
+    fn fresh_proposal_cancellable_until_first_vote() {
+        let fresh = || Proposal {
+            proposer: Pubkey::new_unique(),
+            governance_config: Pubkey::new_unique(),
+            proposal_id: 0,
+            description_hash: [0; 32],
+            yes_votes: 0,
+            no_votes: 0,
+            deadline: 1_000,
+            status: ProposalStatus::Active,
+            power_snapshot_slot: 0,
+            bump: 255,
+        };
+
+        let mut proposal = fresh();
+        proposal.cancel(500).unwrap();
+        assert!(proposal.status == ProposalStatus::Cancelled);
+        assert_eq!(
+            proposal.assert_voting_open(500).unwrap_err(),
+            McpTokenError::VotingClosed.into()
+        );
+
+        let mut proposal = fresh();
+        proposal.add_votes(false, 1).unwrap();
+        assert_eq!(
+            proposal.cancel(500).unwrap_err(),
+            McpTokenError::ProposalHasVotes.into()
+        );
+    }
+
+    #[test]
+    fn quadratic_mode_dampens_large_holder() {
+        let (whale, minnow) = (1_000_000, 100);
+        assert_eq!(
+            VotingMode::Linear.weight(whale) / VotingMode::Linear.weight(minnow),
+            10_000
+        );
+        assert_eq!(VotingMode::Quadratic.weight(whale), 1_000);
+        assert_eq!(VotingMode::Quadratic.weight(minnow), 10);
+        assert_eq!(VotingMode::Quadratic.weight(99), 9);
+    }
+
+    #[test]
+    fn voting_period_bounded_by_config() {
+        let config = GovernanceConfig {
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            tie_break: TieBreak::RejectOnTie,
+            voting_mode: VotingMode::Linear,
+            min_voting_period: 100,
+            max_voting_period: 1_000,
+            bump: 255,
+        };
+
+        assert_eq!(
+            config.assert_voting_period(1_099, 1_000).unwrap_err(),
+            McpTokenError::VotingPeriodTooShort.into()
+        );
+        assert_eq!(
+            config.assert_voting_period(2_001, 1_000).unwrap_err(),
+            McpTokenError::VotingPeriodTooLong.into()
+        );
+        config.assert_voting_period(1_100, 1_000).unwrap();
+        config.assert_voting_period(2_000, 1_000).unwrap();
+    }
+}
@@ -0,0 +1,666 @@
+//! hold_period_module module for MCP token
+//!
+//! This module provides an opt-in minimum hold time for anti-bot launches.
+//! Once a mint has a HoldConfig with a non-zero `min_hold_seconds`,
+//! `mint_tokens`, `transfer_tokens` and `transfer_all` record when each token
+//! account last received tokens, and transfers refuse to move its balance out
+//! until the hold has passed. Any receipt restarts the hold for the whole
+//! balance of the receiving account. Balances with no record, received before
+//! the hold was enabled or outside this program, are held from when the hold
+//! was enabled. Exempt wallets and token accounts, such as liquidity
+//! pools and program vaults, are neither held nor tracked.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::errors_module::McpTokenError;
+use crate::global_config_module::GlobalConfig;
+use crate::utils_module::{create_pda_account, get_bump, validate_bounded_vec};
+use crate::MintAuthority;
+
+/// Maximum number of keys exempt from the hold period per mint
+pub const MAX_HOLD_EXEMPTIONS: usize = 16;
+
+/// HoldConfig state account
+#[account]
+pub struct HoldConfig {
+    /// The mint the hold period applies to
+    pub mint: Pubkey,
+
+    /// Seconds received tokens must be held before moving on; zero disables the hold
+    pub min_hold_seconds: i64,
+
+    /// When the hold was last turned on; untracked balances are held from then
+    pub enabled_at: i64,
+
+    /// Wallets and token accounts the hold period does not apply to
+    pub exemptions: Vec<Pubkey>,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl HoldConfig {
+    pub const LEN: usize = 32 + 8 + 8 + (4 + 32 * MAX_HOLD_EXEMPTIONS) + 1;
+
+    /// Whether a token account, or the wallet owning it, is exempt
+    pub fn is_exempt(&self, account: &Account<TokenAccount>) -> bool {
+        self.exemptions.contains(&account.key()) || self.exemptions.contains(&account.owner)
+    }
+
+    /// Set the minimum hold time; turning the hold on from zero holds
+    /// untracked balances from `now`
+    pub fn set_min_hold_seconds(&mut self, min_hold_seconds: i64, now: i64) -> Result<()> {
+        require!(min_hold_seconds >= 0, McpTokenError::InvalidCooldown);
+        if self.min_hold_seconds == 0 {
+            self.enabled_at = now;
+        }
+        self.min_hold_seconds = min_hold_seconds;
+
+        Ok(())
+    }
+
+    /// Time a balance last received at `last_received_at` may move; a balance
+    /// without a record is held from `enabled_at`
+    pub fn unlocked_at(&self, last_received_at: Option<i64>) -> Result<i64> {
+        last_received_at
+            .unwrap_or(self.enabled_at)
+            .checked_add(self.min_hold_seconds)
+            .ok_or_else(|| error!(McpTokenError::MathOverflow))
+    }
+}
+
+/// HoldRecord state account, tracking when a token account last received tokens
+#[account]
+pub struct HoldRecord {
+    /// The token account tracked
+    pub token_account: Pubkey,
+
+    /// Time of the most recent receipt
+    pub last_received_at: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl HoldRecord {
+    pub const LEN: usize = 32 + 8 + 1;
+}
+
+/// Accounts a transfer needs to enforce the hold period
+pub struct HoldAccounts<'a, 'info> {
+    /// HoldConfig PDA of the mint, possibly uninitialized
+    pub hold_config: &'a AccountInfo<'info>,
+
+    /// HoldRecord PDA of the sending token account, possibly uninitialized
+    pub from_hold: &'a AccountInfo<'info>,
+
+    /// HoldRecord PDA of the receiving token account, created when needed
+    pub to_hold: &'a AccountInfo<'info>,
+
+    /// Pays for a newly created receiving HoldRecord
+    pub payer: &'a AccountInfo<'info>,
+
+    /// System program
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+/// The HoldConfig of a mint when its hold is enabled
+fn active_hold_config(hold_config: &AccountInfo) -> Result<Option<HoldConfig>> {
+    if hold_config.data_is_empty() {
+        return Ok(None);
+    }
+    let config = Account::<HoldConfig>::try_from(hold_config)?.into_inner();
+
+    Ok((config.min_hold_seconds != 0).then_some(config))
+}
+
+/// Reject moving tokens out of `from` before its hold has passed, then
+/// record the receipt on `to`.
+///
+/// Does nothing while the mint has no HoldConfig or its hold is zero.
+pub fn enforce_hold_period<'info>(
+    accounts: HoldAccounts<'_, 'info>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    global_config: &GlobalConfig,
+    program_id: &Pubkey,
+) -> Result<()> {
+    enforce_hold_period_at(
+        accounts,
+        from,
+        to,
+        global_config,
+        Clock::get()?.unix_timestamp,
+        program_id,
+    )
+}
+
+/// `enforce_hold_period` at time `now`
+fn enforce_hold_period_at<'info>(
+    accounts: HoldAccounts<'_, 'info>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    global_config: &GlobalConfig,
+    now: i64,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let Some(config) = active_hold_config(accounts.hold_config)? else {
+        return Ok(());
+    };
+
+    if !config.is_exempt(from) {
+        let last_received_at = match accounts.from_hold.data_is_empty() {
+            true => None,
+            false => Some(Account::<HoldRecord>::try_from(accounts.from_hold)?.last_received_at),
+        };
+        require!(
+            global_config.has_elapsed(now, config.unlocked_at(last_received_at)?),
+            McpTokenError::HoldPeriodNotMet
+        );
+    }
+
+    record_receipt(&config, &accounts, to, now, program_id)
+}
+
+/// Record tokens minted into `to`, starting its hold.
+///
+/// `from_hold` is unused, as minted tokens have no sender. Does nothing while
+/// the mint has no HoldConfig or its hold is zero.
+pub fn record_mint_receipt<'info>(
+    accounts: HoldAccounts<'_, 'info>,
+    to: &Account<'info, TokenAccount>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let Some(config) = active_hold_config(accounts.hold_config)? else {
+        return Ok(());
+    };
+
+    record_receipt(
+        &config,
+        &accounts,
+        to,
+        Clock::get()?.unix_timestamp,
+        program_id,
+    )
+}
+
+/// Set the HoldRecord of `to` to `now`, creating it when needed
+fn record_receipt<'info>(
+    config: &HoldConfig,
+    accounts: &HoldAccounts<'_, 'info>,
+    to: &Account<'info, TokenAccount>,
+    now: i64,
+    program_id: &Pubkey,
+) -> Result<()> {
+    if config.is_exempt(to) {
+        return Ok(());
+    }
+    if accounts.to_hold.data_is_empty() {
+        let to_key = to.key();
+        let (_, bump) =
+            Pubkey::find_program_address(&[b"hold".as_ref(), to_key.as_ref()], program_id);
+        create_pda_account(
+            accounts.payer,
+            accounts.to_hold,
+            accounts.system_program,
+            8 + HoldRecord::LEN,
+            program_id,
+            &[b"hold".as_ref(), to_key.as_ref(), &[bump]],
+        )?;
+        let record = HoldRecord {
+            token_account: to_key,
+            last_received_at: now,
+            bump,
+        };
+        record.try_serialize(&mut &mut accounts.to_hold.try_borrow_mut_data()?[..])?;
+    } else {
+        let mut record = Account::<HoldRecord>::try_from(accounts.to_hold)?;
+        record.last_received_at = now;
+        record.exit(program_id)?;
+    }
+
+    Ok(())
+}
+
+/// Initialize the HoldConfig of a mint
+pub fn initialize_hold_config(
+    ctx: Context<InitializeHoldConfig>,
+    min_hold_seconds: i64,
+) -> Result<()> {
+    require!(min_hold_seconds >= 0, McpTokenError::InvalidCooldown);
+    let config = &mut ctx.accounts.hold_config;
+    config.mint = ctx.accounts.mint.key();
+    config.min_hold_seconds = min_hold_seconds;
+    config.enabled_at = Clock::get()?.unix_timestamp;
+    config.exemptions = Vec::new();
+    config.bump = get_bump(&ctx.bumps, "hold_config")?;
+
+    Ok(())
+}
+
+/// Update the minimum hold time; zero turns the hold off
+pub fn set_min_hold_seconds(ctx: Context<UpdateHoldConfig>, min_hold_seconds: i64) -> Result<()> {
+    ctx.accounts
+        .hold_config
+        .set_min_hold_seconds(min_hold_seconds, Clock::get()?.unix_timestamp)
+}
+
+/// Replace the keys exempt from the hold period
+pub fn set_hold_exemptions(ctx: Context<UpdateHoldConfig>, exemptions: Vec<Pubkey>) -> Result<()> {
+    validate_bounded_vec(&exemptions, MAX_HOLD_EXEMPTIONS)?;
+    ctx.accounts.hold_config.exemptions = exemptions;
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeHoldConfig<'info> {
+    /// The mint authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The mint the hold period applies to
+    pub mint: Account<'info, Mint>,
+
+    /// Mint authority of `mint`
+    #[account(
+        seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
+        bump = mint_authority.bump,
+        has_one = authority,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + HoldConfig::LEN,
+        seeds = [b"hold-config".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub hold_config: Account<'info, HoldConfig>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateHoldConfig<'info> {
+    /// The mint authority
+    pub authority: Signer<'info>,
+
+    /// Mint authority of the hold config's mint
+    #[account(
+        seeds = [b"mint-authority".as_ref(), hold_config.mint.as_ref()],
+        bump = mint_authority.bump,
+        has_one = authority,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"hold-config".as_ref(), hold_config.mint.as_ref()],
+        bump = hold_config.bump,
+    )]
+    pub hold_config: Account<'info, HoldConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::global_config_module::tests::global_config;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn hold_config() -> HoldConfig {
+        HoldConfig {
+            mint: Pubkey::new_unique(),
+            min_hold_seconds: 100,
+            enabled_at: 50,
+            exemptions: Vec::new(),
+            bump: 255,
+        }
+    }
+
+    /// Token account `key` of wallet `owner`, with a HoldRecord when it has
+    /// received tokens at `last_received_at`
+    struct Holder {
+        key: Pubkey,
+        owner: Pubkey,
+        last_received_at: Option<i64>,
+    }
+
+    fn holder(last_received_at: Option<i64>) -> Holder {
+        Holder {
+            key: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            last_received_at,
+        }
+    }
+
+    /// Packed SPL token account of `holder` holding `mint`
+    fn token_account_data(mint: Pubkey, holder: &Holder) -> Vec<u8> {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner: holder.owner,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    /// Serialized HoldRecord of `holder`, empty when it has none
+    fn hold_record_data(holder: &Holder) -> Vec<u8> {
+        let mut data = Vec::new();
+        if let Some(last_received_at) = holder.last_received_at {
+            HoldRecord {
+                token_account: holder.key,
+                last_received_at,
+                bump: 255,
+            }
+            .try_serialize(&mut data)
+            .unwrap();
+        }
+        data
+    }
+
+    /// Move tokens from `from` to `to` at `now`, returning the outcome and
+    /// when `to` last received tokens afterwards
+    fn transfer(
+        config: &HoldConfig,
+        from: &Holder,
+        to: &Holder,
+        now: i64,
+    ) -> (Result<()>, Option<i64>) {
+        let (program_id, system_program, token_program) = (crate::ID, System::id(), spl_token::ID);
+        let record_owner = |holder: &Holder| match holder.last_received_at {
+            Some(_) => program_id,
+            None => system_program,
+        };
+        let (from_hold_owner, to_hold_owner) = (record_owner(from), record_owner(to));
+        let (hold_config_key, from_hold_key, to_hold_key, payer_key) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut hold_config_data = Vec::new();
+        config.try_serialize(&mut hold_config_data).unwrap();
+        let mut from_data = token_account_data(config.mint, from);
+        let mut to_data = token_account_data(config.mint, to);
+        let mut from_hold_data = hold_record_data(from);
+        let mut to_hold_data = hold_record_data(to);
+        let (mut payer_data, mut system_program_data) = ([], []);
+        let (mut config_lamports, mut from_lamports, mut to_lamports) = (0, 0, 0);
+        let (mut from_hold_lamports, mut to_hold_lamports) = (0, 0);
+        let (mut payer_lamports, mut system_lamports) = (0, 0);
+
+        let result = {
+            let hold_config = AccountInfo::new(
+                &hold_config_key,
+                false,
+                false,
+                &mut config_lamports,
+                &mut hold_config_data,
+                &program_id,
+                false,
+                0,
+            );
+            let from_info = AccountInfo::new(
+                &from.key,
+                false,
+                true,
+                &mut from_lamports,
+                &mut from_data,
+                &token_program,
+                false,
+                0,
+            );
+            let to_info = AccountInfo::new(
+                &to.key,
+                false,
+                true,
+                &mut to_lamports,
+                &mut to_data,
+                &token_program,
+                false,
+                0,
+            );
+            let from_hold = AccountInfo::new(
+                &from_hold_key,
+                false,
+                false,
+                &mut from_hold_lamports,
+                &mut from_hold_data,
+                &from_hold_owner,
+                false,
+                0,
+            );
+            let to_hold = AccountInfo::new(
+                &to_hold_key,
+                false,
+                true,
+                &mut to_hold_lamports,
+                &mut to_hold_data,
+                &to_hold_owner,
+                false,
+                0,
+            );
+            let payer = AccountInfo::new(
+                &payer_key,
+                true,
+                true,
+                &mut payer_lamports,
+                &mut payer_data,
+                &system_program,
+                false,
+                0,
+            );
+            let system_program_info = AccountInfo::new(
+                &system_program,
+                false,
+                false,
+                &mut system_lamports,
+                &mut system_program_data,
+                &system_program,
+                true,
+                0,
+            );
+            let accounts = HoldAccounts {
+                hold_config: &hold_config,
+                from_hold: &from_hold,
+                to_hold: &to_hold,
+                payer: &payer,
+                system_program: &system_program_info,
+            };
+            enforce_hold_period_at(
+                accounts,
+                &Account::<TokenAccount>::try_from(&from_info).unwrap(),
+                &Account::<TokenAccount>::try_from(&to_info).unwrap(),
+                &global_config(),
+                now,
+                &program_id,
+            )
+        };
+        let last_received_at = (!to_hold_data.is_empty()).then(|| {
+            HoldRecord::try_deserialize(&mut &to_hold_data[..])
+                .unwrap()
+                .last_received_at
+        });
+
+        (result, last_received_at)
+    }
+
+    #[test]
+    fn received_balance_held_for_the_period() {
+        let config = hold_config();
+        assert_eq!(config.unlocked_at(Some(1_000)).unwrap(), 1_100);
+    }
+
+    #[test]
+    fn untracked_balance_held_from_when_hold_enabled() {
+        let config = hold_config();
+        assert_eq!(config.unlocked_at(None).unwrap(), 150);
+    }
+
+    #[test]
+    fn exemptions_over_bound_rejected() {
+        let program_id = crate::ID;
+        let authority = Pubkey::new_unique();
+        let mut hold_config = hold_config();
+        let (hold_config_key, bump) =
+            Pubkey::find_program_address(&[b"hold-config", hold_config.mint.as_ref()], &program_id);
+        hold_config.bump = bump;
+        let (mint_authority_key, bump) = Pubkey::find_program_address(
+            &[b"mint-authority", hold_config.mint.as_ref()],
+            &program_id,
+        );
+        let mint_authority = MintAuthority {
+            authority,
+            bump,
+            pending_authority: None,
+            last_authority_change: 0,
+            authority_change_cooldown: 0,
+            allowed_callers: Vec::new(),
+            minted_in_window: 0,
+            window_start: 0,
+            backup_authority: None,
+            inactivity_period: 0,
+            last_active: 0,
+        };
+        let exemptions = |count: usize| (0..count).map(|_| Pubkey::new_unique()).collect();
+
+        for (count, result) in [
+            (MAX_HOLD_EXEMPTIONS, Ok(())),
+            (
+                MAX_HOLD_EXEMPTIONS + 1,
+                Err(McpTokenError::TooManyEntries.into()),
+            ),
+        ] {
+            let mut mint_authority_data = Vec::new();
+            mint_authority
+                .try_serialize(&mut mint_authority_data)
+                .unwrap();
+            let mut hold_config_data = Vec::new();
+            hold_config.try_serialize(&mut hold_config_data).unwrap();
+            let mut lamports = [0; 3];
+            let [authority_lamports, mint_authority_lamports, hold_config_lamports] = &mut lamports;
+            let mut authority_data = [];
+            let system_program = System::id();
+            let accounts = [
+                AccountInfo::new(
+                    &authority,
+                    true,
+                    false,
+                    authority_lamports,
+                    &mut authority_data,
+                    &system_program,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &mint_authority_key,
+                    false,
+                    false,
+                    mint_authority_lamports,
+                    &mut mint_authority_data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &hold_config_key,
+                    false,
+                    true,
+                    hold_config_lamports,
+                    &mut hold_config_data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+            ];
+
+            let mut bumps = BTreeMap::new();
+            let mut update = UpdateHoldConfig::try_accounts(
+                &program_id,
+                &mut &accounts[..],
+                &[],
+                &mut bumps,
+                &mut BTreeSet::new(),
+            )
+            .unwrap();
+            assert_eq!(
+                set_hold_exemptions(
+                    Context::new(&program_id, &mut update, &[], bumps),
+                    exemptions(count),
+                ),
+                result
+            );
+        }
+    }
+    #[test]
+    fn exempt_accounts_neither_held_nor_recorded() {
+        let (from, to) = (holder(Some(990)), holder(Some(10)));
+
+        for exemptions in [vec![from.owner, to.key], vec![from.key, to.owner]] {
+            let config = HoldConfig {
+                exemptions,
+                ..hold_config()
+            };
+            assert_eq!(transfer(&config, &from, &to, 1_000), (Ok(()), Some(10)));
+        }
+
+        let (result, _) = transfer(&hold_config(), &from, &to, 1_000);
+        assert_eq!(result, Err(McpTokenError::HoldPeriodNotMet.into()));
+        let config = HoldConfig {
+            exemptions: vec![from.key],
+            ..hold_config()
+        };
+        assert_eq!(transfer(&config, &from, &to, 1_000), (Ok(()), Some(1_000)));
+    }
+
+    #[test]
+    fn new_receipt_restarts_hold() {
+        let config = hold_config();
+        let (from, mut to) = (holder(Some(0)), holder(Some(950)));
+
+        assert_eq!(transfer(&config, &from, &to, 1_000), (Ok(()), Some(1_000)));
+
+        to.last_received_at = Some(1_000);
+        let (result, _) = transfer(&config, &to, &from, 1_080);
+        assert_eq!(result, Err(McpTokenError::HoldPeriodNotMet.into()));
+        let (result, _) = transfer(&config, &to, &from, 1_100);
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn enabling_hold_restarts_untracked_balances() {
+        let mut config = HoldConfig {
+            min_hold_seconds: 0,
+            ..hold_config()
+        };
+
+        config.set_min_hold_seconds(100, 500).unwrap();
+        assert_eq!(config.enabled_at, 500);
+        assert_eq!(config.unlocked_at(None).unwrap(), 600);
+
+        config.set_min_hold_seconds(200, 700).unwrap();
+        assert_eq!(config.enabled_at, 500);
+
+        config.set_min_hold_seconds(0, 800).unwrap();
+        config.set_min_hold_seconds(100, 900).unwrap();
+        assert_eq!(config.enabled_at, 900);
+
+        assert_eq!(
+            config.set_min_hold_seconds(-1, 1_000).unwrap_err(),
+            McpTokenError::InvalidCooldown.into()
+        );
+    }
+}
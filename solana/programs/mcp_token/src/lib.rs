@@ -1,5 +1,41 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint};
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+pub mod airdrop_module;
+pub mod blacklist_module;
+pub mod errors_module;
+pub mod global_config_module;
+pub mod governance_module;
+pub mod hold_period_module;
+pub mod mint_allowlist_module;
+pub mod staking_module;
+pub mod transfer_fee_module;
+pub mod utils_module;
+pub mod vaults_module;
+pub mod vesting_module;
+
+use blacklist_module::assert_transfer_not_blacklisted;
+use errors_module::McpTokenError;
+use global_config_module::{GlobalConfig, GLOBAL_CONFIG_SEED};
+use hold_period_module::{enforce_hold_period, record_mint_receipt, HoldAccounts};
+use mint_allowlist_module::assert_mint_destination_allowed;
+use utils_module::{assert_allowed_caller, full_balance, get_bump, validate_bounded_vec};
+
+use airdrop_module::*;
+use blacklist_module::*;
+use global_config_module::*;
+use governance_module::*;
+use hold_period_module::*;
+use mint_allowlist_module::*;
+use staking_module::*;
+use transfer_fee_module::*;
+use vesting_module::*;
+
+/// Cooldown applied between authority changes until configured otherwise
+pub const DEFAULT_AUTHORITY_CHANGE_COOLDOWN: i64 = 7 * 24 * 60 * 60;
+
+/// Maximum number of programs allowed to call sensitive instructions via CPI
+pub const MAX_ALLOWED_CALLERS: usize = 8;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
@@ -7,26 +43,20 @@ declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 pub mod mcp_token {
     use super::*;
 
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-    # Modified: 2025-04-26T22:17:41.994896
-    # Modified: 2025-04-26T22:17:39.555860
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-    # Modified: 2025-04-26T22:17:27.967313
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-# processes data for the system: This function processes data for the system
-# initialize: This function processes data for the system
+    /// Create the MCP mint and mint the initial supply to the creator
     pub fn initialize(ctx: Context<Initialize>, initial_supply: u64) -> Result<()> {
         let mint_auth = &mut ctx.accounts.mint_authority;
         mint_auth.authority = ctx.accounts.authority.key();
-        mint_auth.bump = *ctx.bumps.get("mint_authority").unwrap();
+        mint_auth.bump = get_bump(&ctx.bumps, "mint_authority")?;
+        mint_auth.pending_authority = None;
+        mint_auth.last_authority_change = 0;
+        mint_auth.authority_change_cooldown = DEFAULT_AUTHORITY_CHANGE_COOLDOWN;
+        mint_auth.allowed_callers = Vec::new();
+        mint_auth.minted_in_window = 0;
+        mint_auth.window_start = 0;
+        mint_auth.backup_authority = None;
+        mint_auth.inactivity_period = 0;
+        mint_auth.last_active = Clock::get()?.unix_timestamp;
 
         // Mint initial supply to the creator
         token::mint_to(
@@ -49,9 +79,19 @@ pub mod mcp_token {
         Ok(())
     }
 
+    /// Mint tokens; only callable directly or via CPI from an allowed program,
+    /// within the global per-window mint limit, and while the protocol is not paused.
+    /// The receipt starts the destination's hold period, and a mint by the
+    /// authority counts as activity against its backup.
     pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
-        let mint_auth = &ctx.accounts.mint_authority;
-        
+        ctx.accounts.global_config.assert_not_paused()?;
+        let mint_auth = &mut ctx.accounts.mint_authority;
+        assert_allowed_caller(&ctx.accounts.instructions, &mint_auth.allowed_callers)?;
+        assert_mint_destination_allowed(&ctx.accounts.mint_allowlist, &ctx.accounts.token_account)?;
+        let now = Clock::get()?.unix_timestamp;
+        mint_auth.record_mint(amount, &ctx.accounts.global_config, now)?;
+        mint_auth.record_activity(&ctx.accounts.authority.key(), now);
+
         token::mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
@@ -69,10 +109,42 @@ pub mod mcp_token {
             amount,
         )?;
 
-        Ok(())
+        record_mint_receipt(
+            HoldAccounts {
+                hold_config: &ctx.accounts.hold_config,
+                from_hold: &ctx.accounts.to_hold,
+                to_hold: &ctx.accounts.to_hold,
+                payer: &ctx.accounts.authority,
+                system_program: &ctx.accounts.system_program,
+            },
+            &ctx.accounts.token_account,
+            ctx.program_id,
+        )
     }
 
+    /// Transfer tokens between accounts that are not blacklisted, honouring
+    /// the mint's hold period and the global freeze
     pub fn transfer_tokens(ctx: Context<TransferTokens>, amount: u64) -> Result<()> {
+        ctx.accounts.global_config.assert_transfers_allowed()?;
+        assert_transfer_not_blacklisted(
+            &ctx.accounts.blacklist,
+            &ctx.accounts.from,
+            &ctx.accounts.to,
+        )?;
+        enforce_hold_period(
+            HoldAccounts {
+                hold_config: &ctx.accounts.hold_config,
+                from_hold: &ctx.accounts.from_hold,
+                to_hold: &ctx.accounts.to_hold,
+                payer: &ctx.accounts.authority,
+                system_program: &ctx.accounts.system_program,
+            },
+            &ctx.accounts.from,
+            &ctx.accounts.to,
+            &ctx.accounts.global_config,
+            ctx.program_id,
+        )?;
+
         token::transfer(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
@@ -84,9 +156,476 @@ pub mod mcp_token {
             ),
             amount,
         )?;
-        
+
+        Ok(())
+    }
+
+    /// Transfer the whole balance of `from`; an empty account is a no-op
+    pub fn transfer_all(ctx: Context<TransferAll>) -> Result<()> {
+        let Some(amount) = full_balance(&ctx.accounts.from) else {
+            return Ok(());
+        };
+
+        ctx.accounts.global_config.assert_transfers_allowed()?;
+        assert_transfer_not_blacklisted(
+            &ctx.accounts.blacklist,
+            &ctx.accounts.from,
+            &ctx.accounts.to,
+        )?;
+        enforce_hold_period(
+            HoldAccounts {
+                hold_config: &ctx.accounts.hold_config,
+                from_hold: &ctx.accounts.from_hold,
+                to_hold: &ctx.accounts.to_hold,
+                payer: &ctx.accounts.authority,
+                system_program: &ctx.accounts.system_program,
+            },
+            &ctx.accounts.from,
+            &ctx.accounts.to,
+            &ctx.accounts.global_config,
+            ctx.program_id,
+        )?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.from.to_account_info(),
+                    to: ctx.accounts.to.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    /// Propose a new mint authority, who must call `accept_ownership`
+    pub fn transfer_authority(
+        ctx: Context<TransferAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let mint_auth = &mut ctx.accounts.mint_authority;
+        let now = Clock::get()?.unix_timestamp;
+        mint_auth.assert_authority_change_allowed(&ctx.accounts.global_config, now)?;
+        mint_auth.pending_authority = Some(new_authority);
+        mint_auth.last_active = now;
+
+        Ok(())
+    }
+
+    /// Accept a pending mint authority transfer
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let mint_auth = &mut ctx.accounts.mint_authority;
+        mint_auth.assert_authority_change_allowed(&ctx.accounts.global_config, now)?;
+        require!(
+            mint_auth.pending_authority == Some(ctx.accounts.new_authority.key()),
+            McpTokenError::NotPendingAuthority
+        );
+
+        let old_authority = mint_auth.authority;
+        mint_auth.authority = ctx.accounts.new_authority.key();
+        mint_auth.pending_authority = None;
+        mint_auth.last_authority_change = now;
+        mint_auth.last_active = now;
+
+        emit!(AuthorityChanged {
+            mint: ctx.accounts.mint.key(),
+            old_authority,
+            new_authority: mint_auth.authority,
+        });
+
+        Ok(())
+    }
+
+    /// Designate a backup that may take over after `inactivity_period` seconds
+    /// without any action by the authority; `None` removes the backup and
+    /// ignores the period
+    pub fn set_backup_authority(
+        ctx: Context<TransferAuthority>,
+        backup_authority: Option<Pubkey>,
+        inactivity_period: i64,
+    ) -> Result<()> {
+        ctx.accounts.mint_authority.set_backup_authority(
+            backup_authority,
+            inactivity_period,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// Take over the mint authority as its backup once the authority has been
+    /// inactive for the configured period
+    pub fn claim_authority_as_backup(ctx: Context<ClaimAuthorityAsBackup>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let mint_auth = &mut ctx.accounts.mint_authority;
+        mint_auth.assert_backup_claim_allowed(&ctx.accounts.backup_authority.key(), now)?;
+
+        let old_authority = mint_auth.authority;
+        mint_auth.authority = ctx.accounts.backup_authority.key();
+        mint_auth.backup_authority = None;
+        mint_auth.pending_authority = None;
+        mint_auth.last_authority_change = now;
+        mint_auth.last_active = now;
+
+        emit!(AuthorityChanged {
+            mint: ctx.accounts.mint.key(),
+            old_authority,
+            new_authority: mint_auth.authority,
+        });
+
         Ok(())
     }
+
+    /// Replace the programs allowed to call sensitive instructions via CPI
+    pub fn set_allowed_callers(
+        ctx: Context<TransferAuthority>,
+        allowed_callers: Vec<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts
+            .mint_authority
+            .set_allowed_callers(allowed_callers, Clock::get()?.unix_timestamp)
+    }
+
+    /// Update the minimum time between authority changes
+    pub fn set_authority_change_cooldown(
+        ctx: Context<TransferAuthority>,
+        cooldown: i64,
+    ) -> Result<()> {
+        require!(cooldown >= 0, McpTokenError::InvalidCooldown);
+        let mint_auth = &mut ctx.accounts.mint_authority;
+        mint_auth.authority_change_cooldown = cooldown;
+        mint_auth.last_active = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Initialize a new AirdropDistributor and the bitmap of its first generation
+    pub fn initialize_distributor(
+        ctx: Context<InitializeDistributor>,
+        distributor_id: u64,
+        merkle_root: [u8; 32],
+        claim_deadline: i64,
+    ) -> Result<()> {
+        airdrop_module::initialize_distributor(ctx, distributor_id, merkle_root, claim_deadline)
+    }
+
+    /// Start a new airdrop wave.
+    pub fn update_merkle_root(ctx: Context<UpdateMerkleRoot>, new_root: [u8; 32]) -> Result<()> {
+        airdrop_module::update_merkle_root(ctx, new_root)
+    }
+
+    /// Claim `amount` for leaf `index` of the current generation
+    pub fn claim_airdrop(
+        ctx: Context<ClaimAirdrop>,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        airdrop_module::claim_airdrop(ctx, index, amount, proof)
+    }
+
+    /// Return unclaimed tokens to the admin once the claim deadline has passed.
+    pub fn sweep_unclaimed(ctx: Context<SweepUnclaimed>) -> Result<()> {
+        airdrop_module::sweep_unclaimed(ctx)
+    }
+
+    /// Initialize the Blacklist of a mint under `compliance_authority`
+    pub fn initialize_blacklist(
+        ctx: Context<InitializeBlacklist>,
+        compliance_authority: Pubkey,
+    ) -> Result<()> {
+        blacklist_module::initialize_blacklist(ctx, compliance_authority)
+    }
+
+    /// Blacklist `key`; adding an existing entry is a no-op
+    pub fn add_to_blacklist(ctx: Context<UpdateBlacklist>, key: Pubkey) -> Result<()> {
+        blacklist_module::add_to_blacklist(ctx, key)
+    }
+
+    /// Remove `key` from the blacklist; removing a missing entry is a no-op
+    pub fn remove_from_blacklist(ctx: Context<UpdateBlacklist>, key: Pubkey) -> Result<()> {
+        blacklist_module::remove_from_blacklist(ctx, key)
+    }
+
+    /// Initialize the GlobalConfig with minting unlimited
+    pub fn initialize_global_config(ctx: Context<InitializeGlobalConfig>) -> Result<()> {
+        global_config_module::initialize_global_config(ctx)
+    }
+
+    /// Limit minting to `mint_limit_per_window` every `window_seconds`
+    pub fn set_mint_rate_limit(
+        ctx: Context<UpdateGlobalConfig>,
+        mint_limit_per_window: u64,
+        window_seconds: i64,
+    ) -> Result<()> {
+        global_config_module::set_mint_rate_limit(ctx, mint_limit_per_window, window_seconds)
+    }
+
+    /// Pause or unpause the whole protocol.
+    pub fn set_global_paused(ctx: Context<SetGlobalPaused>, global_paused: bool) -> Result<()> {
+        global_config_module::set_global_paused(ctx, global_paused)
+    }
+
+    /// Update the clock skew forgiven at time boundaries, up to `MAX_CLOCK_SKEW_TOLERANCE`
+    pub fn set_clock_skew_tolerance(
+        ctx: Context<UpdateGlobalConfig>,
+        clock_skew_tolerance: i64,
+    ) -> Result<()> {
+        global_config_module::set_clock_skew_tolerance(ctx, clock_skew_tolerance)
+    }
+
+    /// Replace the keys allowed to co-sign the global freeze; once set, two of
+    /// the current freeze admins must co-sign
+    pub fn set_freeze_admins(
+        ctx: Context<UpdateGlobalConfig>,
+        freeze_admins: Vec<Pubkey>,
+    ) -> Result<()> {
+        global_config_module::set_freeze_admins(ctx, freeze_admins)
+    }
+
+    /// Freeze or unfreeze all token transfers.
+    pub fn set_global_freeze_multisig(
+        ctx: Context<SetGlobalFreeze>,
+        global_freeze: bool,
+    ) -> Result<()> {
+        global_config_module::set_global_freeze_multisig(ctx, global_freeze)
+    }
+
+    /// Permanently freeze every global setting except the pause
+    pub fn freeze_config(ctx: Context<UpdateGlobalConfig>) -> Result<()> {
+        global_config_module::freeze_config(ctx)
+    }
+
+    /// Initialize the GovernanceConfig of a mint
+    pub fn initialize_governance_config(
+        ctx: Context<InitializeGovernanceConfig>,
+        tie_break: TieBreak,
+        voting_mode: VotingMode,
+    ) -> Result<()> {
+        governance_module::initialize_governance_config(ctx, tie_break, voting_mode)
+    }
+
+    /// Update how tied proposals are resolved
+    pub fn set_tie_break(ctx: Context<UpdateGovernanceConfig>, tie_break: TieBreak) -> Result<()> {
+        governance_module::set_tie_break(ctx, tie_break)
+    }
+
+    /// Switch between linear and quadratic vote weighting
+    pub fn set_voting_mode(
+        ctx: Context<UpdateGovernanceConfig>,
+        voting_mode: VotingMode,
+    ) -> Result<()> {
+        governance_module::set_voting_mode(ctx, voting_mode)
+    }
+
+    /// Update the bounds on how long proposals stay open for voting
+    pub fn set_voting_period_bounds(
+        ctx: Context<UpdateGovernanceConfig>,
+        min_voting_period: i64,
+        max_voting_period: i64,
+    ) -> Result<()> {
+        governance_module::set_voting_period_bounds(ctx, min_voting_period, max_voting_period)
+    }
+
+    /// Create a new Proposal open for voting until `deadline`.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        proposal_id: u64,
+        description_hash: [u8; 32],
+        deadline: i64,
+    ) -> Result<()> {
+        governance_module::create_proposal(ctx, proposal_id, description_hash, deadline)
+    }
+
+    /// Record the voter's current token balance as their voting power for
+    /// proposals created from the next slot on
+    pub fn record_snapshot(ctx: Context<RecordSnapshot>) -> Result<()> {
+        governance_module::record_snapshot(ctx)
+    }
+
+    /// Vote on a proposal with the voter's balance recorded before the proposal's
+    /// snapshot slot, weighted by the governance's voting mode.
+    pub fn cast_vote(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+        governance_module::cast_vote(ctx, approve)
+    }
+
+    /// Return the tokens locked by a vote once the proposal's deadline has passed,
+    /// closing the vote vault.
+    pub fn withdraw_vote_tokens(ctx: Context<WithdrawVoteTokens>) -> Result<()> {
+        governance_module::withdraw_vote_tokens(ctx)
+    }
+
+    /// Withdraw a proposal that has not received any vote yet.
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        governance_module::cancel_proposal(ctx)
+    }
+
+    /// Resolve a proposal once voting has closed.
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        governance_module::execute_proposal(ctx)
+    }
+
+    /// Initialize the HoldConfig of a mint
+    pub fn initialize_hold_config(
+        ctx: Context<InitializeHoldConfig>,
+        min_hold_seconds: i64,
+    ) -> Result<()> {
+        hold_period_module::initialize_hold_config(ctx, min_hold_seconds)
+    }
+
+    /// Update the minimum hold time; zero turns the hold off
+    pub fn set_min_hold_seconds(
+        ctx: Context<UpdateHoldConfig>,
+        min_hold_seconds: i64,
+    ) -> Result<()> {
+        hold_period_module::set_min_hold_seconds(ctx, min_hold_seconds)
+    }
+
+    /// Replace the keys exempt from the hold period
+    pub fn set_hold_exemptions(
+        ctx: Context<UpdateHoldConfig>,
+        exemptions: Vec<Pubkey>,
+    ) -> Result<()> {
+        hold_period_module::set_hold_exemptions(ctx, exemptions)
+    }
+
+    /// Initialize the MintDestinationAllowlist of a mint, unrestricted and empty
+    pub fn initialize_mint_allowlist(ctx: Context<InitializeMintAllowlist>) -> Result<()> {
+        mint_allowlist_module::initialize_mint_allowlist(ctx)
+    }
+
+    /// Turn restricted mode on or off
+    pub fn set_mint_restricted(ctx: Context<UpdateMintAllowlist>, restricted: bool) -> Result<()> {
+        mint_allowlist_module::set_mint_restricted(ctx, restricted)
+    }
+
+    /// Allowlist `key`; adding an existing entry is a no-op
+    pub fn add_mint_destination(ctx: Context<UpdateMintAllowlist>, key: Pubkey) -> Result<()> {
+        mint_allowlist_module::add_mint_destination(ctx, key)
+    }
+
+    /// Remove `key` from the allowlist; removing a missing entry is a no-op
+    pub fn remove_mint_destination(ctx: Context<UpdateMintAllowlist>, key: Pubkey) -> Result<()> {
+        mint_allowlist_module::remove_mint_destination(ctx, key)
+    }
+
+    /// Initialize a new StakePool
+    pub fn initialize_stake_pool(ctx: Context<InitializeStakePool>) -> Result<()> {
+        staking_module::initialize_stake_pool(ctx)
+    }
+
+    /// Stake tokens into the pool vault
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        staking_module::stake(ctx, amount)
+    }
+
+    /// Withdraw staked tokens from the pool vault.
+    pub fn unstake<'info>(
+        ctx: Context<'_, '_, '_, 'info, Unstake<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        staking_module::unstake(ctx, amount)
+    }
+
+    /// Configure the early unstake penalty of a pool; a zero penalty disables it
+    pub fn set_early_unstake_penalty(
+        ctx: Context<UpdateStakePool>,
+        lock_period: i64,
+        early_unstake_penalty_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        staking_module::set_early_unstake_penalty(
+            ctx,
+            lock_period,
+            early_unstake_penalty_bps,
+            treasury,
+        )
+    }
+
+    /// Cap the rewards paid out per epoch; zero removes the cap
+    pub fn set_reward_budget(
+        ctx: Context<UpdateStakePool>,
+        reward_budget_per_epoch: u64,
+    ) -> Result<()> {
+        staking_module::set_reward_budget(ctx, reward_budget_per_epoch)
+    }
+
+    /// Deposit rewards for everyone currently staked in the pool, pro rata to stake
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+        staking_module::fund_rewards(ctx, amount)
+    }
+
+    /// Opt in or out of auto-compounding claimed rewards
+    pub fn set_auto_compound(ctx: Context<SetAutoCompound>, auto_compound: bool) -> Result<()> {
+        staking_module::set_auto_compound(ctx, auto_compound)
+    }
+
+    /// Lock a stake until the locker releases it
+    pub fn lock_stake(ctx: Context<LockStake>) -> Result<()> {
+        staking_module::lock_stake(ctx)
+    }
+
+    /// Release a stake lock
+    pub fn unlock_stake(ctx: Context<UnlockStake>) -> Result<()> {
+        staking_module::unlock_stake(ctx)
+    }
+
+    /// Forfeit part of a locked stake to the pool treasury
+    pub fn slash_stake(ctx: Context<SlashStake>, amount: u64) -> Result<()> {
+        staking_module::slash_stake(ctx, amount)
+    }
+
+    /// Claim accrued staking rewards.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        staking_module::claim_rewards(ctx)
+    }
+
+    /// Set the transfer fee of a Token-2022 mint, signed by the mint authority PDA.
+    pub fn configure_transfer_fee(
+        ctx: Context<ConfigureTransferFee>,
+        fee_bps: u16,
+        max_fee: u64,
+    ) -> Result<()> {
+        transfer_fee_module::configure_transfer_fee(ctx, fee_bps, max_fee)
+    }
+
+    /// Collect withheld transfer fees into `destination`.
+    pub fn harvest_withheld_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, HarvestWithheldFees<'info>>,
+    ) -> Result<()> {
+        transfer_fee_module::harvest_withheld_fees(ctx)
+    }
+
+    /// Create a vesting schedule and lock `total_amount` in its vault
+    pub fn create_vesting_schedule(
+        ctx: Context<CreateVestingSchedule>,
+        schedule_id: u64,
+        total_amount: u64,
+        start_time: i64,
+        cliff_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        vesting_module::create_vesting_schedule(
+            ctx,
+            schedule_id,
+            total_amount,
+            start_time,
+            cliff_time,
+            end_time,
+        )
+    }
+
+    /// Release everything vested so far on one schedule to its beneficiary
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        vesting_module::claim_vested(ctx)
+    }
+
+    /// Release vested tokens for several schedules at once.
+    pub fn batch_claim_vested<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchClaimVested<'info>>,
+    ) -> Result<()> {
+        vesting_module::batch_claim_vested(ctx)
+    }
 }
 
 #[derive(Accounts)]
@@ -123,6 +662,7 @@ pub struct Initialize<'info> {
 
 #[derive(Accounts)]
 pub struct MintTokens<'info> {
+    #[account(mut)]
     pub authority: Signer<'info>,
 
     #[account(mut)]
@@ -135,16 +675,52 @@ pub struct MintTokens<'info> {
     pub token_account: Account<'info, TokenAccount>,
 
     #[account(
+        mut,
         seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
         bump = mint_authority.bump,
     )]
     pub mint_authority: Account<'info, MintAuthority>,
 
+    /// CHECK: Mint destination allowlist, enforced only once initialized
+    #[account(
+        seeds = [b"mint-allowlist".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_allowlist: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Instructions sysvar, used to identify CPI callers
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// CHECK: Hold config of the mint, applied only once initialized
+    #[account(
+        seeds = [b"hold-config".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub hold_config: UncheckedAccount<'info>,
+
+    /// CHECK: Hold record of `token_account`, created on receipt while the hold is enabled
+    #[account(
+        mut,
+        seeds = [b"hold".as_ref(), token_account.key().as_ref()],
+        bump,
+    )]
+    pub to_hold: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct TransferTokens<'info> {
+    #[account(mut)]
     pub authority: Signer<'info>,
 
     #[account(
@@ -153,76 +729,416 @@ pub struct TransferTokens<'info> {
     )]
     pub from: Account<'info, TokenAccount>,
 
+    #[account(
+        mut,
+        token::mint = from.mint,
+    )]
+    pub to: Account<'info, TokenAccount>,
+
+    /// CHECK: Blacklist of the mint, enforced only once initialized
+    #[account(
+        seeds = [b"blacklist".as_ref(), from.mint.as_ref()],
+        bump,
+    )]
+    pub blacklist: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Hold config of the mint, enforced only once initialized
+    #[account(
+        seeds = [b"hold-config".as_ref(), from.mint.as_ref()],
+        bump,
+    )]
+    pub hold_config: UncheckedAccount<'info>,
+
+    /// CHECK: Hold record of `from`, checked only once initialized
+    #[account(
+        seeds = [b"hold".as_ref(), from.key().as_ref()],
+        bump,
+    )]
+    pub from_hold: UncheckedAccount<'info>,
+
+    /// CHECK: Hold record of `to`, created on receipt while the hold is enabled
+    #[account(
+        mut,
+        seeds = [b"hold".as_ref(), to.key().as_ref()],
+        bump,
+    )]
+    pub to_hold: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAll<'info> {
     #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        token::authority = authority,
+    )]
+    pub from: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = from.mint,
+    )]
     pub to: Account<'info, TokenAccount>,
 
+    /// CHECK: Blacklist of the mint, enforced only once initialized
+    #[account(
+        seeds = [b"blacklist".as_ref(), from.mint.as_ref()],
+        bump,
+    )]
+    pub blacklist: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Hold config of the mint, enforced only once initialized
+    #[account(
+        seeds = [b"hold-config".as_ref(), from.mint.as_ref()],
+        bump,
+    )]
+    pub hold_config: UncheckedAccount<'info>,
+
+    /// CHECK: Hold record of `from`, checked only once initialized
+    #[account(
+        seeds = [b"hold".as_ref(), from.key().as_ref()],
+        bump,
+    )]
+    pub from_hold: UncheckedAccount<'info>,
+
+    /// CHECK: Hold record of `to`, created on receipt while the hold is enabled
+    #[account(
+        mut,
+        seeds = [b"hold".as_ref(), to.key().as_ref()],
+        bump,
+    )]
+    pub to_hold: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
+        bump = mint_authority.bump,
+        has_one = authority,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    pub new_authority: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
+        bump = mint_authority.bump,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAuthorityAsBackup<'info> {
+    pub backup_authority: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
+        bump = mint_authority.bump,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
 }
 
 #[account]
 pub struct MintAuthority {
     pub authority: Pubkey,
     pub bump: u8,
-} 
+    pub pending_authority: Option<Pubkey>,
+    pub last_authority_change: i64,
+    pub authority_change_cooldown: i64,
+    pub allowed_callers: Vec<Pubkey>,
+    pub minted_in_window: u64,
+    pub window_start: i64,
+    pub backup_authority: Option<Pubkey>,
+    pub inactivity_period: i64,
+    pub last_active: i64,
+}
 
+impl MintAuthority {
+    /// Replace the programs allowed to call sensitive instructions via CPI,
+    /// counting the change as activity of the authority at `now`
+    pub fn set_allowed_callers(&mut self, allowed_callers: Vec<Pubkey>, now: i64) -> Result<()> {
+        validate_bounded_vec(&allowed_callers, MAX_ALLOWED_CALLERS)?;
+        self.allowed_callers = allowed_callers;
+        self.last_active = now;
 
-pub fn preprocess_data(ctx: Context<Preprocess_data>) -> Result<()> {
-    // Implementation
-    Ok(())
-}
+        Ok(())
+    }
 
+    /// Designate `backup_authority` with its `inactivity_period`, which is
+    /// only required when a backup is set; clearing the backup resets it
+    pub fn set_backup_authority(
+        &mut self,
+        backup_authority: Option<Pubkey>,
+        inactivity_period: i64,
+        now: i64,
+    ) -> Result<()> {
+        self.inactivity_period = match backup_authority {
+            Some(_) => {
+                require!(
+                    inactivity_period > 0,
+                    McpTokenError::InvalidInactivityPeriod
+                );
+                inactivity_period
+            }
+            None => 0,
+        };
+        self.backup_authority = backup_authority;
+        self.last_active = now;
 
+        Ok(())
+    }
 
-pub fn preprocess_data(ctx: Context<Preprocess_data>) -> Result<()> {
-    // Implementation
-    Ok(())
-}
+    /// Reject authority changes within the cooldown of the previous change
+    pub fn assert_authority_change_allowed(&self, config: &GlobalConfig, now: i64) -> Result<()> {
+        let allowed_at = self
+            .last_authority_change
+            .checked_add(self.authority_change_cooldown)
+            .ok_or(McpTokenError::MathOverflow)?;
+        require!(
+            config.has_elapsed(now, allowed_at),
+            McpTokenError::AuthorityChangeCooldown
+        );
 
+        Ok(())
+    }
 
+    /// Count `amount` against the current mint window, starting a new window
+    /// once the previous one has elapsed
+    pub fn record_mint(&mut self, amount: u64, config: &GlobalConfig, now: i64) -> Result<()> {
+        if config.mint_limit_per_window == 0 {
+            return Ok(());
+        }
 
-pub fn process_context(ctx: Context<Process_context>) -> Result<()> {
-    // Implementation
-    Ok(())
-}
+        let window_end = self
+            .window_start
+            .checked_add(config.window_seconds)
+            .ok_or(McpTokenError::MathOverflow)?;
+        if config.has_elapsed(now, window_end) {
+            self.window_start = now;
+            self.minted_in_window = 0;
+        }
+
+        let minted = self
+            .minted_in_window
+            .checked_add(amount)
+            .ok_or(McpTokenError::MathOverflow)?;
+        require!(
+            minted <= config.mint_limit_per_window,
+            McpTokenError::MintLimitExceeded
+        );
+        self.minted_in_window = minted;
 
+        Ok(())
+    }
 
+    /// Count an action by `actor` as activity of the authority; actions by
+    /// anyone else do not hold off the backup
+    pub fn record_activity(&mut self, actor: &Pubkey, now: i64) {
+        if *actor == self.authority {
+            self.last_active = now;
+        }
+    }
 
-pub fn preprocess_data(ctx: Context<Preprocess_data>) -> Result<()> {
-    // Implementation
-    Ok(())
+    /// Reject a takeover by anyone but the backup, or before the authority has
+    /// been inactive for `inactivity_period`
+    pub fn assert_backup_claim_allowed(&self, backup: &Pubkey, now: i64) -> Result<()> {
+        require!(
+            self.backup_authority == Some(*backup),
+            McpTokenError::NotBackupAuthority
+        );
+        let inactive_until = self
+            .last_active
+            .checked_add(self.inactivity_period)
+            .ok_or(McpTokenError::MathOverflow)?;
+        require!(now >= inactive_until, McpTokenError::AuthorityStillActive);
+
+        Ok(())
+    }
 }
 
+#[event]
+pub struct AuthorityChanged {
+    pub mint: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::global_config_module::tests::global_config;
 
-pub fn convert_format(ctx: Context<Convert_format>) -> Result<()> {
-    // Implementation
-    Ok(())
-}
+    fn mint_authority() -> MintAuthority {
+        MintAuthority {
+            authority: Pubkey::new_unique(),
+            bump: 255,
+            pending_authority: None,
+            last_authority_change: 0,
+            authority_change_cooldown: 0,
+            allowed_callers: Vec::new(),
+            minted_in_window: 0,
+            window_start: 0,
+            backup_authority: Some(Pubkey::new_unique()),
+            inactivity_period: 1_000,
+            last_active: 0,
+        }
+    }
 
+    #[test]
+    fn allowed_callers_over_bound_rejected() {
+        let mut mint_auth = mint_authority();
+        let callers = |count: usize| (0..count).map(|_| Pubkey::new_unique()).collect();
 
+        mint_auth
+            .set_allowed_callers(callers(MAX_ALLOWED_CALLERS), 500)
+            .unwrap();
+        assert_eq!(mint_auth.allowed_callers.len(), MAX_ALLOWED_CALLERS);
+        assert_eq!(mint_auth.last_active, 500);
+        assert_eq!(
+            mint_auth
+                .set_allowed_callers(callers(MAX_ALLOWED_CALLERS + 1), 600)
+                .unwrap_err(),
+            McpTokenError::TooManyEntries.into()
+        );
+        assert_eq!(mint_auth.last_active, 500);
+    }
 
-pub fn validate_input(ctx: Context<Validate_input>) -> Result<()> {
-    // Implementation
-    Ok(())
-}
+    #[test]
+    fn minting_by_authority_holds_off_backup() {
+        let mut mint_auth = mint_authority();
+        let backup = mint_auth.backup_authority.unwrap();
+        let authority = mint_auth.authority;
 
+        mint_auth.record_activity(&Pubkey::new_unique(), 900);
+        mint_auth
+            .assert_backup_claim_allowed(&backup, 1_000)
+            .unwrap();
 
+        mint_auth.record_activity(&authority, 900);
+        assert_eq!(
+            mint_auth
+                .assert_backup_claim_allowed(&backup, 1_000)
+                .unwrap_err(),
+            McpTokenError::AuthorityStillActive.into()
+        );
+        mint_auth
+            .assert_backup_claim_allowed(&backup, 1_900)
+            .unwrap();
+        assert_eq!(
+            mint_auth
+                .assert_backup_claim_allowed(&Pubkey::new_unique(), 1_900)
+                .unwrap_err(),
+            McpTokenError::NotBackupAuthority.into()
+        );
+    }
 
-pub fn analyze_results(ctx: Context<Analyze_results>) -> Result<()> {
-    // Implementation
-    Ok(())
-}
+    #[test]
+    fn backup_requires_period_only_when_set() {
+        let mut mint_auth = mint_authority();
+        let backup = Pubkey::new_unique();
 
+        for period in [0, -1] {
+            assert_eq!(
+                mint_auth
+                    .set_backup_authority(Some(backup), period, 1_000)
+                    .unwrap_err(),
+                McpTokenError::InvalidInactivityPeriod.into()
+            );
+        }
 
+        mint_auth
+            .set_backup_authority(Some(backup), 500, 1_000)
+            .unwrap();
+        assert_eq!(mint_auth.backup_authority, Some(backup));
+        assert_eq!(mint_auth.inactivity_period, 500);
 
-pub fn connect_blockchain(ctx: Context<Connect_blockchain>) -> Result<()> {
-    // Implementation
-    Ok(())
-}
+        mint_auth.set_backup_authority(None, 0, 1_200).unwrap();
+        assert_eq!(mint_auth.backup_authority, None);
+        assert_eq!(mint_auth.inactivity_period, 0);
+        assert_eq!(mint_auth.last_active, 1_200);
+    }
+
+    #[test]
+    fn second_authority_change_within_cooldown_rejected() {
+        let config = global_config();
+        let mut mint_auth = mint_authority();
+        mint_auth.authority_change_cooldown = 100;
+        mint_auth.last_authority_change = 1_000;
 
+        assert_eq!(
+            mint_auth
+                .assert_authority_change_allowed(&config, 1_099)
+                .unwrap_err(),
+            McpTokenError::AuthorityChangeCooldown.into()
+        );
+        mint_auth
+            .assert_authority_change_allowed(&config, 1_100)
+            .unwrap();
+    }
 
+    #[test]
+    fn minting_blocked_past_window_limit_until_rollover() {
+        let mut config = global_config();
+        config.mint_limit_per_window = 1_000;
+        config.window_seconds = 100;
+        let mut mint_auth = mint_authority();
 
-pub fn register_model(ctx: Context<Register_model>) -> Result<()> {
-    // Implementation
-    Ok(())
+        mint_auth.record_mint(600, &config, 1_000).unwrap();
+        mint_auth.record_mint(400, &config, 1_050).unwrap();
+        assert_eq!(
+            mint_auth.record_mint(1, &config, 1_099).unwrap_err(),
+            McpTokenError::MintLimitExceeded.into()
+        );
+        mint_auth.record_mint(1_000, &config, 1_100).unwrap();
+        assert_eq!(mint_auth.window_start, 1_100);
+    }
 }
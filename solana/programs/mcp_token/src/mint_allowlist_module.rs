@@ -0,0 +1,226 @@
+//! mint_allowlist_module module for MCP token
+//!
+//! This module provides an opt-in allowlist of `mint_tokens` destinations for
+//! tightly controlled distributions. Once a mint has a MintDestinationAllowlist
+//! in restricted mode, tokens can only be minted into listed token accounts or
+//! token accounts owned by listed wallets. Entries are managed by the mint
+//! authority.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::errors_module::McpTokenError;
+use crate::utils_module::get_bump;
+use crate::MintAuthority;
+
+/// Maximum number of allowlisted mint destinations per mint
+pub const MAX_MINT_DESTINATIONS: usize = 32;
+
+/// MintDestinationAllowlist state account
+#[account]
+pub struct MintDestinationAllowlist {
+    /// The mint the allowlist applies to
+    pub mint: Pubkey,
+
+    /// Whether minting is limited to `entries`
+    pub restricted: bool,
+
+    /// Allowlisted wallets and token accounts
+    pub entries: Vec<Pubkey>,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl MintDestinationAllowlist {
+    pub const LEN: usize = 32 + 1 + (4 + 32 * MAX_MINT_DESTINATIONS) + 1;
+
+    pub fn is_allowlisted(&self, key: &Pubkey) -> bool {
+        self.entries.contains(key)
+    }
+}
+
+/// Reject minting into `destination` while the mint's allowlist is restricted
+/// and neither the token account nor its owner is listed.
+///
+/// Does nothing while the mint has no MintDestinationAllowlist.
+pub fn assert_mint_destination_allowed(
+    allowlist: &AccountInfo,
+    destination: &Account<TokenAccount>,
+) -> Result<()> {
+    if allowlist.data_is_empty() {
+        return Ok(());
+    }
+    let allowlist = Account::<MintDestinationAllowlist>::try_from(allowlist)?;
+    require!(
+        !allowlist.restricted
+            || allowlist.is_allowlisted(&destination.key())
+            || allowlist.is_allowlisted(&destination.owner),
+        McpTokenError::MintDestinationNotAllowed
+    );
+
+    Ok(())
+}
+
+/// Initialize the MintDestinationAllowlist of a mint, unrestricted and empty
+pub fn initialize_mint_allowlist(ctx: Context<InitializeMintAllowlist>) -> Result<()> {
+    let allowlist = &mut ctx.accounts.mint_allowlist;
+    allowlist.mint = ctx.accounts.mint.key();
+    allowlist.restricted = false;
+    allowlist.entries = Vec::new();
+    allowlist.bump = get_bump(&ctx.bumps, "mint_allowlist")?;
+
+    Ok(())
+}
+
+/// Turn restricted mode on or off
+pub fn set_mint_restricted(ctx: Context<UpdateMintAllowlist>, restricted: bool) -> Result<()> {
+    ctx.accounts.mint_allowlist.restricted = restricted;
+
+    Ok(())
+}
+
+/// Allowlist `key`; adding an existing entry is a no-op
+pub fn add_mint_destination(ctx: Context<UpdateMintAllowlist>, key: Pubkey) -> Result<()> {
+    let allowlist = &mut ctx.accounts.mint_allowlist;
+    if allowlist.is_allowlisted(&key) {
+        return Ok(());
+    }
+    require!(
+        allowlist.entries.len() < MAX_MINT_DESTINATIONS,
+        McpTokenError::TooManyEntries
+    );
+    allowlist.entries.push(key);
+
+    Ok(())
+}
+
+/// Remove `key` from the allowlist; removing a missing entry is a no-op
+pub fn remove_mint_destination(ctx: Context<UpdateMintAllowlist>, key: Pubkey) -> Result<()> {
+    ctx.accounts
+        .mint_allowlist
+        .entries
+        .retain(|entry| *entry != key);
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeMintAllowlist<'info> {
+    /// The mint authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The mint the allowlist applies to
+    pub mint: Account<'info, Mint>,
+
+    /// Mint authority of `mint`
+    #[account(
+        seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
+        bump = mint_authority.bump,
+        has_one = authority,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MintDestinationAllowlist::LEN,
+        seeds = [b"mint-allowlist".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_allowlist: Account<'info, MintDestinationAllowlist>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for update
+#[derive(Accounts)]
+pub struct UpdateMintAllowlist<'info> {
+    /// The mint authority
+    pub authority: Signer<'info>,
+
+    /// Mint authority of the allowlist's mint
+    #[account(
+        seeds = [b"mint-authority".as_ref(), mint_allowlist.mint.as_ref()],
+        bump = mint_authority.bump,
+        has_one = authority,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"mint-allowlist".as_ref(), mint_allowlist.mint.as_ref()],
+        bump = mint_allowlist.bump,
+    )]
+    pub mint_allowlist: Account<'info, MintDestinationAllowlist>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+
+    #[test]
+    fn restricted_mint_only_to_allowlisted_destination() {
+        let listed = Pubkey::new_unique();
+        let mut allowlist_data = Vec::new();
+        MintDestinationAllowlist {
+            mint: Pubkey::new_unique(),
+            restricted: true,
+            entries: vec![listed],
+            bump: 255,
+        }
+        .try_serialize(&mut allowlist_data)
+        .unwrap();
+        let allowlist_key = Pubkey::new_unique();
+        let mut allowlist_lamports = 0;
+        let allowlist = AccountInfo::new(
+            &allowlist_key,
+            false,
+            false,
+            &mut allowlist_lamports,
+            &mut allowlist_data,
+            &crate::ID,
+            false,
+            0,
+        );
+
+        let mut token_data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            owner: Pubkey::new_unique(),
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut token_data);
+        for (key, expected) in [
+            (listed, Ok(())),
+            (
+                Pubkey::new_unique(),
+                Err(McpTokenError::MintDestinationNotAllowed.into()),
+            ),
+        ] {
+            let (mut lamports, mut data) = (0, token_data.clone());
+            let info = AccountInfo::new(
+                &key,
+                false,
+                true,
+                &mut lamports,
+                &mut data,
+                &spl_token::ID,
+                false,
+                0,
+            );
+            let destination = Account::<TokenAccount>::try_from(&info).unwrap();
+            assert_eq!(
+                assert_mint_destination_allowed(&allowlist, &destination),
+                expected
+            );
+        }
+    }
+}
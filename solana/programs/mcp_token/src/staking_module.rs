@@ -0,0 +1,1070 @@
+//! staking_module module for MCP token
+//!
+//! This module provides staking of MCP tokens into a program-owned vault.
+//! Rewards funded into the pool are shared pro rata to stake using a
+//! reward-per-share accumulator, and stakers may opt into auto-compounding.
+//! Pools can set a lock period during which unstaking forfeits a penalty to
+//! the pool treasury, and a per-epoch budget that caps the rewards distributed
+//! in each epoch. Funded rewards beyond the budget are queued and distributed
+//! in later epochs, so every staker's share is scaled down alike rather than
+//! the budget going to whoever claims first. A stake can be locked by another
+//! program's PDA, e.g. while it backs a model registration, and cannot be
+//! unstaked until that PDA unlocks it. The locking PDA may also slash the
+//! stake, forfeiting part of it to the pool treasury.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use crate::errors_module::McpTokenError;
+use crate::global_config_module::{GlobalConfig, GLOBAL_CONFIG_SEED};
+use crate::utils_module::{get_bump, mul_div, BPS_DENOMINATOR};
+use crate::vaults_module::{REWARD_VAULT_SEED, STAKE_VAULT_SEED};
+
+/// Fixed-point scale of `StakePool::acc_reward_per_share`
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// StakePool state account
+#[account]
+pub struct StakePool {
+    /// The authority that can update this account
+    pub authority: Pubkey,
+
+    /// Mint being staked
+    pub mint: Pubkey,
+
+    /// Total tokens staked in the vault
+    pub total_staked: u64,
+
+    /// Rewards accrued per staked token, scaled by `REWARD_PRECISION`
+    pub acc_reward_per_share: u128,
+
+    /// Seconds after the most recent stake before unstaking is penalty free
+    pub lock_period: i64,
+
+    /// Share of the principal forfeited when unstaking early, in basis points
+    pub early_unstake_penalty_bps: u16,
+
+    /// Token account receiving early unstake penalties
+    pub treasury: Pubkey,
+
+    /// Most rewards distributed to stakers per epoch; zero is unlimited
+    pub reward_budget_per_epoch: u64,
+
+    /// Rewards distributed to stakers during `reward_epoch`
+    pub rewards_paid_this_epoch: u64,
+
+    /// Epoch `rewards_paid_this_epoch` was counted in
+    pub reward_epoch: u64,
+
+    /// Funded rewards waiting for budget to be distributed
+    pub queued_rewards: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl StakePool {
+    pub const LEN: usize = 32 + 32 + 8 + 16 + 8 + 2 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// Distribute as much of `queued_rewards` as the budget of `epoch` allows
+    /// into the accumulator, pro rata to stake.
+    ///
+    /// Must be called before the accumulator is read or `total_staked` changes.
+    pub fn distribute_rewards(&mut self, epoch: u64) -> Result<()> {
+        if epoch != self.reward_epoch {
+            self.reward_epoch = epoch;
+            self.rewards_paid_this_epoch = 0;
+        }
+        if self.total_staked == 0 {
+            return Ok(());
+        }
+        let amount = match self.reward_budget_per_epoch {
+            0 => self.queued_rewards,
+            budget => self
+                .queued_rewards
+                .min(budget.saturating_sub(self.rewards_paid_this_epoch)),
+        };
+        if amount == 0 {
+            return Ok(());
+        }
+
+        self.acc_reward_per_share = (amount as u128)
+            .checked_mul(REWARD_PRECISION)
+            .map(|scaled| scaled / self.total_staked as u128)
+            .and_then(|increase| self.acc_reward_per_share.checked_add(increase))
+            .ok_or(McpTokenError::MathOverflow)?;
+        self.queued_rewards -= amount;
+        self.rewards_paid_this_epoch = self
+            .rewards_paid_this_epoch
+            .checked_add(amount)
+            .ok_or(McpTokenError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Penalty for unstaking `amount` from a stake last topped up at `staked_at`
+    pub fn early_unstake_penalty(&self, amount: u64, staked_at: i64, now: i64) -> Result<u64> {
+        let unlock_at = staked_at
+            .checked_add(self.lock_period)
+            .ok_or(McpTokenError::MathOverflow)?;
+        if now >= unlock_at {
+            return Ok(0);
+        }
+        mul_div(
+            amount,
+            self.early_unstake_penalty_bps as u64,
+            BPS_DENOMINATOR,
+        )
+    }
+
+    /// Re-stake a `reward` claimed by `stake_account` into the stake vault
+    pub fn compound(&mut self, stake_account: &mut StakeAccount, reward: u64) -> Result<()> {
+        stake_account.amount = stake_account
+            .amount
+            .checked_add(reward)
+            .ok_or(McpTokenError::MathOverflow)?;
+        self.total_staked = self
+            .total_staked
+            .checked_add(reward)
+            .ok_or(McpTokenError::MathOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// StakeAccount state account
+#[account]
+pub struct StakeAccount {
+    /// The owner of the stake
+    pub owner: Pubkey,
+
+    /// The pool the stake belongs to
+    pub pool: Pubkey,
+
+    /// Tokens currently staked
+    pub amount: u64,
+
+    /// Time of the most recent stake
+    pub staked_at: i64,
+
+    /// Rewards already accounted for at the current `amount`, scaled by `REWARD_PRECISION`
+    pub reward_debt: u128,
+
+    /// Rewards settled but not yet claimed
+    pub pending_rewards: u64,
+
+    /// Whether claimed rewards are re-staked instead of paid out
+    pub auto_compound: bool,
+
+    /// Account holding the stake lock, if any; unstaking is blocked while set
+    pub locked_by: Option<Pubkey>,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl StakeAccount {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 16 + 8 + 1 + 33 + 1;
+
+    /// Lock the stake on behalf of `locker`
+    pub fn lock(&mut self, locker: Pubkey) -> Result<()> {
+        require!(self.locked_by.is_none(), McpTokenError::StakeLocked);
+        self.locked_by = Some(locker);
+
+        Ok(())
+    }
+
+    /// Release the lock held by `locker`
+    pub fn unlock(&mut self, locker: Pubkey) -> Result<()> {
+        require!(
+            self.locked_by == Some(locker),
+            McpTokenError::NotStakeLocker
+        );
+        self.locked_by = None;
+
+        Ok(())
+    }
+
+    /// Forfeit `amount` of the stake on behalf of `locker`, which must hold the
+    /// lock, settling the rewards earned before the stake shrinks
+    pub fn slash(&mut self, locker: Pubkey, amount: u64, acc_reward_per_share: u128) -> Result<()> {
+        require!(
+            self.locked_by == Some(locker),
+            McpTokenError::NotStakeLocker
+        );
+        require!(amount > 0, McpTokenError::InvalidAmount);
+        require!(amount <= self.amount, McpTokenError::InsufficientStake);
+        self.settle_rewards(acc_reward_per_share)?;
+        self.amount -= amount;
+        self.sync_reward_debt(acc_reward_per_share)
+    }
+
+    fn accrued(&self, acc_reward_per_share: u128) -> Result<u128> {
+        (self.amount as u128)
+            .checked_mul(acc_reward_per_share)
+            .map(|accrued| accrued / REWARD_PRECISION)
+            .ok_or_else(|| error!(McpTokenError::MathOverflow))
+    }
+
+    /// Move rewards earned since the last settlement into `pending_rewards`.
+    ///
+    /// Must be called before `amount` changes, followed by `sync_reward_debt`.
+    pub fn settle_rewards(&mut self, acc_reward_per_share: u128) -> Result<()> {
+        let earned = self
+            .accrued(acc_reward_per_share)?
+            .checked_sub(self.reward_debt)
+            .ok_or(McpTokenError::MathOverflow)?;
+        self.pending_rewards = u64::try_from(earned)
+            .ok()
+            .and_then(|earned| self.pending_rewards.checked_add(earned))
+            .ok_or(McpTokenError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Reset the reward debt to the current `amount`
+    pub fn sync_reward_debt(&mut self, acc_reward_per_share: u128) -> Result<()> {
+        self.reward_debt = self.accrued(acc_reward_per_share)?;
+
+        Ok(())
+    }
+}
+
+/// Initialize a new StakePool
+pub fn initialize_stake_pool(ctx: Context<InitializeStakePool>) -> Result<()> {
+    let pool = &mut ctx.accounts.stake_pool;
+    pool.authority = ctx.accounts.authority.key();
+    pool.mint = ctx.accounts.mint.key();
+    pool.total_staked = 0;
+    pool.acc_reward_per_share = 0;
+    pool.lock_period = 0;
+    pool.early_unstake_penalty_bps = 0;
+    pool.treasury = Pubkey::default();
+    pool.reward_budget_per_epoch = 0;
+    pool.rewards_paid_this_epoch = 0;
+    pool.reward_epoch = 0;
+    pool.queued_rewards = 0;
+    pool.bump = get_bump(&ctx.bumps, "stake_pool")?;
+
+    Ok(())
+}
+
+/// Stake tokens into the pool vault
+pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    require!(amount > 0, McpTokenError::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.stake_vault.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    ctx.accounts
+        .stake_pool
+        .distribute_rewards(Clock::get()?.epoch)?;
+    let acc_reward_per_share = ctx.accounts.stake_pool.acc_reward_per_share;
+    let stake_account = &mut ctx.accounts.stake_account;
+    if stake_account.owner == Pubkey::default() {
+        stake_account.owner = ctx.accounts.owner.key();
+        stake_account.pool = ctx.accounts.stake_pool.key();
+        stake_account.bump = get_bump(&ctx.bumps, "stake_account")?;
+    }
+    stake_account.settle_rewards(acc_reward_per_share)?;
+    stake_account.amount = stake_account
+        .amount
+        .checked_add(amount)
+        .ok_or(McpTokenError::MathOverflow)?;
+    stake_account.sync_reward_debt(acc_reward_per_share)?;
+    stake_account.staked_at = Clock::get()?.unix_timestamp;
+
+    let pool = &mut ctx.accounts.stake_pool;
+    pool.total_staked = pool
+        .total_staked
+        .checked_add(amount)
+        .ok_or(McpTokenError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Withdraw staked tokens from the pool vault.
+///
+/// Unstaking within the pool's lock period of the most recent stake forfeits
+/// `early_unstake_penalty_bps` of `amount` to the pool treasury.
+pub fn unstake<'info>(ctx: Context<'_, '_, '_, 'info, Unstake<'info>>, amount: u64) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    require!(amount > 0, McpTokenError::InvalidAmount);
+    require!(
+        ctx.accounts.stake_account.locked_by.is_none(),
+        McpTokenError::StakeLocked
+    );
+    require!(
+        amount <= ctx.accounts.stake_account.amount,
+        McpTokenError::InsufficientStake
+    );
+
+    let pool = &ctx.accounts.stake_pool;
+    let penalty = pool.early_unstake_penalty(
+        amount,
+        ctx.accounts.stake_account.staked_at,
+        Clock::get()?.unix_timestamp,
+    )?;
+    let bump = [pool.bump];
+    let signer_seeds: &[&[u8]] = &[b"stake-pool".as_ref(), pool.mint.as_ref(), &bump];
+    let transfer_from_vault = |to: AccountInfo<'info>, amount: u64| -> Result<()> {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    to,
+                    authority: pool.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+        )
+    };
+
+    if penalty > 0 {
+        let treasury = ctx
+            .accounts
+            .treasury
+            .as_ref()
+            .ok_or(McpTokenError::InvalidTreasury)?;
+        transfer_from_vault(treasury.to_account_info(), penalty)?;
+    }
+    transfer_from_vault(
+        ctx.accounts.owner_token_account.to_account_info(),
+        amount - penalty,
+    )?;
+
+    ctx.accounts
+        .stake_pool
+        .distribute_rewards(Clock::get()?.epoch)?;
+    let acc_reward_per_share = ctx.accounts.stake_pool.acc_reward_per_share;
+    let stake_account = &mut ctx.accounts.stake_account;
+    stake_account.settle_rewards(acc_reward_per_share)?;
+    stake_account.amount -= amount;
+    stake_account.sync_reward_debt(acc_reward_per_share)?;
+
+    let pool = &mut ctx.accounts.stake_pool;
+    pool.total_staked = pool
+        .total_staked
+        .checked_sub(amount)
+        .ok_or(McpTokenError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Configure the early unstake penalty of a pool; a zero penalty disables it
+pub fn set_early_unstake_penalty(
+    ctx: Context<UpdateStakePool>,
+    lock_period: i64,
+    early_unstake_penalty_bps: u16,
+    treasury: Pubkey,
+) -> Result<()> {
+    require!(lock_period >= 0, McpTokenError::InvalidCooldown);
+    require!(
+        early_unstake_penalty_bps as u64 <= BPS_DENOMINATOR,
+        McpTokenError::InvalidPenalty
+    );
+    let pool = &mut ctx.accounts.stake_pool;
+    pool.lock_period = lock_period;
+    pool.early_unstake_penalty_bps = early_unstake_penalty_bps;
+    pool.treasury = treasury;
+
+    Ok(())
+}
+
+/// Cap the rewards distributed per epoch; zero removes the cap
+pub fn set_reward_budget(
+    ctx: Context<UpdateStakePool>,
+    reward_budget_per_epoch: u64,
+) -> Result<()> {
+    ctx.accounts.stake_pool.reward_budget_per_epoch = reward_budget_per_epoch;
+
+    Ok(())
+}
+
+/// Deposit rewards for everyone staked in the pool, pro rata to stake.
+///
+/// The rewards are queued and distributed within the pool's epoch budget.
+pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    require!(amount > 0, McpTokenError::InvalidAmount);
+    let total_staked = ctx.accounts.stake_pool.total_staked;
+    require!(total_staked > 0, McpTokenError::NoStakers);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.reward_vault.to_account_info(),
+                authority: ctx.accounts.funder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let pool = &mut ctx.accounts.stake_pool;
+    pool.queued_rewards = pool
+        .queued_rewards
+        .checked_add(amount)
+        .ok_or(McpTokenError::MathOverflow)?;
+    pool.distribute_rewards(Clock::get()?.epoch)
+}
+
+/// Opt in or out of auto-compounding claimed rewards
+pub fn set_auto_compound(ctx: Context<SetAutoCompound>, auto_compound: bool) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    ctx.accounts.stake_account.auto_compound = auto_compound;
+
+    Ok(())
+}
+
+/// Lock a stake so it cannot be unstaked until `locker` unlocks it.
+///
+/// Both the stake owner and the locker must sign; the locker is normally a
+/// PDA of another program signing through CPI.
+pub fn lock_stake(ctx: Context<LockStake>) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    let locker = ctx.accounts.locker.key();
+    ctx.accounts.stake_account.lock(locker)
+}
+
+/// Release a stake lock, signed by the account holding it
+pub fn unlock_stake(ctx: Context<UnlockStake>) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    let locker = ctx.accounts.locker.key();
+    ctx.accounts.stake_account.unlock(locker)
+}
+
+/// Forfeit `amount` of a locked stake to the pool treasury, signed by the
+/// account holding the lock
+pub fn slash_stake(ctx: Context<SlashStake>, amount: u64) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    ctx.accounts
+        .stake_pool
+        .distribute_rewards(Clock::get()?.epoch)?;
+    let acc_reward_per_share = ctx.accounts.stake_pool.acc_reward_per_share;
+    let locker = ctx.accounts.locker.key();
+    ctx.accounts
+        .stake_account
+        .slash(locker, amount, acc_reward_per_share)?;
+
+    let pool = &mut ctx.accounts.stake_pool;
+    pool.total_staked = pool
+        .total_staked
+        .checked_sub(amount)
+        .ok_or(McpTokenError::MathOverflow)?;
+
+    let pool = &ctx.accounts.stake_pool;
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.stake_vault.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            &[&[b"stake-pool".as_ref(), pool.mint.as_ref(), &[pool.bump]]],
+        ),
+        amount,
+    )
+}
+
+/// Claim accrued staking rewards.
+///
+/// With `auto_compound` set the rewards move from the reward vault into the
+/// stake vault and are added to the stake; otherwise they are paid out to the
+/// owner. Either way the reward debt is re-synced to the new stake.
+///
+/// The epoch budget is applied as rewards are distributed, so every claim
+/// pays out all the staker has earned.
+pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    ctx.accounts
+        .stake_pool
+        .distribute_rewards(Clock::get()?.epoch)?;
+    let acc_reward_per_share = ctx.accounts.stake_pool.acc_reward_per_share;
+    let stake_account = &mut ctx.accounts.stake_account;
+    stake_account.settle_rewards(acc_reward_per_share)?;
+    let reward = std::mem::take(&mut stake_account.pending_rewards);
+    let auto_compound = stake_account.auto_compound;
+    if reward == 0 {
+        return stake_account.sync_reward_debt(acc_reward_per_share);
+    }
+
+    let destination = if auto_compound {
+        ctx.accounts.stake_vault.to_account_info()
+    } else {
+        ctx.accounts.owner_token_account.to_account_info()
+    };
+    let pool = &ctx.accounts.stake_pool;
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.reward_vault.to_account_info(),
+                to: destination,
+                authority: pool.to_account_info(),
+            },
+            &[&[b"stake-pool".as_ref(), pool.mint.as_ref(), &[pool.bump]]],
+        ),
+        reward,
+    )?;
+
+    let stake_account = &mut ctx.accounts.stake_account;
+    if auto_compound {
+        ctx.accounts.stake_pool.compound(stake_account, reward)?;
+    }
+    stake_account.sync_reward_debt(acc_reward_per_share)
+}
+
+/// Account validation
+#[derive(Accounts)]
+pub struct InitializeStakePool<'info> {
+    /// The authority of the pool
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + StakePool::LEN,
+        seeds = [b"stake-pool".as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// Vault holding staked tokens
+    #[account(
+        init,
+        payer = authority,
+        seeds = [STAKE_VAULT_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = stake_pool,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// Vault holding undistributed rewards
+    #[account(
+        init,
+        payer = authority,
+        seeds = [REWARD_VAULT_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = stake_pool,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    /// Mint being staked
+    pub mint: Account<'info, Mint>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for stake
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    /// The owner staking tokens
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The pool to stake into
+    #[account(
+        mut,
+        seeds = [b"stake-pool".as_ref(), stake_pool.mint.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// The owner's stake, created on first stake
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + StakeAccount::LEN,
+        seeds = [b"stake".as_ref(), stake_pool.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// Vault holding staked tokens
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED, stake_pool.mint.as_ref()],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// Owner's token account
+    #[account(
+        mut,
+        token::mint = stake_pool.mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for unstake
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    /// The owner of the stake
+    pub owner: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The pool the stake belongs to
+    #[account(
+        mut,
+        seeds = [b"stake-pool".as_ref(), stake_pool.mint.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// The owner's stake
+    #[account(
+        mut,
+        seeds = [b"stake".as_ref(), stake_pool.key().as_ref(), owner.key().as_ref()],
+        bump = stake_account.bump,
+        has_one = owner,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// Vault holding staked tokens
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED, stake_pool.mint.as_ref()],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// Owner's token account
+    #[account(
+        mut,
+        token::mint = stake_pool.mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Treasury token account, required when an early unstake penalty applies
+    #[account(
+        mut,
+        address = stake_pool.treasury,
+        token::mint = stake_pool.mint,
+    )]
+    pub treasury: Option<Account<'info, TokenAccount>>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for pool update
+#[derive(Accounts)]
+pub struct UpdateStakePool<'info> {
+    /// The authority of the pool
+    pub authority: Signer<'info>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"stake-pool".as_ref(), stake_pool.mint.as_ref()],
+        bump = stake_pool.bump,
+        has_one = authority,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+}
+
+/// Account validation for reward funding
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    /// The account depositing rewards
+    pub funder: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The pool whose stakers are rewarded
+    #[account(
+        mut,
+        seeds = [b"stake-pool".as_ref(), stake_pool.mint.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// Vault holding undistributed rewards
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED, stake_pool.mint.as_ref()],
+        bump,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    /// Funder's token account
+    #[account(
+        mut,
+        token::mint = stake_pool.mint,
+        token::authority = funder,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for auto-compound toggle
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    /// The owner of the stake
+    pub owner: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The account to update
+    #[account(
+        mut,
+        seeds = [b"stake".as_ref(), stake_account.pool.as_ref(), owner.key().as_ref()],
+        bump = stake_account.bump,
+        has_one = owner,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+}
+
+/// Account validation for locking a stake
+#[derive(Accounts)]
+pub struct LockStake<'info> {
+    /// The owner of the stake
+    pub owner: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The account taking the lock
+    pub locker: Signer<'info>,
+
+    /// The stake to lock
+    #[account(
+        mut,
+        seeds = [b"stake".as_ref(), stake_account.pool.as_ref(), owner.key().as_ref()],
+        bump = stake_account.bump,
+        has_one = owner,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+}
+
+/// Account validation for unlocking a stake
+#[derive(Accounts)]
+pub struct UnlockStake<'info> {
+    /// The account holding the lock
+    pub locker: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The stake to unlock
+    #[account(
+        mut,
+        seeds = [b"stake".as_ref(), stake_account.pool.as_ref(), stake_account.owner.as_ref()],
+        bump = stake_account.bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+}
+
+/// Account validation for slashing a stake
+#[derive(Accounts)]
+pub struct SlashStake<'info> {
+    /// The account holding the lock
+    pub locker: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The pool the stake belongs to
+    #[account(
+        mut,
+        seeds = [b"stake-pool".as_ref(), stake_pool.mint.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// The stake to slash
+    #[account(
+        mut,
+        seeds = [b"stake".as_ref(), stake_pool.key().as_ref(), stake_account.owner.as_ref()],
+        bump = stake_account.bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// Vault holding staked tokens
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED, stake_pool.mint.as_ref()],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// Treasury token account receiving the slashed tokens
+    #[account(
+        mut,
+        address = stake_pool.treasury @ McpTokenError::InvalidTreasury,
+        token::mint = stake_pool.mint,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for reward claim
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    /// The owner of the stake
+    pub owner: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The pool the stake belongs to
+    #[account(
+        mut,
+        seeds = [b"stake-pool".as_ref(), stake_pool.mint.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// The owner's stake
+    #[account(
+        mut,
+        seeds = [b"stake".as_ref(), stake_pool.key().as_ref(), owner.key().as_ref()],
+        bump = stake_account.bump,
+        has_one = owner,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// Vault holding staked tokens
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED, stake_pool.mint.as_ref()],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// Vault holding undistributed rewards
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED, stake_pool.mint.as_ref()],
+        bump,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    /// Owner's token account
+    #[account(
+        mut,
+        token::mint = stake_pool.mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stake_account() -> StakeAccount {
+        StakeAccount {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            amount: 1000,
+            staked_at: 0,
+            reward_debt: 0,
+            pending_rewards: 0,
+            auto_compound: false,
+            locked_by: None,
+            bump: 255,
+        }
+    }
+
+    fn stake_pool() -> StakePool {
+        StakePool {
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            total_staked: 10_000,
+            acc_reward_per_share: 0,
+            lock_period: 0,
+            early_unstake_penalty_bps: 0,
+            treasury: Pubkey::default(),
+            reward_budget_per_epoch: 500,
+            rewards_paid_this_epoch: 0,
+            reward_epoch: 0,
+            queued_rewards: 2_000,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn many_stakers_claim_within_epoch_budget() {
+        let mut pool = stake_pool();
+        let mut stakers: Vec<StakeAccount> = (0..10).map(|_| stake_account()).collect();
+
+        pool.distribute_rewards(1).unwrap();
+        pool.distribute_rewards(1).unwrap();
+        let mut claimed = 0;
+        for staker in &mut stakers {
+            staker.settle_rewards(pool.acc_reward_per_share).unwrap();
+            assert_eq!(staker.pending_rewards, 50);
+            claimed += std::mem::take(&mut staker.pending_rewards);
+            staker.sync_reward_debt(pool.acc_reward_per_share).unwrap();
+        }
+        assert_eq!(claimed, 500);
+        assert_eq!(pool.queued_rewards, 1_500);
+
+        pool.distribute_rewards(2).unwrap();
+        stakers[0]
+            .settle_rewards(pool.acc_reward_per_share)
+            .unwrap();
+        assert_eq!(stakers[0].pending_rewards, 50);
+    }
+
+    #[test]
+    fn locked_stake_only_unlocks_for_its_locker() {
+        let mut stake = stake_account();
+        let locker = Pubkey::new_unique();
+        stake.lock(locker).unwrap();
+
+        assert_eq!(
+            stake.lock(Pubkey::new_unique()).unwrap_err(),
+            McpTokenError::StakeLocked.into()
+        );
+        assert_eq!(
+            stake.unlock(Pubkey::new_unique()).unwrap_err(),
+            McpTokenError::NotStakeLocker.into()
+        );
+        stake.unlock(locker).unwrap();
+        assert_eq!(stake.locked_by, None);
+    }
+
+    #[test]
+    fn only_locker_slashes_and_rewards_settled_first() {
+        let mut pool = stake_pool();
+        let mut stake = stake_account();
+        let locker = Pubkey::new_unique();
+        pool.distribute_rewards(1).unwrap();
+        let acc_reward_per_share = pool.acc_reward_per_share;
+
+        assert_eq!(
+            stake.slash(locker, 100, acc_reward_per_share).unwrap_err(),
+            McpTokenError::NotStakeLocker.into()
+        );
+        stake.lock(locker).unwrap();
+        assert_eq!(
+            stake
+                .slash(locker, 1_001, acc_reward_per_share)
+                .unwrap_err(),
+            McpTokenError::InsufficientStake.into()
+        );
+
+        stake.slash(locker, 400, acc_reward_per_share).unwrap();
+        assert_eq!(stake.amount, 600);
+        assert_eq!(stake.pending_rewards, 50);
+        assert_eq!(stake.locked_by, Some(locker));
+        // Rewards earned before the slash are not paid a second time
+        stake.settle_rewards(acc_reward_per_share).unwrap();
+        assert_eq!(stake.pending_rewards, 50);
+    }
+
+    #[test]
+    fn auto_compound_grows_principal_without_leaving_vault() {
+        let mut pool = stake_pool();
+        let mut stake = stake_account();
+        stake.auto_compound = true;
+
+        pool.distribute_rewards(1).unwrap();
+        stake.settle_rewards(pool.acc_reward_per_share).unwrap();
+        let reward = std::mem::take(&mut stake.pending_rewards);
+        assert_eq!(reward, 50);
+        pool.compound(&mut stake, reward).unwrap();
+        stake.sync_reward_debt(pool.acc_reward_per_share).unwrap();
+        assert_eq!(stake.amount, 1050);
+        assert_eq!(pool.total_staked, 10_050);
+
+        // The compounded reward is not paid a second time
+        stake.settle_rewards(pool.acc_reward_per_share).unwrap();
+        assert_eq!(stake.pending_rewards, 0);
+    }
+
+    #[test]
+    fn early_unstake_penalized_and_on_time_unstake_not() {
+        let mut pool = stake_pool();
+        pool.lock_period = 100;
+        pool.early_unstake_penalty_bps = 1_000;
+
+        assert_eq!(pool.early_unstake_penalty(1_000, 0, 99).unwrap(), 100);
+        assert_eq!(pool.early_unstake_penalty(1_000, 0, 100).unwrap(), 0);
+
+        pool.early_unstake_penalty_bps = 0;
+        assert_eq!(pool.early_unstake_penalty(1_000, 0, 99).unwrap(), 0);
+    }
+}
@@ -0,0 +1,388 @@
+//! transfer_fee_module module for MCP token
+//!
+//! This module provides management of the Token-2022 transfer-fee extension for
+//! mints whose transfer fee config and withdraw withheld authorities are the
+//! `MintAuthority` PDA. The extension must be initialized when the mint is
+//! created; these instructions only update the fee and collect what it
+//! withheld. Classic SPL Token mints have no transfer fee and are rejected by
+//! the owner check on the mint.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::instruction as transfer_fee;
+use anchor_spl::token_2022::Token2022;
+
+use crate::errors_module::McpTokenError;
+use crate::global_config_module::{GlobalConfig, GLOBAL_CONFIG_SEED};
+use crate::MintAuthority;
+
+/// Highest transfer fee that can be configured, in basis points
+pub const MAX_TRANSFER_FEE_BPS: u16 = 1_000;
+
+/// Build the Token-2022 instruction setting the transfer fee of `mint`,
+/// rejecting fees out of bounds
+fn set_transfer_fee_instruction(
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    fee_bps: u16,
+    max_fee: u64,
+) -> Result<Instruction> {
+    require!(
+        fee_bps <= MAX_TRANSFER_FEE_BPS,
+        McpTokenError::InvalidTransferFee
+    );
+    require!(
+        fee_bps == 0 || max_fee > 0,
+        McpTokenError::InvalidTransferFee
+    );
+
+    Ok(transfer_fee::set_transfer_fee(
+        token_program,
+        mint,
+        mint_authority,
+        &[],
+        fee_bps,
+        max_fee,
+    )?)
+}
+
+/// Build the Token-2022 instruction withdrawing everything withheld in `mint`
+/// into `destination`
+fn withdraw_withheld_instruction(
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    destination: &Pubkey,
+) -> Result<Instruction> {
+    Ok(transfer_fee::withdraw_withheld_tokens_from_mint(
+        token_program,
+        mint,
+        destination,
+        mint_authority,
+        &[],
+    )?)
+}
+
+/// Set the transfer fee of a Token-2022 mint, signed by the mint authority PDA.
+///
+/// A non-zero fee needs a non-zero `max_fee`, the most withheld from a single
+/// transfer. Token-2022 applies the new fee two epochs later.
+pub fn configure_transfer_fee(
+    ctx: Context<ConfigureTransferFee>,
+    fee_bps: u16,
+    max_fee: u64,
+) -> Result<()> {
+    let mint_authority = &ctx.accounts.mint_authority;
+    let instruction = set_transfer_fee_instruction(
+        ctx.accounts.token_program.key,
+        ctx.accounts.mint.key,
+        &mint_authority.key(),
+        fee_bps,
+        max_fee,
+    )?;
+    invoke_signed(
+        &instruction,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+        &[&[
+            b"mint-authority".as_ref(),
+            ctx.accounts.mint.key().as_ref(),
+            &[mint_authority.bump],
+        ]],
+    )?;
+
+    Ok(())
+}
+
+/// Collect withheld transfer fees into `destination`.
+///
+/// Fees withheld in the token accounts passed as remaining accounts are first
+/// harvested into the mint, then everything withheld in the mint is withdrawn.
+pub fn harvest_withheld_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, HarvestWithheldFees<'info>>,
+) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    let token_program = ctx.accounts.token_program.key;
+    let mint = ctx.accounts.mint.key;
+
+    if !ctx.remaining_accounts.is_empty() {
+        let sources: Vec<&Pubkey> = ctx.remaining_accounts.iter().map(|info| info.key).collect();
+        let instruction =
+            transfer_fee::harvest_withheld_tokens_to_mint(token_program, mint, &sources)?;
+        let mut accounts = vec![ctx.accounts.mint.to_account_info()];
+        accounts.extend(ctx.remaining_accounts.iter().cloned());
+        invoke(&instruction, &accounts)?;
+    }
+
+    let mint_authority = &ctx.accounts.mint_authority;
+    let instruction = withdraw_withheld_instruction(
+        token_program,
+        mint,
+        &mint_authority.key(),
+        ctx.accounts.destination.key,
+    )?;
+    invoke_signed(
+        &instruction,
+        &[
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            mint_authority.to_account_info(),
+        ],
+        &[&[
+            b"mint-authority".as_ref(),
+            mint.as_ref(),
+            &[mint_authority.bump],
+        ]],
+    )?;
+
+    Ok(())
+}
+
+/// Account validation for transfer fee configuration
+#[derive(Accounts)]
+pub struct ConfigureTransferFee<'info> {
+    /// The mint authority
+    pub authority: Signer<'info>,
+
+    /// CHECK: Token-2022 mint with the transfer-fee extension, validated by the token program
+    #[account(
+        mut,
+        owner = token_program.key(),
+    )]
+    pub mint: UncheckedAccount<'info>,
+
+    /// Mint authority of `mint`, the transfer fee config authority
+    #[account(
+        seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
+        bump = mint_authority.bump,
+        has_one = authority,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// Token-2022 program
+    pub token_program: Program<'info, Token2022>,
+}
+
+/// Account validation for withheld fee collection
+#[derive(Accounts)]
+pub struct HarvestWithheldFees<'info> {
+    /// The mint authority
+    pub authority: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Token-2022 mint with the transfer-fee extension, validated by the token program
+    #[account(
+        mut,
+        owner = token_program.key(),
+    )]
+    pub mint: UncheckedAccount<'info>,
+
+    /// Mint authority of `mint`, the withdraw withheld authority
+    #[account(
+        seeds = [b"mint-authority".as_ref(), mint.key().as_ref()],
+        bump = mint_authority.bump,
+        has_one = authority,
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// CHECK: Token-2022 account receiving the fees, validated by the token program
+    #[account(
+        mut,
+        owner = token_program.key(),
+    )]
+    pub destination: UncheckedAccount<'info>,
+
+    /// Token-2022 program
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::{
+        instruction::TransferFeeInstruction, TransferFee, TransferFeeAmount, TransferFeeConfig,
+    };
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions, StateWithExtensionsMut,
+    };
+    use anchor_spl::token_2022::spl_token_2022::instruction::TokenInstruction;
+    use anchor_spl::token_2022::spl_token_2022::processor::Processor;
+    use anchor_spl::token_2022::spl_token_2022::state::{Account, AccountState, Mint};
+    use anchor_spl::token_2022::spl_token_2022::{self, pod::OptionalNonZeroPubkey};
+
+    /// A Token-2022 mint charging `fee_bps` with both fee authorities `authority`
+    fn mint_with_transfer_fee(authority: Pubkey, fee_bps: u16, max_fee: u64) -> Vec<u8> {
+        let mut data =
+            vec![0; ExtensionType::get_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        let authority = OptionalNonZeroPubkey::try_from(Some(authority)).unwrap();
+        config.transfer_fee_config_authority = authority;
+        config.withdraw_withheld_authority = authority;
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: max_fee.into(),
+            transfer_fee_basis_points: fee_bps.into(),
+        };
+        config.older_transfer_fee = fee;
+        config.newer_transfer_fee = fee;
+        state.base = Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    /// A Token-2022 account of `mint` holding `amount` with `withheld` in fees
+    fn token_account(mint: Pubkey, amount: u64, withheld: u64) -> Vec<u8> {
+        let mut data =
+            vec![0; ExtensionType::get_account_len::<Account>(&[ExtensionType::TransferFeeAmount])];
+        let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+        state
+            .init_extension::<TransferFeeAmount>(true)
+            .unwrap()
+            .withheld_amount = withheld.into();
+        state.base = Account {
+            mint,
+            owner: Pubkey::new_unique(),
+            amount,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn fee_configured_and_harvested_after_transfer() {
+        let token_program = spl_token_2022::id();
+        let mint = Pubkey::new_unique();
+        let (authority, _) =
+            Pubkey::find_program_address(&[b"mint-authority", mint.as_ref()], &crate::ID);
+
+        assert_eq!(
+            set_transfer_fee_instruction(&token_program, &mint, &authority, 1_001, 1_000)
+                .unwrap_err(),
+            McpTokenError::InvalidTransferFee.into()
+        );
+        assert_eq!(
+            set_transfer_fee_instruction(&token_program, &mint, &authority, 100, 0).unwrap_err(),
+            McpTokenError::InvalidTransferFee.into()
+        );
+        let instruction =
+            set_transfer_fee_instruction(&token_program, &mint, &authority, 100, 1_000).unwrap();
+        assert_eq!(instruction.accounts[1].pubkey, authority);
+        assert!(instruction.accounts[1].is_signer);
+        assert_eq!(
+            TokenInstruction::unpack(&instruction.data).unwrap(),
+            TokenInstruction::TransferFeeExtension(TransferFeeInstruction::SetTransferFee {
+                transfer_fee_basis_points: 100,
+                maximum_fee: 1_000,
+            })
+        );
+
+        // The transfer of 10_000 after the fee takes effect withholds 1%
+        let mut mint_data = mint_with_transfer_fee(authority, 100, 1_000);
+        let fee = StateWithExtensions::<Mint>::unpack(&mint_data)
+            .unwrap()
+            .get_extension::<TransferFeeConfig>()
+            .unwrap()
+            .calculate_epoch_fee(0, 10_000)
+            .unwrap();
+        assert_eq!(fee, 100);
+
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let mut source_data = token_account(mint, 9_900, fee);
+        let mut destination_data = token_account(mint, 0, 0);
+        let (mut mint_lamports, mut source_lamports, mut destination_lamports) = (0, 0, 0);
+        let (mut authority_lamports, mut authority_data) = (0, []);
+        let mint_info = AccountInfo::new(
+            &mint,
+            false,
+            true,
+            &mut mint_lamports,
+            &mut mint_data,
+            &token_program,
+            false,
+            0,
+        );
+        let source_info = AccountInfo::new(
+            &source,
+            false,
+            true,
+            &mut source_lamports,
+            &mut source_data,
+            &token_program,
+            false,
+            0,
+        );
+        let destination_info = AccountInfo::new(
+            &destination,
+            false,
+            true,
+            &mut destination_lamports,
+            &mut destination_data,
+            &token_program,
+            false,
+            0,
+        );
+        let authority_info = AccountInfo::new(
+            &authority,
+            true,
+            false,
+            &mut authority_lamports,
+            &mut authority_data,
+            &crate::ID,
+            false,
+            0,
+        );
+
+        let harvest =
+            transfer_fee::harvest_withheld_tokens_to_mint(&token_program, &mint, &[&source])
+                .unwrap();
+        Processor::process(
+            &token_program,
+            &[mint_info.clone(), source_info.clone()],
+            &harvest.data,
+        )
+        .unwrap();
+        let withdraw =
+            withdraw_withheld_instruction(&token_program, &mint, &authority, &destination).unwrap();
+        Processor::process(
+            &token_program,
+            &[mint_info, destination_info.clone(), authority_info],
+            &withdraw.data,
+        )
+        .unwrap();
+
+        let destination_data = destination_info.data.borrow();
+        let destination = StateWithExtensions::<Account>::unpack(&destination_data).unwrap();
+        assert_eq!(destination.base.amount, fee);
+        let source_data = source_info.data.borrow();
+        let source = StateWithExtensions::<Account>::unpack(&source_data).unwrap();
+        assert_eq!(
+            u64::from(
+                source
+                    .get_extension::<TransferFeeAmount>()
+                    .unwrap()
+                    .withheld_amount
+            ),
+            0
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! utils_module module for MCP token
+//!
+//! This module provides helpers shared by MCP token instructions.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::get_instruction_relative;
+use anchor_lang::system_program;
+use anchor_spl::token::TokenAccount;
+use std::collections::BTreeMap;
+
+use crate::errors_module::McpTokenError;
+
+/// Denominator for basis point amounts
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Most entries a batch instruction of any MCP program may process at once,
+/// keeping it well within the compute budget
+pub const MAX_BATCH: usize = 8;
+
+/// Look up a PDA bump recorded by Anchor during account validation.
+///
+/// Returns `BumpNotFound` instead of panicking when `name` is missing.
+pub fn get_bump(bumps: &BTreeMap<String, u8>, name: &str) -> Result<u8> {
+    bumps
+        .get(name)
+        .copied()
+        .ok_or_else(|| error!(McpTokenError::BumpNotFound))
+}
+
+/// Check that a vector stored on an account stays within its fixed bound.
+///
+/// Account sizes are computed from these bounds, so every vector field must be
+/// validated here before it is written.
+pub fn validate_bounded_vec<T>(entries: &[T], max: usize) -> Result<()> {
+    require!(entries.len() <= max, McpTokenError::TooManyEntries);
+
+    Ok(())
+}
+
+/// Fail with `BatchTooLarge` when a batch has more than `MAX_BATCH` entries.
+///
+/// Called before a batch instruction touches any state.
+pub fn assert_batch_size(entries: usize) -> Result<()> {
+    require!(entries <= MAX_BATCH, McpTokenError::BatchTooLarge);
+
+    Ok(())
+}
+
+/// Create a `space`-byte account owned by `owner` at the PDA signed for by `seeds`.
+///
+/// Like Anchor's `init`, an address that already holds lamports is topped up
+/// to the rent-exempt minimum, allocated and assigned rather than created, so
+/// anyone pre-funding the address cannot block its creation.
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(space);
+    if target.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                &[seeds],
+            ),
+            required,
+            space as u64,
+            owner,
+        );
+    }
+
+    let top_up = required.saturating_sub(target.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: target.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: target.clone(),
+            },
+            &[seeds],
+        ),
+        owner,
+    )
+}
+
+/// Compute `amount * numerator / denominator` without intermediate overflow
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    require!(denominator > 0, McpTokenError::MathOverflow);
+    let result = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(McpTokenError::MathOverflow)?
+        / denominator as u128;
+
+    u64::try_from(result).map_err(|_| error!(McpTokenError::MathOverflow))
+}
+
+/// Reject calls made through CPI unless the calling program is in `allowed`.
+///
+/// A top-level call is always accepted. A CPI is only accepted directly from
+/// the program of the enclosing top-level instruction, read from the
+/// instructions sysvar, since deeper in the call stack that program is not
+/// the direct caller.
+pub fn assert_allowed_caller(instructions: &AccountInfo, allowed: &[Pubkey]) -> Result<()> {
+    let stack_height = get_stack_height();
+    if stack_height <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(());
+    }
+
+    let top_level_program = get_instruction_relative(0, instructions)?.program_id;
+    check_caller(stack_height, &top_level_program, allowed)
+}
+
+/// Accept a call at `stack_height` made from `top_level_program` when it is
+/// top level, or a CPI made directly by an allowed top-level program
+fn check_caller(stack_height: usize, top_level_program: &Pubkey, allowed: &[Pubkey]) -> Result<()> {
+    if stack_height <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(());
+    }
+    require!(
+        stack_height == TRANSACTION_LEVEL_STACK_HEIGHT + 1 && allowed.contains(top_level_program),
+        McpTokenError::UnauthorizedCaller
+    );
+
+    Ok(())
+}
+
+/// Integer square root, rounded down
+pub fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+
+    // Newton's method from an initial guess at or above the root
+    let mut x = 1u64 << (64 - value.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + value / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Balance `transfer_all` moves out of `account`; `None` leaves an empty account alone
+pub fn full_balance(account: &TokenAccount) -> Option<u64> {
+    (account.amount > 0).then_some(account.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::global_config_module::MAX_FREEZE_ADMINS;
+    use crate::hold_period_module::MAX_HOLD_EXEMPTIONS;
+    use crate::MAX_ALLOWED_CALLERS;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+
+    #[test]
+    fn missing_bump_returns_bump_not_found() {
+        let mut bumps = BTreeMap::new();
+        bumps.insert("mint_authority".to_string(), 254);
+
+        assert_eq!(get_bump(&bumps, "mint_authority").unwrap(), 254);
+        assert_eq!(
+            get_bump(&bumps, "missing").unwrap_err(),
+            McpTokenError::BumpNotFound.into()
+        );
+    }
+
+    #[test]
+    fn vec_over_bound_rejected() {
+        for max in [MAX_FREEZE_ADMINS, MAX_HOLD_EXEMPTIONS, MAX_ALLOWED_CALLERS] {
+            validate_bounded_vec(&vec![Pubkey::default(); max], max).unwrap();
+            assert_eq!(
+                validate_bounded_vec(&vec![Pubkey::default(); max + 1], max).unwrap_err(),
+                McpTokenError::TooManyEntries.into()
+            );
+        }
+    }
+
+    #[test]
+    fn only_direct_calls_and_allowed_callers_accepted() {
+        let allowed = Pubkey::new_unique();
+        let unlisted = Pubkey::new_unique();
+        let top = TRANSACTION_LEVEL_STACK_HEIGHT;
+
+        assert!(check_caller(top, &unlisted, &[allowed]).is_ok());
+        assert!(check_caller(top + 1, &allowed, &[allowed]).is_ok());
+        assert_eq!(
+            check_caller(top + 1, &unlisted, &[allowed]).unwrap_err(),
+            McpTokenError::UnauthorizedCaller.into()
+        );
+        assert_eq!(
+            check_caller(top + 2, &allowed, &[allowed]).unwrap_err(),
+            McpTokenError::UnauthorizedCaller.into()
+        );
+    }
+
+    #[test]
+    fn full_balance_moved_and_empty_account_skipped() {
+        for (amount, expected) in [(1_234, Some(1_234)), (0, None)] {
+            let mut data = vec![0; spl_token::state::Account::LEN];
+            spl_token::state::Account {
+                amount,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            let account = TokenAccount::try_deserialize(&mut &data[..]).unwrap();
+            assert_eq!(full_balance(&account), expected);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! vaults_module module for MCP token
+//!
+//! This module provides the canonical PDA seeds for MCP token vaults.
+//! Instructions and off-chain clients must derive vault addresses through these
+//! helpers so the seeds cannot drift between call sites.
+
+use anchor_lang::prelude::*;
+
+/// Seed prefix of the token vault holding staked tokens
+pub const STAKE_VAULT_SEED: &[u8] = b"stake-vault";
+
+/// Seed prefix of the token vault holding undistributed staking rewards
+pub const REWARD_VAULT_SEED: &[u8] = b"reward-vault";
+
+/// Seed prefix of the token vault funding an airdrop distributor
+pub const AIRDROP_VAULT_SEED: &[u8] = b"airdrop-vault";
+
+/// Seed prefix of the token vault holding a vesting schedule's locked tokens
+pub const VESTING_VAULT_SEED: &[u8] = b"vesting-vault";
+
+/// Seed prefix of the token vault locking a voter's tokens until the proposal deadline
+pub const VOTE_VAULT_SEED: &[u8] = b"vote-vault";
+
+/// Seeds of the staking vault for `mint`
+pub fn stake_vault_seeds(mint: &Pubkey) -> [&[u8]; 2] {
+    [STAKE_VAULT_SEED, mint.as_ref()]
+}
+
+/// Seeds of the staking reward vault for `mint`
+pub fn reward_vault_seeds(mint: &Pubkey) -> [&[u8]; 2] {
+    [REWARD_VAULT_SEED, mint.as_ref()]
+}
+
+/// Seeds of the token vault owned by `distributor`
+pub fn airdrop_vault_seeds(distributor: &Pubkey) -> [&[u8]; 2] {
+    [AIRDROP_VAULT_SEED, distributor.as_ref()]
+}
+
+/// Derive the staking vault address for `mint`
+pub fn find_stake_vault_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&stake_vault_seeds(mint), &crate::ID)
+}
+
+/// Derive the staking reward vault address for `mint`
+pub fn find_reward_vault_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&reward_vault_seeds(mint), &crate::ID)
+}
+
+/// Derive the token vault address owned by `distributor`
+pub fn find_airdrop_vault_address(distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&airdrop_vault_seeds(distributor), &crate::ID)
+}
+
+/// Seeds of the token vault owned by `schedule`
+pub fn vesting_vault_seeds(schedule: &Pubkey) -> [&[u8]; 2] {
+    [VESTING_VAULT_SEED, schedule.as_ref()]
+}
+
+/// Derive the token vault address owned by `schedule`
+pub fn find_vesting_vault_address(schedule: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&vesting_vault_seeds(schedule), &crate::ID)
+}
+
+/// Seeds of the token vault owned by `vote_record`
+pub fn vote_vault_seeds(vote_record: &Pubkey) -> [&[u8]; 2] {
+    [VOTE_VAULT_SEED, vote_record.as_ref()]
+}
+
+/// Derive the token vault address owned by `vote_record`
+pub fn find_vote_vault_address(vote_record: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&vote_vault_seeds(vote_record), &crate::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_addresses_match_off_chain_derivation() {
+        let key = Pubkey::new_unique();
+        let derive =
+            |prefix: &[u8]| Pubkey::find_program_address(&[prefix, key.as_ref()], &crate::ID);
+
+        assert_eq!(find_stake_vault_address(&key), derive(b"stake-vault"));
+        assert_eq!(find_reward_vault_address(&key), derive(b"reward-vault"));
+        assert_eq!(find_airdrop_vault_address(&key), derive(b"airdrop-vault"));
+        assert_eq!(find_vesting_vault_address(&key), derive(b"vesting-vault"));
+        assert_eq!(find_vote_vault_address(&key), derive(b"vote-vault"));
+    }
+}
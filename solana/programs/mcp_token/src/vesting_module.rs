@@ -0,0 +1,415 @@
+//! vesting_module module for MCP token
+//!
+//! This module provides linear vesting schedules with a cliff. Locked tokens
+//! sit in a vault owned by the schedule and are released to the beneficiary as
+//! they vest, either by the beneficiary or in batches by any keeper.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use crate::errors_module::McpTokenError;
+use crate::global_config_module::{GlobalConfig, GLOBAL_CONFIG_SEED};
+use crate::utils_module::{assert_batch_size, get_bump};
+use crate::vaults_module::VESTING_VAULT_SEED;
+
+/// Remaining accounts passed per schedule: schedule, vault, beneficiary token account
+pub const ACCOUNTS_PER_VESTING_CLAIM: usize = 3;
+
+/// VestingSchedule state account
+#[account]
+pub struct VestingSchedule {
+    /// The authority that funded the schedule
+    pub authority: Pubkey,
+
+    /// The wallet receiving vested tokens
+    pub beneficiary: Pubkey,
+
+    /// Unique schedule identifier per beneficiary
+    pub schedule_id: u64,
+
+    /// Mint being vested
+    pub mint: Pubkey,
+
+    /// Total tokens locked at creation
+    pub total_amount: u64,
+
+    /// Tokens already released to the beneficiary
+    pub released: u64,
+
+    /// Time vesting starts accruing
+    pub start_time: i64,
+
+    /// Time before which nothing can be released
+    pub cliff_time: i64,
+
+    /// Time at which everything has vested
+    pub end_time: i64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl VestingSchedule {
+    pub const LEN: usize = 32 + 32 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    /// Total amount vested at `now`
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        if now < self.cliff_time {
+            return Ok(0);
+        }
+        if now >= self.end_time {
+            return Ok(self.total_amount);
+        }
+
+        let vested = (self.total_amount as u128)
+            .checked_mul((now - self.start_time) as u128)
+            .ok_or(McpTokenError::MathOverflow)?
+            / (self.end_time - self.start_time) as u128;
+        u64::try_from(vested).map_err(|_| error!(McpTokenError::MathOverflow))
+    }
+
+    /// Amount vested at `now` that has not been released yet
+    pub fn claimable(&self, now: i64) -> Result<u64> {
+        self.vested_amount(now)?
+            .checked_sub(self.released)
+            .ok_or_else(|| error!(McpTokenError::MathOverflow))
+    }
+}
+
+/// Emitted when vested tokens are released to a beneficiary
+#[event]
+pub struct VestedClaimed {
+    pub schedule: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub released: u64,
+}
+
+/// Create a vesting schedule and lock `total_amount` in its vault
+pub fn create_vesting_schedule(
+    ctx: Context<CreateVestingSchedule>,
+    schedule_id: u64,
+    total_amount: u64,
+    start_time: i64,
+    cliff_time: i64,
+    end_time: i64,
+) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    require!(total_amount > 0, McpTokenError::InvalidAmount);
+    require!(
+        start_time <= cliff_time && cliff_time <= end_time && start_time < end_time,
+        McpTokenError::InvalidVestingSchedule
+    );
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.authority_token_account.to_account_info(),
+                to: ctx.accounts.vesting_vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        total_amount,
+    )?;
+
+    let schedule = &mut ctx.accounts.vesting_schedule;
+    schedule.authority = ctx.accounts.authority.key();
+    schedule.beneficiary = ctx.accounts.beneficiary.key();
+    schedule.schedule_id = schedule_id;
+    schedule.mint = ctx.accounts.mint.key();
+    schedule.total_amount = total_amount;
+    schedule.released = 0;
+    schedule.start_time = start_time;
+    schedule.cliff_time = cliff_time;
+    schedule.end_time = end_time;
+    schedule.bump = get_bump(&ctx.bumps, "vesting_schedule")?;
+
+    Ok(())
+}
+
+/// Release everything vested so far on one schedule to its beneficiary
+pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    let amount = ctx
+        .accounts
+        .vesting_schedule
+        .claimable(Clock::get()?.unix_timestamp)?;
+    require!(amount > 0, McpTokenError::NothingToClaim);
+
+    release_vested(
+        &mut ctx.accounts.vesting_schedule,
+        &ctx.accounts.vesting_vault.to_account_info(),
+        &ctx.accounts.beneficiary_token_account.to_account_info(),
+        &ctx.accounts.token_program,
+        amount,
+    )
+}
+
+/// Release vested tokens for several schedules at once.
+///
+/// For each schedule the remaining accounts must hold the schedule, its vault
+/// and the beneficiary's token account, in that order. Anyone may call this;
+/// tokens only ever go to each schedule's beneficiary. Schedules with nothing
+/// to claim are skipped rather than failing the batch.
+pub fn batch_claim_vested<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchClaimVested<'info>>,
+) -> Result<()> {
+    ctx.accounts.global_config.assert_not_paused()?;
+    let accounts = ctx.remaining_accounts;
+    require!(
+        !accounts.is_empty() && accounts.len().is_multiple_of(ACCOUNTS_PER_VESTING_CLAIM),
+        McpTokenError::InvalidVestingAccounts
+    );
+    assert_batch_size(accounts.len() / ACCOUNTS_PER_VESTING_CLAIM)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    for claim in accounts.chunks(ACCOUNTS_PER_VESTING_CLAIM) {
+        let mut schedule = Account::<VestingSchedule>::try_from(&claim[0])?;
+        let expected = Pubkey::create_program_address(
+            &[
+                b"vesting".as_ref(),
+                schedule.beneficiary.as_ref(),
+                schedule.schedule_id.to_le_bytes().as_ref(),
+                &[schedule.bump],
+            ],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(McpTokenError::InvalidVestingAccounts))?;
+        require_keys_eq!(
+            schedule.key(),
+            expected,
+            McpTokenError::InvalidVestingAccounts
+        );
+
+        let (vault, _) = Pubkey::find_program_address(
+            &[VESTING_VAULT_SEED, schedule.key().as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(claim[1].key(), vault, McpTokenError::InvalidVestingAccounts);
+
+        let beneficiary_token_account = Account::<TokenAccount>::try_from(&claim[2])?;
+        require!(
+            beneficiary_token_account.owner == schedule.beneficiary
+                && beneficiary_token_account.mint == schedule.mint,
+            McpTokenError::InvalidVestingAccounts
+        );
+
+        let amount = schedule.claimable(now)?;
+        if amount == 0 {
+            continue;
+        }
+        release_vested(
+            &mut schedule,
+            &claim[1],
+            &claim[2],
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        schedule.exit(ctx.program_id)?;
+    }
+
+    Ok(())
+}
+
+fn release_vested<'info>(
+    schedule: &mut Account<'info, VestingSchedule>,
+    vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let beneficiary = schedule.beneficiary;
+    let schedule_id = schedule.schedule_id.to_le_bytes();
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: vault.clone(),
+                to: destination.clone(),
+                authority: schedule.to_account_info(),
+            },
+            &[&[
+                b"vesting".as_ref(),
+                beneficiary.as_ref(),
+                schedule_id.as_ref(),
+                &[schedule.bump],
+            ]],
+        ),
+        amount,
+    )?;
+
+    schedule.released = schedule
+        .released
+        .checked_add(amount)
+        .ok_or(McpTokenError::MathOverflow)?;
+
+    emit!(VestedClaimed {
+        schedule: schedule.key(),
+        beneficiary,
+        amount,
+        released: schedule.released,
+    });
+
+    Ok(())
+}
+
+/// Account validation
+#[derive(Accounts)]
+#[instruction(schedule_id: u64)]
+pub struct CreateVestingSchedule<'info> {
+    /// The authority funding the schedule
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: The wallet receiving vested tokens
+    pub beneficiary: UncheckedAccount<'info>,
+
+    /// The account to initialize
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VestingSchedule::LEN,
+        seeds = [
+            b"vesting".as_ref(),
+            beneficiary.key().as_ref(),
+            schedule_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    /// Vault holding the locked tokens
+    #[account(
+        init,
+        payer = authority,
+        seeds = [VESTING_VAULT_SEED, vesting_schedule.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vesting_schedule,
+    )]
+    pub vesting_vault: Account<'info, TokenAccount>,
+
+    /// Mint being vested
+    pub mint: Account<'info, Mint>,
+
+    /// Authority's token account
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = authority,
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Rent sysvar
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account validation for claim
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    /// The beneficiary of the schedule
+    pub beneficiary: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// The schedule to release from
+    #[account(
+        mut,
+        seeds = [
+            b"vesting".as_ref(),
+            beneficiary.key().as_ref(),
+            vesting_schedule.schedule_id.to_le_bytes().as_ref(),
+        ],
+        bump = vesting_schedule.bump,
+        has_one = beneficiary,
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    /// Vault holding the locked tokens
+    #[account(
+        mut,
+        seeds = [VESTING_VAULT_SEED, vesting_schedule.key().as_ref()],
+        bump,
+    )]
+    pub vesting_vault: Account<'info, TokenAccount>,
+
+    /// Beneficiary's token account
+    #[account(
+        mut,
+        token::mint = vesting_schedule.mint,
+        token::authority = beneficiary,
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for batch claim
+#[derive(Accounts)]
+pub struct BatchClaimVested<'info> {
+    /// The keeper triggering the distribution
+    pub keeper: Signer<'info>,
+
+    /// Protocol-wide settings, checked for the pause
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(cliff_time: i64, released: u64) -> VestingSchedule {
+        VestingSchedule {
+            authority: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            schedule_id: 0,
+            mint: Pubkey::new_unique(),
+            total_amount: 1000,
+            released,
+            start_time: 0,
+            cliff_time,
+            end_time: 1000,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn mixed_batch_claims_only_vested_schedules() {
+        let batch = [schedule(0, 0), schedule(600, 0), schedule(0, 500)];
+
+        let claims: Vec<u64> = batch
+            .iter()
+            .map(|schedule| schedule.claimable(500).unwrap())
+            .collect();
+        assert_eq!(claims, [500, 0, 0]);
+        assert_eq!(batch[1].claimable(600).unwrap(), 600);
+        assert_eq!(batch[2].claimable(1500).unwrap(), 500);
+    }
+}